@@ -0,0 +1,22 @@
+// Batch inserts for programs embedding the crate
+//
+// The CLI inserts through `insert_item` inside the transaction its command already holds, and
+// doesn't use this. Only lib.rs declares the module, so it stays out of the openinv binary.
+
+use anyhow::Result;
+use rusqlite::Connection;
+use crate::db::queries::{insert_item, NewItem};
+
+/// Inserts every item in one transaction, returning how many were inserted. If any row is
+/// rejected (a duplicate SKU, a title over the limit, ...) nothing is inserted and the error
+/// names the index of the item that failed. `conn` must not already be in a transaction.
+pub fn bulk_insert(conn: &Connection, items: &[NewItem]) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    for (index, item) in items.iter().enumerate() {
+        // Dropping the uncommitted transaction on the way out rolls back the rows before it
+        insert_item(&tx, item)
+            .map_err(|e| anyhow::Error::new(e).context(format!("Item {} ('{}') could not be inserted", index, item.title)))?;
+    }
+    tx.commit()?;
+    Ok(items.len())
+}
@@ -3,9 +3,219 @@
 
 use anyhow::Result;
 use rusqlite::Connection;
-use crate::db::schema::initialize_database;
-use crate::db::queries::{insert_item, NewItem};
-use crate::validation::{validate_item_ebay, validate_item_stockx, validate_item_poshmark, validate_item_mercari};
+use std::io::{self, BufRead, IsTerminal, Write};
+use crate::db::history::{record_operation, snapshot_items, OperationKind};
+use crate::db::queries::{distinct_brands, distinct_categories, insert_item, NewItem};
+use crate::config::currency::{format_money, normalize_currency, DEFAULT_CURRENCY};
+use crate::output::console::status;
+use crate::validation::{
+    closest_match, normalize_condition, normalize_release_date, validate_brand, validate_category, validate_condition,
+    validate_currency, validate_item_for, validate_price, validate_quantity, validate_release_date, validate_sku,
+    validate_title_ebay, validate_title_mercari,
+    validate_title_poshmark, validate_title_stockx, validate_upc_ebay, ItemFields, Platform, ValidationResult, CONDITIONS,
+};
+use crate::error::error::validation_failed;
+
+/// The `add` fields the wizard can ask for; `None` for any not given as flags
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemDraft {
+    pub title: Option<String>,
+    pub price: Option<f64>,
+    pub quantity: Option<i32>,
+    pub category: Option<String>,
+    pub condition: Option<String>,
+    pub brand: Option<String>,
+    pub description: Option<String>,
+    pub upc: Option<String>,
+    pub size: Option<String>,
+    pub currency: Option<String>,
+    pub sku: Option<String>,
+}
+
+impl ItemDraft {
+    /// The flags a complete item still needs
+    pub fn missing_required(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if self.title.is_none() { missing.push("--title"); }
+        if self.price.is_none() { missing.push("--price"); }
+        if self.quantity.is_none() { missing.push("--quantity"); }
+        if self.category.is_none() { missing.push("--category"); }
+        if self.condition.is_none() { missing.push("--condition"); }
+        missing
+    }
+}
+
+/// The known value `value` is probably a misspelling of: its closest near match, unless it's
+/// already known exactly
+fn spelling_hint<'a>(value: &str, known: &'a [String]) -> Option<&'a str> {
+    if known.iter().any(|known| known == value) {
+        return None;
+    }
+    closest_match(value, known.iter().map(String::as_str))
+}
+
+/// Same rule as import and update: prompt only on a terminal, unless INVENTORY_NONINTERACTIVE is set
+pub fn can_prompt() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal() && std::env::var("INVENTORY_NONINTERACTIVE").is_err()
+}
+
+fn first_error(result: ValidationResult) -> std::result::Result<(), String> {
+    match result.errors.into_iter().next() {
+        Some(error) => Err(error.message),
+        None => Ok(()),
+    }
+}
+
+/// Asks for one field until `parse` accepts the answer, showing its complaint each time. An
+/// empty answer is `None` for optional fields. Input running out is an error, not a hang.
+fn ask<T>(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    label: &str,
+    hint: Option<&str>,
+    optional: bool,
+    mut parse: impl FnMut(&str) -> std::result::Result<T, String>,
+) -> Result<Option<T>> {
+    loop {
+        let hint = hint.map(|hint| format!(" ({})", hint)).unwrap_or_default();
+        let skip = if optional { " [Enter to skip]" } else { "" };
+        write!(output, "{}{}{}: ", label, hint, skip)?;
+        output.flush()?;
+        
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            anyhow::bail!("Input ended before {} was entered", label.to_lowercase());
+        }
+        let answer = answer.trim();
+        if answer.is_empty() && optional {
+            return Ok(None);
+        }
+        match parse(answer) {
+            Ok(value) => return Ok(Some(value)),
+            Err(message) => writeln!(output, "  {}", message)?,
+        }
+    }
+}
+
+/// Fills in a draft by prompting on `input`/`output`: every missing required field, the UPC and
+/// size when `platform` requires them, and with `all_fields` the optional ones not yet given.
+/// Each answer is checked with the same validators as the flags. Ends by showing the item and
+/// asking to confirm; `None` if that's declined.
+pub fn prompt_for_item(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    mut draft: ItemDraft,
+    all_fields: bool,
+    platform: Platform,
+    conn: &Connection,
+) -> Result<Option<ItemDraft>> {
+    let validate_title = match platform {
+        Platform::Ebay => validate_title_ebay,
+        Platform::Stockx => validate_title_stockx,
+        Platform::Poshmark => validate_title_poshmark,
+        Platform::Mercari => validate_title_mercari,
+    };
+    let text = |validate: fn(&str) -> Result<ValidationResult>| move |answer: &str| {
+        validate(answer).map_err(|e| e.to_string()).and_then(first_error).map(|_| answer.to_string())
+    };
+    
+    if draft.title.is_none() {
+        draft.title = ask(input, output, "Title", None, false, text(validate_title))?;
+    }
+    if draft.currency.is_none() && all_fields {
+        draft.currency = ask(input, output, "Currency", Some(DEFAULT_CURRENCY), true, |answer| {
+            first_error(validate_currency(answer).map_err(|e| e.to_string())?)?;
+            Ok(answer.to_ascii_uppercase())
+        })?;
+    }
+    let currency = draft.currency.clone().unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+    if draft.price.is_none() {
+        draft.price = ask(input, output, "Price", Some(&currency), false, |answer| {
+            let price: f64 = answer.parse().map_err(|_| format!("'{}' isn't a number", answer))?;
+            first_error(validate_price(price, &currency).map_err(|e| e.to_string())?)?;
+            Ok(price)
+        })?;
+    }
+    if draft.quantity.is_none() {
+        draft.quantity = ask(input, output, "Quantity", None, false, |answer| {
+            let quantity: i32 = answer.parse().map_err(|_| format!("'{}' isn't a whole number", answer))?;
+            first_error(validate_quantity(quantity).map_err(|e| e.to_string())?)?;
+            Ok(quantity)
+        })?;
+    }
+    if draft.category.is_none() {
+        draft.category = ask(input, output, "Category", None, false, text(validate_category))?;
+    }
+    if draft.condition.is_none() {
+        let hint = CONDITIONS.join(", ");
+        draft.condition = ask(input, output, "Condition", Some(&hint), false, text(validate_condition))?
+            .map(|condition| normalize_condition(&condition).to_lowercase());
+    }
+    if draft.brand.is_none() && all_fields {
+        draft.brand = ask(input, output, "Brand", None, true, text(validate_brand))?;
+    }
+    if draft.description.is_none() && all_fields {
+        draft.description = ask(input, output, "Description", None, true, |answer| Ok(answer.to_string()))?;
+    }
+    if draft.upc.is_none() && (all_fields || platform == Platform::Stockx) {
+        let optional = platform != Platform::Stockx;
+        draft.upc = ask(input, output, "UPC", Some("UPC-A or EAN-13"), optional, text(validate_upc_ebay))?;
+    }
+    if draft.size.is_none() && (all_fields || matches!(platform, Platform::Stockx | Platform::Poshmark)) {
+        let optional = platform == Platform::Ebay || platform == Platform::Mercari;
+        draft.size = ask(input, output, "Size", None, optional, |answer| Ok(answer.to_string()))?;
+    }
+    if draft.sku.is_none() && all_fields {
+        draft.sku = ask(input, output, "SKU", None, true, |answer| {
+            first_error(validate_sku(conn, answer).map_err(|e| e.to_string())?)?;
+            Ok(answer.to_string())
+        })?;
+    }
+    
+    writeln!(output, "\nNew item:")?;
+    let price = draft.price.map(|price| format_money(price, &currency)).unwrap_or_default();
+    let fields = [
+        ("Title", draft.title.clone()),
+        ("Price", Some(price)),
+        ("Quantity", draft.quantity.map(|quantity| quantity.to_string())),
+        ("Category", draft.category.clone()),
+        ("Condition", draft.condition.clone()),
+        ("Brand", draft.brand.clone()),
+        ("Description", draft.description.clone()),
+        ("UPC", draft.upc.clone()),
+        ("Size", draft.size.clone()),
+        ("SKU", draft.sku.clone()),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            writeln!(output, "  {:<12} {}", format!("{}:", label), value)?;
+        }
+    }
+    let confirmed = ask(input, output, "Add this item? [Y/n]", None, true, |answer| {
+        match answer.to_lowercase().as_str() {
+            "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            _ => Err("Answer y or n".to_string()),
+        }
+    })?;
+    Ok(if confirmed.unwrap_or(true) { Some(draft) } else { None })
+}
+
+/// Runs the wizard on the terminal when `interactive` is set or required flags are missing, and
+/// fails fast with the missing flags when there's no terminal to ask on. `None` if cancelled.
+pub fn complete_item(draft: ItemDraft, interactive: bool, platform: Platform, conn: &Connection) -> Result<Option<ItemDraft>> {
+    let missing = draft.missing_required();
+    if missing.is_empty() && !interactive {
+        return Ok(Some(draft));
+    }
+    if !can_prompt() {
+        if missing.is_empty() {
+            anyhow::bail!("--interactive needs a terminal; pass every field as a flag instead");
+        }
+        anyhow::bail!("Missing required options: {} (run in a terminal to be prompted for them)", missing.join(", "));
+    }
+    prompt_for_item(&mut io::stdin().lock(), &mut io::stdout(), draft, interactive, platform, conn)
+}
 
 pub fn handle_add_item(
     title: String,
@@ -22,28 +232,69 @@ pub fn handle_add_item(
     colorway: Option<String>,
     release_date: Option<String>,
     internal_notes: Option<String>,
+    currency: Option<String>,
+    sku: Option<String>,
+    strict_brands: bool,
+    platform: Platform,
+    conn: &Connection,
 ) -> Result<()> {
-    // Initialize database connection
-    let conn = initialize_database(None)?;
+    let currency = currency.unwrap_or_else(|| DEFAULT_CURRENCY.to_string());
+    let condition = normalize_condition(&condition);
     
-    // Validate the item for all platforms (we'll use eBay as default for now)
-    let validation_result = validate_item_ebay(
-        &title,
+    // Validate the item for the marketplace it's going to be listed on
+    let mut validation_result = validate_item_for(platform, &ItemFields {
+        title: &title,
         price,
         quantity,
-        &category,
-        &condition,
-        brand.as_deref(),
-        upc.as_deref(),
-    )?;
+        category: &category,
+        condition: &condition,
+        brand: brand.as_deref(),
+        upc: upc.as_deref(),
+        size: size.as_deref(),
+        currency: &currency,
+    })?;
+    if let Some(sku) = &sku {
+        validation_result.errors.extend(validate_sku(conn, sku)?.errors);
+    }
+    if let Some(release_date) = &release_date {
+        validation_result.errors.extend(validate_release_date(release_date)?.errors);
+    }
+    
+    // A new brand or category one typo away from an existing one would split it in two
+    let (brands, categories) = (distinct_brands(conn)?, distinct_categories(conn)?);
+    let brand_hint = brand.as_deref().and_then(|brand| spelling_hint(brand, &brands));
+    // Differing only in case isn't a typo: the item joins that category under its spelling
+    let category_hint = spelling_hint(&category, &categories).filter(|known| !known.eq_ignore_ascii_case(&category));
+    if let Some(brand) = brand.as_deref().filter(|brand| strict_brands && !brands.iter().any(|known| known == brand)) {
+        let message = match brand_hint {
+            Some(known) => format!("Unknown brand '{}'. Did you mean '{}'?", brand, known),
+            None => format!("Unknown brand '{}'; --strict-brands only accepts brands already in the inventory", brand),
+        };
+        validation_result.add_error("brand", &message, None, Some(brand));
+    }
     
     // If validation fails, output JSON errors and return
     if !validation_result.is_valid() {
+        for err in &validation_result.errors {
+            log::info!("Item '{}' failed {} validation: {}: {}", title, platform, err.field, err.message);
+        }
         let error_json = validation_result.to_json()?;
         eprintln!("{}", error_json);
-        return Ok(());
+        anyhow::bail!(validation_failed(format!("Item failed {} validation", platform)));
     }
     
+    if let (Some(brand), Some(known)) = (&brand, brand_hint) {
+        eprintln!("Warning: brand '{}' is close to existing brand '{}'; check the spelling", brand, known);
+    }
+    if let Some(known) = category_hint {
+        eprintln!("Warning: category '{}' is close to existing category '{}'; check the spelling", category, known);
+    }
+    
+    // Validation only accepts known codes, so this just upper-cases it
+    let currency = normalize_currency(&currency).unwrap_or(currency);
+    // Stored as YYYY-MM-DD whichever accepted format it was given in; blank means none
+    let release_date = release_date.as_deref().and_then(normalize_release_date);
+    
     // Create the item for database insertion
     let item = NewItem {
         title: &title,
@@ -65,29 +316,40 @@ pub fn handle_add_item(
         platform_status: None, // TODO: Add platform status handling
         internal_notes: internal_notes.as_deref(),
         status: "active",
+        currency: Some(&currency),
+        import_run: None,
+        sku: sku.as_deref(),
     };
     
-    // Insert the item into the database
-    let rows_affected = insert_item(&conn, &item)?;
+    // Insert the item into the database, logging it so `undo` can remove it again
+    let tx = conn.unchecked_transaction()?;
+    let rows_affected = insert_item(&tx, &item)?;
+    record_operation(&tx, OperationKind::Add, &snapshot_items(&tx, &[tx.last_insert_rowid()])?)?;
+    tx.commit()?;
     
     if rows_affected == 1 {
-        println!("Successfully added item: {} (${:.2}, qty: {})", title, price, quantity);
-        println!("Category: {}, Condition: {}", category, condition);
+        log::info!("Added item '{}'", title);
+        println!("Successfully added item: {} ({}, qty: {})", title, format_money(price, &currency), quantity);
+        status!("Category: {}, Condition: {}", category, condition);
         
         if let Some(brand_name) = brand {
-            println!("Brand: {}", brand_name);
+            status!("Brand: {}", brand_name);
         }
         
         if let Some(desc) = description {
-            println!("Description: {}", desc);
+            status!("Description: {}", desc);
         }
         
         if let Some(upc_code) = upc {
-            println!("UPC: {}", upc_code);
+            status!("UPC: {}", upc_code);
+        }
+        
+        if let Some(sku_code) = sku {
+            status!("SKU: {}", sku_code);
         }
         
         if let Some(size_value) = size {
-            println!("Size: {}", size_value);
+            status!("Size: {}", size_value);
         }
     } else {
         eprintln!("Error: Failed to insert item into database");
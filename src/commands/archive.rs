@@ -0,0 +1,35 @@
+// Archive command implementation
+// Implements: inventory archive --id 1,2,3 [--status sold|draft|active]
+
+use anyhow::Result;
+use rusqlite::Connection;
+use crate::db::history::{record_operation, snapshot_items, OperationKind};
+use crate::db::queries::set_item_status;
+use crate::error::error::not_found;
+
+/// Mark items as sold or draft (or back to active) instead of deleting them
+pub fn handle_archive(ids: &[i32], status: &str, conn: &Connection) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let mut updated = 0;
+
+    // A status change is logged as an update, so `undo` puts the old statuses back
+    let item_ids: Vec<i64> = ids.iter().map(|&id| id as i64).collect();
+    record_operation(&tx, OperationKind::Update, &snapshot_items(&tx, &item_ids)?)?;
+
+    for &id in ids {
+        if set_item_status(&tx, id as i64, status)? == 0 {
+            eprintln!("Warning: Item with ID {} does not exist", id);
+        } else {
+            updated += 1;
+        }
+    }
+
+    if updated == 0 {
+        anyhow::bail!(not_found("None of the requested items exist"));
+    }
+    tx.commit()?;
+
+    println!("✓ Marked {} item(s) as {}", updated, status);
+    log::info!("Marked {} item(s) as {}: {:?}", updated, status, ids);
+    Ok(())
+}
@@ -0,0 +1,104 @@
+// Backup and restore command implementation
+// Implements: inventory backup [--output PATH] and inventory restore --input FILE --force
+
+use anyhow::{Context, Result};
+use dirs::home_dir;
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::db::migrate::run_migrations;
+use crate::error::error::not_found;
+
+/// Pages copied per backup step; small steps keep the live database available to other writers
+const PAGES_PER_STEP: i32 = 256;
+
+/// Returns the default backup directory (~/.inventory/backups)
+pub fn default_backup_dir() -> PathBuf {
+    let mut dir = home_dir().expect("Could not determine home directory");
+    dir.push(".inventory/backups");
+    dir
+}
+
+/// Resolves where a backup is written: a path ending in `.db` is used as is,
+/// anything else is a directory that gets a timestamped file
+pub fn backup_path(output: Option<&Path>) -> PathBuf {
+    match output {
+        Some(path) if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("db")) => path.to_path_buf(),
+        Some(dir) => dir.join(backup_filename()),
+        None => default_backup_dir().join(backup_filename()),
+    }
+}
+
+fn backup_filename() -> String {
+    format!("inventory_{}.db", chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S"))
+}
+
+/// Counts the items in a database, failing if it has no items table
+fn count_inventory_items(conn: &Connection) -> Result<i64> {
+    let has_items: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'items'",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_items == 0 {
+        anyhow::bail!("Not an inventory database: no items table found");
+    }
+    Ok(conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))?)
+}
+
+/// Copies the live database to a timestamped `.db` file with SQLite's online backup API,
+/// which is consistent even while other connections write in WAL mode.
+/// Returns the path of the backup file.
+pub fn handle_backup(conn: &Connection, output: Option<PathBuf>) -> Result<PathBuf> {
+    let path = backup_path(output.as_deref());
+    if path.exists() {
+        anyhow::bail!("Backup file already exists: {}", path.display());
+    }
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create backup directory: {}", parent.display()))?;
+    }
+
+    conn.backup(DatabaseName::Main, &path, None)
+        .with_context(|| format!("Failed to back up database to {}", path.display()))?;
+
+    let items = count_inventory_items(conn)?;
+    println!("✓ Backed up {} items to {}", items, path.display());
+    log::info!("Backed up {} items to {}", items, path.display());
+    Ok(path)
+}
+
+/// Replaces the live database with the contents of a backup file.
+/// Refuses to run without `force`, and checks the source is an inventory database before copying.
+pub fn handle_restore(conn: &mut Connection, input: &Path, force: bool) -> Result<()> {
+    if !input.is_file() {
+        anyhow::bail!(not_found(format!("Backup file not found: {}", input.display())));
+    }
+
+    // Open read-only so a bad path can't create or modify anything
+    let source = Connection::open_with_flags(input, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open backup file: {}", input.display()))?;
+    let items = count_inventory_items(&source)
+        .with_context(|| format!("Cannot restore from {}", input.display()))?;
+
+    if !force {
+        anyhow::bail!(
+            "Restoring replaces the live database with {} items from {}; re-run with --force to proceed",
+            items,
+            input.display()
+        );
+    }
+
+    Backup::new(&source, conn)?
+        .run_to_completion(PAGES_PER_STEP, Duration::from_millis(0), None)
+        .with_context(|| format!("Failed to restore database from {}", input.display()))?;
+
+    // Backups taken by older versions may predate some columns
+    run_migrations(conn)?;
+
+    println!("✓ Restored {} items from {}", items, input.display());
+    log::info!("Restored {} items from {}", items, input.display());
+    Ok(())
+}
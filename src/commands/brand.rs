@@ -0,0 +1,101 @@
+// Brand command implementation
+// Implements: inventory brand rename FROM TO [--ignore-case] | merge FROM... --into TO [--ignore-case]
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use rusqlite::{params, Connection};
+use crate::db::history::{record_operation, snapshot_items, OperationKind};
+use crate::db::timestamp::now_timestamp;
+use crate::error::error::{not_found, validation_failed};
+
+#[derive(Debug, Subcommand)]
+pub enum BrandAction {
+    /// Change a brand on every item that has it, e.g. to fix a misspelling
+    Rename {
+        /// Brand to replace
+        from: String,
+        /// Brand to set instead
+        to: String,
+        /// Also match FROM spelled with different case
+        #[arg(long)]
+        ignore_case: bool,
+    },
+    /// Move the items of several brands onto one
+    Merge {
+        /// Brands to replace
+        #[arg(required = true)]
+        from: Vec<String>,
+        /// Brand to set instead
+        #[arg(long)]
+        into: String,
+        /// Also match each FROM spelled with different case
+        #[arg(long)]
+        ignore_case: bool,
+    },
+}
+
+fn brand_condition(ignore_case: bool) -> &'static str {
+    if ignore_case { "brand = ? COLLATE NOCASE" } else { "brand = ?" }
+}
+
+/// IDs of the items with `brand` whose brand isn't already `to`, or None if no item has `brand`
+fn items_to_rebrand(conn: &Connection, brand: &str, to: &str, ignore_case: bool) -> Result<Option<Vec<i64>>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT item_id, brand IS NOT ? FROM items WHERE {} ORDER BY item_id", brand_condition(ignore_case)
+    ))?;
+    let matches = stmt.query_map(params![to, brand], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, bool>(1)?)))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+    if matches.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(matches.into_iter().filter(|(_, changes)| *changes).map(|(id, _)| id).collect()))
+}
+
+/// Sets the brand of every item with one of the `from` brands to `to`, in one transaction that
+/// `undo` reverses as a whole. Matching is exact unless `ignore_case`. Fails without changing
+/// anything if no item has one of the `from` brands. Returns how many items changed; items
+/// already spelled `to` don't count.
+pub fn rebrand_items(conn: &Connection, from: &[String], to: &str, ignore_case: bool) -> Result<usize> {
+    let to = to.trim();
+    if to.is_empty() {
+        anyhow::bail!(validation_failed("Brand can't be blank"));
+    }
+    let mut ids = Vec::new();
+    for brand in from {
+        match items_to_rebrand(conn, brand, to, ignore_case)? {
+            Some(matched) => ids.extend(matched),
+            None => anyhow::bail!(not_found(format!("No items have brand '{}'", brand))),
+        }
+    }
+    ids.sort_unstable();
+    ids.dedup();
+
+    let tx = conn.unchecked_transaction()?;
+    record_operation(&tx, OperationKind::Update, &snapshot_items(&tx, &ids)?)?;
+    let now = now_timestamp();
+    let mut changed = 0;
+    for brand in from {
+        changed += tx.execute(
+            &format!("UPDATE items SET brand = ?, last_updated = ? WHERE {} AND brand IS NOT ?", brand_condition(ignore_case)),
+            params![to, now, brand, to],
+        )?;
+    }
+    tx.commit().context("Failed to commit the brand change")?;
+    log::info!("Changed brand {:?} to '{}' on {} item(s)", from, to, changed);
+    Ok(changed)
+}
+
+/// Runs a brand subcommand and reports how many items it changed
+pub fn handle_brand(conn: &Connection, action: BrandAction) -> Result<()> {
+    match action {
+        BrandAction::Rename { from, to, ignore_case } => {
+            let changed = rebrand_items(conn, std::slice::from_ref(&from), &to, ignore_case)?;
+            println!("✓ Renamed brand '{}' to '{}' on {} item(s)", from, to.trim(), changed);
+        }
+        BrandAction::Merge { from, into, ignore_case } => {
+            let changed = rebrand_items(conn, &from, &into, ignore_case)?;
+            println!("✓ Merged {} into '{}' on {} item(s)", from.join(", "), into.trim(), changed);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+// Cache command implementation
+// Implements: inventory cache [--stats] [--clear]
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use crate::commands::list::OutputFormat;
+use crate::commands::vacuum::format_size;
+use crate::config::optimization::{CacheStats, QueryCache};
+use crate::output::format::{to_json, OutputOptions};
+
+fn print_stats(stats: &CacheStats, format: Option<&OutputFormat>, options: &OutputOptions) -> Result<()> {
+    if let Some(OutputFormat::Json) = format {
+        println!("{}", to_json(stats, options)?);
+        return Ok(());
+    }
+    println!("Entries: {}", stats.entries);
+    println!("Hits: {}", stats.hits);
+    println!("Misses: {}", stats.misses);
+    println!("Approximate memory: {}", format_size(stats.approx_bytes as u64));
+    Ok(())
+}
+
+/// Shows what the query cache holds and how often `filter` and `stats` found their results in
+/// it, or empties it. With neither flag it shows the stats; with both it shows them for the
+/// cache as it was before clearing. Clearing also zeroes the hit and miss counts and rewrites
+/// the cache file at `cache_path` right away, when there is one.
+pub fn handle_cache(
+    cache: &QueryCache,
+    cache_path: Option<&Path>,
+    clear: bool,
+    stats: bool,
+    format: Option<OutputFormat>,
+    options: &OutputOptions,
+) -> Result<()> {
+    if stats || !clear {
+        print_stats(&cache.stats(), format.as_ref(), options)?;
+    }
+    if clear {
+        let entries = cache.len();
+        cache.reset();
+        if let Some(cache_path) = cache_path {
+            cache.save_to(cache_path)
+                .with_context(|| format!("Failed to clear the query cache at {}", cache_path.display()))?;
+        }
+        println!("✓ Cleared {} cached result(s)", entries);
+    }
+    Ok(())
+}
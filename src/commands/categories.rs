@@ -0,0 +1,138 @@
+// Categories command implementation
+// Implements: inventory categories [list [--format F] | rename OLD NEW | merge FROM... --into TO]
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection};
+use crate::commands::list::OutputFormat;
+use crate::db::history::{record_operation, snapshot_items, OperationKind};
+use crate::db::queries::{find_category, items_in_category, list_categories, Category};
+use crate::db::timestamp::now_timestamp;
+use crate::error::error::{not_found, validation_failed};
+use crate::output::format::{format_columns, OutputOptions};
+
+#[derive(Debug, Subcommand)]
+pub enum CategoryAction {
+    /// List every category with how many items it has (the default)
+    List {
+        /// Output format [default: table]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+    /// Rename a category, refiling every item in it under the new name
+    Rename {
+        /// Current name (case doesn't matter)
+        old: String,
+        /// New name; only its case may match another category's
+        new: String,
+    },
+    /// Move every item in the given categories into another one, then delete them
+    Merge {
+        /// Categories to merge away
+        #[arg(required = true)]
+        from: Vec<String>,
+        /// Category that receives their items
+        #[arg(long)]
+        into: String,
+    },
+}
+
+fn existing_category(conn: &Connection, name: &str) -> Result<Category> {
+    find_category(conn, name)?
+        .ok_or_else(|| not_found(format!("No category named '{}'", name.trim())).into())
+}
+
+/// Refiles the items of each of `from` under the category named `to`, recording them as one
+/// operation for `undo`. The category trigger points each item's `category_id` at `to`.
+fn refile_items(conn: &Connection, from: &[&Category], to: &str) -> Result<usize> {
+    let mut ids = Vec::new();
+    for category in from {
+        ids.extend(items_in_category(conn, category.id)?);
+    }
+    record_operation(conn, OperationKind::Update, &snapshot_items(conn, &ids)?)?;
+    let now = now_timestamp();
+    for category in from {
+        conn.execute(
+            "UPDATE items SET category = ?, last_updated = ? WHERE category_id = ?",
+            params![to, now, category.id],
+        )?;
+    }
+    Ok(ids.len())
+}
+
+/// Prints the categories with their item counts, as a table unless another format is given
+pub fn handle_list_categories(conn: &Connection, format: Option<OutputFormat>, options: &OutputOptions) -> Result<()> {
+    let format = format.unwrap_or(OutputFormat::Table);
+    let columns = vec!["category".to_string(), "items".to_string()];
+    let rows: Vec<Vec<Value>> = list_categories(conn)?.into_iter()
+        .map(|category| vec![Value::Text(category.name), Value::Integer(category.items)])
+        .collect();
+    print!("{}", format_columns(&columns, &rows, &format.to_string(), options)?);
+    Ok(())
+}
+
+/// Renames a category and every item filed under it, in one transaction. Returns how many
+/// items were refiled. Changing only the case of a name is allowed; taking another category's
+/// name isn't, since that's a merge.
+pub fn handle_rename_category(conn: &Connection, old: &str, new: &str) -> Result<usize> {
+    let new = new.trim();
+    if new.is_empty() {
+        anyhow::bail!(validation_failed("Category name can't be blank"));
+    }
+    let category = existing_category(conn, old)?;
+    if let Some(existing) = find_category(conn, new)? {
+        if existing.id != category.id {
+            anyhow::bail!(validation_failed(format!(
+                "Category '{}' already exists; use `categories merge {} --into {}` to combine them",
+                existing.name, category.name, existing.name
+            )));
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("UPDATE categories SET name = ? WHERE category_id = ?", params![new, category.id])?;
+    let refiled = refile_items(&tx, &[&category], new)?;
+    tx.commit().context("Failed to commit the category rename")?;
+
+    println!("✓ Renamed category '{}' to '{}' ({} item(s))", category.name, new, refiled);
+    log::info!("Renamed category '{}' to '{}', refiling {} item(s)", category.name, new, refiled);
+    Ok(refiled)
+}
+
+/// Moves the items of each `from` category into `into` and deletes the emptied categories, in
+/// one transaction. Returns how many items moved.
+pub fn handle_merge_categories(conn: &Connection, from: &[String], into: &str) -> Result<usize> {
+    let target = existing_category(conn, into)?;
+    let mut sources: Vec<Category> = Vec::new();
+    for name in from {
+        let source = existing_category(conn, name)?;
+        if source.id == target.id {
+            anyhow::bail!(validation_failed(format!("Can't merge category '{}' into itself", target.name)));
+        }
+        if !sources.iter().any(|seen| seen.id == source.id) {
+            sources.push(source);
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let moved = refile_items(&tx, &sources.iter().collect::<Vec<_>>(), &target.name)?;
+    for source in &sources {
+        tx.execute("DELETE FROM categories WHERE category_id = ?", [source.id])?;
+    }
+    tx.commit().context("Failed to commit the category merge")?;
+
+    let names: Vec<&str> = sources.iter().map(|source| source.name.as_str()).collect();
+    println!("✓ Merged {} into '{}' ({} item(s) moved)", names.join(", "), target.name, moved);
+    log::info!("Merged categories {:?} into '{}', moving {} item(s)", names, target.name, moved);
+    Ok(moved)
+}
+
+/// Runs a categories subcommand; with none, lists the categories
+pub fn handle_categories(conn: &Connection, action: Option<CategoryAction>, default_format: Option<OutputFormat>, options: &OutputOptions) -> Result<()> {
+    match action.unwrap_or(CategoryAction::List { format: None }) {
+        CategoryAction::List { format } => handle_list_categories(conn, format.or(default_format), options),
+        CategoryAction::Rename { old, new } => handle_rename_category(conn, &old, &new).map(|_| ()),
+        CategoryAction::Merge { from, into } => handle_merge_categories(conn, &from, &into).map(|_| ()),
+    }
+}
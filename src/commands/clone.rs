@@ -0,0 +1,52 @@
+// Clone command implementation
+// Implements: inventory clone --id 3 [--size 11] [--colorway Bred] [--quantity 2] [--price 210] [--upc 012345678905]
+
+use anyhow::Result;
+use rusqlite::Connection;
+use crate::db::history::{record_operation, snapshot_items, OperationKind};
+use crate::db::queries::{get_full_item, insert_item, NewItem};
+use crate::error::error::not_found;
+
+/// Copy an item under a new ID, e.g. to add another size or colorway of it. The copy starts out
+/// active and unlisted whatever the original's status; the given overrides replace its values.
+pub fn handle_clone(
+    id: i32,
+    size: Option<String>,
+    colorway: Option<String>,
+    quantity: Option<i32>,
+    price: Option<f64>,
+    upc: Option<String>,
+    conn: &Connection,
+) -> Result<()> {
+    let Some(source) = get_full_item(conn, id as i64)? else {
+        anyhow::bail!(not_found(format!("Item with ID {} does not exist", id)));
+    };
+    if quantity.is_some_and(|quantity| quantity < 0) {
+        anyhow::bail!("Quantity must be non-negative");
+    }
+    if price.is_some_and(|price| price < 0.0) {
+        anyhow::bail!("Price must be non-negative");
+    }
+
+    let copy = NewItem {
+        size: size.as_deref().or(source.size.as_deref()),
+        colorway: colorway.as_deref().or(source.colorway.as_deref()),
+        quantity: quantity.unwrap_or(source.quantity),
+        price: price.unwrap_or(source.price),
+        upc: upc.as_deref().or(source.upc.as_deref()),
+        status: "active",
+        platform_status: None,
+        ..source.as_new_item()
+    };
+
+    // Logged like an add, so `undo` removes the copy again
+    let tx = conn.unchecked_transaction()?;
+    insert_item(&tx, &copy)?;
+    let new_id = tx.last_insert_rowid();
+    record_operation(&tx, OperationKind::Add, &snapshot_items(&tx, &[new_id])?)?;
+    tx.commit()?;
+
+    log::info!("Cloned item {} as {}", id, new_id);
+    println!("Successfully cloned item {} as item {}", id, new_id);
+    Ok(())
+}
@@ -8,14 +8,34 @@ pub fn handle_commands() -> Result<()> {
     println!("  add        - Add a new item to inventory");
     println!("  update     - Update items from CSV file");
     println!("  delete     - Delete an item by ID");
+    println!("  clone      - Copy an item, optionally with a different size, colorway or quantity");
+    println!("  archive    - Mark items as sold or draft without deleting them");
+    println!("  tag        - Add or remove an item's tags");
+    println!("  history    - Show an item's recorded changes");
+    println!("  categories - List, rename or merge categories");
+    println!("  brand      - Rename or merge brands across all items");
+    println!("  reprice    - Raise or lower prices by a percentage or amount");
     println!("  list       - List inventory items");
     println!("  import     - Import items from CSV file");
     println!("  filter     - Filter inventory items");
+    println!("  count      - Count the items matching the given filters");
     println!("  migrate    - Run database migrations");
     println!("  fields     - Show field shortcuts");
     println!("  commands   - List available commands");
     println!("  stats      - Show inventory statistics");
     println!("  validate   - Validate a CSV file against the inventory schema");
+    println!("  validate-item - Check an item against each marketplace's rules");
+    println!("  search     - Full-text search over titles and descriptions");
+    println!("  fix-timestamps - Normalize last_updated values to RFC3339");
+    println!("  vacuum     - Reclaim space from deleted rows and refresh query statistics");
+    println!("  cache      - Show query cache statistics, or clear the cache");
+    println!("  backup     - Back up the database to a timestamped .db file");
+    println!("  restore    - Restore the database from a backup (requires --force)");
+    println!("  rollback-import - Remove the items an import run created and revert the ones it updated");
+    println!("  db-diff    - Compare the live database with another database file");
+    println!("  export     - Export items, or a random sample of them, to a file");
+    println!("  plugins    - List exporter and formatter plugins");
+    println!("  undo       - Reverse the most recent add, update, delete or import");
     
     Ok(())
 } 
\ No newline at end of file
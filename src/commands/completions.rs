@@ -0,0 +1,15 @@
+// Completions command implementation
+// Implements: inventory completions bash|zsh|fish|powershell|elvish
+
+use anyhow::Result;
+use clap_complete::{generate, Shell};
+use std::io;
+
+/// Name the completion script registers for: the installed binary, not the command's display name
+const BIN_NAME: &str = "openinv";
+
+/// Writes the completion script for `shell` to stdout, to be saved in the shell's completion directory
+pub fn handle_completions(shell: Shell, cmd: &mut clap::Command) -> Result<()> {
+    generate(shell, cmd, BIN_NAME, &mut io::stdout());
+    Ok(())
+}
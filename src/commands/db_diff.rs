@@ -0,0 +1,160 @@
+// Database diff command implementation
+// Implements: inventory db-diff --other backup.db
+
+use anyhow::{Context, Result};
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use std::path::Path;
+use crate::error::error::not_found;
+
+/// Schema name the other database is attached under
+const OTHER_SCHEMA: &str = "other_db";
+
+/// An item (ID and title) that exists in only one of the databases
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingItem {
+    pub item_id: i64,
+    pub title: String,
+}
+
+/// A field whose value differs between the two copies of an item
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDifference {
+    pub item_id: i64,
+    pub field: String,
+    pub live: String,
+    pub other: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DbDiff {
+    pub only_in_live: Vec<MissingItem>,
+    pub only_in_other: Vec<MissingItem>,
+    pub changed: Vec<FieldDifference>,
+}
+
+impl DbDiff {
+    pub fn is_empty(&self) -> bool {
+        self.only_in_live.is_empty() && self.only_in_other.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(i) => i.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Text(s) => s.clone(),
+        Value::Blob(b) => format!("<{} bytes>", b.len()),
+    }
+}
+
+fn item_columns(conn: &Connection, schema: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('items', ?)")?;
+    let columns = stmt.query_map([schema], |row| row.get(0))?
+        .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+    Ok(columns)
+}
+
+fn items_missing_from(conn: &Connection, present: &str, absent: &str) -> Result<Vec<MissingItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT item_id, title FROM {present}.items WHERE item_id NOT IN (SELECT item_id FROM {absent}.items) ORDER BY item_id"
+    ))?;
+    let items = stmt.query_map([], |row| Ok(MissingItem { item_id: row.get(0)?, title: row.get(1)? }))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+    Ok(items)
+}
+
+fn compare_attached(conn: &Connection) -> Result<DbDiff> {
+    let other_columns = item_columns(conn, OTHER_SCHEMA)?;
+    if other_columns.is_empty() {
+        anyhow::bail!("Not an inventory database: no items table found");
+    }
+
+    let mut diff = DbDiff {
+        only_in_live: items_missing_from(conn, "main", OTHER_SCHEMA)?,
+        only_in_other: items_missing_from(conn, OTHER_SCHEMA, "main")?,
+        changed: Vec::new(),
+    };
+
+    // Compare only the columns both schemas have; an older backup may predate some of them.
+    // Category IDs are numbered per database, so the names in `category` are compared instead.
+    let shared: Vec<String> = item_columns(conn, "main")?
+        .into_iter()
+        .filter(|column| column != "item_id" && column != "category_id" && other_columns.contains(column))
+        .collect();
+
+    for column in &shared {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT live.item_id, live.{column}, other.{column} FROM main.items AS live \
+             JOIN {OTHER_SCHEMA}.items AS other ON other.item_id = live.item_id \
+             WHERE live.{column} IS NOT other.{column}"
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok(FieldDifference {
+                item_id: row.get(0)?,
+                field: column.clone(),
+                live: display_value(&row.get(1)?),
+                other: display_value(&row.get(2)?),
+            })
+        })?;
+        for row in rows {
+            diff.changed.push(row?);
+        }
+    }
+
+    diff.changed.sort_by_key(|change| change.item_id);
+    Ok(diff)
+}
+
+/// Compares the live database with another database file by attaching it
+pub fn diff_databases(conn: &Connection, other: &Path) -> Result<DbDiff> {
+    if !other.is_file() {
+        anyhow::bail!(not_found(format!("Database file not found: {}", other.display())));
+    }
+
+    conn.execute(&format!("ATTACH DATABASE ? AS {}", OTHER_SCHEMA), [other.to_string_lossy()])
+        .with_context(|| format!("Failed to attach database: {}", other.display()))?;
+
+    let diff = compare_attached(conn);
+
+    // Detach even when the comparison failed so the connection is left as it was
+    conn.execute(&format!("DETACH DATABASE {}", OTHER_SCHEMA), [])?;
+
+    diff.with_context(|| format!("Cannot compare with {}", other.display()))
+}
+
+pub fn handle_db_diff(conn: &Connection, other: &Path) -> Result<()> {
+    let diff = diff_databases(conn, other)?;
+    let other_name = other.display();
+
+    println!("Comparing live database with {}", other_name);
+
+    if diff.is_empty() {
+        println!("No differences found.");
+        return Ok(());
+    }
+
+    if !diff.only_in_live.is_empty() {
+        println!("\nOnly in live database ({}):", diff.only_in_live.len());
+        for item in &diff.only_in_live {
+            println!("  #{} {}", item.item_id, item.title);
+        }
+    }
+
+    if !diff.only_in_other.is_empty() {
+        println!("\nOnly in {} ({}):", other_name, diff.only_in_other.len());
+        for item in &diff.only_in_other {
+            println!("  #{} {}", item.item_id, item.title);
+        }
+    }
+
+    if !diff.changed.is_empty() {
+        println!("\nField differences ({}):", diff.changed.len());
+        for change in &diff.changed {
+            println!("  #{} {}: live '{}', other '{}'", change.item_id, change.field, change.live, change.other);
+        }
+    }
+
+    Ok(())
+}
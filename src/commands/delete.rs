@@ -1,9 +1,13 @@
 // Delete item command implementation
-// TODO: Implement delete-item subcommand 
+// Implements: inventory delete --id 1,2,3 [--yes]
 
 use anyhow::{Result, Context};
 use rusqlite::Connection;
+use std::io::IsTerminal;
+use crate::db::history::{record_operation, snapshot_items, OperationKind};
 use crate::db::queries::count_items;
+use crate::db::retry::retry_on_busy;
+use crate::error::error::not_found;
 
 /// Check if an item exists in the database
 fn item_exists(conn: &Connection, id: i32) -> Result<bool> {
@@ -32,49 +36,110 @@ fn get_item_details(conn: &Connection, id: i32) -> Result<Option<(String, f64, i
     }
 }
 
-/// Delete an item from the database
-fn delete_item(conn: &Connection, id: i32) -> Result<usize> {
-    conn.execute("DELETE FROM items WHERE item_id = ?", [id])
-        .with_context(|| format!("Failed to delete item with ID: {}", id))
+/// Result of deleting a set of items
+#[derive(Debug, PartialEq, Eq)]
+pub struct DeleteOutcome {
+    pub deleted: usize,
+    /// Requested IDs that weren't in the database
+    pub missing: Vec<i32>,
 }
 
-pub fn handle_delete_item(id: i32, conn: &Connection) -> Result<()> {
-    // Check if the item exists
-    if !item_exists(conn, id)? {
-        anyhow::bail!("Item with ID {} does not exist", id);
-    }
-
-    // Get item details for confirmation
-    if let Some((title, price, quantity, category)) = get_item_details(conn, id)? {
-        println!("Item to delete:");
-        println!("  ID: {}", id);
-        println!("  Title: {}", title);
-        println!("  Price: ${:.2}", price);
-        println!("  Quantity: {}", quantity);
-        println!("  Category: {}", category);
+/// Delete the given items in one transaction, so either all existing items are removed or none are.
+/// IDs that don't exist are reported in the outcome rather than failing the deletion.
+/// The full rows are logged first so `undo` can restore them. A locked database is retried.
+pub fn delete_items(conn: &Connection, ids: &[i32]) -> Result<DeleteOutcome> {
+    // Retrying reruns the whole transaction, snapshot included
+    retry_on_busy(|| {
+        let tx = conn.unchecked_transaction()?;
+        let mut deleted = 0;
+        let mut missing = Vec::new();
         
-        // Simple confirmation prompt
-        print!("Are you sure you want to delete this item? (y/N): ");
-        std::io::Write::flush(&mut std::io::stdout())?;
+        let item_ids: Vec<i64> = ids.iter().map(|&id| id as i64).collect();
+        record_operation(&tx, OperationKind::Delete, &snapshot_items(&tx, &item_ids)?)?;
         
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
-        let input = input.trim().to_lowercase();
+        for &id in ids {
+            let rows = tx.execute("DELETE FROM items WHERE item_id = ?", [id])
+                .with_context(|| format!("Failed to delete item with ID: {}", id))?;
+            if rows == 0 {
+                missing.push(id);
+            }
+            deleted += rows;
+        }
         
-        if input != "y" && input != "yes" {
+        tx.commit().context("Failed to commit deletions")?;
+        Ok(DeleteOutcome { deleted, missing })
+    })
+}
+
+/// Ask once before deleting the listed items
+fn confirm_deletion(conn: &Connection, ids: &[i32]) -> Result<bool> {
+    let noun = if ids.len() == 1 { "this item" } else { "these items" };
+    println!("{}:", if ids.len() == 1 { "Item to delete" } else { "Items to delete" });
+    for &id in ids {
+        if let Some((title, price, quantity, category)) = get_item_details(conn, id)? {
+            println!("  ID: {}", id);
+            println!("  Title: {}", title);
+            println!("  Price: ${:.2}", price);
+            println!("  Quantity: {}", quantity);
+            println!("  Category: {}", category);
+        }
+    }
+    
+    print!("Are you sure you want to delete {}? (y/N): ", noun);
+    std::io::Write::flush(&mut std::io::stdout())?;
+    
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let input = input.trim().to_lowercase();
+    Ok(input == "y" || input == "yes")
+}
+
+pub fn handle_delete_items(ids: &[i32], yes: bool, conn: &Connection) -> Result<()> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    
+    if let [id] = ids[..] {
+        if !item_exists(conn, id)? {
+            anyhow::bail!(not_found(format!("Item with ID {} does not exist", id)));
+        }
+    }
+    
+    let mut existing = Vec::new();
+    for &id in &ids {
+        if item_exists(conn, id)? {
+            existing.push(id);
+        } else {
+            eprintln!("Warning: Item with ID {} does not exist", id);
+        }
+    }
+    if existing.is_empty() {
+        anyhow::bail!(not_found("None of the requested items exist"));
+    }
+    
+    if !yes {
+        // Prompting needs a person at the keyboard; scripts must opt in with --yes
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("Refusing to delete without confirmation: stdin is not a terminal (pass --yes to skip the prompt)");
+        }
+        if !confirm_deletion(conn, &existing)? {
             println!("Deletion cancelled.");
             return Ok(());
         }
     }
-
-    // Delete the item
-    let rows_affected = delete_item(conn, id)?;
     
-    if rows_affected == 1 {
+    let outcome = delete_items(conn, &existing)?;
+    log::info!("Deleted {} item(s): {:?}", outcome.deleted, existing);
+    // Items removed between the check and the deletion are reported the same way
+    for id in &outcome.missing {
+        eprintln!("Warning: Item with ID {} does not exist", id);
+    }
+    
+    if let [id] = existing[..] {
         println!("Successfully deleted item with ID: {}", id);
     } else {
-        anyhow::bail!("Failed to delete item with ID: {} (no rows affected)", id);
+        println!("Successfully deleted {} items", outcome.deleted);
     }
     
     Ok(())
-} 
\ No newline at end of file
+}
@@ -0,0 +1,223 @@
+// Export command implementation
+// Implements: inventory export [--format json|ndjson|yaml|csv|table | --exporter NAME] [--output FILE [--append]] [--sample N [--seed S]]
+//             inventory export --split-by category|brand --output DIR [--format ...]
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use crate::commands::list::{print_output, OutputFormat};
+use crate::db::queries::{get_items, get_sample_items};
+use crate::output::format::{format_items, InventoryItem, OutputOptions};
+use crate::plugins::loader::load_exporter;
+
+/// How many items to export and how to pick them
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub size: usize,
+    /// Picks the same items on every run instead of a fresh random subset
+    pub seed: Option<u32>,
+}
+
+/// The file an export writes
+#[derive(Debug, Clone)]
+pub struct ExportFile {
+    pub path: PathBuf,
+    /// Add to the end of the file instead of replacing it; csv and ndjson only
+    pub append: bool,
+}
+
+/// The first line of a file that has content, without its line ending. `None` when the file
+/// is missing or empty, so there's nothing to append to yet.
+fn first_line(path: &Path) -> Result<Option<String>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let mut line = String::new();
+    BufReader::new(file).read_line(&mut line)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(if line.is_empty() { None } else { Some(line.trim_end_matches(['\r', '\n']).to_string()) })
+}
+
+/// Fails for output that can't grow by adding to the end of the file
+fn check_appendable(format: &OutputFormat, exporter: Option<&str>) -> Result<()> {
+    if let Some(name) = exporter {
+        anyhow::bail!("--append works with csv and ndjson output, not exporter plugins ('{}')", name);
+    }
+    match format {
+        OutputFormat::Csv | OutputFormat::Ndjson => Ok(()),
+        OutputFormat::Json => anyhow::bail!(
+            "Can't append a JSON export: the file holds one array, and writing another after it makes it invalid. Use --format csv or ndjson"
+        ),
+        other => anyhow::bail!("Can't append {} output; --append works with csv and ndjson", other),
+    }
+}
+
+/// Adds `rendered` to the end of `path`. A CSV file that already has rows keeps its header, so
+/// the new header is dropped, and must match it.
+fn append_export(path: &Path, format: &OutputFormat, rendered: &str, existing_header: Option<&str>) -> Result<()> {
+    let mut rendered = rendered;
+    if let (OutputFormat::Csv, Some(existing)) = (format, existing_header) {
+        let (header, rows) = rendered.split_once('\n').unwrap_or((rendered, ""));
+        if header.trim_end_matches('\r') != existing {
+            anyhow::bail!(
+                "Can't append to {}: its header '{}' doesn't match the export's '{}'",
+                path.display(), existing, header.trim_end_matches('\r')
+            );
+        }
+        rendered = rows;
+    }
+    
+    let mut file = OpenOptions::new().read(true).create(true).append(true).open(path)
+        .with_context(|| format!("Failed to open export file: {}", path.display()))?;
+    // Start on a new line if the file's last one wasn't finished
+    if file.metadata()?.len() > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            file.write_all(b"\n")?;
+        }
+    }
+    file.write_all(rendered.as_bytes())
+        .with_context(|| format!("Failed to write export file: {}", path.display()))
+}
+
+/// Export items (all of them, or a random sample) to a file or stdout, formatted with
+/// `format` or, when given, by the exporter plugin named `exporter`
+pub fn handle_export(
+    conn: &Connection,
+    format: Option<OutputFormat>,
+    output: Option<ExportFile>,
+    include_archived: bool,
+    sample: Option<Sample>,
+    exporter: Option<&str>,
+    options: &OutputOptions,
+) -> Result<()> {
+    let format = format.unwrap_or(OutputFormat::Json);
+    
+    // What the file to append to starts with; appended ndjson doesn't repeat the schema line
+    let existing = match &output {
+        Some(file) if file.append => {
+            check_appendable(&format, exporter)?;
+            first_line(&file.path)?
+        }
+        _ => None,
+    };
+    let options = &OutputOptions { ndjson_schema: options.ndjson_schema && existing.is_none(), ..*options };
+    
+    let items = match sample {
+        Some(sample) => get_sample_items(conn, include_archived, sample.size, sample.seed)?,
+        None => get_items(conn, include_archived)?,
+    };
+    
+    let rendered = match exporter {
+        Some(name) => {
+            let plugin = load_exporter(name)?;
+            plugin.export(&items)
+                .with_context(|| format!("Exporter plugin '{}' returned an error", plugin.name()))?
+        }
+        None => format_items(&items, &format.to_string(), options)?,
+    };
+    
+    match output {
+        Some(ExportFile { path, append: true }) => {
+            // Headerless CSV has nothing to check against the file's first line
+            append_export(&path, &format, &rendered, existing.as_deref().filter(|_| options.csv_header))?;
+            println!("✓ Appended {} item(s) to {}", items.len(), path.display());
+        }
+        Some(ExportFile { path, append: false }) => {
+            std::fs::write(&path, &rendered)
+                .with_context(|| format!("Failed to write export file: {}", path.display()))?;
+            println!("✓ Exported {} item(s) to {}", items.len(), path.display());
+        }
+        None => print_output(&rendered),
+    }
+    
+    Ok(())
+}
+
+/// The field `export --split-by` writes one file per value of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SplitBy {
+    Category,
+    Brand,
+}
+
+impl SplitBy {
+    /// The item's value of the field; items without a brand share one group
+    fn value_of(self, item: &InventoryItem) -> &str {
+        match self {
+            SplitBy::Category => &item.category,
+            SplitBy::Brand => item.brand.as_deref().filter(|brand| !brand.trim().is_empty()).unwrap_or("no brand"),
+        }
+    }
+}
+
+/// Extension of the files a format is written to
+fn file_extension(format: &OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Json => "json",
+        OutputFormat::Ndjson => "ndjson",
+        OutputFormat::Yaml => "yaml",
+        OutputFormat::Csv => "csv",
+        OutputFormat::Table | OutputFormat::Custom(_) => "txt",
+    }
+}
+
+/// A category or brand as part of a file name: everything but letters, digits, `-` and `_`
+/// becomes `_`, so "Trading Cards/Pokémon" is safe on every platform as "Trading_Cards_Pokémon"
+pub fn safe_file_stem(value: &str) -> String {
+    let stem: String = value.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if stem.chars().all(|c| c == '_') { "unnamed".to_string() } else { stem }
+}
+
+/// Exports items to one file per distinct category or brand in `dir`, named
+/// `export_<value>.<ext>` and formatted as a single export would be. Values that clean up to
+/// the same file name get a numeric suffix. Returns each file written with its item count.
+pub fn handle_export_split(
+    conn: &Connection,
+    split_by: SplitBy,
+    format: Option<OutputFormat>,
+    dir: &Path,
+    include_archived: bool,
+    sample: Option<Sample>,
+    options: &OutputOptions,
+) -> Result<Vec<(PathBuf, usize)>> {
+    let format = format.unwrap_or(OutputFormat::Json);
+    let items = match sample {
+        Some(sample) => get_sample_items(conn, include_archived, sample.size, sample.seed)?,
+        None => get_items(conn, include_archived)?,
+    };
+    
+    let mut groups: BTreeMap<&str, Vec<InventoryItem>> = BTreeMap::new();
+    for item in &items {
+        groups.entry(split_by.value_of(item)).or_default().push(item.clone());
+    }
+    
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create export directory: {}", dir.display()))?;
+    let mut used = HashSet::new();
+    let mut written = Vec::new();
+    for (value, group) in &groups {
+        let stem = safe_file_stem(value);
+        let stem = (1..).map(|n| if n == 1 { stem.clone() } else { format!("{}_{}", stem, n) })
+            .find(|candidate| used.insert(candidate.to_lowercase()))
+            .expect("some suffix is unused");
+        let path = dir.join(format!("export_{}.{}", stem, file_extension(&format)));
+        let rendered = format_items(group, &format.to_string(), options)?;
+        std::fs::write(&path, rendered)
+            .with_context(|| format!("Failed to write export file: {}", path.display()))?;
+        println!("✓ Exported {} item(s) to {}", group.len(), path.display());
+        written.push((path, group.len()));
+    }
+    println!("Wrote {} file(s) covering {} item(s)", written.len(), items.len());
+    Ok(written)
+}
@@ -0,0 +1,60 @@
+// Field selection shared by filter --fields and list --columns
+// Field names match the items table columns; shortcuts such as `t` or `p` expand to them
+
+use anyhow::{anyhow, Result};
+
+pub fn get_field_shortcuts() -> std::collections::HashMap<&'static str, &'static str> {
+    let mut shortcuts = std::collections::HashMap::new();
+    shortcuts.insert("id", "item_id");
+    shortcuts.insert("t", "title");
+    shortcuts.insert("d", "description");
+    shortcuts.insert("p", "price");
+    shortcuts.insert("q", "quantity");
+    shortcuts.insert("c", "condition");
+    shortcuts.insert("cat", "category");
+    shortcuts.insert("b", "brand");
+    shortcuts.insert("u", "upc");
+    shortcuts.insert("cur", "currency");
+    shortcuts
+}
+
+pub fn expand_field_shortcuts(fields: &str) -> Result<Vec<String>> {
+    let shortcuts = get_field_shortcuts();
+    let field_list: Vec<&str> = fields.split(',').map(|s| s.trim()).collect();
+    let mut expanded_fields = Vec::new();
+    
+    for field in field_list {
+        if field.is_empty() {
+            continue;
+        }
+        
+        let expanded = if shortcuts.contains_key(field) {
+            shortcuts[field].to_string()
+        } else {
+            field.to_string()
+        };
+        
+        expanded_fields.push(expanded);
+    }
+    
+    if expanded_fields.is_empty() {
+        return Err(anyhow!("No valid fields specified"));
+    }
+    
+    Ok(expanded_fields)
+}
+
+pub fn validate_fields(fields: &[String]) -> Result<()> {
+    let valid_fields = [
+        "item_id", "title", "description", "price", "quantity", 
+        "category", "condition", "brand", "upc", "currency", "original_price"
+    ];
+    
+    for field in fields {
+        if !valid_fields.contains(&field.as_str()) {
+            return Err(anyhow!("Unknown field: {}", field));
+        }
+    }
+    
+    Ok(())
+}
@@ -1,14 +1,22 @@
 // Filter command implementation
-// Implements: inventory filter --price 10-50 --category clothing --condition new --brand nike -f id,title,price --format json
+// Implements: inventory filter --price 10-50 --quantity 1- [--out-of-stock] --category clothing --condition new --brand nike --tag grail --import-run ID --updated-since 2024-01-01 --filter-expr "category = 'books' OR price < 20" --group-by brand --include-archived -f id,title,price --format json
 
 use anyhow::{Result, anyhow};
+use clap::Args;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
+use crate::commands::fields::{expand_field_shortcuts, validate_fields};
+use crate::commands::filter_expr::FilterExpr;
 use crate::commands::list::OutputFormat;
-use crate::config::optimization::{PerformanceMonitor, QueryCache, measure_query_performance, generate_cache_key};
+use crate::config::optimization::{PerformanceMonitor, QueryCache, measure_query_performance, database_cache_key, generate_cache_key};
+use crate::config::currency::{format_money, DEFAULT_CURRENCY};
+use crate::db::queries::normalize_tag;
+use crate::db::timestamp::normalize_timestamp;
+use crate::error::error::validation_failed;
+use crate::output::format::{buffered_stdout, escape_csv_field, fit_cell, format_ndjson_records, render_table, stdout_ndjson, to_json, CsvWriter, OutputOptions};
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FilteredItem {
@@ -21,269 +29,432 @@ pub struct FilteredItem {
     pub condition: Option<String>,
     pub brand: Option<String>,
     pub upc: Option<String>,
+    pub currency: Option<String>,
+    pub original_price: Option<f64>,
+    /// `price * quantity`, only read (and only serialized) when `line_value` is a chosen field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_value: Option<f64>,
 }
 
-#[derive(Debug)]
+/// The synthetic field for an item's extended value, which isn't a column of its own
+const LINE_VALUE: &str = "line_value";
+
+/// What `--fields line_value` selects
+const LINE_VALUE_SQL: &str = "price * quantity AS line_value";
+
+/// Columns `--group-by` accepts
+const GROUP_BY_COLUMNS: [&str; 3] = ["brand", "category", "condition"];
+
+/// One group of `filter --group-by`: the items sharing a value of the grouping column
+#[derive(Debug, Serialize)]
+pub struct FilterGroup {
+    /// None for items without a value, such as those with no brand
+    pub group: Option<String>,
+    pub count: i64,
+    /// Sum of price * quantity over the group's items
+    pub total_value: f64,
+}
+
+#[derive(Debug, PartialEq)]
 pub struct PriceRange {
     pub min: Option<f64>,
     pub max: Option<f64>,
+    /// Bounds are inclusive unless set by a strict `>` or `<`
+    pub min_exclusive: bool,
+    pub max_exclusive: bool,
 }
 
 impl PriceRange {
-    fn parse(price_str: &str) -> Result<Self> {
+    fn inclusive(min: Option<f64>, max: Option<f64>) -> Self {
+        PriceRange { min, max, min_exclusive: false, max_exclusive: false }
+    }
+    
+    /// Parses `min-max`, `min-`, `-max`, an exact price, or a comparison: `>50`, `>=25`, `<100`, `<=100`
+    pub(crate) fn parse(price_str: &str) -> Result<Self> {
+        Self::parse_labeled(price_str, "price")
+    }
+    
+    /// Parses the same range syntax for another numeric column, naming it in errors
+    fn parse_labeled(price_str: &str, label: &str) -> Result<Self> {
+        // Two-character operators first so `>=` isn't read as `>` followed by `=25`
+        let comparisons = [(">=", true, false), ("<=", false, false), (">", true, true), ("<", false, true)];
+        for (op, is_min, exclusive) in comparisons {
+            if let Some(value) = price_str.strip_prefix(op) {
+                let price = value.trim().parse::<f64>().map_err(|_| anyhow!("Invalid {} value", label))?;
+                return Ok(if is_min {
+                    PriceRange { min: Some(price), max: None, min_exclusive: exclusive, max_exclusive: false }
+                } else {
+                    PriceRange { min: None, max: Some(price), min_exclusive: false, max_exclusive: exclusive }
+                });
+            }
+        }
+        
         if price_str.contains('-') {
             let parts: Vec<&str> = price_str.split('-').collect();
             if parts.len() != 2 {
-                return Err(anyhow!("Invalid price range format. Use 'min-max' or 'min-' or '-max'"));
+                return Err(anyhow!("Invalid {} range format. Use 'min-max' or 'min-' or '-max'", label));
             }
             
             let min = if parts[0].is_empty() {
                 None
             } else {
-                Some(parts[0].parse::<f64>().map_err(|_| anyhow!("Invalid minimum price"))?)
+                Some(parts[0].parse::<f64>().map_err(|_| anyhow!("Invalid minimum {}", label))?)
             };
             
             let max = if parts[1].is_empty() {
                 None
             } else {
-                Some(parts[1].parse::<f64>().map_err(|_| anyhow!("Invalid maximum price"))?)
+                Some(parts[1].parse::<f64>().map_err(|_| anyhow!("Invalid maximum {}", label))?)
             };
             
-            Ok(PriceRange { min, max })
+            Ok(PriceRange::inclusive(min, max))
         } else {
             // Single price value
-            let price = price_str.parse::<f64>().map_err(|_| anyhow!("Invalid price value"))?;
-            Ok(PriceRange { min: Some(price), max: Some(price) })
+            let price = price_str.parse::<f64>().map_err(|_| anyhow!("Invalid {} value", label))?;
+            Ok(PriceRange::inclusive(Some(price), Some(price)))
         }
     }
 }
 
-fn get_field_shortcuts() -> std::collections::HashMap<&'static str, &'static str> {
-    let mut shortcuts = std::collections::HashMap::new();
-    shortcuts.insert("id", "item_id");
-    shortcuts.insert("t", "title");
-    shortcuts.insert("d", "description");
-    shortcuts.insert("p", "price");
-    shortcuts.insert("q", "quantity");
-    shortcuts.insert("c", "condition");
-    shortcuts.insert("cat", "category");
-    shortcuts.insert("b", "brand");
-    shortcuts.insert("u", "upc");
-    shortcuts
+/// The WHERE criteria of a filter query; every criterion that is set must match
+#[derive(Debug, Default)]
+pub(crate) struct FilterCriteria<'a> {
+    pub price: Option<&'a PriceRange>,
+    pub quantity: Option<&'a PriceRange>,
+    pub category: Option<&'a str>,
+    pub condition: Option<&'a str>,
+    pub brand: Option<&'a str>,
+    /// Normalized tag name, as stored in `tags`
+    pub tag: Option<&'a str>,
+    pub import_run: Option<&'a str>,
+    /// Canonical timestamps bounding `last_updated`: since is inclusive, before exclusive
+    pub updated_since: Option<&'a str>,
+    pub updated_before: Option<&'a str>,
+    /// Only items priced below their `original_price`
+    pub on_sale: bool,
+    /// A `--filter-expr`, which has to match as well as the other criteria
+    pub expr: Option<&'a FilterExpr>,
+    pub include_archived: bool,
 }
 
-fn expand_field_shortcuts(fields: &str) -> Result<Vec<String>> {
-    let shortcuts = get_field_shortcuts();
-    let field_list: Vec<&str> = fields.split(',').map(|s| s.trim()).collect();
-    let mut expanded_fields = Vec::new();
-    
-    for field in field_list {
-        if field.is_empty() {
-            continue;
-        }
-        
-        let expanded = if shortcuts.contains_key(field) {
-            shortcuts[field].to_string()
-        } else {
-            field.to_string()
-        };
-        
-        expanded_fields.push(expanded);
+/// Adds the comparisons for a numeric range on `column`
+fn push_range_conditions(
+    column: &str,
+    range: &PriceRange,
+    conditions: &mut Vec<String>,
+    params: &mut Vec<rusqlite::types::Value>,
+) {
+    if let Some(min) = range.min {
+        conditions.push(format!("{} {} ?", column, if range.min_exclusive { ">" } else { ">=" }));
+        params.push(rusqlite::types::Value::Real(min));
     }
-    
-    if expanded_fields.is_empty() {
-        return Err(anyhow!("No valid fields specified"));
+    if let Some(max) = range.max {
+        conditions.push(format!("{} {} ?", column, if range.max_exclusive { "<" } else { "<=" }));
+        params.push(rusqlite::types::Value::Real(max));
     }
-    
-    Ok(expanded_fields)
 }
 
-fn validate_fields(fields: &[String]) -> Result<()> {
-    let valid_fields = [
-        "item_id", "title", "description", "price", "quantity", 
-        "category", "condition", "brand", "upc"
-    ];
-    
-    for field in fields {
-        if !valid_fields.contains(&field.as_str()) {
-            return Err(anyhow!("Unknown field: {}", field));
-        }
-    }
-    
-    Ok(())
+/// Parses a `--updated-since`/`--updated-before` value into the canonical stored timestamp format
+fn parse_date_bound(flag: &str, value: &str) -> Result<String> {
+    normalize_timestamp(value).ok_or_else(|| anyhow!(
+        "Invalid date for {}: '{}'. Use YYYY-MM-DD (midnight UTC) or an RFC3339 datetime such as 2024-01-01T12:00:00Z",
+        flag,
+        value
+    ))
 }
 
-fn build_filter_query(
-    price_range: Option<&PriceRange>,
-    category: Option<&str>,
-    condition: Option<&str>,
-    brand: Option<&str>,
-    fields: &[String],
-) -> Result<(String, Vec<rusqlite::types::Value>)> {
-    let mut conditions = Vec::new();
+/// Builds the WHERE clause (empty when nothing is filtered) and its parameters for the criteria
+pub(crate) fn build_where_clause(criteria: &FilterCriteria) -> (String, Vec<rusqlite::types::Value>) {
+    let mut conditions: Vec<String> = Vec::new();
     let mut params = Vec::new();
     
-    // Price range filter
-    if let Some(range) = price_range {
-        if let Some(min) = range.min {
-            conditions.push("price >= ?");
-            params.push(rusqlite::types::Value::Real(min));
-        }
-        if let Some(max) = range.max {
-            conditions.push("price <= ?");
-            params.push(rusqlite::types::Value::Real(max));
-        }
+    // Price and quantity range filters
+    if let Some(range) = criteria.price {
+        push_range_conditions("price", range, &mut conditions, &mut params);
+    }
+    if let Some(range) = criteria.quantity {
+        push_range_conditions("quantity", range, &mut conditions, &mut params);
     }
     
     // Category filter
-    if let Some(cat) = category {
-        conditions.push("category = ?");
+    if let Some(cat) = criteria.category {
+        conditions.push("category = ?".to_string());
         params.push(rusqlite::types::Value::Text(cat.to_string()));
     }
     
     // Condition filter
-    if let Some(cond) = condition {
-        conditions.push("condition = ?");
+    if let Some(cond) = criteria.condition {
+        conditions.push("condition = ?".to_string());
         params.push(rusqlite::types::Value::Text(cond.to_string()));
     }
     
     // Brand filter
-    if let Some(brand_name) = brand {
-        conditions.push("brand = ?");
+    if let Some(brand_name) = criteria.brand {
+        conditions.push("brand = ?".to_string());
         params.push(rusqlite::types::Value::Text(brand_name.to_string()));
     }
     
-    // Build SELECT clause
-    let select_clause = fields.join(", ");
+    // Tag filter, joining through item_tags
+    if let Some(tag) = criteria.tag {
+        conditions.push("item_id IN (SELECT it.item_id FROM item_tags it JOIN tags t ON t.tag_id = it.tag_id WHERE t.name = ?)".to_string());
+        params.push(rusqlite::types::Value::Text(tag.to_string()));
+    }
+    
+    // Import run filter
+    if let Some(run) = criteria.import_run {
+        conditions.push("last_import_run = ?".to_string());
+        params.push(rusqlite::types::Value::Text(run.to_string()));
+    }
+    
+    // Last-updated window; stored timestamps are canonical, so text comparison orders them
+    if let Some(since) = criteria.updated_since {
+        conditions.push("last_updated >= ?".to_string());
+        params.push(rusqlite::types::Value::Text(since.to_string()));
+    }
+    if let Some(before) = criteria.updated_before {
+        conditions.push("last_updated < ?".to_string());
+        params.push(rusqlite::types::Value::Text(before.to_string()));
+    }
+    
+    // Marked-down items; a NULL or zero original price means no discount
+    if criteria.on_sale {
+        conditions.push("original_price > 0 AND price < original_price".to_string());
+    }
+    
+    // The expression's SQL is built from whitelisted columns and operators; its values are bound
+    if let Some(expr) = criteria.expr {
+        let (sql, expr_params) = expr.to_sql();
+        conditions.push(sql);
+        params.extend(expr_params);
+    }
+    
+    // Sold and draft items are hidden unless asked for
+    if !criteria.include_archived {
+        conditions.push("status = 'active'".to_string());
+    }
     
-    // Build WHERE clause
     let where_clause = if conditions.is_empty() {
         String::new()
     } else {
         format!("WHERE {}", conditions.join(" AND "))
     };
     
+    (where_clause, params)
+}
+
+fn build_filter_query(
+    criteria: &FilterCriteria,
+    fields: &[String],
+) -> Result<(String, Vec<rusqlite::types::Value>)> {
+    let select_clause = fields.iter()
+        .map(|field| if field == LINE_VALUE { LINE_VALUE_SQL } else { field.as_str() })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let (where_clause, params) = build_where_clause(criteria);
+    
     let query = format!("SELECT {} FROM items {}", select_clause, where_clause);
     
     Ok((query, params))
 }
 
+/// Counts and values the matching items for each value of `column`, which must be one of
+/// `GROUP_BY_COLUMNS` since it goes into the SQL as is
+pub(crate) fn group_filtered_items(conn: &Connection, criteria: &FilterCriteria, column: &str) -> Result<Vec<FilterGroup>> {
+    if !GROUP_BY_COLUMNS.contains(&column) {
+        anyhow::bail!(validation_failed(format!(
+            "Can't group by '{}'. Choose one of: {}", column, GROUP_BY_COLUMNS.join(", ")
+        )));
+    }
+    let (where_clause, params) = build_where_clause(criteria);
+    let query = format!(
+        "SELECT {column}, COUNT(*), COALESCE(SUM(price * quantity), 0.0) FROM items {where_clause} GROUP BY {column} ORDER BY {column}"
+    );
+    log::debug!("Filter group query: {} ({} parameters)", query, params.len());
+    let mut stmt = conn.prepare(&query)?;
+    let groups = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| Ok(FilterGroup {
+        group: row.get(0)?,
+        count: row.get(1)?,
+        total_value: row.get(2)?,
+    }))?
+    .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+    Ok(groups)
+}
+
+fn format_groups(groups: &[FilterGroup], column: &str, format: &OutputFormat, options: &OutputOptions) -> Result<String> {
+    let header = vec![column.to_uppercase(), "COUNT".to_string(), "TOTAL VALUE".to_string()];
+    Ok(match format {
+        OutputFormat::Json => to_json(&groups, options)?,
+        OutputFormat::Yaml => serde_yaml::to_string(&groups)?,
+        OutputFormat::Ndjson => {
+            let fields = ["group", "count", "total_value"].map(String::from);
+            format_ndjson_records(groups, &fields, options)?
+        }
+        OutputFormat::Csv => {
+            let mut writer = CsvWriter::new(Vec::new(), &header, options);
+            for group in groups {
+                writer.write_row(&[
+                    escape_csv_field(group.group.as_deref().unwrap_or_default(), options.csv_delimiter),
+                    group.count.to_string(),
+                    format!("{:.2}", group.total_value),
+                ])?;
+            }
+            String::from_utf8(writer.into_inner())?
+        }
+        OutputFormat::Table => {
+            let rows = groups.iter()
+                .map(|group| (vec![
+                    fit_cell(group.group.as_deref().unwrap_or("(none)"), 30, options),
+                    group.count.to_string(),
+                    format_money(group.total_value, DEFAULT_CURRENCY),
+                ], None))
+                .collect();
+            render_table(header, rows, options.color)
+        }
+        OutputFormat::Custom(name) => {
+            anyhow::bail!("Formatter plugin '{}' can't be used with filter; custom formats apply to list and search", name);
+        }
+    })
+}
+
+/// Reads a row of a filter query into the fields its SELECT clause names
+fn map_filtered_row(row: &rusqlite::Row, fields: &[&str]) -> rusqlite::Result<FilteredItem> {
+    let mut item = FilteredItem {
+        item_id: None,
+        title: None,
+        description: None,
+        price: None,
+        quantity: None,
+        category: None,
+        condition: None,
+        brand: None,
+        upc: None,
+        currency: None,
+        original_price: None,
+        line_value: None,
+    };
+    
+    // Map fields based on their position in the SELECT clause
+    for (col_idx, field) in fields.iter().enumerate() {
+        match *field {
+            "item_id" => item.item_id = row.get(col_idx).ok(),
+            "title" => item.title = row.get(col_idx).ok(),
+            "description" => item.description = row.get(col_idx).ok(),
+            "price" => item.price = row.get(col_idx).ok(),
+            "quantity" => item.quantity = row.get(col_idx).ok(),
+            "category" => item.category = row.get(col_idx).ok(),
+            "condition" => item.condition = row.get(col_idx).ok(),
+            "brand" => item.brand = row.get(col_idx).ok(),
+            "upc" => item.upc = row.get(col_idx).ok(),
+            "currency" => item.currency = row.get(col_idx).ok(),
+            "original_price" => item.original_price = row.get(col_idx).ok(),
+            LINE_VALUE_SQL => item.line_value = row.get(col_idx).ok(),
+            _ => {}
+        }
+    }
+    
+    Ok(item)
+}
+
+/// The column expressions a filter query selects, in order
+fn selected_fields(query: &str) -> Vec<&str> {
+    let columns = query.split("SELECT ").nth(1).unwrap_or_default();
+    columns.split(" FROM").next().unwrap_or_default().split(", ").map(str::trim).collect()
+}
+
 fn execute_filter_query(
     conn: &Connection,
     query: &str,
     params: &[rusqlite::types::Value],
 ) -> Result<Vec<FilteredItem>> {
-    let mut stmt = conn.prepare(query)?;
-    
-    let items = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        let mut item = FilteredItem {
-            item_id: None,
-            title: None,
-            description: None,
-            price: None,
-            quantity: None,
-            category: None,
-            condition: None,
-            brand: None,
-            upc: None,
-        };
-        
-        // Map fields based on their position in the SELECT clause
-        let mut col_idx = 0;
-        
-        for field in query.split("SELECT ").nth(1).unwrap().split(" FROM").next().unwrap().split(", ") {
-            match field.trim() {
-                "item_id" => item.item_id = row.get(col_idx).ok(),
-                "title" => item.title = row.get(col_idx).ok(),
-                "description" => item.description = row.get(col_idx).ok(),
-                "price" => item.price = row.get(col_idx).ok(),
-                "quantity" => item.quantity = row.get(col_idx).ok(),
-                "category" => item.category = row.get(col_idx).ok(),
-                "condition" => item.condition = row.get(col_idx).ok(),
-                "brand" => item.brand = row.get(col_idx).ok(),
-                "upc" => item.upc = row.get(col_idx).ok(),
-                _ => {}
-            }
-            col_idx += 1;
-        }
-        
-        Ok(item)
-    })?
-    .collect::<Result<Vec<_>, rusqlite::Error>>()
-    .map_err(|e| anyhow!("Database error: {}", e))?;
+    log::debug!("Filter query: {} ({} parameters)", query, params.len());
+    let mut stmt = conn.prepare_cached(query)?;
+    let fields = selected_fields(query);
+    
+    let items = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| map_filtered_row(row, &fields))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()
+        .map_err(|e| anyhow!("Database error: {}", e))?;
     
     Ok(items)
 }
 
-fn format_filtered_items_table(items: &[FilteredItem], fields: &[String]) -> String {
+/// Runs a filter query, handing each matching item to `f` as it's read. Returns how many matched.
+fn for_each_filtered_item(
+    conn: &Connection,
+    query: &str,
+    params: &[rusqlite::types::Value],
+    mut f: impl FnMut(FilteredItem) -> Result<()>,
+) -> Result<usize> {
+    log::debug!("Filter query: {} ({} parameters)", query, params.len());
+    let mut stmt = conn.prepare_cached(query)?;
+    let fields = selected_fields(query);
+    let mut count = 0;
+    for item in stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| map_filtered_row(row, &fields))? {
+        f(item.map_err(|e| anyhow!("Database error: {}", e))?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn format_filtered_items_table(items: &[FilteredItem], fields: &[String], options: &OutputOptions) -> String {
     if items.is_empty() {
         return "No items found matching the filter criteria.\n".to_string();
     }
     
-    let mut output = String::new();
-    
-    // Header
     let header: Vec<String> = fields.iter().map(|f| f.to_uppercase()).collect();
-    output.push_str(&header.join(" | "));
-    output.push('\n');
     
-    // Separator
-    let separator: Vec<String> = fields.iter().map(|_| "---".to_string()).collect();
-    output.push_str(&separator.join(" | "));
-    output.push('\n');
+    let rows = items.iter()
+        .map(|item| (fields.iter()
+            .map(|field| {
+                let value = match field.as_str() {
+                    "item_id" => item.item_id.map(|v| v.to_string()).unwrap_or_default(),
+                    "title" => item.title.clone().unwrap_or_default(),
+                    "description" => item.description.clone().unwrap_or_default(),
+                    "price" => item.price
+                        .map(|v| format_money(v, item.currency.as_deref().unwrap_or(DEFAULT_CURRENCY)))
+                        .unwrap_or_default(),
+                    "quantity" => item.quantity.map(|v| v.to_string()).unwrap_or_default(),
+                    "category" => item.category.clone().unwrap_or_default(),
+                    "condition" => item.condition.clone().unwrap_or_default(),
+                    "brand" => item.brand.clone().unwrap_or_default(),
+                    "upc" => item.upc.clone().unwrap_or_default(),
+                    "currency" => item.currency.clone().unwrap_or_default(),
+                    "original_price" => item.original_price
+                        .map(|v| format_money(v, item.currency.as_deref().unwrap_or(DEFAULT_CURRENCY)))
+                        .unwrap_or_default(),
+                    LINE_VALUE => item.line_value
+                        .map(|v| format_money(v, item.currency.as_deref().unwrap_or(DEFAULT_CURRENCY)))
+                        .unwrap_or_default(),
+                    _ => String::new(),
+                };
+                
+                // Truncate (or wrap) long values
+                fit_cell(&value, 30, options)
+            })
+            .collect(), item.quantity.map(i64::from)))
+        .collect();
     
-    // Rows
-    for item in items {
-        let mut row = Vec::new();
-        
-        for field in fields {
-            let value = match field.as_str() {
-                "item_id" => item.item_id.map(|v| v.to_string()).unwrap_or_default(),
-                "title" => item.title.clone().unwrap_or_default(),
-                "description" => item.description.clone().unwrap_or_default(),
-                "price" => item.price.map(|v| format!("${:.2}", v)).unwrap_or_default(),
-                "quantity" => item.quantity.map(|v| v.to_string()).unwrap_or_default(),
-                "category" => item.category.clone().unwrap_or_default(),
-                "condition" => item.condition.clone().unwrap_or_default(),
-                "brand" => item.brand.clone().unwrap_or_default(),
-                "upc" => item.upc.clone().unwrap_or_default(),
-                _ => String::new(),
-            };
-            
-            // Truncate long values
-            let truncated = if value.len() > 30 {
-                format!("{}...", &value[..27])
-            } else {
-                value
-            };
-            
-            row.push(truncated);
-        }
-        
-        output.push_str(&row.join(" | "));
-        output.push('\n');
+    let mut table = render_table(header, rows, options.color);
+    if fields.iter().any(|field| field == LINE_VALUE) {
+        table.push_str(&format!("Total value: {}\n", format_total_value(items)));
     }
-    
-    output
+    table
 }
 
-fn format_filtered_items_csv(items: &[FilteredItem], fields: &[String]) -> String {
-    if items.is_empty() {
-        return "No items found matching the filter criteria.\n".to_string();
+/// The sum of the items' line values, in their currency when they all share one
+fn format_total_value(items: &[FilteredItem]) -> String {
+    let total: f64 = items.iter().filter_map(|item| item.line_value).sum();
+    let currency = items[0].currency.as_deref().unwrap_or(DEFAULT_CURRENCY);
+    if items.iter().all(|item| item.currency.as_deref().unwrap_or(DEFAULT_CURRENCY) == currency) {
+        format_money(total, currency)
+    } else {
+        format!("{:.2} (mixed currencies)", total)
     }
-    
-    let mut output = String::new();
-    
-    // Header
-    let header: Vec<String> = fields.iter().map(|f| f.to_uppercase()).collect();
-    output.push_str(&header.join(","));
-    output.push('\n');
-    
-    // Rows
-    for item in items {
-        let mut row = Vec::new();
-        
-        for field in fields {
+}
+
+/// A filtered item's CSV fields, escaped for `delimiter`, in the order of `fields`
+fn filtered_csv_fields(item: &FilteredItem, fields: &[String], delimiter: char) -> Vec<String> {
+    fields.iter()
+        .map(|field| {
             let value = match field.as_str() {
                 "item_id" => item.item_id.map(|v| v.to_string()).unwrap_or_default(),
                 "title" => item.title.clone().unwrap_or_default(),
@@ -294,38 +465,121 @@ fn format_filtered_items_csv(items: &[FilteredItem], fields: &[String]) -> Strin
                 "condition" => item.condition.clone().unwrap_or_default(),
                 "brand" => item.brand.clone().unwrap_or_default(),
                 "upc" => item.upc.clone().unwrap_or_default(),
+                "currency" => item.currency.clone().unwrap_or_default(),
+                "original_price" => item.original_price.map(|v| format!("{:.2}", v)).unwrap_or_default(),
+                LINE_VALUE => item.line_value.map(|v| format!("{:.2}", v)).unwrap_or_default(),
                 _ => String::new(),
             };
-            
-            // Escape CSV values
-            let escaped = if value.contains(',') || value.contains('"') || value.contains('\n') {
-                format!("\"{}\"", value.replace("\"", "\"\""))
-            } else {
-                value
-            };
-            
-            row.push(escaped);
-        }
-        
-        output.push_str(&row.join(","));
-        output.push('\n');
-    }
+            escape_csv_field(&value, delimiter)
+        })
+        .collect()
+}
+
+/// The CSV row after the items when `line_value` is chosen: `total` under LINE_VALUE, TOTAL in
+/// the first column (unless that's LINE_VALUE itself) and nothing elsewhere
+fn csv_total_row(fields: &[String], total: f64) -> Vec<String> {
+    fields.iter()
+        .enumerate()
+        .map(|(idx, field)| if field == LINE_VALUE {
+            format!("{:.2}", total)
+        } else if idx == 0 {
+            "TOTAL".to_string()
+        } else {
+            String::new()
+        })
+        .collect()
+}
+
+/// What `filter` was asked for: the filters, how many items to sample, and how to show them
+#[derive(Debug, Clone, Default, Args)]
+pub struct FilterArgs {
+    /// Price range (e.g., 10-50, >50, <=100)
+    #[arg(short, long)]
+    pub price: Option<String>,
+    
+    /// Quantity range, same syntax as --price (e.g., 0, 1-, -5)
+    #[arg(short, long)]
+    pub quantity: Option<String>,
+    
+    /// Only items with a quantity of 0 (same as --quantity 0)
+    #[arg(long, conflicts_with = "quantity")]
+    pub out_of_stock: bool,
+    
+    /// Only items marked down: priced below their original price
+    #[arg(long)]
+    pub on_sale: bool,
+    
+    /// Category filter
+    #[arg(short, long)]
+    pub category: Option<String>,
     
-    output
+    /// Condition filter
+    #[arg(short = 'n', long)]
+    pub condition: Option<String>,
+    
+    /// Brand filter
+    #[arg(short, long)]
+    pub brand: Option<String>,
+    
+    /// Only items with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+    
+    /// Only items created by this import run
+    #[arg(long)]
+    pub import_run: Option<String>,
+    
+    /// Only items updated at or after this date (YYYY-MM-DD or RFC3339)
+    #[arg(long)]
+    pub updated_since: Option<String>,
+    
+    /// Only items updated before this date (YYYY-MM-DD or RFC3339)
+    #[arg(long)]
+    pub updated_before: Option<String>,
+    
+    /// Only items matching an expression, e.g. "category = 'books' OR price < 20";
+    /// combines with the other filters
+    #[arg(long, value_name = "EXPR")]
+    pub filter_expr: Option<String>,
+    
+    /// Count and total the matching items per brand, category or condition instead of listing them
+    #[arg(long, value_name = "COLUMN")]
+    pub group_by: Option<String>,
+    
+    /// Also show sold and draft items
+    #[arg(long)]
+    pub include_archived: bool,
+    
+    /// Show this many matching items picked at random instead of all of them
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub sample: Option<u64>,
+    
+    /// Fields to display; line_value is price * quantity, totalled under table and CSV output
+    #[arg(short = 'l', long)]
+    pub fields: Option<String>,
+    
+    /// Output format
+    #[arg(short, long)]
+    pub format: Option<OutputFormat>,
 }
 
+/// Without matches there's no header, just a note saying so
+const NO_MATCHES: &str = "No items found matching the filter criteria.\n";
+
 pub fn handle_filter(
     conn: &Connection,
-    price: Option<String>,
-    category: Option<String>,
-    condition: Option<String>,
-    brand: Option<String>,
-    fields: Option<String>,
-    format: Option<OutputFormat>,
+    args: FilterArgs,
+    output_options: &OutputOptions,
     monitor: Option<Arc<PerformanceMonitor>>,
     cache: Option<Arc<QueryCache>>,
 ) -> Result<()> {
+    let FilterArgs {
+        price, quantity, out_of_stock, on_sale, category, condition, brand, tag, import_run,
+        updated_since, updated_before, filter_expr, group_by, include_archived, sample, fields, format,
+    } = args;
+    let sample = sample.map(|n| n as usize);
     let format = format.unwrap_or(OutputFormat::Json);
+    let start = Instant::now();
     
     // Parse price range
     let price_range = if let Some(ref price_str) = price {
@@ -334,81 +588,274 @@ pub fn handle_filter(
         None
     };
     
+    // Parse quantity range; --out-of-stock is shorthand for a quantity of 0
+    let quantity = if out_of_stock { Some("0".to_string()) } else { quantity };
+    let quantity_range = if let Some(ref quantity_str) = quantity {
+        Some(PriceRange::parse_labeled(quantity_str, "quantity")?)
+    } else {
+        None
+    };
+    
+    // Parse the last-updated window
+    let updated_since = updated_since.map(|date| parse_date_bound("--updated-since", &date)).transpose()?;
+    let updated_before = updated_before.map(|date| parse_date_bound("--updated-before", &date)).transpose()?;
+    
+    let expr = filter_expr.as_deref().map(FilterExpr::parse).transpose()?;
+    
+    // Tags are stored normalized; a blank name is an error
+    let tag = tag.map(|name| normalize_tag(&name).ok_or_else(|| anyhow!("--tag can't be empty"))).transpose()?;
+    
     // Parse and validate fields
     let fields_str = fields.unwrap_or_else(|| "item_id,title,price,quantity,category,condition,brand".to_string());
     let expanded_fields = expand_field_shortcuts(&fields_str)?;
-    validate_fields(&expanded_fields)?;
+    let columns: Vec<String> = expanded_fields.iter().filter(|field| *field != LINE_VALUE).cloned().collect();
+    if !columns.is_empty() {
+        validate_fields(&columns)?;
+    }
+    let with_line_value = columns.len() < expanded_fields.len();
     
-    // Check cache first if available
-    if let Some(cache) = &cache {
+    // Every input that changes the output is part of the cache key
+    let cache_key = {
         let mut params = HashMap::new();
+        params.insert("db".to_string(), database_cache_key(conn));
         if let Some(price_str) = &price { params.insert("price".to_string(), price_str.clone()); }
+        if let Some(quantity_str) = &quantity { params.insert("quantity".to_string(), quantity_str.clone()); }
         if let Some(cat) = &category { params.insert("category".to_string(), cat.clone()); }
         if let Some(cond) = &condition { params.insert("condition".to_string(), cond.clone()); }
         if let Some(brand_name) = &brand { params.insert("brand".to_string(), brand_name.clone()); }
-        params.insert("fields".to_string(), fields_str.clone());
+        if let Some(tag_name) = &tag { params.insert("tag".to_string(), tag_name.clone()); }
+        if let Some(run) = &import_run { params.insert("import_run".to_string(), run.clone()); }
+        if let Some(since) = &updated_since { params.insert("updated_since".to_string(), since.clone()); }
+        if let Some(before) = &updated_before { params.insert("updated_before".to_string(), before.clone()); }
+        if on_sale { params.insert("on_sale".to_string(), "true".to_string()); }
+        if let Some(expr) = &filter_expr { params.insert("filter_expr".to_string(), expr.clone()); }
+        if include_archived { params.insert("include_archived".to_string(), "true".to_string()); }
+        params.insert("fields".to_string(), fields_str);
         params.insert("format".to_string(), format!("{:?}", format));
-        
-        let cache_key = generate_cache_key("filter", &params);
-        
+        if output_options.crlf { params.insert("crlf".to_string(), "true".to_string()); }
+        if output_options.color { params.insert("color".to_string(), "true".to_string()); }
+        if output_options.json_compact { params.insert("json_compact".to_string(), "true".to_string()); }
+        if let Some(width) = output_options.wrap { params.insert("wrap".to_string(), width.to_string()); }
+        generate_cache_key("filter", &params)
+    };
+    
+    let criteria = FilterCriteria {
+        price: price_range.as_ref(),
+        quantity: quantity_range.as_ref(),
+        category: category.as_deref(),
+        condition: condition.as_deref(),
+        brand: brand.as_deref(),
+        tag: tag.as_deref(),
+        import_run: import_run.as_deref(),
+        updated_since: updated_since.as_deref(),
+        updated_before: updated_before.as_deref(),
+        on_sale,
+        expr: expr.as_ref(),
+        include_archived,
+    };
+    
+    // Grouping replaces the item rows with one aggregate row per group
+    if let Some(column) = group_by {
+        if sample.is_some() {
+            return Err(anyhow!("--sample can't be combined with --group-by"));
+        }
+        let groups = group_filtered_items(conn, &criteria, &column.to_lowercase())?;
+        if let Some(monitor) = &monitor {
+            monitor.report_verbose("filter", start.elapsed(), Some(groups.len()), None);
+        }
+        if groups.is_empty() && matches!(format, OutputFormat::Table | OutputFormat::Csv) {
+            print!("{}", NO_MATCHES);
+        } else {
+            print!("{}", format_groups(&groups, &column.to_lowercase(), &format, output_options)?);
+        }
+        return Ok(());
+    }
+    
+    let (mut query, params) = build_filter_query(&criteria, &expanded_fields)?;
+    if let Some(size) = sample {
+        query.push_str(&format!(" ORDER BY RANDOM() LIMIT {}", size));
+    }
+    // A random sample differs on every run, so it's never served from or stored in the cache
+    let cache = if sample.is_some() { None } else { cache };
+    
+    // ndjson and CSV are streamed a row at a time, so they're neither built up as one string
+    // nor cached. Tables still collect every row: each column is sized to its widest cell, which
+    // isn't known until the last row is read.
+    if matches!(format, OutputFormat::Ndjson | OutputFormat::Csv) {
+        let count = if format == OutputFormat::Ndjson {
+            let mut writer = stdout_ndjson(&expanded_fields, output_options)?;
+            for_each_filtered_item(conn, &query, &params, |item| writer.write(&item))?;
+            writer.finish()?
+        } else {
+            let header: Vec<String> = expanded_fields.iter().map(|f| f.to_uppercase()).collect();
+            let mut writer = CsvWriter::new(buffered_stdout(), &header, output_options);
+            let mut total = 0.0;
+            let count = for_each_filtered_item(conn, &query, &params, |item| {
+                total += item.line_value.unwrap_or_default();
+                writer.write_row(&filtered_csv_fields(&item, &expanded_fields, output_options.csv_delimiter))
+            })?;
+            if with_line_value && count > 0 {
+                writer.write_row(&csv_total_row(&expanded_fields, total))?;
+            }
+            writer.finish()?;
+            if count == 0 {
+                print!("{}", NO_MATCHES);
+            }
+            count
+        };
+        if let Some(monitor) = &monitor {
+            monitor.report_verbose("filter", start.elapsed(), Some(count), None);
+        }
+        return Ok(());
+    }
+    
+    // Check cache first if available
+    if let Some(cache) = &cache {
         if let Some(cached_result) = cache.get(&cache_key) {
+            if let Some(monitor) = &monitor {
+                monitor.report_verbose("filter", start.elapsed(), None, Some(true));
+            }
             print!("{}", cached_result);
             return Ok(());
         }
     }
     
-    // Build and execute query with performance monitoring
+    // Execute query with performance monitoring
     let items = if let Some(monitor) = &monitor {
-        measure_query_performance(monitor, "filter_query", || {
-            let (query, params) = build_filter_query(
-                price_range.as_ref(),
-                category.as_deref(),
-                condition.as_deref(),
-                brand.as_deref(),
-                &expanded_fields,
-            )?;
-            
-            execute_filter_query(conn, &query, &params)
-        })?
+        measure_query_performance(monitor, "filter_query", || execute_filter_query(conn, &query, &params))?
     } else {
-        let (query, params) = build_filter_query(
-            price_range.as_ref(),
-            category.as_deref(),
-            condition.as_deref(),
-            brand.as_deref(),
-            &expanded_fields,
-        )?;
-        
         execute_filter_query(conn, &query, &params)?
     };
     
+    if let Some(monitor) = &monitor {
+        monitor.report_verbose("filter", start.elapsed(), Some(items.len()), cache.as_ref().map(|_| false));
+    }
+    
     // Format output
-    let output = match format {
+    let output = match &format {
         OutputFormat::Json => {
-            serde_json::to_string_pretty(&items)?
+            to_json(&items, output_options)?
+        }
+        OutputFormat::Yaml => {
+            serde_yaml::to_string(&items)?
         }
         OutputFormat::Table => {
-            format_filtered_items_table(&items, &expanded_fields)
+            format_filtered_items_table(&items, &expanded_fields, output_options)
         }
-        OutputFormat::Csv => {
-            format_filtered_items_csv(&items, &expanded_fields)
+        OutputFormat::Csv | OutputFormat::Ndjson => unreachable!("{:?} is streamed above", format),
+        OutputFormat::Custom(name) => {
+            return Err(anyhow!("Formatter plugin '{}' can't be used with filter; custom formats apply to list and search", name));
         }
     };
     
     // Cache the result if cache is available
     if let Some(cache) = &cache {
-        let mut params = HashMap::new();
-        if let Some(price_str) = &price { params.insert("price".to_string(), price_str.clone()); }
-        if let Some(cat) = &category { params.insert("category".to_string(), cat.clone()); }
-        if let Some(cond) = &condition { params.insert("condition".to_string(), cond.clone()); }
-        if let Some(brand_name) = &brand { params.insert("brand".to_string(), brand_name.clone()); }
-        params.insert("fields".to_string(), fields_str);
-        params.insert("format".to_string(), format!("{:?}", format));
-        
-        let cache_key = generate_cache_key("filter", &params);
         cache.set(cache_key, output.clone(), Duration::from_secs(300)); // 5 minute TTL
     }
     
     print!("{}", output);
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Number of items matching the same filters `filter` accepts
+pub fn count_items(
+    conn: &Connection,
+    price: Option<&str>,
+    category: Option<&str>,
+    condition: Option<&str>,
+    brand: Option<&str>,
+    include_archived: bool,
+) -> Result<i64> {
+    let price_range = price.map(PriceRange::parse).transpose()?;
+    let criteria = FilterCriteria {
+        price: price_range.as_ref(),
+        category,
+        condition,
+        brand,
+        include_archived,
+        ..Default::default()
+    };
+    let (query, params) = build_filter_query(&criteria, &["COUNT(*)".to_string()])?;
+    Ok(conn.query_row(&query, rusqlite::params_from_iter(params), |row| row.get(0))?)
+}
+
+/// Prints how many items match, as a bare integer or `{"count": N}` for JSON
+pub fn handle_count(
+    conn: &Connection,
+    price: Option<String>,
+    category: Option<String>,
+    condition: Option<String>,
+    brand: Option<String>,
+    include_archived: bool,
+    format: Option<OutputFormat>,
+) -> Result<()> {
+    let count = count_items(conn, price.as_deref(), category.as_deref(), condition.as_deref(), brand.as_deref(), include_archived)?;
+    match format {
+        Some(OutputFormat::Json) => println!("{}", serde_json::json!({ "count": count })),
+        _ => println!("{}", count),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_comparison_operators() {
+        let range = |min, max, min_exclusive, max_exclusive| PriceRange { min, max, min_exclusive, max_exclusive };
+        assert_eq!(PriceRange::parse(">50").unwrap(), range(Some(50.0), None, true, false));
+        assert_eq!(PriceRange::parse(">=25").unwrap(), range(Some(25.0), None, false, false));
+        assert_eq!(PriceRange::parse("<100").unwrap(), range(None, Some(100.0), false, true));
+        assert_eq!(PriceRange::parse("<=100").unwrap(), range(None, Some(100.0), false, false));
+    }
+
+    #[test]
+    fn test_price_dash_syntax_unchanged() {
+        assert_eq!(PriceRange::parse("10-50").unwrap(), PriceRange::inclusive(Some(10.0), Some(50.0)));
+        assert_eq!(PriceRange::parse("-30").unwrap(), PriceRange::inclusive(None, Some(30.0)));
+        assert_eq!(PriceRange::parse("25.99").unwrap(), PriceRange::inclusive(Some(25.99), Some(25.99)));
+    }
+
+    #[test]
+    fn test_quantity_range_combines_with_price() {
+        let price = PriceRange::parse("10-50").unwrap();
+        let quantity = PriceRange::parse_labeled("1-", "quantity").unwrap();
+        let criteria = FilterCriteria { price: Some(&price), quantity: Some(&quantity), category: Some("shoes"), ..Default::default() };
+        let (query, params) = build_filter_query(&criteria, &["title".to_string()]).unwrap();
+        assert_eq!(
+            query,
+            "SELECT title FROM items WHERE price >= ? AND price <= ? AND quantity >= ? AND category = ? AND status = 'active'"
+        );
+        assert_eq!(params.len(), 4);
+        assert_eq!(PriceRange::parse_labeled("x", "quantity").unwrap_err().to_string(), "Invalid quantity value");
+    }
+
+    #[test]
+    fn test_updated_since_bounds() {
+        assert_eq!(parse_date_bound("--updated-since", "2024-01-01").unwrap(), "2024-01-01T00:00:00.000000+00:00");
+        assert_eq!(parse_date_bound("--updated-before", "2024-01-01T12:00:00+02:00").unwrap(), "2024-01-01T10:00:00.000000+00:00");
+        let err = parse_date_bound("--updated-since", "01/02/2024").unwrap_err().to_string();
+        assert!(err.contains("--updated-since") && err.contains("YYYY-MM-DD"));
+        
+        let since = parse_date_bound("--updated-since", "2024-01-01").unwrap();
+        let criteria = FilterCriteria { updated_since: Some(&since), include_archived: true, ..Default::default() };
+        let (query, _) = build_filter_query(&criteria, &["title".to_string()]).unwrap();
+        assert_eq!(query, "SELECT title FROM items WHERE last_updated >= ?");
+    }
+
+    #[test]
+    fn test_price_operator_errors() {
+        assert_eq!(PriceRange::parse(">abc").unwrap_err().to_string(), "Invalid price value");
+        assert!(PriceRange::parse(">=").is_err());
+        assert!(PriceRange::parse("<10-20").is_err());
+    }
+
+    #[test]
+    fn test_strict_operators_build_strict_sql() {
+        let range = PriceRange::parse(">50").unwrap();
+        let criteria = FilterCriteria { price: Some(&range), include_archived: true, ..Default::default() };
+        let (query, _) = build_filter_query(&criteria, &["title".to_string()]).unwrap();
+        assert_eq!(query, "SELECT title FROM items WHERE price > ?");
+    }
+}
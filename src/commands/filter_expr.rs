@@ -0,0 +1,266 @@
+// Filter expressions for `filter --filter-expr`
+//
+// A small boolean language over item columns, e.g.
+// `category = 'electronics' AND price < 20 OR brand = 'Nike'`. AND binds tighter than OR and
+// parentheses group. Columns and operators come from fixed lists and every value becomes a bound
+// parameter, so nothing the user types is spliced into the SQL.
+
+use anyhow::Result;
+use nom::{
+    branch::alt,
+    bytes::complete::{is_not, tag, tag_no_case, take_while1},
+    character::complete::{char, multispace0, satisfy},
+    combinator::{all_consuming, map, not, peek, value, verify},
+    multi::{fold_many0, many0},
+    number::complete::double,
+    sequence::{delimited, preceded, terminated, tuple},
+    IResult,
+};
+use rusqlite::types::Value;
+use crate::error::error::validation_failed;
+
+/// What a column holds, and so which values it can be compared with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Number,
+    Text,
+}
+
+/// Columns an expression can test
+const COLUMNS: [(&str, ColumnType); 17] = [
+    ("item_id", ColumnType::Number),
+    ("title", ColumnType::Text),
+    ("description", ColumnType::Text),
+    ("price", ColumnType::Number),
+    ("quantity", ColumnType::Number),
+    ("category", ColumnType::Text),
+    ("condition", ColumnType::Text),
+    ("brand", ColumnType::Text),
+    ("upc", ColumnType::Text),
+    ("size", ColumnType::Text),
+    ("colorway", ColumnType::Text),
+    ("currency", ColumnType::Text),
+    ("original_price", ColumnType::Number),
+    ("release_date", ColumnType::Text),
+    ("status", ColumnType::Text),
+    ("sku", ColumnType::Text),
+    ("last_updated", ColumnType::Text),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// SQL LIKE: `%` matches any run of characters and `_` any one, ignoring ASCII case
+    Like,
+}
+
+impl CompareOp {
+    fn sql(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Like => "LIKE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    /// A single-quoted string; `''` inside it stands for one quote
+    Text(String),
+}
+
+/// A parsed `--filter-expr`
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    Compare { column: String, op: CompareOp, value: Literal },
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+}
+
+fn ws<'a, O>(inner: impl FnMut(&'a str) -> IResult<&'a str, O>) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    delimited(multispace0, inner, multispace0)
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// A keyword, in any case, that isn't the start of a longer word
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag_no_case(word), not(peek(satisfy(is_identifier_char))))
+}
+
+fn identifier(input: &str) -> IResult<&str, &str> {
+    take_while1(is_identifier_char)(input)
+}
+
+fn operator(input: &str) -> IResult<&str, CompareOp> {
+    alt((
+        value(CompareOp::Le, tag("<=")),
+        value(CompareOp::Ge, tag(">=")),
+        value(CompareOp::Ne, tag("!=")),
+        value(CompareOp::Ne, tag("<>")),
+        value(CompareOp::Eq, tag("=")),
+        value(CompareOp::Lt, tag("<")),
+        value(CompareOp::Gt, tag(">")),
+        value(CompareOp::Like, keyword("LIKE")),
+    ))(input)
+}
+
+fn string_literal(input: &str) -> IResult<&str, String> {
+    delimited(
+        char('\''),
+        fold_many0(alt((is_not("'"), value("'", tag("''")))), String::new, |mut text, piece| {
+            text.push_str(piece);
+            text
+        }),
+        char('\''),
+    )(input)
+}
+
+fn literal(input: &str) -> IResult<&str, Literal> {
+    alt((
+        map(string_literal, Literal::Text),
+        map(verify(double, |number: &f64| number.is_finite()), Literal::Number),
+    ))(input)
+}
+
+fn comparison(input: &str) -> IResult<&str, FilterExpr> {
+    map(tuple((ws(identifier), ws(operator), ws(literal))), |(column, op, value)| {
+        FilterExpr::Compare { column: column.to_lowercase(), op, value }
+    })(input)
+}
+
+fn term(input: &str) -> IResult<&str, FilterExpr> {
+    alt((delimited(ws(char('(')), or_expr, ws(char(')'))), comparison))(input)
+}
+
+fn and_expr(input: &str) -> IResult<&str, FilterExpr> {
+    let (input, first) = term(input)?;
+    let (input, rest) = many0(preceded(ws(keyword("AND")), term))(input)?;
+    Ok((input, rest.into_iter().fold(first, |left, right| FilterExpr::And(Box::new(left), Box::new(right)))))
+}
+
+fn or_expr(input: &str) -> IResult<&str, FilterExpr> {
+    let (input, first) = and_expr(input)?;
+    let (input, rest) = many0(preceded(ws(keyword("OR")), and_expr))(input)?;
+    Ok((input, rest.into_iter().fold(first, |left, right| FilterExpr::Or(Box::new(left), Box::new(right)))))
+}
+
+impl FilterExpr {
+    /// Parses an expression and checks every column exists and is compared with a value of
+    /// its type. Errors point at where parsing stopped.
+    pub fn parse(input: &str) -> Result<Self> {
+        let expr = match all_consuming(ws(or_expr))(input) {
+            Ok((_, expr)) => expr,
+            Err(nom::Err::Error(err) | nom::Err::Failure(err)) => {
+                let rest = err.input.trim_start();
+                let position = input.len() - rest.len() + 1;
+                anyhow::bail!(validation_failed(if rest.is_empty() {
+                    format!("Invalid --filter-expr: '{}' ends too soon", input)
+                } else {
+                    format!("Invalid --filter-expr: can't read '{}' at character {}", rest, position)
+                }));
+            }
+            Err(nom::Err::Incomplete(_)) => anyhow::bail!(validation_failed(format!("Invalid --filter-expr: '{}' ends too soon", input))),
+        };
+        expr.check()?;
+        Ok(expr)
+    }
+
+    fn check(&self) -> Result<()> {
+        match self {
+            FilterExpr::And(left, right) | FilterExpr::Or(left, right) => {
+                left.check()?;
+                right.check()
+            }
+            FilterExpr::Compare { column, op, value } => {
+                let Some(&(_, column_type)) = COLUMNS.iter().find(|(name, _)| name == column) else {
+                    let names: Vec<&str> = COLUMNS.iter().map(|(name, _)| *name).collect();
+                    anyhow::bail!(validation_failed(format!(
+                        "Unknown column '{}' in --filter-expr. Columns: {}", column, names.join(", ")
+                    )));
+                };
+                match (column_type, value) {
+                    (ColumnType::Number, Literal::Text(text)) => anyhow::bail!(validation_failed(format!(
+                        "{} is a number, so compare it with one rather than '{}'", column, text
+                    ))),
+                    (ColumnType::Number, Literal::Number(_)) if *op == CompareOp::Like => anyhow::bail!(validation_failed(format!(
+                        "LIKE only matches text, and {} is a number", column
+                    ))),
+                    (ColumnType::Text, Literal::Number(number)) => anyhow::bail!(validation_failed(format!(
+                        "{} is text, so quote the value: {} {} '{}'", column, column, op.sql(), number
+                    ))),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+
+    /// The expression as a parenthesized SQL condition with `?` placeholders, and the values
+    /// to bind to them in order
+    pub fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut params = Vec::new();
+        let sql = self.push_sql(&mut params);
+        (sql, params)
+    }
+
+    fn push_sql(&self, params: &mut Vec<Value>) -> String {
+        match self {
+            FilterExpr::Compare { column, op, value } => {
+                params.push(match value {
+                    Literal::Number(number) => Value::Real(*number),
+                    Literal::Text(text) => Value::Text(text.clone()),
+                });
+                format!("{} {} ?", column, op.sql())
+            }
+            FilterExpr::And(left, right) => format!("({} AND {})", left.push_sql(params), right.push_sql(params)),
+            FilterExpr::Or(left, right) => format!("({} OR {})", left.push_sql(params), right.push_sql(params)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        let (sql, params) = FilterExpr::parse("category = 'electronics' AND price < 20 OR brand = 'Nike'").unwrap().to_sql();
+        assert_eq!(sql, "((category = ? AND price < ?) OR brand = ?)");
+        assert_eq!(params, vec![Value::Text("electronics".into()), Value::Real(20.0), Value::Text("Nike".into())]);
+
+        let (sql, _) = FilterExpr::parse("(category = 'books' or category = 'toys') and quantity >= 1").unwrap().to_sql();
+        assert_eq!(sql, "((category = ? OR category = ?) AND quantity >= ?)");
+    }
+
+    #[test]
+    fn test_values_are_parameters() {
+        let (sql, params) = FilterExpr::parse("title LIKE '%O''Neill%' AND brand <> 'x; DROP TABLE items'").unwrap().to_sql();
+        assert_eq!(sql, "(title LIKE ? AND brand != ?)");
+        assert_eq!(params, vec![Value::Text("%O'Neill%".into()), Value::Text("x; DROP TABLE items".into())]);
+    }
+
+    #[test]
+    fn test_rejects_unknown_columns_and_mismatched_values() {
+        let message = |input: &str| FilterExpr::parse(input).unwrap_err().to_string();
+        assert!(message("internal_notes = 'x'").starts_with("Unknown column 'internal_notes'"));
+        assert_eq!(message("price = 'cheap'"), "price is a number, so compare it with one rather than 'cheap'");
+        assert_eq!(message("category = 5"), "category is text, so quote the value: category = '5'");
+        assert_eq!(message("price LIKE 5"), "LIKE only matches text, and price is a number");
+        assert_eq!(message("price < 20 AND"), "Invalid --filter-expr: can't read 'AND' at character 12");
+        assert_eq!(message("price < 20; DELETE FROM items"), "Invalid --filter-expr: can't read '; DELETE FROM items' at character 11");
+        assert_eq!(message("category = 'books"), "Invalid --filter-expr: can't read ''books' at character 12");
+    }
+}
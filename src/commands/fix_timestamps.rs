@@ -0,0 +1,49 @@
+// Fix timestamps command implementation
+// Implements: inventory fix-timestamps
+
+use anyhow::Result;
+use rusqlite::Connection;
+use crate::db::schema::CREATE_TIMESTAMP_TRIGGERS_SQL;
+use crate::db::timestamp::normalize_timestamp;
+
+pub fn handle_fix_timestamps(conn: &mut Connection) -> Result<()> {
+    // Make sure future writes are normalized too
+    conn.execute_batch(CREATE_TIMESTAMP_TRIGGERS_SQL)?;
+    
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT item_id, last_updated FROM items ORDER BY item_id")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+        rows
+    };
+    
+    let mut fixed = 0;
+    let mut unparseable = Vec::new();
+    
+    let tx = conn.transaction()?;
+    for (id, value) in &rows {
+        match normalize_timestamp(value) {
+            Some(normalized) if &normalized != value => {
+                tx.execute(
+                    "UPDATE items SET last_updated = ? WHERE item_id = ?",
+                    rusqlite::params![normalized, id],
+                )?;
+                fixed += 1;
+            }
+            Some(_) => {}
+            None => unparseable.push((*id, value.clone())),
+        }
+    }
+    tx.commit()?;
+    
+    for (id, value) in &unparseable {
+        eprintln!("Warning: item {} has an unrecognized last_updated value '{}'", id, value);
+    }
+    
+    println!("Checked {} items, normalized {} timestamps.", rows.len(), fixed);
+    if !unparseable.is_empty() {
+        println!("{} timestamps could not be parsed and were left unchanged.", unparseable.len());
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,45 @@
+// History command implementation
+// Implements: inventory history --id 1
+
+use anyhow::Result;
+use rusqlite::Connection;
+use serde_json::Value;
+use crate::db::history::item_history;
+use crate::db::queries::get_item_by_id;
+use crate::error::error::not_found;
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "(none)".to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Prints an item's recorded changes, oldest first, with the fields each one changed.
+/// Deleted items still have their history.
+pub fn handle_history(id: i32, conn: &Connection) -> Result<()> {
+    let id = id as i64;
+    let history = item_history(conn, id)?;
+    if history.is_empty() {
+        if get_item_by_id(conn, id)?.is_none() {
+            anyhow::bail!(not_found(format!("Item with ID {} does not exist", id)));
+        }
+        println!("No recorded changes for item {}", id);
+        return Ok(());
+    }
+
+    println!("History for item {} ({} change(s)):", id, history.len());
+    for entry in &history {
+        let action = if entry.action == "insert" { "created" } else { "updated" };
+        println!("{}  {}", entry.changed_at, action);
+        for change in &entry.changes {
+            if entry.action == "insert" {
+                println!("    {}: {}", change.field, display_value(&change.new));
+            } else {
+                println!("    {}: {} → {}", change.field, display_value(&change.old), display_value(&change.new));
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,16 +1,29 @@
 // Import command implementation
-// Implements: inventory import --file items.csv
+// Implements: inventory import --file items.csv|items.json|items.ndjson|items.xlsx [--dry-run] [--comment-char '#'] [--encoding windows-1252] [--fuzzy-headers] [--map "Product Name=title,Cost=price"] [--on-long-title reject|truncate] [--upsert [--upsert-key item_id|upc]] [--skip-duplicates [--duplicate-key title,brand,upc]]
+//             inventory import --retry ~/.inventory/failed/failed_import_<timestamp>.json
 
 use anyhow::{Result, Context};
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, IsTerminal, Write};
+use std::str::FromStr;
+use std::path::{Path, PathBuf};
+use calamine::{open_workbook_auto, Data, Reader};
 use chrono::Utc;
 use csv::ReaderBuilder;
+use encoding_rs::{DecoderResult, Encoding};
 use dirs::home_dir;
+use serde::{Deserialize, Serialize};
 use serde_json;
+use crate::commands::rollback_import::count_import_run_items;
+use crate::db::history::{record_import_operation, snapshot_items, OperationKind};
 use crate::db::queries;
-use crate::validation::{validate_item_ebay, ValidationResult, ValidationError};
+use crate::output::format::{NdjsonSchema, NDJSON_SCHEMA, NDJSON_SCHEMA_VERSION};
+use crate::output::console::status;
+use crate::output::progress::RowProgress;
+use crate::config::currency::{normalize_currency, DEFAULT_CURRENCY};
+use crate::validation::{normalize_condition, normalize_release_date, truncate_title, validate_item_ebay, validate_release_date, validate_sku_except, title_limit, ItemFields, Platform, ValidationResult, ValidationError};
 use rusqlite::Connection;
+use crate::error::error::{not_found, validation_failed};
 
 /// Fixed CSV schema as per specification
 const REQUIRED_FIELDS: [&str; 9] = [
@@ -18,6 +31,285 @@ const REQUIRED_FIELDS: [&str; 9] = [
     "upc", "category", "condition", "brand"
 ];
 
+/// Columns read when present
+const OPTIONAL_FIELDS: [&str; 3] = ["currency", "sku", "release_date"];
+
+/// Header text reduced to what fuzzy matching compares: lowercase, without spaces or underscores
+fn fuzzy_key(header: &str) -> String {
+    header.chars()
+        .filter(|c| !c.is_whitespace() && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Renames headers that differ from a canonical field name only in casing, spaces or underscores
+/// (e.g. "Item ID" -> item_id), printing how each header was mapped
+fn map_headers_fuzzy(headers: &csv::StringRecord) -> Result<csv::StringRecord> {
+    let canonical: Vec<&str> = REQUIRED_FIELDS.iter().chain(OPTIONAL_FIELDS.iter()).copied().collect();
+    let mut mapped = csv::StringRecord::new();
+    
+    status!("Header mapping:");
+    for header in headers.iter() {
+        let key = fuzzy_key(header);
+        match canonical.iter().find(|field| fuzzy_key(field) == key) {
+            Some(field) => {
+                if mapped.iter().any(|existing| existing == *field) {
+                    anyhow::bail!("Headers map to the same field twice: '{}' -> {}", header, field);
+                }
+                status!("  '{}' -> {}", header, field);
+                mapped.push_field(field);
+            }
+            None => {
+                status!("  '{}' (not mapped, ignored)", header);
+                mapped.push_field(header);
+            }
+        }
+    }
+    
+    Ok(mapped)
+}
+
+/// The text encoding of an import file, from `--encoding windows-1252`. Any WHATWG label is
+/// accepted, so `latin1` and `cp1252` work too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputEncoding(&'static Encoding);
+
+impl FromStr for InputEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Encoding::for_label(s.trim().as_bytes())
+            .map(Self)
+            .ok_or_else(|| format!("unknown encoding '{}'; try utf-8, windows-1252 or iso-8859-1", s.trim()))
+    }
+}
+
+impl InputEncoding {
+    /// Decodes `bytes` to UTF-8, dropping a byte order mark for this encoding. Bytes the
+    /// encoding can't decode are an error naming the line they're on, rather than becoming
+    /// replacement characters.
+    fn decode(self, bytes: &[u8], source: &str) -> Result<String> {
+        let mut decoder = self.0.new_decoder_with_bom_removal();
+        let mut text = String::with_capacity(bytes.len());
+        let mut read = 0;
+        loop {
+            let (result, consumed) = decoder.decode_to_string_without_replacement(&bytes[read..], &mut text, true);
+            read += consumed;
+            match result {
+                DecoderResult::InputEmpty => return Ok(text),
+                DecoderResult::OutputFull => text.reserve(bytes.len() - read + 16),
+                DecoderResult::Malformed(bad, after) => {
+                    let start = read - after as usize - bad as usize;
+                    anyhow::bail!(
+                        "{} isn't valid {}: byte 0x{:02X} on line {} can't be decoded. Pass the file's encoding with --encoding, e.g. --encoding windows-1252",
+                        source,
+                        self.0.name(),
+                        bytes[start],
+                        text.matches('\n').count() + 1
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Reads a whole text file, decoding it from `encoding` when one is given and as UTF-8 otherwise
+fn read_text(file_path: &Path, encoding: Option<InputEncoding>, kind: &str) -> Result<String> {
+    let context = || format!("Failed to open {} file: {}", kind, file_path.display());
+    match encoding {
+        Some(encoding) => encoding.decode(&std::fs::read(file_path).with_context(context)?, &file_path.display().to_string()),
+        None => std::fs::read_to_string(file_path).with_context(context),
+    }
+}
+
+/// Source columns read as schema fields, from `--map "Product Name=title,Cost=price"`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnMap {
+    /// (source column, schema field) pairs
+    aliases: Vec<(String, String)>,
+}
+
+impl FromStr for ColumnMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut aliases: Vec<(String, String)> = Vec::new();
+        for pair in s.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (source, field) = pair.split_once('=')
+                .ok_or_else(|| format!("expected Column=field, got '{}'", pair.trim()))?;
+            let (source, field) = (source.trim(), field.trim().to_lowercase());
+            if source.is_empty() {
+                return Err(format!("no source column given for {}", field));
+            }
+            if !REQUIRED_FIELDS.contains(&field.as_str()) && !OPTIONAL_FIELDS.contains(&field.as_str()) {
+                return Err(format!("'{}' isn't an import field; use {}, {}", field, REQUIRED_FIELDS.join(", "), OPTIONAL_FIELDS.join(", ")));
+            }
+            if aliases.iter().any(|(_, existing)| *existing == field) {
+                return Err(format!("{} is mapped more than once", field));
+            }
+            aliases.push((source.to_string(), field));
+        }
+        if aliases.is_empty() {
+            return Err("column map needs at least one Column=field pair".to_string());
+        }
+        Ok(Self { aliases })
+    }
+}
+
+/// What to do with a title over the platform limit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LongTitlePolicy {
+    /// Fail validation for the row, as for any other invalid field
+    #[default]
+    Reject,
+    /// Shorten the title to the limit, preferably at a word break, and warn
+    Truncate,
+}
+
+/// Column that matches an import row to an existing item in `--upsert` mode
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum UpsertKey {
+    #[default]
+    #[value(name = "item_id")]
+    ItemId,
+    Upc,
+}
+
+/// Text fields a duplicate key can be built from
+pub const DUPLICATE_KEY_FIELDS: [&str; 6] = ["title", "description", "upc", "category", "condition", "brand"];
+
+/// Fields that make two rows the same item in `--skip-duplicates` mode. Values are compared
+/// ignoring case and surrounding whitespace; an empty value matches an empty or missing one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    fields: Vec<String>,
+}
+
+impl Default for DuplicateKey {
+    fn default() -> Self {
+        Self { fields: vec!["title".to_string(), "brand".to_string(), "upc".to_string()] }
+    }
+}
+
+impl FromStr for DuplicateKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let fields: Vec<String> = s.split(',')
+            .map(|field| field.trim().to_lowercase())
+            .filter(|field| !field.is_empty())
+            .collect();
+        if fields.is_empty() {
+            return Err("duplicate key needs at least one field".to_string());
+        }
+        if let Some(field) = fields.iter().find(|field| !DUPLICATE_KEY_FIELDS.contains(&field.as_str())) {
+            return Err(format!("'{}' can't be part of a duplicate key; use {}", field, DUPLICATE_KEY_FIELDS.join(", ")));
+        }
+        Ok(Self { fields })
+    }
+}
+
+impl DuplicateKey {
+    /// The row's normalized values for the key fields
+    fn key_of(&self, row: &RowFields) -> Vec<String> {
+        self.fields.iter()
+            .map(|field| match field.as_str() {
+                "title" => &row.title,
+                "description" => &row.description,
+                "upc" => &row.upc,
+                "category" => &row.category,
+                "condition" => &row.condition,
+                _ => &row.brand,
+            })
+            .map(|value| value.trim().to_lowercase())
+            .collect()
+    }
+    
+    /// True if an item in the database already has these key values
+    fn exists_in(&self, conn: &Connection, key: &[String]) -> Result<bool> {
+        // Field names were checked against DUPLICATE_KEY_FIELDS when the key was parsed
+        let conditions: Vec<String> = self.fields.iter()
+            .map(|field| format!("LOWER(TRIM(IFNULL({}, ''))) = ?", field))
+            .collect();
+        let count: i64 = conn.prepare_cached(&format!("SELECT COUNT(*) FROM items WHERE {}", conditions.join(" AND ")))?
+            .query_row(rusqlite::params_from_iter(key), |row| row.get(0))?;
+        Ok(count > 0)
+    }
+}
+
+/// Identifies the rows written by one import, so they can be filtered or rolled back together.
+/// Ids are timestamps, suffixed if an earlier run in the same millisecond already used one.
+pub fn new_import_run_id(conn: &Connection) -> Result<String> {
+    let base = Utc::now().format("%Y%m%dT%H%M%S%3f").to_string();
+    let mut run_id = base.clone();
+    let mut attempt = 1;
+    while count_import_run_items(conn, &run_id)? > 0 {
+        attempt += 1;
+        run_id = format!("{}-{}", base, attempt);
+    }
+    Ok(run_id)
+}
+
+/// Contents of a `failed_import_*.json` file: the `ValidationResult` errors, plus the fields of
+/// each row that failed so `import --retry` can rebuild it
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FailedImport {
+    errors: Vec<ValidationError>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rows: Vec<FailedRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FailedRow {
+    row: usize,
+    #[serde(flatten)]
+    fields: RowFields,
+}
+
+impl FailedImport {
+    fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.rows.is_empty()
+    }
+    
+    /// Writes the file under a new timestamped name and returns its path
+    fn save(&self) -> Result<PathBuf> {
+        let path = failed_imports_path();
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+    
+    /// Splits the file into the rows to retry and the errors that belong to no row. Rows saved
+    /// without their fields (older files, or rows that couldn't be read) are rebuilt from the
+    /// values their errors recorded; anything else is left for validation to report.
+    fn into_retry_rows(self) -> (Vec<FailedRow>, Vec<ValidationError>) {
+        let mut rows = self.rows;
+        let saved: HashSet<usize> = rows.iter().map(|failed| failed.row).collect();
+        let mut unattached = Vec::new();
+        
+        for error in self.errors {
+            let Some(row) = error.row.filter(|row| !saved.contains(row)) else {
+                if error.row.is_none() {
+                    unattached.push(error);
+                }
+                continue;
+            };
+            let idx = match rows.iter().position(|failed| failed.row == row) {
+                Some(idx) => idx,
+                None => {
+                    rows.push(FailedRow { row, fields: RowFields::default() });
+                    rows.len() - 1
+                }
+            };
+            if let Some(value) = error.value {
+                rows[idx].fields.set_field(&error.field, value);
+            }
+        }
+        
+        rows.sort_by_key(|failed| failed.row);
+        (rows, unattached)
+    }
+}
+
 fn failed_imports_path() -> PathBuf {
     let mut dir = home_dir().expect("Could not determine home directory");
     dir.push(".inventory/failed");
@@ -29,19 +321,419 @@ fn failed_imports_path() -> PathBuf {
     dir
 }
 
-/// Validate CSV headers against fixed schema
-fn validate_headers(headers: &csv::StringRecord) -> Result<()> {
-    let header_fields: Vec<&str> = headers.iter().collect();
+/// Maps each schema field to the CSV header it's read from. Headers match field names ignoring
+/// case and surrounding whitespace; `--map` aliases take precedence. Fails naming the first
+/// required field left without a column.
+fn build_column_mapping(headers: &csv::StringRecord, column_map: Option<&ColumnMap>) -> Result<HashMap<String, String>> {
+    let find_header = |name: &str| {
+        let name = name.trim().to_lowercase();
+        headers.iter().find(|header| header.trim().to_lowercase() == name)
+    };
+    let mut mapping = HashMap::new();
+    
+    if let Some(column_map) = column_map {
+        status!("Column mapping:");
+        for (source, field) in &column_map.aliases {
+            let header = find_header(source)
+                .ok_or_else(|| anyhow::anyhow!("Column '{}' from --map isn't in the file", source))?;
+            status!("  '{}' -> {}", header, field);
+            mapping.insert(field.clone(), header.to_string());
+        }
+    }
+    for field in REQUIRED_FIELDS.iter().chain(OPTIONAL_FIELDS.iter()) {
+        if !mapping.contains_key(*field) {
+            if let Some(header) = find_header(field) {
+                mapping.insert(field.to_string(), header.to_string());
+            }
+        }
+    }
+    
+    if let Some(missing) = REQUIRED_FIELDS.iter().find(|field| !mapping.contains_key(**field)) {
+        anyhow::bail!("Missing required field: {} (no column matches it; map one with --map \"Column={}\")", missing, missing);
+    }
+    
+    Ok(mapping)
+}
+
+/// An empty currency column means the default currency
+fn currency_or_default(currency: &str) -> &str {
+    if currency.is_empty() { DEFAULT_CURRENCY } else { currency }
+}
+
+/// Raw field values of one input row, whichever file format it came from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RowFields {
+    item_id: String,
+    title: String,
+    description: String,
+    price: String,
+    quantity: String,
+    upc: String,
+    category: String,
+    condition: String,
+    brand: String,
+    currency: String,
+    sku: String,
+    release_date: String,
+}
+
+impl RowFields {
+    fn from_csv(record: &csv::StringRecord, headers: &csv::StringRecord, mapping: &HashMap<String, String>) -> Self {
+        let get_field = |field: &str| -> String {
+            mapping.get(field)
+                .and_then(|header| headers.iter().position(|h| h == header))
+                .and_then(|pos| record.get(pos))
+                .unwrap_or("")
+                .to_string()
+        };
+        
+        Self {
+            item_id: get_field("item_id"),
+            title: get_field("title"),
+            description: get_field("description"),
+            price: get_field("price"),
+            quantity: get_field("quantity"),
+            upc: get_field("upc"),
+            category: get_field("category"),
+            condition: get_field("condition"),
+            brand: get_field("brand"),
+            currency: get_field("currency"),
+            sku: get_field("sku"),
+            release_date: get_field("release_date"),
+        }
+    }
+    
+    /// Sets a field by its schema name; other names are ignored
+    fn set_field(&mut self, field: &str, value: String) {
+        match field {
+            "item_id" => self.item_id = value,
+            "title" => self.title = value,
+            "description" => self.description = value,
+            "price" => self.price = value,
+            "quantity" => self.quantity = value,
+            "upc" => self.upc = value,
+            "category" => self.category = value,
+            "condition" => self.condition = value,
+            "brand" => self.brand = value,
+            "currency" => self.currency = value,
+            "sku" => self.sku = value,
+            "release_date" => self.release_date = value,
+            _ => {}
+        }
+    }
+    
+    /// Validates the row's fields for eBay, without looking at the database
+    fn validate_fields(&self) -> Result<ValidationResult> {
+        let mut result = validate_item_ebay(&ItemFields {
+            title: &self.title,
+            price: self.price.parse().unwrap_or(-1.0),
+            quantity: self.quantity.parse().unwrap_or(-1),
+            category: &self.category,
+            condition: &self.condition,
+            brand: if self.brand.is_empty() { None } else { Some(&self.brand) },
+            upc: if self.upc.is_empty() { None } else { Some(&self.upc) },
+            size: None,
+            currency: currency_or_default(&self.currency),
+        })?;
+        result.errors.extend(validate_release_date(&self.release_date)?.errors);
+        Ok(result)
+    }
     
-    for required_field in &REQUIRED_FIELDS {
-        if !header_fields.contains(required_field) {
-            anyhow::bail!("Missing required field: {}", required_field);
+    /// Validates the row for eBay, and that its SKU (if any) isn't already in the inventory on
+    /// an item other than `existing`, the one an upserted row is written to
+    fn validate(&self, conn: &Connection, existing: Option<i64>) -> Result<ValidationResult> {
+        let mut result = self.validate_fields()?;
+        if !self.sku.is_empty() {
+            result.errors.extend(validate_sku_except(conn, &self.sku, existing)?.errors);
         }
+        Ok(result)
+    }
+    
+    /// The raw value of a field by its schema name
+    fn field_value(&self, field: &str) -> Option<&str> {
+        match field {
+            "title" => Some(&self.title),
+            "price" => Some(&self.price),
+            "quantity" => Some(&self.quantity),
+            "category" => Some(&self.category),
+            "condition" => Some(&self.condition),
+            "brand" => Some(&self.brand),
+            "upc" => Some(&self.upc),
+            "currency" => Some(&self.currency),
+            "sku" => Some(&self.sku),
+            "release_date" => Some(&self.release_date),
+            _ => None,
+        }
+    }
+    
+    /// Tags each error with the row it came from and the value that failed
+    fn annotate(&self, result: &mut ValidationResult, row_idx: usize) {
+        for err in &mut result.errors {
+            err.row = Some(row_idx + 1);
+            err.value = self.field_value(&err.field).map(str::to_string);
+        }
+    }
+    
+    /// The row laid over an existing item: fields the row leaves empty keep the item's values
+    fn merged_with(&self, existing: &queries::FullItem) -> Self {
+        let pick = |value: &str, current: &str| {
+            if value.is_empty() { current.to_string() } else { value.to_string() }
+        };
+        Self {
+            item_id: existing.item_id.to_string(),
+            title: pick(&self.title, &existing.title),
+            description: pick(&self.description, existing.description.as_deref().unwrap_or_default()),
+            price: pick(&self.price, &existing.price.to_string()),
+            quantity: pick(&self.quantity, &existing.quantity.to_string()),
+            upc: pick(&self.upc, existing.upc.as_deref().unwrap_or_default()),
+            category: pick(&self.category, &existing.category),
+            condition: pick(&self.condition, &existing.condition),
+            brand: pick(&self.brand, existing.brand.as_deref().unwrap_or_default()),
+            currency: pick(&self.currency, &existing.currency),
+            sku: pick(&self.sku, existing.sku.as_deref().unwrap_or_default()),
+            release_date: pick(&self.release_date, existing.release_date.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+/// An item in a JSON import file; missing fields are left for validation to report
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JsonItem {
+    item_id: Option<i64>,
+    title: Option<String>,
+    description: Option<String>,
+    price: Option<f64>,
+    quantity: Option<i64>,
+    upc: Option<String>,
+    category: Option<String>,
+    condition: Option<String>,
+    brand: Option<String>,
+    currency: Option<String>,
+    sku: Option<String>,
+    release_date: Option<String>,
+}
+
+impl From<JsonItem> for RowFields {
+    fn from(item: JsonItem) -> Self {
+        Self {
+            item_id: item.item_id.map(|id| id.to_string()).unwrap_or_default(),
+            title: item.title.unwrap_or_default(),
+            description: item.description.unwrap_or_default(),
+            price: item.price.map(|p| p.to_string()).unwrap_or_default(),
+            quantity: item.quantity.map(|q| q.to_string()).unwrap_or_default(),
+            upc: item.upc.unwrap_or_default(),
+            category: item.category.unwrap_or_default(),
+            condition: item.condition.unwrap_or_default(),
+            brand: item.brand.unwrap_or_default(),
+            currency: item.currency.unwrap_or_default(),
+            sku: item.sku.unwrap_or_default(),
+            release_date: item.release_date.unwrap_or_default(),
+        }
+    }
+}
+
+/// A row that couldn't be read at all, recorded like a validation failure
+fn parse_error(field: &str, message: String, row_idx: usize) -> ValidationError {
+    ValidationError {
+        field: field.to_string(),
+        message,
+        row: Some(row_idx + 1),
+        value: None,
+    }
+}
+
+/// True for records with no content at all, such as `,,,` separator lines
+fn is_empty_record(record: &csv::StringRecord) -> bool {
+    record.iter().all(|field| field.trim().is_empty())
+}
+
+/// Keeps the first `limit` items, counting the rest without keeping them
+fn take_limit<I: Iterator>(mut items: I, limit: Option<usize>) -> (Vec<I::Item>, usize) {
+    match limit {
+        Some(limit) => {
+            let taken = items.by_ref().take(limit).collect();
+            (taken, items.count())
+        }
+        None => (items.collect(), 0),
+    }
+}
+
+fn read_csv_rows(file_path: &Path, comment_char: Option<u8>, fuzzy_headers: bool, column_map: Option<&ColumnMap>, encoding: Option<InputEncoding>, limit: Option<usize>) -> Result<(Vec<Result<RowFields, ValidationError>>, usize)> {
+    let mut builder = ReaderBuilder::new();
+    builder.flexible(true).comment(comment_char);
+    // Another encoding is decoded up front, so a bad byte fails the import before any row
+    let mut rdr = if let Some(encoding) = encoding {
+        let text = if is_stdin(file_path) {
+            let mut bytes = Vec::new();
+            io::Read::read_to_end(&mut io::stdin().lock(), &mut bytes).context("Failed to read CSV from standard input")?;
+            encoding.decode(&bytes, "standard input")?
+        } else {
+            read_text(file_path, Some(encoding), "CSV")?
+        };
+        builder.from_reader(Box::new(io::Cursor::new(text.into_bytes())) as Box<dyn io::Read>)
+    } else if is_stdin(file_path) {
+        builder.from_reader(Box::new(io::stdin().lock()) as Box<dyn io::Read>)
+    } else {
+        let file = std::fs::File::open(file_path)
+            .with_context(|| format!("Failed to open CSV file: {}", file_path.display()))?;
+        builder.from_reader(Box::new(file) as Box<dyn io::Read>)
+    };
+    
+    // Validate headers
+    let mut headers = rdr.headers()?.clone();
+    if fuzzy_headers {
+        headers = map_headers_fuzzy(&headers)?;
     }
+    let mapping = build_column_mapping(&headers, column_map)?;
     
+    status!("CSV schema validated.");
+    
+    let records = rdr.records()
+        .filter(|result| !matches!(result, Ok(record) if is_empty_record(record)));
+    let (records, over_limit) = take_limit(records, limit);
+    let rows = records.into_iter()
+        .enumerate()
+        .map(|(row_idx, result)| match result {
+            Ok(record) => Ok(RowFields::from_csv(&record, &headers, &mapping)),
+            Err(e) => Err(parse_error("csv_parse", format!("CSV parse error: {}", e), row_idx)),
+        })
+        .collect();
+    
+    Ok((rows, over_limit))
+}
+
+/// A spreadsheet cell as import text. Numbers come from the stored value, not the displayed
+/// one, so a price formatted as "$29.99" still reads as 29.99.
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(s) => s.clone(),
+        Data::Int(i) => i.to_string(),
+        Data::Float(f) => f.to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Read the first worksheet of an Excel workbook, with row 1 as the headers
+fn read_xlsx_rows(file_path: &Path, fuzzy_headers: bool, column_map: Option<&ColumnMap>, limit: Option<usize>) -> Result<(Vec<Result<RowFields, ValidationError>>, usize)> {
+    let mut workbook = open_workbook_auto(file_path)
+        .with_context(|| format!("Failed to open workbook: {}", file_path.display()))?;
+    let sheet = workbook.sheet_names().first().cloned()
+        .ok_or_else(|| anyhow::anyhow!("Workbook has no worksheets: {}", file_path.display()))?;
+    let range = workbook.worksheet_range(&sheet)
+        .with_context(|| format!("Failed to read worksheet '{}'", sheet))?;
+    
+    let mut sheet_rows = range.rows();
+    let header_cells = sheet_rows.next()
+        .ok_or_else(|| anyhow::anyhow!("Worksheet '{}' is empty", sheet))?;
+    let mut headers: csv::StringRecord = header_cells.iter().map(cell_text).collect();
+    if fuzzy_headers {
+        headers = map_headers_fuzzy(&headers)?;
+    }
+    let mapping = build_column_mapping(&headers, column_map)?;
+    
+    status!("Worksheet '{}' schema validated.", sheet);
+    
+    let records = sheet_rows
+        .map(|cells| cells.iter().map(cell_text).collect::<csv::StringRecord>())
+        .filter(|record| !is_empty_record(record));
+    let (records, over_limit) = take_limit(records, limit);
+    let rows = records.iter()
+        .map(|record| Ok(RowFields::from_csv(record, &headers, &mapping)))
+        .collect();
+    
+    Ok((rows, over_limit))
+}
+
+/// Read a JSON import file: either a bare array of items or an object with an "items" array.
+/// Malformed JSON fails the whole import; an element that isn't a usable item fails its row.
+fn read_json_rows(file_path: &Path, encoding: Option<InputEncoding>, limit: Option<usize>) -> Result<(Vec<Result<RowFields, ValidationError>>, usize)> {
+    let contents = read_text(file_path, encoding, "JSON")?;
+    let document: serde_json::Value = serde_json::from_str(&contents)
+        .with_context(|| format!("Malformed JSON in {}", file_path.display()))?;
+    
+    let items = match document {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(mut object) => match object.remove("items") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => anyhow::bail!("JSON import object must have an \"items\" array"),
+        },
+        _ => anyhow::bail!("JSON import file must be an array of items or an object with an \"items\" array"),
+    };
+    
+    status!("JSON parsed: {} items. Starting import...", items.len());
+    
+    // The document is parsed whole, but items past the limit are never read as rows
+    let (items, over_limit) = take_limit(items.into_iter(), limit);
+    let rows = items.into_iter()
+        .enumerate()
+        .map(|(row_idx, value)| {
+            serde_json::from_value::<JsonItem>(value)
+                .map(RowFields::from)
+                .map_err(|e| parse_error("json_parse", format!("JSON item error: {}", e), row_idx))
+        })
+        .collect();
+    
+    Ok((rows, over_limit))
+}
+
+/// Fields an ndjson stream must carry for its items to be importable
+const NDJSON_REQUIRED_FIELDS: [&str; 5] = ["title", "price", "quantity", "category", "condition"];
+
+/// Check an ndjson schema line: it must describe an item stream in a version this build reads
+fn check_ndjson_schema(schema: &NdjsonSchema) -> Result<()> {
+    if schema.schema != NDJSON_SCHEMA {
+        anyhow::bail!("Not an inventory item stream: schema is '{}', expected '{}'", schema.schema, NDJSON_SCHEMA);
+    }
+    if schema.version == 0 || schema.version > NDJSON_SCHEMA_VERSION {
+        anyhow::bail!(
+            "Unsupported ndjson schema version {} (this version reads versions 1 to {})",
+            schema.version,
+            NDJSON_SCHEMA_VERSION
+        );
+    }
+    for field in NDJSON_REQUIRED_FIELDS {
+        if !schema.fields.iter().any(|f| f == field) {
+            anyhow::bail!("ndjson schema is missing required field: {}", field);
+        }
+    }
     Ok(())
 }
 
+/// Read an ndjson import file: one item object per line, optionally preceded by a schema line
+/// (as written by `--format ndjson --ndjson-schema`), which is checked before anything is imported.
+/// A line that isn't a usable item fails its row.
+fn read_ndjson_rows(file_path: &Path, encoding: Option<InputEncoding>, limit: Option<usize>) -> Result<(Vec<Result<RowFields, ValidationError>>, usize)> {
+    let contents = read_text(file_path, encoding, "ndjson")?;
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty()).peekable();
+    
+    let schema_line = lines.peek()
+        .and_then(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|value| value.get("schema").is_some());
+    if let Some(value) = schema_line {
+        let schema: NdjsonSchema = serde_json::from_value(value)
+            .context("Invalid ndjson schema line")?;
+        check_ndjson_schema(&schema)?;
+        status!("NDJSON schema {} v{} validated.", schema.schema, schema.version);
+        lines.next();
+    }
+    
+    let (lines, over_limit) = take_limit(lines, limit);
+    let rows: Vec<_> = lines.into_iter()
+        .enumerate()
+        .map(|(row_idx, line)| {
+            serde_json::from_str::<JsonItem>(line)
+                .map(RowFields::from)
+                .map_err(|e| parse_error("json_parse", format!("ndjson line error: {}", e), row_idx))
+        })
+        .collect();
+    
+    status!("NDJSON parsed: {} items. Starting import...", rows.len());
+    Ok((rows, over_limit))
+}
+
 /// Interactive prompt for correcting invalid data
 fn prompt_for_correction(field: &str, current_value: &str, error_message: &str, row: usize) -> Result<Option<String>> {
     println!("\nRow {}: Invalid {} - {}", row, field, error_message);
@@ -60,80 +752,120 @@ fn prompt_for_correction(field: &str, current_value: &str, error_message: &str,
     }
 }
 
-/// Process a single CSV row with interactive correction or non-interactive skip
-fn process_row(
-    record: &csv::StringRecord,
-    headers: &csv::StringRecord,
-    row_idx: usize,
-    conn: &Connection,
+fn ascii_comment_char(comment_char: Option<char>) -> Result<Option<u8>> {
+    match comment_char {
+        Some(c) if c.is_ascii() => Ok(Some(c as u8)),
+        Some(c) => anyhow::bail!("Comment character must be ASCII, got '{}'", c),
+        None => Ok(None),
+    }
+}
+
+/// The path that stands for standard input, which is always read as CSV
+pub const STDIN_PATH: &str = "-";
+
+fn is_stdin(file_path: &Path) -> bool {
+    file_path == Path::new(STDIN_PATH)
+}
+
+/// Reads the rows of an import file in the format its extension names; anything else is CSV.
+/// Without an `encoding`, text files are read as UTF-8. Only the first `limit` rows are read
+/// into fields; the rest are counted, and the count is returned alongside.
+fn read_rows(file_path: &Path, comment_char: Option<u8>, fuzzy_headers: bool, column_map: Option<&ColumnMap>, encoding: Option<InputEncoding>, limit: Option<usize>) -> Result<(Vec<Result<RowFields, ValidationError>>, usize)> {
+    let extension = file_path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("json") => read_json_rows(file_path, encoding, limit),
+        Some("ndjson") | Some("jsonl") => read_ndjson_rows(file_path, encoding, limit),
+        Some("xlsx") if encoding.is_some() => anyhow::bail!("--encoding only applies to text files; workbooks carry their own encoding"),
+        Some("xlsx") => read_xlsx_rows(file_path, fuzzy_headers, column_map, limit),
+        _ => read_csv_rows(file_path, comment_char, fuzzy_headers, column_map, encoding, limit),
+    }
+}
+
+/// Checks every row of an import file against the rules import applies, without touching the
+/// database: the same columns are read and the same item validation runs, so a file that
+/// passes only fails to import on database checks such as a SKU already in use. Rows that
+/// can't be read are reported as errors too. On top of import's rules, a row in the `stockx`
+/// category needs a UPC, since it can't be listed there without one.
+pub fn validate_csv(file_path: &Path, comment_char: Option<char>, fuzzy_headers: bool, column_map: Option<&ColumnMap>, encoding: Option<InputEncoding>) -> Result<ValidationResult> {
+    if !is_stdin(file_path) && !file_path.exists() {
+        anyhow::bail!(not_found(format!("File not found: {}", file_path.display())));
+    }
+    let (rows, _) = read_rows(file_path, ascii_comment_char(comment_char)?, fuzzy_headers, column_map, encoding, None)?;
+    let mut result = ValidationResult::new();
+    for (row_idx, row) in rows.into_iter().enumerate() {
+        match row {
+            Ok(row) => {
+                let mut validation = row.validate_fields()?;
+                if row.category == "stockx" && row.upc.trim().is_empty() {
+                    validation.add_error("upc", "UPC required for StockX", None, None);
+                }
+                row.annotate(&mut validation, row_idx);
+                result.errors.extend(validation.errors);
+            }
+            Err(error) => result.errors.push(error),
+        }
+    }
+    Ok(result)
+}
+
+/// What every row of one import run is processed with
+struct RowContext<'a> {
+    conn: &'a Connection,
     non_interactive: bool,
-) -> Result<bool> {
-    // Extract fields from CSV
-    let get_field = |field: &str| -> String {
-        headers.iter()
-            .position(|h| h == field)
-            .and_then(|pos| record.get(pos))
-            .unwrap_or("")
-            .to_string()
-    };
-    
-    let mut title = get_field("title");
-    let description = get_field("description");
-    let mut price_str = get_field("price");
-    let mut quantity_str = get_field("quantity");
-    let mut upc = get_field("upc");
-    let mut category = get_field("category");
-    let mut condition = get_field("condition");
-    let mut brand = get_field("brand");
+    dry_run: bool,
+    run_id: &'a str,
+    progress: &'a RowProgress,
+}
+
+/// Process a single row with interactive correction or non-interactive skip
+fn process_row(row: &RowFields, row_idx: usize, existing: Option<i64>, context: &RowContext) -> Result<bool> {
+    let RowContext { conn, non_interactive, dry_run, run_id, progress } = *context;
+    let RowFields {
+        item_id: _,
+        mut title,
+        description,
+        price: price_str,
+        quantity: quantity_str,
+        mut upc,
+        mut category,
+        mut condition,
+        mut brand,
+        mut currency,
+        mut sku,
+        mut release_date,
+    } = row.clone();
     
     // Parse numeric fields
     let mut price = price_str.parse::<f64>().unwrap_or(-1.0);
     let mut quantity = quantity_str.parse::<i32>().unwrap_or(-1);
     
     // Validate the row
-    let mut validation = validate_item_ebay(
-        &title,
-        price,
-        quantity,
-        &category,
-        &condition,
-        if brand.is_empty() { None } else { Some(&brand) },
-        if upc.is_empty() { None } else { Some(&upc) },
-    )?;
-    
-    // Add row information to errors
-    for err in &mut validation.errors {
-        err.row = Some(row_idx + 1);
-        err.value = match err.field.as_str() {
-            "title" => Some(title.clone()),
-            "price" => Some(price_str.clone()),
-            "quantity" => Some(quantity_str.clone()),
-            "category" => Some(category.clone()),
-            "condition" => Some(condition.clone()),
-            "brand" => Some(brand.clone()),
-            "upc" => Some(upc.clone()),
-            _ => None,
-        };
-    }
+    let mut validation = row.validate(conn, existing)?;
+    row.annotate(&mut validation, row_idx);
     
     // If validation fails, prompt for corrections (unless non_interactive)
     if !validation.is_valid() {
+        for err in &validation.errors {
+            log::info!("Row {} failed validation: {}: {}", row_idx + 1, err.field, err.message);
+        }
         if non_interactive {
             // In non-interactive mode, always skip invalid rows
             return Ok(false);
         }
-        println!("\n=== Row {} has validation errors ===", row_idx + 1);
+        progress.println(format!("\n=== Row {} has validation errors ===", row_idx + 1));
         
         // Show all errors first
         for err in &validation.errors {
-            println!("- {}: {} (value: '{}')", 
-                err.field, err.message, err.value.as_deref().unwrap_or(""));
+            progress.println(format!("- {}: {} (value: '{}')", 
+                err.field, err.message, err.value.as_deref().unwrap_or("")));
         }
         
         // Prompt for corrections for each error
         for err in &validation.errors {
             let current_value = err.value.as_deref().unwrap_or("");
-            let correction = prompt_for_correction(&err.field, current_value, &err.message, row_idx + 1)?;
+            let correction = progress.suspend(|| prompt_for_correction(&err.field, current_value, &err.message, row_idx + 1))?;
             
             if let Some(new_value) = correction {
                 // Update the corresponding field
@@ -149,6 +881,9 @@ fn process_row(
                     "condition" => condition = new_value,
                     "brand" => brand = new_value,
                     "upc" => upc = new_value,
+                    "currency" => currency = new_value,
+                    "sku" => sku = new_value,
+                    "release_date" => release_date = new_value,
                     _ => {}
                 }
             } else {
@@ -158,23 +893,32 @@ fn process_row(
         }
         
         // Re-validate after corrections
-        let revalidation = validate_item_ebay(
-            &title,
+        let mut revalidation = validate_item_ebay(&ItemFields {
+            title: &title,
             price,
             quantity,
-            &category,
-            &condition,
-            if brand.is_empty() { None } else { Some(&brand) },
-            if upc.is_empty() { None } else { Some(&upc) },
-        )?;
+            category: &category,
+            condition: &condition,
+            brand: if brand.is_empty() { None } else { Some(&brand) },
+            upc: if upc.is_empty() { None } else { Some(&upc) },
+            size: None,
+            currency: currency_or_default(&currency),
+        })?;
+        if !sku.is_empty() {
+            revalidation.errors.extend(validate_sku_except(conn, &sku, existing)?.errors);
+        }
+        revalidation.errors.extend(validate_release_date(&release_date)?.errors);
         
         if !revalidation.is_valid() {
-            println!("Row {} still has validation errors after correction. Skipping.", row_idx + 1);
+            progress.error(format!("Row {} still has validation errors after correction. Skipping.", row_idx + 1));
             return Ok(false);
         }
     }
     
-    // Insert into database
+    // Insert into database, storing an alias like "pre-owned" as the condition it stands for
+    let currency = normalize_currency(&currency);
+    let condition = normalize_condition(&condition);
+    let release_date = normalize_release_date(&release_date);
     let item = queries::NewItem {
         title: &title,
         description: if description.is_empty() { None } else { Some(&description) },
@@ -191,79 +935,226 @@ fn process_row(
         original_price: None,
         hashtags: None,
         colorway: None,
-        release_date: None,
+        release_date: release_date.as_deref(),
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: currency.as_deref(),
+        import_run: Some(run_id),
+        sku: if sku.is_empty() { None } else { Some(&sku) },
     };
     
+    if dry_run {
+        progress.detail(format!("✓ Row {} is valid", row_idx + 1));
+        return Ok(true);
+    }
+    
+    // An upserted row updates the item it matched instead of inserting a new one
+    if let Some(id) = existing {
+        let changes = queries::ItemChanges {
+            title: Some(item.title),
+            description: item.description,
+            price: Some(price),
+            quantity: Some(quantity),
+            category: Some(item.category),
+            condition: Some(item.condition),
+            brand: item.brand,
+            upc: item.upc,
+            release_date: item.release_date,
+            currency: item.currency,
+            sku: item.sku,
+            import_run: Some(run_id),
+        };
+        return match queries::update_item_columns(conn, id, &changes) {
+            Ok(()) => {
+                progress.detail(format!("✓ Row {} updated item {}", row_idx + 1, id));
+                Ok(true)
+            },
+            Err(e) => {
+                progress.error(format!("✗ Row {} database error: {}", row_idx + 1, e));
+                log::error!("Row {} could not update item {}: {}", row_idx + 1, id, e);
+                Ok(false)
+            }
+        };
+    }
+    
     match queries::insert_item(conn, &item) {
         Ok(_) => {
-            println!("✓ Row {} imported successfully", row_idx + 1);
+            progress.detail(format!("✓ Row {} imported successfully", row_idx + 1));
             Ok(true)
         },
         Err(e) => {
-            println!("✗ Row {} database error: {}", row_idx + 1, e);
+            progress.error(format!("✗ Row {} database error: {}", row_idx + 1, e));
+            log::error!("Row {} could not be inserted: {}", row_idx + 1, e);
             Ok(false)
         }
     }
 }
 
-pub fn handle_import(file: String, conn: &Connection, non_interactive: bool) -> Result<()> {
+/// Finds the existing item an upsert row refers to. A row without a key value, or whose key
+/// matches nothing, is a new item.
+fn find_upsert_target(conn: &Connection, row: &RowFields, key: UpsertKey) -> Result<Option<queries::FullItem>> {
+    let id = match key {
+        UpsertKey::ItemId if row.item_id.trim().is_empty() => return Ok(None),
+        UpsertKey::ItemId => row.item_id.trim().parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("item_id '{}' is not a number", row.item_id))?,
+        UpsertKey::Upc if row.upc.trim().is_empty() => return Ok(None),
+        UpsertKey::Upc => match queries::find_item_ids_by_upc(conn, row.upc.trim())?[..] {
+            [] => return Ok(None),
+            [id] => id,
+            ref ids => anyhow::bail!("UPC {} matches {} items, so the row can't be matched to one", row.upc, ids.len()),
+        },
+    };
+    let existing = queries::get_full_item(conn, id)?;
+    if let Some(item) = &existing {
+        // The price is validated and stored in the item's currency, so an upsert can't change it
+        if let Some(currency) = normalize_currency(&row.currency).filter(|currency| *currency != item.currency) {
+            anyhow::bail!("Can't change item {}'s currency from {} to {} on upsert", item.item_id, item.currency, currency);
+        }
+    }
+    Ok(existing)
+}
+
+/// IDs of the items tagged with an import run: the ones it created and the ones it upserted
+fn import_run_item_ids(conn: &Connection, run: &str) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT item_id FROM items WHERE last_import_run = ? ORDER BY item_id")?;
+    let ids = stmt.query_map([run], |row| row.get(0))?
+        .collect::<Result<Vec<i64>, rusqlite::Error>>()?;
+    Ok(ids)
+}
+
+/// How `import` reads a file and writes its rows; the defaults prompt for corrections and
+/// insert every valid row
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportOptions<'a> {
+    /// Skip invalid rows instead of prompting for corrections
+    pub non_interactive: bool,
+    pub dry_run: bool,
+    pub comment_char: Option<char>,
+    pub fuzzy_headers: bool,
+    pub on_long_title: LongTitlePolicy,
+    /// Update the item a row matches on this key instead of inserting it
+    pub upsert: Option<UpsertKey>,
+    pub skip_duplicates: Option<&'a DuplicateKey>,
+    pub column_map: Option<&'a ColumnMap>,
+    pub max_rows: Option<usize>,
+    pub encoding: Option<InputEncoding>,
+}
+
+pub fn handle_import(file: String, conn: &Connection, options: &ImportOptions) -> Result<()> {
+    let ImportOptions { non_interactive, dry_run, comment_char, fuzzy_headers, on_long_title, upsert, skip_duplicates, column_map, max_rows, encoding } = *options;
     let file_path = PathBuf::from(&file);
-    if !file_path.exists() {
-        anyhow::bail!("File not found: {}", file);
+    let from_stdin = is_stdin(&file_path);
+    if !from_stdin && !file_path.exists() {
+        anyhow::bail!(not_found(format!("File not found: {}", file)));
     }
+    let comment_char = ascii_comment_char(comment_char)?;
+    // The rows come in on stdin, so there's no way to read corrections from it too
+    let non_interactive = non_interactive || from_stdin;
+    let source = if from_stdin { "standard input" } else { file.as_str() };
     
-    println!("Importing from: {}", file);
-    
-    // Read CSV file
-    let mut rdr = ReaderBuilder::new()
-        .flexible(true)
-        .from_path(&file_path)
-        .with_context(|| format!("Failed to open CSV file: {}", file))?;
-    
-    // Validate headers
-    let headers = rdr.headers()?.clone();
-    validate_headers(&headers)?;
+    if dry_run {
+        status!("Importing from: {} (dry run)", source);
+    } else {
+        status!("Importing from: {}", source);
+    }
     
-    println!("CSV schema validated. Starting import...");
+    // Rows past --max-rows are only counted, never read into fields; they count as skipped
+    let (rows, over_limit) = read_rows(&file_path, comment_char, fuzzy_headers, column_map, encoding, max_rows)?;
     
+    let run_id = new_import_run_id(conn)?;
     let mut failed_rows: Vec<ValidationError> = Vec::new();
+    // Fields of the rows that failed, so `import --retry` can rebuild them
+    let mut failed_data: Vec<FailedRow> = Vec::new();
+    let mut warnings: Vec<ValidationError> = Vec::new();
     let mut imported = 0;
+    let mut updated = 0;
     let mut skipped = 0;
+    let mut duplicates = 0;
+    // Keys of the rows imported so far, catching repeats within the file (and in dry runs)
+    let mut seen: HashSet<Vec<String>> = HashSet::new();
+    // Rows of upserted items before their update, so `undo` and `rollback-import` can revert them
+    let mut previous = Vec::new();
+    let mut upserted: HashSet<i64> = HashSet::new();
+    
+    // Run the whole file in one transaction; committing per row is the dominant cost on
+    // large files. A failed insert only rolls back its own statement, so one bad row
+    // doesn't undo the good ones.
+    let tx = conn.unchecked_transaction()?;
     
     // Process each row
-    for (row_idx, result) in rdr.records().enumerate() {
-        let record = match result {
-            Ok(r) => r,
-            Err(e) => {
-                let error = ValidationError {
-                    field: "csv_parse".to_string(),
-                    message: format!("CSV parse error: {}", e),
-                    row: Some(row_idx + 1),
-                    value: None,
-                };
+    let progress = RowProgress::new(rows.len(), if dry_run { "valid" } else { "imported" });
+    let context = RowContext { conn: &tx, non_interactive, dry_run, run_id: &run_id, progress: &progress };
+    for (row_idx, result) in rows.into_iter().enumerate() {
+        progress.update(row_idx, imported + updated, skipped);
+        let mut row = match result {
+            Ok(row) => row,
+            Err(error) => {
+                progress.error(format!("✗ Row {}: {}", row_idx + 1, error.message));
                 failed_rows.push(error);
-                println!("✗ Row {}: CSV parse error - {}", row_idx + 1, e);
                 skipped += 1;
                 continue;
             }
         };
         
-        match process_row(&record, &headers, row_idx, conn, non_interactive) {
-            Ok(true) => imported += 1,
+        // Rows are validated for eBay, so titles are cut to its limit
+        if on_long_title == LongTitlePolicy::Truncate && row.title.len() > title_limit(Platform::Ebay) {
+            let truncated = truncate_title(&row.title, title_limit(Platform::Ebay));
+            progress.println(format!("⚠ Row {}: title truncated to {} characters", row_idx + 1, truncated.chars().count()));
+            warnings.push(ValidationError {
+                field: "title".to_string(),
+                message: format!("Truncated to '{}'", truncated),
+                row: Some(row_idx + 1),
+                value: Some(std::mem::replace(&mut row.title, truncated)),
+            });
+        }
+        
+        // Compared as the row would be written, after any title truncation
+        let duplicate_key = skip_duplicates.map(|key| (key, key.key_of(&row)));
+        if let Some((key, values)) = &duplicate_key {
+            let in_database = key.exists_in(&tx, values)?;
+            if in_database || seen.contains(values) {
+                progress.println(format!("↷ Row {}: duplicate of an item {}, skipped", row_idx + 1, if in_database { "already in the inventory" } else { "earlier in the file" }));
+                duplicates += 1;
+                continue;
+            }
+        }
+        
+        // In upsert mode a row matching an existing item is merged into it and validated as merged
+        let existing = match upsert.map(|key| find_upsert_target(&tx, &row, key)).transpose() {
+            Ok(existing) => existing.flatten(),
+            Err(e) => {
+                progress.error(format!("✗ Row {}: {}", row_idx + 1, e));
+                failed_rows.push(parse_error("upsert", e.to_string(), row_idx));
+                skipped += 1;
+                continue;
+            }
+        };
+        if let Some(item) = &existing {
+            row = row.merged_with(item);
+        }
+        let before = match &existing {
+            Some(item) if !dry_run => snapshot_items(&tx, &[item.item_id])?,
+            _ => Vec::new(),
+        };
+        
+        let outcome = process_row(&row, row_idx, existing.as_ref().map(|item| item.item_id), &context);
+        let succeeded = matches!(outcome, Ok(true));
+        match outcome {
+            Ok(true) if existing.is_some() => {
+                updated += 1;
+                previous.extend(before);
+                upserted.extend(existing.as_ref().map(|item| item.item_id));
+            }
+            Ok(true) => {
+                imported += 1;
+                if let Some((_, values)) = duplicate_key {
+                    seen.insert(values);
+                }
+            }
             Ok(false) => {
                 // Add validation errors for this row
-                let validation = validate_item_ebay(
-                    record.get(headers.iter().position(|h| h == "title").unwrap_or(usize::MAX)).unwrap_or(""),
-                    record.get(headers.iter().position(|h| h == "price").unwrap_or(usize::MAX)).unwrap_or("").parse().unwrap_or(-1.0),
-                    record.get(headers.iter().position(|h| h == "quantity").unwrap_or(usize::MAX)).unwrap_or("").parse().unwrap_or(-1),
-                    record.get(headers.iter().position(|h| h == "category").unwrap_or(usize::MAX)).unwrap_or(""),
-                    record.get(headers.iter().position(|h| h == "condition").unwrap_or(usize::MAX)).unwrap_or(""),
-                    record.get(headers.iter().position(|h| h == "brand").unwrap_or(usize::MAX)),
-                    record.get(headers.iter().position(|h| h == "upc").unwrap_or(usize::MAX)),
-                )?;
+                let validation = row.validate(&tx, existing.as_ref().map(|item| item.item_id))?;
                 
                 for mut err in validation.errors {
                     err.row = Some(row_idx + 1);
@@ -272,24 +1163,135 @@ pub fn handle_import(file: String, conn: &Connection, non_interactive: bool) ->
                 skipped += 1;
             }
             Err(e) => {
-                println!("✗ Row {}: Processing error - {}", row_idx + 1, e);
+                progress.error(format!("✗ Row {}: Processing error - {}", row_idx + 1, e));
                 skipped += 1;
             }
         }
+        // A retry inserts, so rows that matched an item for an upsert are only kept as errors
+        if !succeeded && existing.is_none() {
+            failed_data.push(FailedRow { row: row_idx + 1, fields: row });
+        }
+    }
+    progress.finish();
+    let failed_count = skipped;
+    if over_limit > 0 {
+        println!("Stopped after {} rows (--max-rows); the remaining {} rows were skipped", max_rows.unwrap_or_default(), over_limit);
+        skipped += over_limit;
+    }
+    
+    // A dry run never inserts, but dropping the transaction uncommitted keeps it that way
+    if !dry_run {
+        // Logged as two operations: the first undo removes the inserted items, the next reverts the updates
+        let inserted: Vec<i64> = import_run_item_ids(&tx, &run_id)?.into_iter().filter(|id| !upserted.contains(id)).collect();
+        record_import_operation(&tx, OperationKind::Update, &run_id, &previous)?;
+        record_import_operation(&tx, OperationKind::Import, &run_id, &snapshot_items(&tx, &inserted)?)?;
+        tx.commit().context("Failed to commit imported rows")?;
     }
     
     // Save failed rows if any
-    if !failed_rows.is_empty() {
-        let path = failed_imports_path();
-        let json = serde_json::to_string_pretty(&ValidationResult { errors: failed_rows })?;
-        std::fs::write(&path, json)?;
+    let failed = FailedImport { errors: failed_rows, rows: failed_data };
+    if !failed.is_empty() {
+        let path = failed.save()?;
         println!("\nFailed rows saved to: {}", path.display());
     }
     
-    println!("\n=== Import Summary ===");
-    println!("Successfully imported: {} items", imported);
+    if dry_run {
+        println!("\n=== Import Summary (DRY RUN — no changes written) ===");
+        println!("Would import: {} items", imported + updated);
+    } else {
+        println!("\n=== Import Summary ===");
+        println!("Successfully imported: {} items", imported + updated);
+    }
+    if upsert.is_some() {
+        println!("Inserted: {} items", imported);
+        println!("Updated: {} items", updated);
+    }
+    if skip_duplicates.is_some() {
+        println!("Duplicates skipped: {} items", duplicates);
+    }
     println!("Skipped/Failed: {} items", skipped);
-    println!("Total processed: {} rows", imported + skipped);
+    println!("Total processed: {} rows", imported + updated + skipped + duplicates);
+    if !warnings.is_empty() {
+        println!("Warnings: {} (titles truncated to {} characters)", warnings.len(), title_limit(Platform::Ebay));
+        for warning in &warnings {
+            println!("  Row {}: {}", warning.row.unwrap_or_default(), warning.message);
+        }
+    }
+    log::info!("Import of {} finished: {} inserted, {} updated, {} skipped, {} duplicates (run {}{})", file, imported, updated, skipped, duplicates, run_id, if dry_run { ", dry run" } else { "" });
+    if !dry_run && imported + updated > 0 {
+        println!("Import run: {} (undo with: rollback-import --run {})", run_id, run_id);
+    }
+    
+    // A script running the import learns of failed rows, so a dry run works as a pre-flight
+    // check. On a real run the valid rows are committed either way.
+    if failed_count > 0 {
+        let outcome = if dry_run { "would fail" } else { "failed" };
+        anyhow::bail!(validation_failed(format!("{} rows {} to import", failed_count, outcome)));
+    }
+    Ok(())
+} 
+
+/// Reprocess the rows of a `failed_import_*.json` file. On a terminal each invalid row is shown
+/// with prompts for corrections before it's inserted; otherwise rows are revalidated as saved.
+/// Rows that still fail go to a new failed import file.
+pub fn handle_import_retry(file: &str, conn: &Connection) -> Result<()> {
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file))?;
+    let failed: FailedImport = serde_json::from_str(&contents)
+        .with_context(|| format!("{} isn't a failed import file", file))?;
+    let (rows, unattached) = failed.into_retry_rows();
+    
+    // Same rule as update --retry: prompt only on a terminal, unless INVENTORY_NONINTERACTIVE is set
+    let noninteractive = std::env::var("INVENTORY_NONINTERACTIVE").is_ok();
+    let is_interactive = io::stdin().is_terminal() && io::stdout().is_terminal() && !noninteractive;
+    
+    status!("Retrying {} failed rows from: {}", rows.len(), file);
+    let run_id = new_import_run_id(conn)?;
+    let mut still_failing = FailedImport { errors: unattached, rows: Vec::new() };
+    let total = rows.len();
+    let mut imported = 0;
+    
+    let tx = conn.unchecked_transaction()?;
+    let progress = RowProgress::new(total, "imported");
+    let context = RowContext { conn: &tx, non_interactive: !is_interactive, dry_run: false, run_id: &run_id, progress: &progress };
+    for (done, FailedRow { row, fields }) in rows.into_iter().enumerate() {
+        progress.update(done, imported, done - imported);
+        match process_row(&fields, row.saturating_sub(1), None, &context) {
+            Ok(true) => {
+                imported += 1;
+                continue;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                progress.error(format!("✗ Row {}: Processing error - {}", row, e));
+            }
+        }
+        for mut err in fields.validate(&tx, None)?.errors {
+            err.row = Some(row);
+            still_failing.errors.push(err);
+        }
+        still_failing.rows.push(FailedRow { row, fields });
+    }
+    progress.finish();
     
+    record_import_operation(&tx, OperationKind::Import, &run_id, &snapshot_items(&tx, &import_run_item_ids(&tx, &run_id)?)?)?;
+    tx.commit().context("Failed to commit retried rows")?;
+    
+    if !still_failing.is_empty() {
+        let path = still_failing.save()?;
+        println!("\nRows still failing saved to: {}", path.display());
+    }
+    
+    println!("\n=== Retry Summary ===");
+    println!("Successfully imported: {} items", imported);
+    println!("Still failing: {} rows", total - imported);
+    log::info!("Retry of {} finished: {} imported, {} still failing (run {})", file, imported, total - imported, run_id);
+    if imported > 0 {
+        println!("Import run: {} (undo with: rollback-import --run {})", run_id, run_id);
+    }
+    
+    if imported < total {
+        anyhow::bail!(validation_failed(format!("{} rows still failed to import", total - imported)));
+    }
     Ok(())
-} 
\ No newline at end of file
+}
@@ -3,31 +3,200 @@
 
 use anyhow::Result;
 use rusqlite::Connection;
-use crate::db::queries::get_all_items;
-use crate::output::format::{format_items, InventoryItem};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Instant;
+use crate::config::optimization::{PerformanceMonitor, measure_query_performance};
+use crate::commands::fields::{expand_field_shortcuts, validate_fields};
+use crate::db::queries::{for_each_item, for_each_item_columns, get_item_columns, get_items, get_sample_item_columns, get_sample_items};
+use crate::output::format::{
+    buffered_stdout, column_csv_fields, format_columns, format_items, item_csv_fields, stdout_ndjson, ColumnRecord, CsvWriter,
+    InventoryItem, OutputOptions, ITEM_FIELDS,
+};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum OutputFormat {
     Json,
+    /// Newline-delimited JSON, one item per line
+    Ndjson,
+    /// YAML mirroring the JSON structure
+    Yaml,
     Csv,
     Table,
+    /// A formatter plugin, selected with `custom:NAME`
+    Custom(String),
 }
 
-pub fn handle_list_inventory(conn: &Connection, format: Option<OutputFormat>) -> Result<()> {
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            "csv" => Ok(OutputFormat::Csv),
+            "table" => Ok(OutputFormat::Table),
+            _ => match s.strip_prefix("custom:") {
+                Some(name) if !name.is_empty() => Ok(OutputFormat::Custom(name.to_string())),
+                _ => Err(format!("invalid format '{}': expected json, ndjson, yaml, csv, table or custom:NAME", s)),
+            },
+        }
+    }
+}
+
+/// Deserializes from the same strings `--format` accepts, e.g. in the config file
+impl<'de> serde::Deserialize<'de> for OutputFormat {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        OutputFormat::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Ndjson => write!(f, "ndjson"),
+            OutputFormat::Yaml => write!(f, "yaml"),
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::Table => write!(f, "table"),
+            OutputFormat::Custom(name) => write!(f, "custom:{}", name),
+        }
+    }
+}
+
+pub fn handle_list_inventory(
+    conn: &Connection,
+    format: Option<OutputFormat>,
+    include_archived: bool,
+    columns: Option<String>,
+    sample: Option<usize>,
+    output: &OutputOptions,
+    monitor: Option<Arc<PerformanceMonitor>>,
+) -> Result<()> {
     let format = format.unwrap_or(OutputFormat::Json);
+    let start = Instant::now();
     
-    // Retrieve all items from the database
-    let items = get_all_items(conn)?;
+    // Chosen columns take their own path; without them the full item is listed as before
+    if let Some(columns) = columns {
+        let columns = expand_field_shortcuts(&columns)?;
+        validate_fields(&columns)?;
+        log::debug!("Listing columns {} (include_archived: {})", columns.join(", "), include_archived);
+        if let OutputFormat::Custom(name) = &format {
+            anyhow::bail!("Formatter plugin '{}' formats whole items and can't be combined with --columns", name);
+        }
+        
+        // A sample is small, so it's never streamed
+        if sample.is_none() {
+            if let Some(count) = stream_columns(conn, &columns, include_archived, &format, output)? {
+                if let Some(monitor) = &monitor {
+                    monitor.report_verbose("list", start.elapsed(), Some(count), None);
+                }
+                return Ok(());
+            }
+        }
+        
+        let query = || Ok(match sample {
+            Some(size) => get_sample_item_columns(conn, &columns, include_archived, size)?,
+            None => get_item_columns(conn, &columns, include_archived)?,
+        });
+        let rows = if let Some(monitor) = &monitor {
+            measure_query_performance(monitor, "list_query", query)?
+        } else {
+            query()?
+        };
+        
+        if let Some(monitor) = &monitor {
+            monitor.report_verbose("list", start.elapsed(), Some(rows.len()), None);
+        }
+        
+        let rendered = format_columns(&columns, &rows, &format.to_string(), output)?;
+        print_output(&rendered);
+        return Ok(());
+    }
     
-    // Format output according to specified format
-    let format_str = match format {
-        OutputFormat::Json => "json",
-        OutputFormat::Csv => "csv",
-        OutputFormat::Table => "table",
+    // Retrieve items from the database; sold and draft items only on request
+    log::debug!("Listing items (include_archived: {})", include_archived);
+    if sample.is_none() {
+        if let Some(count) = stream_items(conn, include_archived, &format, output)? {
+            if let Some(monitor) = &monitor {
+                monitor.report_verbose("list", start.elapsed(), Some(count), None);
+            }
+            return Ok(());
+        }
+    }
+    
+    let query = || Ok(match sample {
+        Some(size) => get_sample_items(conn, include_archived, size, None)?,
+        None => get_items(conn, include_archived)?,
+    });
+    let items: Vec<InventoryItem> = if let Some(monitor) = &monitor {
+        measure_query_performance(monitor, "list_query", query)?
+    } else {
+        query()?
     };
     
-    let output = format_items(&items, format_str)?;
-    println!("{}", output);
+    if let Some(monitor) = &monitor {
+        monitor.report_verbose("list", start.elapsed(), Some(items.len()), None);
+    }
+    
+    // Format output according to specified format
+    let rendered = format_items(&items, &format.to_string(), output)?;
+    print_output(&rendered);
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Writes ndjson or CSV to stdout a row at a time as items are read, so memory stays flat however
+/// large the table. Returns the number of items, or None for formats that need every item first:
+/// JSON and YAML are one document, and tables size each column to its widest cell.
+fn stream_items(conn: &Connection, include_archived: bool, format: &OutputFormat, output: &OutputOptions) -> Result<Option<usize>> {
+    let count = match format {
+        OutputFormat::Ndjson => {
+            let fields: Vec<String> = ITEM_FIELDS.iter().map(|field| field.to_string()).collect();
+            let mut writer = stdout_ndjson(&fields, output)?;
+            for_each_item(conn, include_archived, |item| writer.write(&item))?;
+            writer.finish()?
+        }
+        OutputFormat::Csv => {
+            let mut writer = CsvWriter::new(buffered_stdout(), &ITEM_FIELDS, output);
+            writer.write_header()?;
+            for_each_item(conn, include_archived, |item| writer.write_row(&item_csv_fields(&item, output.csv_delimiter)))?;
+            writer.finish()?
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(count))
+}
+
+/// `stream_items` for chosen columns
+fn stream_columns(conn: &Connection, columns: &[String], include_archived: bool, format: &OutputFormat, output: &OutputOptions) -> Result<Option<usize>> {
+    let count = match format {
+        OutputFormat::Ndjson => {
+            let mut writer = stdout_ndjson(columns, output)?;
+            for_each_item_columns(conn, columns, include_archived, |values| {
+                writer.write(&ColumnRecord { columns, values: &values })
+            })?;
+            writer.finish()?
+        }
+        OutputFormat::Csv => {
+            let mut writer = CsvWriter::new(buffered_stdout(), columns, output);
+            writer.write_header()?;
+            for_each_item_columns(conn, columns, include_archived, |values| writer.write_row(&column_csv_fields(columns, &values, output.csv_delimiter)))?;
+            writer.finish()?
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(count))
+}
+
+/// Print formatted output, adding a trailing newline only if the formatter didn't
+pub fn print_output(output: &str) {
+    if output.ends_with('\n') {
+        print!("{}", output);
+    } else {
+        println!("{}", output);
+    }
+}
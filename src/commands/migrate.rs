@@ -1,13 +1,29 @@
 // Migrate command implementation
-// TODO: Implement migrate subcommand 
+// Implements: inventory migrate
 
 use anyhow::Result;
+use rusqlite::Connection;
+use crate::db::migrate::{applied_migrations, current_version, latest_version, run_migrations};
 
-pub fn handle_migrate() -> Result<()> {
+/// Applies pending schema migrations and reports the resulting version. Opening the database
+/// already applies them, so this mostly confirms the schema is current; it's safe to repeat.
+pub fn handle_migrate(conn: &Connection) -> Result<()> {
     println!("Running database migrations...");
-    // TODO: Check current schema version
-    // TODO: Apply pending migrations
-    // TODO: Update schema version
+    let ran = run_migrations(conn)?;
+    if ran.is_empty() {
+        println!("No pending migrations");
+    }
+    for migration in &ran {
+        println!("✓ Applied migration {}: {}", migration.version, migration.description);
+    }
     
+    let version = current_version(conn)?;
+    println!("Schema version: {}", version);
+    if version > latest_version() {
+        eprintln!("Warning: The database was migrated by a newer version of openinv (this one knows up to {})", latest_version());
+    }
+    for migration in applied_migrations(conn)? {
+        println!("  {:>3}  {}  (applied {})", migration.version, migration.description, migration.applied_at);
+    }
     Ok(())
-} 
\ No newline at end of file
+}
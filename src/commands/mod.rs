@@ -5,8 +5,29 @@ pub mod delete;
 pub mod list;
 pub mod import;
 pub mod filter;
+pub mod fields;
 pub mod migrate;
 pub mod help;
 pub mod commands;
 pub mod stats;
-pub mod validate; 
\ No newline at end of file
+pub mod validate;
+pub mod validate_item;
+pub mod search;
+pub mod fix_timestamps;
+pub mod backup;
+pub mod rollback_import;
+pub mod archive;
+pub mod db_diff;
+pub mod export;
+pub mod plugins;
+pub mod undo;
+pub mod completions;
+pub mod clone;
+pub mod reprice;
+pub mod tag;
+pub mod history;
+pub mod vacuum;
+pub mod categories;
+pub mod brand;
+pub mod cache; 
+pub mod filter_expr;
\ No newline at end of file
@@ -0,0 +1,34 @@
+// Plugins command implementation
+// Implements: inventory plugins
+
+use anyhow::Result;
+use crate::plugins::loader::{discover_plugins, plugin_dir};
+
+/// List the plugin libraries in the plugin directory and what each provides
+pub fn handle_plugins() -> Result<()> {
+    let dir = plugin_dir();
+    let plugins = discover_plugins()?;
+
+    if plugins.is_empty() {
+        println!("No plugins found in {}", dir.display());
+        return Ok(());
+    }
+
+    println!("Plugins in {}:", dir.display());
+    for plugin in &plugins {
+        let kinds: Vec<&str> = [(plugin.exporter, "exporter"), (plugin.formatter, "formatter")]
+            .iter()
+            .filter(|(provided, _)| *provided)
+            .map(|(_, kind)| *kind)
+            .collect();
+        let description = match &plugin.error {
+            Some(error) => format!("failed to load: {}", error),
+            None if kinds.is_empty() => "no exporter or formatter functions".to_string(),
+            None => kinds.join(", "),
+        };
+        let file_name = plugin.path.file_name().unwrap_or_default().to_string_lossy();
+        println!("  {:<16} {} ({})", plugin.name, description, file_name);
+    }
+
+    Ok(())
+}
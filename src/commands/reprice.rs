@@ -0,0 +1,96 @@
+// Reprice command implementation
+// Implements: inventory reprice (--percent -10 | --add 5.00) [--category sneakers] [--brand Nike]
+
+use anyhow::{Context, Result};
+use rusqlite::{params, params_from_iter, Connection};
+use crate::commands::filter::{build_where_clause, FilterCriteria};
+use crate::db::history::{record_operation, snapshot_items, OperationKind};
+use crate::db::timestamp::now_timestamp;
+
+/// How `reprice` changes each price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceAdjustment {
+    /// Up or down by this many percent
+    Percent(f64),
+    /// Plus this amount, in each item's own currency
+    Add(f64),
+}
+
+impl PriceAdjustment {
+    /// The adjusted price, rounded to cents
+    pub fn apply(self, price: f64) -> f64 {
+        let adjusted = match self {
+            PriceAdjustment::Percent(percent) => price * (1.0 + percent / 100.0),
+            PriceAdjustment::Add(amount) => price + amount,
+        };
+        (adjusted * 100.0).round() / 100.0
+    }
+}
+
+fn price_range(prices: impl Iterator<Item = f64>) -> (f64, f64) {
+    prices.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), price| (min.min(price), max.max(price)))
+}
+
+/// Adjusts the prices of the active items matching the filters in one transaction. Items whose
+/// price would drop below zero are left alone and reported.
+pub fn handle_reprice(conn: &Connection, category: Option<&str>, brand: Option<&str>, adjustment: PriceAdjustment) -> Result<()> {
+    let criteria = FilterCriteria { category, brand, ..Default::default() };
+    let (where_clause, params) = build_where_clause(&criteria);
+    let mut stmt = conn.prepare(&format!("SELECT item_id, price FROM items {} ORDER BY item_id", where_clause))?;
+    let items = stmt.query_map(params_from_iter(params), |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+
+    if items.is_empty() {
+        println!("No items match.");
+        return Ok(());
+    }
+
+    let mut changed = Vec::new();
+    let mut skipped = Vec::new();
+    for (id, old) in items {
+        let new = adjustment.apply(old);
+        if new < 0.0 {
+            skipped.push((id, old, new));
+        } else if new != old {
+            changed.push((id, old, new));
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let ids: Vec<i64> = changed.iter().map(|(id, _, _)| *id).collect();
+    record_operation(&tx, OperationKind::Update, &snapshot_items(&tx, &ids)?)?;
+    let now = now_timestamp();
+    for (id, _, new) in &changed {
+        tx.execute("UPDATE items SET price = ?, last_updated = ? WHERE item_id = ?", params![new, now, id])?;
+    }
+    tx.commit().context("Failed to commit new prices")?;
+
+    for (id, old, new) in &skipped {
+        eprintln!("Warning: Item {} skipped: its price of {:.2} would become {:.2}", id, old, new);
+    }
+    println!("✓ Repriced {} item(s)", changed.len());
+    if !changed.is_empty() {
+        let (old_min, old_max) = price_range(changed.iter().map(|(_, old, _)| *old));
+        let (new_min, new_max) = price_range(changed.iter().map(|(_, _, new)| *new));
+        println!("  Old prices: {:.2} - {:.2}", old_min, old_max);
+        println!("  New prices: {:.2} - {:.2}", new_min, new_max);
+    }
+    if !skipped.is_empty() {
+        println!("  Skipped {} item(s) whose price would go below zero", skipped.len());
+    }
+    log::info!("Repriced {} item(s) by {:?}, skipped {}", changed.len(), adjustment, skipped.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_adjustment_rounds_to_cents() {
+        assert_eq!(PriceAdjustment::Percent(-10.0).apply(19.99), 17.99);
+        assert_eq!(PriceAdjustment::Percent(15.0).apply(100.0), 115.0);
+        assert_eq!(PriceAdjustment::Add(5.0).apply(0.5), 5.5);
+        assert_eq!(PriceAdjustment::Add(-5.0).apply(3.0), -2.0);
+    }
+}
@@ -0,0 +1,37 @@
+// Rollback import command implementation
+// Implements: inventory rollback-import --run <id>
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use crate::db::history::revert_import_run;
+use crate::error::error::not_found;
+
+/// Counts the items still tagged with an import run
+pub fn count_import_run_items(conn: &Connection, run: &str) -> Result<i64> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*) FROM items WHERE last_import_run = ?",
+        [run],
+        |row| row.get(0),
+    )?)
+}
+
+/// Restores the inventory to how it was before an import run. Items the run upserted get
+/// their old values back from the operations log, which also untags them; whatever is still
+/// tagged with the run was inserted by it and is removed.
+pub fn handle_rollback_import(conn: &mut Connection, run: &str) -> Result<()> {
+    let count = count_import_run_items(conn, run)?;
+    if count == 0 {
+        anyhow::bail!(not_found(format!("No items found for import run {}", run)));
+    }
+
+    let tx = conn.transaction()?;
+    let reverted = revert_import_run(&tx, run)
+        .with_context(|| format!("Failed to revert the items updated by import run {}", run))?;
+    let removed = tx.execute("DELETE FROM items WHERE last_import_run = ?", [run])
+        .with_context(|| format!("Failed to roll back import run {}", run))?;
+    tx.commit()?;
+
+    println!("✓ Rolled back import run {}: removed {} items, reverted {} updated items", run, removed, reverted);
+    log::info!("Rolled back import run {}: removed {} items, reverted {} updated items", run, removed, reverted);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+// Search command implementation
+// Implements: inventory search "jordan retro" [--format {json,csv,table}]
+
+use anyhow::Result;
+use rusqlite::Connection;
+use crate::commands::list::{print_output, OutputFormat};
+use crate::db::queries::{item_select_columns, map_item};
+use crate::db::schema::has_search_index;
+use crate::output::format::{format_items, InventoryItem, OutputOptions};
+
+/// Quote each term so user input is matched literally rather than parsed as FTS5 syntax
+fn build_match_query(query: &str) -> String {
+    query.split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn search_fts(conn: &Connection, query: &str) -> Result<Vec<InventoryItem>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM items_fts
+        JOIN items i ON i.item_id = items_fts.rowid
+        WHERE items_fts MATCH ?
+        ORDER BY rank
+        "#,
+        item_select_columns("i.")
+    ))?;
+    
+    let items = stmt.query_map([build_match_query(query)], map_item)?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    
+    Ok(items)
+}
+
+fn search_like(conn: &Connection, query: &str) -> Result<Vec<InventoryItem>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT {}
+        FROM items
+        WHERE title LIKE ?1 OR description LIKE ?1
+        ORDER BY item_id
+        "#,
+        item_select_columns("")
+    ))?;
+    
+    let pattern = format!("%{}%", query.trim());
+    let items = stmt.query_map([pattern], map_item)?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    
+    Ok(items)
+}
+
+/// Search titles and descriptions, ranked by relevance when the FTS5 index is available
+pub fn search_items(conn: &Connection, query: &str) -> Result<Vec<InventoryItem>> {
+    if query.trim().is_empty() {
+        anyhow::bail!("Search query cannot be empty");
+    }
+    
+    if has_search_index(conn)? {
+        log::debug!("Searching '{}' with the full-text index", query);
+        search_fts(conn, query)
+    } else {
+        eprintln!("Warning: full-text search is unavailable in this SQLite build; falling back to a LIKE scan");
+        log::debug!("Searching '{}' with a LIKE scan", query);
+        search_like(conn, query)
+    }
+}
+
+pub fn handle_search(
+    conn: &Connection,
+    query: &str,
+    format: Option<OutputFormat>,
+    output: &OutputOptions,
+) -> Result<()> {
+    let format = format.unwrap_or(OutputFormat::Json);
+    
+    let items = search_items(conn, query)?;
+    
+    let rendered = format_items(&items, &format.to_string(), output)?;
+    print_output(&rendered);
+    
+    Ok(())
+}
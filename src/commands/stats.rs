@@ -1,24 +1,71 @@
 // Statistics command implementation
-// Implements: inventory stats [--format {json,table}]
+// Implements: inventory stats [--format {json,table,csv}] [--section S] [--rates rates.toml] [--category C --condition N --brand B --price 10-50] [--low-stock [N]]
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use crate::commands::list::OutputFormat;
-use crate::config::optimization::{PerformanceMonitor, QueryCache, measure_query_performance, generate_cache_key};
+use crate::config::currency::{format_money, ExchangeRates, DEFAULT_CURRENCY};
+use crate::config::optimization::{PerformanceMonitor, QueryCache, measure_query_performance, database_cache_key, generate_cache_key};
+use crate::commands::filter::{build_where_clause, FilterCriteria, PriceRange};
+use crate::commands::list::print_output;
+use crate::db::queries::{get_duplicate_upcs, get_low_stock_items};
+use crate::output::format::{escape_csv_field, format_columns, format_items, format_ndjson_records, to_json, CsvWriter, OutputOptions};
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Quantity `stats --low-stock` reports at or below when given no threshold
+/// (and `low_stock_threshold` isn't set in the config file)
+pub const DEFAULT_LOW_STOCK_THRESHOLD: i32 = 5;
+
+/// The table `stats --format csv` writes; CSV holds one table, so the breakdowns are separate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum StatsSection {
+    /// The overall figures, one `metric,value` row each
+    #[default]
+    Summary,
+    Categories,
+    Conditions,
+    Brands,
+    #[value(name = "price_ranges")]
+    PriceRanges,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct InventoryStats {
     pub total_items: i64,
     pub total_value: f64,
     pub average_price: f64,
+    /// Middle price, less skewed by a few expensive items than the average
+    pub median_price: f64,
+    /// Price that 90% of items are at or below
+    pub p90_price: f64,
+    /// Currency of every value and price figure; items in other currencies are
+    /// converted via the rate table, or left out without one
+    pub currency: String,
+    /// Items whose currency has no exchange rate and so are missing from value figures
+    pub unconverted_items: i64,
+    /// Items priced below their original price
+    pub discounted_items: i64,
+    /// How far stock is marked down in total: (original price - price) × quantity
+    pub total_discount: f64,
+    /// Mean markdown of the discounted items, as a percentage of their original price
+    pub average_discount_percent: f64,
     pub categories: Vec<CategoryStats>,
     pub conditions: Vec<ConditionStats>,
     pub brands: Vec<BrandStats>,
     pub price_ranges: PriceRangeStats,
+    /// Unconverted totals per currency
+    pub currencies: Vec<CurrencyStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CurrencyStats {
+    pub currency: String,
+    pub count: i64,
+    pub total_value: f64,
+    pub average_price: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,6 +74,9 @@ pub struct CategoryStats {
     pub count: i64,
     pub total_value: f64,
     pub average_price: f64,
+    /// Cheapest and dearest price in the category, to spot outliers against the average
+    pub min_price: f64,
+    pub max_price: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -55,49 +105,139 @@ pub struct PriceRangeStats {
     pub over_250: i64,
 }
 
-fn get_total_items(conn: &Connection) -> Result<i64> {
-    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))?;
+/// Filters scoping the aggregates, matching `filter`'s options of the same names
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatsFilters {
+    pub category: Option<String>,
+    pub condition: Option<String>,
+    pub brand: Option<String>,
+    /// Price range in `filter --price` syntax, e.g. `10-50` or `<50`
+    pub price: Option<String>,
+}
+
+impl StatsFilters {
+    fn cache_params(&self, params: &mut HashMap<String, String>) {
+        if let Some(cat) = &self.category { params.insert("category".to_string(), cat.clone()); }
+        if let Some(cond) = &self.condition { params.insert("condition".to_string(), cond.clone()); }
+        if let Some(brand_name) = &self.brand { params.insert("brand".to_string(), brand_name.clone()); }
+        if let Some(price_str) = &self.price { params.insert("price".to_string(), price_str.clone()); }
+    }
+}
+
+/// What `stats` was asked for: the output format, the CSV section, the rates converting
+/// values into one currency, and the filters scoping the aggregates
+#[derive(Debug, Clone, Default)]
+pub struct StatsArgs<'a> {
+    pub format: Option<OutputFormat>,
+    pub section: Option<StatsSection>,
+    pub rates: Option<&'a ExchangeRates>,
+    pub filters: StatsFilters,
+}
+
+/// The rows every aggregate query reads: the items table, or the items matching the filters
+struct StatsScope {
+    source: String,
+    params: Vec<rusqlite::types::Value>,
+}
+
+impl StatsScope {
+    fn new(filters: &StatsFilters) -> Result<Self> {
+        let price_range = filters.price.as_deref().map(PriceRange::parse).transpose()?;
+        // Stats have always covered sold and draft items too, so only the given filters apply
+        let criteria = FilterCriteria {
+            price: price_range.as_ref(),
+            category: filters.category.as_deref(),
+            condition: filters.condition.as_deref(),
+            brand: filters.brand.as_deref(),
+            include_archived: true,
+            ..Default::default()
+        };
+        let (where_clause, params) = build_where_clause(&criteria);
+        let source = if where_clause.is_empty() {
+            "items".to_string()
+        } else {
+            format!("(SELECT * FROM items {}) AS items", where_clause)
+        };
+        Ok(StatsScope { source, params })
+    }
+    
+    fn params(&self) -> rusqlite::ParamsFromIter<std::slice::Iter<'_, rusqlite::types::Value>> {
+        rusqlite::params_from_iter(self.params.iter())
+    }
+}
+
+fn get_total_items(conn: &Connection, scope: &StatsScope) -> Result<i64> {
+    let count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", scope.source), scope.params(), |row| row.get(0))?;
     Ok(count)
 }
 
-fn get_total_value(conn: &Connection) -> Result<f64> {
+fn get_total_value(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<f64> {
     let total: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(price * quantity), 0.0) FROM items",
-        [],
+        &format!("SELECT COALESCE(SUM(price * {} * quantity), 0.0) FROM {}", rate, scope.source),
+        scope.params(),
         |row| row.get(0)
     )?;
     Ok(total)
 }
 
-fn get_average_price(conn: &Connection) -> Result<f64> {
+fn get_average_price(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<f64> {
     let avg: f64 = conn.query_row(
-        "SELECT COALESCE(AVG(price), 0.0) FROM items",
-        [],
+        &format!("SELECT COALESCE(AVG(price * {}), 0.0) FROM {}", rate, scope.source),
+        scope.params(),
         |row| row.get(0)
     )?;
     Ok(avg)
 }
 
-fn get_category_stats(conn: &Connection) -> Result<Vec<CategoryStats>> {
-    let mut stmt = conn.prepare(
+/// Price at fraction `p` (0.0 to 1.0) of an ascending list, interpolating between
+/// neighbours, so an even count's median is the mean of the middle two. Empty gives 0.0.
+pub fn percentile(sorted_prices: &[f64], p: f64) -> f64 {
+    let Some(last) = sorted_prices.len().checked_sub(1) else {
+        return 0.0;
+    };
+    let rank = p.clamp(0.0, 1.0) * last as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let weight = rank - lower as f64;
+    sorted_prices[lower] + (sorted_prices[upper] - sorted_prices[lower]) * weight
+}
+
+fn get_sorted_prices(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<Vec<f64>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT price FROM (SELECT price * {} AS price FROM {}) WHERE price IS NOT NULL ORDER BY price",
+        rate, scope.source
+    ))?;
+    let prices = stmt.query_map(scope.params(), |row| row.get(0))?
+        .collect::<Result<Vec<f64>, rusqlite::Error>>()?;
+    Ok(prices)
+}
+
+fn get_category_stats(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<Vec<CategoryStats>> {
+    let mut stmt = conn.prepare(&format!(
         r#"
         SELECT 
             category,
             COUNT(*) as count,
-            COALESCE(SUM(price * quantity), 0.0) as total_value,
-            COALESCE(AVG(price), 0.0) as average_price
-        FROM items 
+            COALESCE(SUM(price * {rate} * quantity), 0.0) as total_value,
+            COALESCE(AVG(price * {rate}), 0.0) as average_price,
+            COALESCE(MIN(price * {rate}), 0.0) as min_price,
+            COALESCE(MAX(price * {rate}), 0.0) as max_price
+        FROM {source} 
         GROUP BY category 
         ORDER BY count DESC
-        "#
-    )?;
+        "#,
+        rate = rate,
+        source = scope.source
+    ))?;
     
-    let stats = stmt.query_map([], |row| {
+    let stats = stmt.query_map(scope.params(), |row| {
         Ok(CategoryStats {
             category: row.get(0)?,
             count: row.get(1)?,
             total_value: row.get(2)?,
             average_price: row.get(3)?,
+            min_price: row.get(4)?,
+            max_price: row.get(5)?,
         })
     })?
     .collect::<Result<Vec<_>, rusqlite::Error>>()
@@ -106,21 +246,23 @@ fn get_category_stats(conn: &Connection) -> Result<Vec<CategoryStats>> {
     Ok(stats)
 }
 
-fn get_condition_stats(conn: &Connection) -> Result<Vec<ConditionStats>> {
-    let mut stmt = conn.prepare(
+fn get_condition_stats(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<Vec<ConditionStats>> {
+    let mut stmt = conn.prepare(&format!(
         r#"
         SELECT 
             condition,
             COUNT(*) as count,
-            COALESCE(SUM(price * quantity), 0.0) as total_value,
-            COALESCE(AVG(price), 0.0) as average_price
-        FROM items 
+            COALESCE(SUM(price * {rate} * quantity), 0.0) as total_value,
+            COALESCE(AVG(price * {rate}), 0.0) as average_price
+        FROM {source} 
         GROUP BY condition 
         ORDER BY count DESC
-        "#
-    )?;
+        "#,
+        rate = rate,
+        source = scope.source
+    ))?;
     
-    let stats = stmt.query_map([], |row| {
+    let stats = stmt.query_map(scope.params(), |row| {
         Ok(ConditionStats {
             condition: row.get(0)?,
             count: row.get(1)?,
@@ -134,22 +276,24 @@ fn get_condition_stats(conn: &Connection) -> Result<Vec<ConditionStats>> {
     Ok(stats)
 }
 
-fn get_brand_stats(conn: &Connection) -> Result<Vec<BrandStats>> {
-    let mut stmt = conn.prepare(
+fn get_brand_stats(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<Vec<BrandStats>> {
+    let mut stmt = conn.prepare(&format!(
         r#"
         SELECT 
             COALESCE(brand, 'Unknown') as brand,
             COUNT(*) as count,
-            COALESCE(SUM(price * quantity), 0.0) as total_value,
-            COALESCE(AVG(price), 0.0) as average_price
-        FROM items 
+            COALESCE(SUM(price * {rate} * quantity), 0.0) as total_value,
+            COALESCE(AVG(price * {rate}), 0.0) as average_price
+        FROM {source} 
         GROUP BY brand 
         ORDER BY count DESC
         LIMIT 10
-        "#
-    )?;
+        "#,
+        rate = rate,
+        source = scope.source
+    ))?;
     
-    let stats = stmt.query_map([], |row| {
+    let stats = stmt.query_map(scope.params(), |row| {
         Ok(BrandStats {
             brand: row.get(0)?,
             count: row.get(1)?,
@@ -163,8 +307,8 @@ fn get_brand_stats(conn: &Connection) -> Result<Vec<BrandStats>> {
     Ok(stats)
 }
 
-fn get_price_range_stats(conn: &Connection) -> Result<PriceRangeStats> {
-    let mut stmt = conn.prepare(
+fn get_price_range_stats(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<PriceRangeStats> {
+    let mut stmt = conn.prepare(&format!(
         r#"
         SELECT 
             COALESCE(SUM(CASE WHEN price < 10 THEN 1 ELSE 0 END), 0) as under_10,
@@ -173,11 +317,12 @@ fn get_price_range_stats(conn: &Connection) -> Result<PriceRangeStats> {
             COALESCE(SUM(CASE WHEN price < 100 THEN 1 ELSE 0 END), 0) as under_100,
             COALESCE(SUM(CASE WHEN price < 250 THEN 1 ELSE 0 END), 0) as under_250,
             COALESCE(SUM(CASE WHEN price >= 250 THEN 1 ELSE 0 END), 0) as over_250
-        FROM items
-        "#
-    )?;
+        FROM (SELECT price * {} AS price FROM {})
+        "#,
+        rate, scope.source
+    ))?;
     
-    let stats = stmt.query_row([], |row| {
+    let stats = stmt.query_row(scope.params(), |row| {
         Ok(PriceRangeStats {
             under_10: row.get(0)?,
             under_25: row.get(1)?,
@@ -191,23 +336,138 @@ fn get_price_range_stats(conn: &Connection) -> Result<PriceRangeStats> {
     Ok(stats)
 }
 
+fn get_currency_stats(conn: &Connection, scope: &StatsScope) -> Result<Vec<CurrencyStats>> {
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT 
+            currency,
+            COUNT(*) as count,
+            COALESCE(SUM(price * quantity), 0.0) as total_value,
+            COALESCE(AVG(price), 0.0) as average_price
+        FROM {source} 
+        GROUP BY currency 
+        ORDER BY count DESC, currency
+        "#,
+        source = scope.source
+    ))?;
+    
+    let stats = stmt.query_map(scope.params(), |row| {
+        Ok(CurrencyStats {
+            currency: row.get(0)?,
+            count: row.get(1)?,
+            total_value: row.get(2)?,
+            average_price: row.get(3)?,
+        })
+    })?
+    .collect::<Result<Vec<_>, rusqlite::Error>>()
+    .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    
+    Ok(stats)
+}
+
+/// Count, total markdown and mean markdown percentage of the items below their original
+/// price. A NULL or zero original price means no discount, which also keeps the percentage
+/// from dividing by zero.
+fn get_discount_stats(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<(i64, f64, f64)> {
+    let stats = conn.query_row(
+        &format!(
+            r#"
+            SELECT
+                COUNT(*),
+                COALESCE(SUM((original_price - price) * {} * quantity), 0.0),
+                COALESCE(AVG((original_price - price) * 100.0 / original_price), 0.0)
+            FROM {}
+            WHERE original_price > 0 AND price < original_price
+            "#,
+            rate, scope.source
+        ),
+        scope.params(),
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    )?;
+    Ok(stats)
+}
+
+fn get_unconverted_items(conn: &Connection, scope: &StatsScope, rate: &str) -> Result<i64> {
+    let count: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM {} WHERE {} IS NULL", scope.source, rate),
+        scope.params(),
+        |row| row.get(0)
+    )?;
+    Ok(count)
+}
+
+/// Gather all statistics over the items matching `filters`, expressing values in the rate
+/// table's base currency. Without a rate table, summing mixed currencies is meaningless, so
+/// value figures cover only the most common currency and `currencies` breaks down the rest.
+pub fn compute_stats(conn: &Connection, rates: Option<&ExchangeRates>, filters: &StatsFilters) -> Result<InventoryStats> {
+    log::debug!("Computing inventory statistics ({:?})", filters);
+    let scope = StatsScope::new(filters)?;
+    let currencies = get_currency_stats(conn, &scope)?;
+    let rates = match rates {
+        Some(rates) => rates.clone(),
+        None => ExchangeRates::identity(
+            currencies.first().map_or(DEFAULT_CURRENCY, |c| c.currency.as_str())
+        ),
+    };
+    let rate = rates.sql_multiplier();
+    let prices = get_sorted_prices(conn, &scope, &rate)?;
+    let (discounted_items, total_discount, average_discount_percent) = get_discount_stats(conn, &scope, &rate)?;
+    
+    Ok(InventoryStats {
+        total_items: get_total_items(conn, &scope)?,
+        total_value: get_total_value(conn, &scope, &rate)?,
+        average_price: get_average_price(conn, &scope, &rate)?,
+        median_price: percentile(&prices, 0.5),
+        p90_price: percentile(&prices, 0.9),
+        currency: rates.base.clone(),
+        unconverted_items: get_unconverted_items(conn, &scope, &rate)?,
+        discounted_items,
+        total_discount,
+        average_discount_percent,
+        categories: get_category_stats(conn, &scope, &rate)?,
+        conditions: get_condition_stats(conn, &scope, &rate)?,
+        brands: get_brand_stats(conn, &scope, &rate)?,
+        price_ranges: get_price_range_stats(conn, &scope, &rate)?,
+        currencies,
+    })
+}
+
 fn format_table(stats: &InventoryStats) -> String {
     let mut output = String::new();
     
     // Overall statistics
     output.push_str("=== INVENTORY STATISTICS ===\n\n");
     output.push_str(&format!("Total Items: {}\n", stats.total_items));
-    output.push_str(&format!("Total Value: ${:.2}\n", stats.total_value));
-    output.push_str(&format!("Average Price: ${:.2}\n\n", stats.average_price));
+    output.push_str(&format!("Total Value: {}\n", format_money(stats.total_value, &stats.currency)));
+    output.push_str(&format!("Average Price: {}\n", format_money(stats.average_price, &stats.currency)));
+    output.push_str(&format!("Median Price: {}\n", format_money(stats.median_price, &stats.currency)));
+    output.push_str(&format!("90th Percentile Price: {}\n", format_money(stats.p90_price, &stats.currency)));
+    if stats.discounted_items > 0 {
+        output.push_str(&format!(
+            "On Sale: {} items, {} off in total (average {:.1}% off)\n",
+            stats.discounted_items, format_money(stats.total_discount, &stats.currency), stats.average_discount_percent
+        ));
+    }
+    if stats.unconverted_items > 0 {
+        output.push_str(&format!(
+            "Note: {} items in other currencies are not included in {} figures\n",
+            stats.unconverted_items, stats.currency
+        ));
+    }
+    output.push('\n');
     
     // Categories
     if !stats.categories.is_empty() {
         output.push_str("=== BY CATEGORY ===\n");
-        output.push_str("Category          | Count | Total Value | Avg Price\n");
-        output.push_str("------------------|-------|-------------|----------\n");
+        output.push_str("Category          | Count | Total Value | Avg Price   | Min Price   | Max Price\n");
+        output.push_str("------------------|-------|-------------|-------------|-------------|----------\n");
         for cat in &stats.categories {
-            output.push_str(&format!("{:<16} | {:<5} | ${:<10.2} | ${:.2}\n", 
-                cat.category, cat.count, cat.total_value, cat.average_price));
+            output.push_str(&format!("{:<17} | {:<5} | {:<11} | {:<11} | {:<11} | {}\n", 
+                cat.category, cat.count,
+                format_money(cat.total_value, &stats.currency),
+                format_money(cat.average_price, &stats.currency),
+                format_money(cat.min_price, &stats.currency),
+                format_money(cat.max_price, &stats.currency)));
         }
         output.push_str("\n");
     }
@@ -218,8 +478,10 @@ fn format_table(stats: &InventoryStats) -> String {
         output.push_str("Condition         | Count | Total Value | Avg Price\n");
         output.push_str("------------------|-------|-------------|----------\n");
         for cond in &stats.conditions {
-            output.push_str(&format!("{:<16} | {:<5} | ${:<10.2} | ${:.2}\n", 
-                cond.condition, cond.count, cond.total_value, cond.average_price));
+            output.push_str(&format!("{:<17} | {:<5} | {:<11} | {}\n", 
+                cond.condition, cond.count,
+                format_money(cond.total_value, &stats.currency),
+                format_money(cond.average_price, &stats.currency)));
         }
         output.push_str("\n");
     }
@@ -230,42 +492,153 @@ fn format_table(stats: &InventoryStats) -> String {
         output.push_str("Brand             | Count | Total Value | Avg Price\n");
         output.push_str("------------------|-------|-------------|----------\n");
         for brand in &stats.brands {
-            output.push_str(&format!("{:<16} | {:<5} | ${:<10.2} | ${:.2}\n", 
-                brand.brand, brand.count, brand.total_value, brand.average_price));
+            output.push_str(&format!("{:<17} | {:<5} | {:<11} | {}\n", 
+                brand.brand, brand.count,
+                format_money(brand.total_value, &stats.currency),
+                format_money(brand.average_price, &stats.currency)));
         }
         output.push_str("\n");
     }
     
     // Price Ranges
+    // Currencies, only worth a section once there is more than one
+    if stats.currencies.len() > 1 {
+        output.push_str("=== BY CURRENCY ===\n");
+        output.push_str("Currency          | Count | Total Value | Avg Price\n");
+        output.push_str("------------------|-------|-------------|----------\n");
+        for cur in &stats.currencies {
+            output.push_str(&format!("{:<17} | {:<5} | {:<11} | {}\n", 
+                cur.currency, cur.count,
+                format_money(cur.total_value, &cur.currency),
+                format_money(cur.average_price, &cur.currency)));
+        }
+        output.push('\n');
+    }
+    
     output.push_str("=== PRICE RANGES ===\n");
     output.push_str("Range             | Count\n");
     output.push_str("------------------|-------\n");
-    output.push_str(&format!("Under $10        | {}\n", stats.price_ranges.under_10));
-    output.push_str(&format!("Under $25        | {}\n", stats.price_ranges.under_25));
-    output.push_str(&format!("Under $50        | {}\n", stats.price_ranges.under_50));
-    output.push_str(&format!("Under $100       | {}\n", stats.price_ranges.under_100));
-    output.push_str(&format!("Under $250       | {}\n", stats.price_ranges.under_250));
-    output.push_str(&format!("$250 and over    | {}\n", stats.price_ranges.over_250));
+    let amount = |value: u32| {
+        if stats.currency == DEFAULT_CURRENCY {
+            format!("${}", value)
+        } else {
+            format!("{} {}", value, stats.currency)
+        }
+    };
+    output.push_str(&format!("Under {:<11}| {}\n", amount(10), stats.price_ranges.under_10));
+    output.push_str(&format!("Under {:<11}| {}\n", amount(25), stats.price_ranges.under_25));
+    output.push_str(&format!("Under {:<11}| {}\n", amount(50), stats.price_ranges.under_50));
+    output.push_str(&format!("Under {:<11}| {}\n", amount(100), stats.price_ranges.under_100));
+    output.push_str(&format!("Under {:<11}| {}\n", amount(250), stats.price_ranges.under_250));
+    output.push_str(&format!("{:<17}| {}\n", format!("{} and over", amount(250)), stats.price_ranges.over_250));
     
     output
 }
 
+/// One section of the stats as CSV. Breakdown rows carry the count, total value and average price
+/// in the stats currency; price ranges carry only counts.
+fn format_csv(stats: &InventoryStats, section: StatsSection, options: &OutputOptions) -> Result<String> {
+    let breakdown = |label: &str, rows: Vec<(&str, i64, f64, f64)>| -> Result<Vec<u8>> {
+        let mut writer = CsvWriter::new(Vec::new(), &[label, "count", "total_value", "average_price"], options);
+        writer.write_header()?;
+        for (name, count, total_value, average_price) in rows {
+            writer.write_row(&[
+                escape_csv_field(name, options.csv_delimiter),
+                count.to_string(),
+                format!("{:.2}", total_value),
+                format!("{:.2}", average_price),
+            ])?;
+        }
+        Ok(writer.into_inner())
+    };
+    let output = match section {
+        StatsSection::Summary => {
+            let mut writer = CsvWriter::new(Vec::new(), &["metric", "value"], options);
+            let rows = [
+                ("total_items", stats.total_items.to_string()),
+                ("total_value", format!("{:.2}", stats.total_value)),
+                ("average_price", format!("{:.2}", stats.average_price)),
+                ("median_price", format!("{:.2}", stats.median_price)),
+                ("p90_price", format!("{:.2}", stats.p90_price)),
+                ("categories_count", stats.categories.len().to_string()),
+                ("conditions_count", stats.conditions.len().to_string()),
+                ("brands_count", stats.brands.len().to_string()),
+                ("currency", stats.currency.clone()),
+                ("unconverted_items", stats.unconverted_items.to_string()),
+                ("discounted_items", stats.discounted_items.to_string()),
+                ("total_discount", format!("{:.2}", stats.total_discount)),
+                ("average_discount_percent", format!("{:.2}", stats.average_discount_percent)),
+            ];
+            for (metric, value) in rows {
+                writer.write_row(&[metric.to_string(), value])?;
+            }
+            writer.into_inner()
+        }
+        StatsSection::Categories => breakdown("category", stats.categories.iter()
+            .map(|c| (c.category.as_str(), c.count, c.total_value, c.average_price)).collect())?,
+        StatsSection::Conditions => breakdown("condition", stats.conditions.iter()
+            .map(|c| (c.condition.as_str(), c.count, c.total_value, c.average_price)).collect())?,
+        StatsSection::Brands => breakdown("brand", stats.brands.iter()
+            .map(|b| (b.brand.as_str(), b.count, b.total_value, b.average_price)).collect())?,
+        StatsSection::PriceRanges => {
+            let ranges = &stats.price_ranges;
+            let mut writer = CsvWriter::new(Vec::new(), &["range", "count"], options);
+            for (range, count) in [
+                ("under_10", ranges.under_10),
+                ("under_25", ranges.under_25),
+                ("under_50", ranges.under_50),
+                ("under_100", ranges.under_100),
+                ("under_250", ranges.under_250),
+                ("over_250", ranges.over_250),
+            ] {
+                writer.write_row(&[range.to_string(), count.to_string()])?;
+            }
+            writer.into_inner()
+        }
+    };
+    Ok(String::from_utf8(output)?)
+}
+
+/// Prints the statistics. `section` picks the table for CSV output (summary when `None`);
+/// the other formats have room for every table and reject it.
 pub fn handle_stats(
     conn: &Connection, 
-    format: Option<OutputFormat>,
+    args: &StatsArgs,
+    output_options: &OutputOptions,
     monitor: Option<Arc<PerformanceMonitor>>,
     cache: Option<Arc<QueryCache>>,
 ) -> Result<()> {
+    let StatsArgs { format, section, rates, filters } = args;
+    let (format, section, rates) = (format.clone(), *section, *rates);
     let format = format.unwrap_or(OutputFormat::Table);
+    if section.is_some() && format != OutputFormat::Csv {
+        return Err(anyhow!("--section picks the table for CSV output; {} output always has every table", format));
+    }
+    let section = section.unwrap_or_default();
+    let start = Instant::now();
     
-    // Check cache first if available
-    if let Some(cache) = &cache {
+    // Every input that changes the output is part of the cache key
+    let cache_key = {
         let mut params = HashMap::new();
+        params.insert("db".to_string(), database_cache_key(conn));
         params.insert("format".to_string(), format!("{:?}", format));
+        if output_options.crlf { params.insert("crlf".to_string(), "true".to_string()); }
+        if output_options.csv_delimiter != ',' { params.insert("delimiter".to_string(), output_options.csv_delimiter.to_string()); }
+        if !output_options.csv_header { params.insert("no_header".to_string(), "true".to_string()); }
+        if output_options.json_compact { params.insert("json_compact".to_string(), "true".to_string()); }
+        if let Some(rates) = rates { params.insert("rates".to_string(), rates.cache_key()); }
+        params.insert("section".to_string(), format!("{:?}", section));
+        filters.cache_params(&mut params);
         
-        let cache_key = generate_cache_key("stats", &params);
-        
+        generate_cache_key("stats", &params)
+    };
+    
+    // Check cache first if available
+    if let Some(cache) = &cache {
         if let Some(cached_result) = cache.get(&cache_key) {
+            if let Some(monitor) = &monitor {
+                monitor.report_verbose("stats", start.elapsed(), None, Some(true));
+            }
             print!("{}", cached_result);
             return Ok(());
         }
@@ -273,60 +646,113 @@ pub fn handle_stats(
     
     // Gather all statistics with performance monitoring
     let stats = if let Some(monitor) = &monitor {
-        measure_query_performance(monitor, "stats_query", || {
-            Ok(InventoryStats {
-                total_items: get_total_items(conn)?,
-                total_value: get_total_value(conn)?,
-                average_price: get_average_price(conn)?,
-                categories: get_category_stats(conn)?,
-                conditions: get_condition_stats(conn)?,
-                brands: get_brand_stats(conn)?,
-                price_ranges: get_price_range_stats(conn)?,
-            })
-        })?
+        measure_query_performance(monitor, "stats_query", || compute_stats(conn, rates, filters))?
     } else {
-        InventoryStats {
-            total_items: get_total_items(conn)?,
-            total_value: get_total_value(conn)?,
-            average_price: get_average_price(conn)?,
-            categories: get_category_stats(conn)?,
-            conditions: get_condition_stats(conn)?,
-            brands: get_brand_stats(conn)?,
-            price_ranges: get_price_range_stats(conn)?,
-        }
+        compute_stats(conn, rates, filters)?
     };
     
+    if let Some(monitor) = &monitor {
+        monitor.report_verbose("stats", start.elapsed(), None, cache.as_ref().map(|_| false));
+    }
+    
     // Format output
-    let output = match format {
+    let output = match &format {
         OutputFormat::Json => {
-            serde_json::to_string_pretty(&stats)?
+            to_json(&stats, output_options)?
+        }
+        OutputFormat::Yaml => {
+            serde_yaml::to_string(&stats)?
         }
         OutputFormat::Table => {
             format_table(&stats)
         }
         OutputFormat::Csv => {
-            // For CSV, we'll output a simplified version with key metrics
-            let mut csv = String::new();
-            csv.push_str("metric,value\n");
-            csv.push_str(&format!("total_items,{}\n", stats.total_items));
-            csv.push_str(&format!("total_value,{:.2}\n", stats.total_value));
-            csv.push_str(&format!("average_price,{:.2}\n", stats.average_price));
-            csv.push_str(&format!("categories_count,{}\n", stats.categories.len()));
-            csv.push_str(&format!("conditions_count,{}\n", stats.conditions.len()));
-            csv.push_str(&format!("brands_count,{}\n", stats.brands.len()));
-            csv
+            format_csv(&stats, section, output_options)?
+        }
+        OutputFormat::Ndjson => {
+            return Err(anyhow!("ndjson output applies to item lists (list, search and filter); use json for stats"));
+        }
+        OutputFormat::Custom(name) => {
+            return Err(anyhow!("Formatter plugin '{}' can't be used with stats; custom formats apply to list and search", name));
         }
     };
     
     // Cache the result if cache is available
     if let Some(cache) = &cache {
-        let mut params = HashMap::new();
-        params.insert("format".to_string(), format!("{:?}", format));
-        
-        let cache_key = generate_cache_key("stats", &params);
         cache.set(cache_key, output.clone(), Duration::from_secs(600)); // 10 minute TTL for stats
     }
     
     print!("{}", output);
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Lists active items with `quantity <= threshold`, most urgent first, in place of the aggregate stats
+pub fn handle_low_stock(
+    conn: &Connection,
+    threshold: i32,
+    format: Option<OutputFormat>,
+    output_options: &OutputOptions,
+) -> Result<()> {
+    let format = format.unwrap_or(OutputFormat::Table);
+    log::debug!("Listing items with quantity at or below {}", threshold);
+    let items = get_low_stock_items(conn, threshold)?;
+    
+    if items.is_empty() && format == OutputFormat::Table {
+        println!("No items with quantity at or below {}.", threshold);
+        return Ok(());
+    }
+    
+    print_output(&format_items(&items, &format.to_string(), output_options)?);
+    Ok(())
+}
+
+/// Lists the UPCs shared by more than one item, with the items sharing each, in place of the
+/// aggregate stats. A shared UPC usually means one of the items was entered wrong.
+pub fn handle_duplicate_upcs(conn: &Connection, format: Option<OutputFormat>, output_options: &OutputOptions) -> Result<()> {
+    let format = format.unwrap_or(OutputFormat::Table);
+    let duplicates = get_duplicate_upcs(conn)?;
+    
+    if duplicates.is_empty() && format == OutputFormat::Table {
+        println!("No duplicate UPCs found.");
+        return Ok(());
+    }
+    
+    let fields = vec!["upc".to_string(), "item_ids".to_string()];
+    let output = match &format {
+        OutputFormat::Json => to_json(&duplicates, output_options)?,
+        OutputFormat::Ndjson => format_ndjson_records(&duplicates, &fields, output_options)?,
+        OutputFormat::Yaml => serde_yaml::to_string(&duplicates)?,
+        OutputFormat::Csv | OutputFormat::Table => {
+            // The IDs share one cell, space-separated so CSV needn't quote them
+            let rows: Vec<Vec<rusqlite::types::Value>> = duplicates.iter()
+                .map(|duplicate| vec![
+                    rusqlite::types::Value::Text(duplicate.upc.clone()),
+                    rusqlite::types::Value::Text(duplicate.item_ids.iter().map(i64::to_string).collect::<Vec<_>>().join(" ")),
+                ])
+                .collect();
+            format_columns(&fields, &rows, &format.to_string(), output_options)?
+        }
+        OutputFormat::Custom(name) => return Err(anyhow!("--duplicate-upc can't use formatter plugin '{}'", name)),
+    };
+    print_output(&output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_known_dataset() {
+        assert_eq!(percentile(&[], 0.5), 0.0);
+        assert_eq!(percentile(&[42.0], 0.9), 42.0);
+        
+        // Odd count: the middle value
+        assert_eq!(percentile(&[5.0, 10.0, 1000.0], 0.5), 10.0);
+        // Even count: the mean of the middle two
+        assert_eq!(percentile(&[5.0, 10.0, 20.0, 1000.0], 0.5), 15.0);
+        
+        let prices: Vec<f64> = (1..=11).map(|n| n as f64 * 10.0).collect();
+        assert!((percentile(&prices, 0.9) - 100.0).abs() < 1e-9);
+    }
+}
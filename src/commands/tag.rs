@@ -0,0 +1,38 @@
+// Tag command implementation
+// Implements: inventory tag --id 1 --add grail,vintage --remove sale
+
+use anyhow::Result;
+use rusqlite::Connection;
+use crate::db::queries::{add_tags, get_item_by_id, get_item_tags, normalize_tags, remove_tags};
+use crate::error::error::not_found;
+
+/// Add and remove an item's tags, then print the tags it has. Names are normalized, so
+/// " Vintage" and "vintage" are the same tag. With neither list, only prints the tags.
+pub fn handle_tag(id: i32, add: &[String], remove: &[String], conn: &Connection) -> Result<()> {
+    let id = id as i64;
+    if get_item_by_id(conn, id)?.is_none() {
+        anyhow::bail!(not_found(format!("Item with ID {} does not exist", id)));
+    }
+    let add = normalize_tags(add);
+    let remove = normalize_tags(remove);
+    if let Some(tag) = add.iter().find(|tag| remove.contains(tag)) {
+        anyhow::bail!("Tag '{}' can't be both added and removed", tag);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let added = add_tags(&tx, id, &add)?;
+    let removed = remove_tags(&tx, id, &remove)?;
+    let tags = get_item_tags(&tx, id)?;
+    tx.commit()?;
+
+    if added > 0 || removed > 0 {
+        log::info!("Item {}: added {} tag(s), removed {}", id, added, removed);
+        println!("✓ Added {} and removed {} tag(s) on item {}", added, removed, id);
+    }
+    if tags.is_empty() {
+        println!("Item {} has no tags", id);
+    } else {
+        println!("Item {} tags: {}", id, tags.join(", "));
+    }
+    Ok(())
+}
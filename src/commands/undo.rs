@@ -0,0 +1,26 @@
+// Undo command implementation
+// Implements: inventory undo
+
+use anyhow::Result;
+use rusqlite::Connection;
+use crate::db::history::{undo_last_operation, OperationKind};
+
+/// Reverses the most recent add, update, delete or import
+pub fn handle_undo(conn: &Connection) -> Result<()> {
+    let Some(outcome) = undo_last_operation(conn)? else {
+        println!("Nothing to undo.");
+        return Ok(());
+    };
+
+    let action = match outcome.kind {
+        OperationKind::Add | OperationKind::Import => "removed",
+        OperationKind::Update => "reverted",
+        OperationKind::Delete => "restored",
+    };
+    println!("✓ Undid {} (operation {}): {} {} item(s)", outcome.kind, outcome.operation_id, action, outcome.items);
+    for (original, new) in &outcome.reassigned {
+        println!("  Item {} restored as {} because its ID is in use", original, new);
+    }
+    log::info!("Undid {} operation {}: {} {} item(s)", outcome.kind, outcome.operation_id, action, outcome.items);
+    Ok(())
+}
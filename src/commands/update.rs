@@ -1,6 +1,8 @@
 use anyhow::{Result, Context};
-use crate::validation::{validate_item_ebay, ValidationResult, ValidationError};
+use crate::validation::{normalize_condition, validate_item_ebay, ItemFields, ValidationResult, ValidationError};
+use crate::db::history::{record_operation, snapshot_items, OperationKind};
 use crate::db::queries;
+use crate::output::progress::RowProgress;
 use clap::Parser;
 use csv::ReaderBuilder;
 use rusqlite::Connection;
@@ -10,6 +12,7 @@ use std::io::{self, Write, IsTerminal};
 use std::path::{Path, PathBuf};
 use chrono::Utc;
 use dirs::home_dir;
+use crate::error::error::{not_found, validation_failed};
 
 #[derive(Parser)]
 pub struct Update {
@@ -36,17 +39,32 @@ pub fn execute(args: Update, conn: &Connection) -> anyhow::Result<()> {
 
 fn update_from_csv(file: String, conn: &Connection) -> anyhow::Result<()> {
     if !Path::new(&file).exists() {
-        return Err(anyhow::anyhow!("File not found: {}", file));
+        return Err(not_found(format!("File not found: {}", file)).into());
     }
 
+    // Counted up front so the progress bar knows its length
+    let total = ReaderBuilder::new()
+        .has_headers(true)
+        .from_path(&file)?
+        .records()
+        .count();
     let mut rdr = ReaderBuilder::new()
         .has_headers(true)
         .from_path(&file)?;
     let headers = rdr.headers()?.clone();
     let mut failed_rows = Vec::new();
+    let mut previous = Vec::new();
     let mut row_num = 1;
+    let mut updated = 0;
+
+    // All rows apply together: an error partway through rolls back the rows before it
+    let tx = conn.unchecked_transaction()?;
+    let conn = &*tx;
 
+    let progress = RowProgress::new(total, "updated");
     for result in rdr.records() {
+        let done = row_num - 1;
+        progress.update(done, updated, done - updated);
         row_num += 1;
         let record = result?;
         let row_data = UpdateRow::from_record(&record, &headers, row_num)?;
@@ -66,30 +84,30 @@ fn update_from_csv(file: String, conn: &Connection) -> anyhow::Result<()> {
         let existing_item = queries::get_item_by_id(conn, row_data.id)?.unwrap();
         
         // Prepare data for validation (use existing values for fields not being updated)
-        let title_for_validation = row_data.title.as_deref().unwrap_or(&existing_item["title"]);
-        let price_for_validation = row_data.price.unwrap_or_else(|| existing_item["price"].parse().unwrap_or(0.0));
-        let quantity_for_validation = row_data.quantity.unwrap_or_else(|| existing_item["quantity"].parse().unwrap_or(0));
-        let category_for_validation = row_data.category.as_deref().unwrap_or(&existing_item["category"]);
-        let condition_for_validation = row_data.condition.as_deref().unwrap_or(&existing_item["condition"]);
+        let title_for_validation = row_data.title.as_deref().unwrap_or(&existing_item.title);
+        let price_for_validation = row_data.price.unwrap_or(existing_item.price);
+        let quantity_for_validation = row_data.quantity.unwrap_or(existing_item.quantity);
+        let category_for_validation = row_data.category.as_deref().unwrap_or(&existing_item.category);
+        let condition_for_validation = row_data.condition.as_deref().unwrap_or(&existing_item.condition);
         let brand_for_validation = row_data.brand.as_deref().or_else(|| {
-            let brand = &existing_item["brand"];
-            if brand.is_empty() { None } else { Some(brand.as_str()) }
+            existing_item.brand.as_deref().filter(|brand| !brand.is_empty())
         });
         let upc_for_validation = row_data.upc.as_deref().or_else(|| {
-            let upc = &existing_item["upc"];
-            if upc.is_empty() { None } else { Some(upc.as_str()) }
+            existing_item.upc.as_deref().filter(|upc| !upc.is_empty())
         });
 
         // Validate the combined data
-        let validation_result = validate_item_ebay(
-            title_for_validation,
-            price_for_validation,
-            quantity_for_validation,
-            category_for_validation,
-            condition_for_validation,
-            brand_for_validation,
-            upc_for_validation,
-        );
+        let validation_result = validate_item_ebay(&ItemFields {
+            title: title_for_validation,
+            price: price_for_validation,
+            quantity: quantity_for_validation,
+            category: category_for_validation,
+            condition: condition_for_validation,
+            brand: brand_for_validation,
+            upc: upc_for_validation,
+            size: None,
+            currency: &existing_item.currency,
+        });
 
         let mut corrected = row_data.clone();
         if let Ok(validation) = validation_result {
@@ -99,16 +117,19 @@ fn update_from_csv(file: String, conn: &Connection) -> anyhow::Result<()> {
                 let is_interactive = io::stdin().is_terminal() && io::stdout().is_terminal() && !noninteractive;
                 
                 if is_interactive {
-                    println!("Invalid row {}:", row_num);
+                    progress.println(format!("Invalid row {}:", row_num));
                     for error in &validation.errors {
                         if !corrected.is_field_provided(&error.field) {
                             continue; // Skip validation for unprovided fields
                         }
-                        print!("Invalid {} for row {}: {:?}. Enter new value or press Enter to skip: ",
-                               error.field, row_num, error.value);
-                        io::stdout().flush()?;
-                        let mut input = String::new();
-                        io::stdin().read_line(&mut input)?;
+                        let input = progress.suspend(|| -> io::Result<String> {
+                            print!("Invalid {} for row {}: {:?}. Enter new value or press Enter to skip: ",
+                                   error.field, row_num, error.value);
+                            io::stdout().flush()?;
+                            let mut input = String::new();
+                            io::stdin().read_line(&mut input)?;
+                            Ok(input)
+                        })?;
                         let input = input.trim();
 
                         if input.is_empty() {
@@ -140,7 +161,7 @@ fn update_from_csv(file: String, conn: &Connection) -> anyhow::Result<()> {
                                         });
                                     }
                                 }
-                                "condition" => corrected.condition = Some(input.to_string()),
+                                "condition" => corrected.condition = Some(normalize_condition(input)),
                                 "category" => corrected.category = Some(input.to_string()),
                                 "brand" => corrected.brand = Some(input.to_string()),
                                 "upc" => corrected.upc = Some(input.to_string()),
@@ -159,15 +180,17 @@ fn update_from_csv(file: String, conn: &Connection) -> anyhow::Result<()> {
                 }
 
                 // Re-validate corrected row
-                let re_validation = validate_item_ebay(
-                    corrected.title.as_deref().unwrap_or(title_for_validation),
-                    corrected.price.unwrap_or(price_for_validation),
-                    corrected.quantity.unwrap_or(quantity_for_validation),
-                    corrected.category.as_deref().unwrap_or(category_for_validation),
-                    corrected.condition.as_deref().unwrap_or(condition_for_validation),
-                    corrected.brand.as_deref().or(brand_for_validation),
-                    corrected.upc.as_deref().or(upc_for_validation),
-                );
+                let re_validation = validate_item_ebay(&ItemFields {
+                    title: corrected.title.as_deref().unwrap_or(title_for_validation),
+                    price: corrected.price.unwrap_or(price_for_validation),
+                    quantity: corrected.quantity.unwrap_or(quantity_for_validation),
+                    category: corrected.category.as_deref().unwrap_or(category_for_validation),
+                    condition: corrected.condition.as_deref().unwrap_or(condition_for_validation),
+                    brand: corrected.brand.as_deref().or(brand_for_validation),
+                    upc: corrected.upc.as_deref().or(upc_for_validation),
+                    size: None,
+                    currency: &existing_item.currency,
+                });
 
                 if let Ok(re_validation) = re_validation {
                     if !re_validation.errors.is_empty() {
@@ -183,7 +206,8 @@ fn update_from_csv(file: String, conn: &Connection) -> anyhow::Result<()> {
             continue;
         }
 
-        // Update item in database
+        // Update item in database, keeping the old row for `undo`
+        previous.extend(snapshot_items(conn, &[corrected.id])?);
         queries::update_item(
             conn,
             corrected.id,
@@ -195,9 +219,15 @@ fn update_from_csv(file: String, conn: &Connection) -> anyhow::Result<()> {
             corrected.brand.as_deref(),
             corrected.upc.as_deref(),
         )?;
+        updated += 1;
     }
+    progress.finish();
+
+    record_operation(conn, OperationKind::Update, &previous)?;
+    tx.commit().context("Failed to commit CSV update")?;
 
-    // Save failed rows
+    // Save failed rows, failing the command once the summary is out
+    let failed = failed_row_count(&failed_rows);
     if !failed_rows.is_empty() {
         let timestamp = Utc::now().format("%Y-%m-%dT%H-%M-%S").to_string();
         let failed_dir = home_dir()
@@ -211,13 +241,22 @@ fn update_from_csv(file: String, conn: &Connection) -> anyhow::Result<()> {
     }
 
     println!("CSV update completed. {} rows processed.", row_num - 1);
+    if failed > 0 {
+        anyhow::bail!(validation_failed(format!("{} rows failed to update", failed)));
+    }
     Ok(())
 }
 
+/// Rows with at least one error; a row can fail on several fields
+fn failed_row_count(errors: &[ValidationError]) -> usize {
+    errors.iter().map(|error| error.row).collect::<std::collections::HashSet<_>>().len()
+}
+
 fn update_from_retry(file: String, conn: &Connection) -> anyhow::Result<()> {
     let failed_file = File::open(&file)?;
     let errors: ValidationResult = serde_json::from_reader(failed_file)?;
     let mut failed_rows = Vec::new();
+    let mut previous = Vec::new();
 
     for error in errors.errors {
         let row_num = error.row.unwrap_or(0);
@@ -290,7 +329,7 @@ fn update_from_retry(file: String, conn: &Connection) -> anyhow::Result<()> {
                         continue;
                     }
                 }
-                "condition" => row_data.condition = Some(input.to_string()),
+                "condition" => row_data.condition = Some(normalize_condition(input)),
                 "category" => row_data.category = Some(input.to_string()),
                 "brand" => row_data.brand = Some(input.to_string()),
                 "upc" => row_data.upc = Some(input.to_string()),
@@ -303,7 +342,7 @@ fn update_from_retry(file: String, conn: &Connection) -> anyhow::Result<()> {
         }
 
         // Check if item exists
-        if queries::get_item_by_id(conn, row_data.id)?.is_none() {
+        let Some(existing_item) = queries::get_item_by_id(conn, row_data.id)? else {
             failed_rows.push(ValidationError {
                 field: "id".to_string(),
                 message: "Item not found".to_string(),
@@ -311,18 +350,20 @@ fn update_from_retry(file: String, conn: &Connection) -> anyhow::Result<()> {
                 value: Some(row_data.id.to_string()),
             });
             continue;
-        }
+        };
 
         // Validate and update
-        let validation_result = validate_item_ebay(
-            &row_data.title.clone().unwrap_or_default(),
-            row_data.price.unwrap_or(f64::MAX),
-            row_data.quantity.unwrap_or(i32::MAX),
-            &row_data.category.clone().unwrap_or_default(),
-            &row_data.condition.clone().unwrap_or_default(),
-            row_data.brand.as_deref(),
-            row_data.upc.as_deref(),
-        );
+        let validation_result = validate_item_ebay(&ItemFields {
+            title: &row_data.title.clone().unwrap_or_default(),
+            price: row_data.price.unwrap_or(f64::MAX),
+            quantity: row_data.quantity.unwrap_or(i32::MAX),
+            category: &row_data.category.clone().unwrap_or_default(),
+            condition: &row_data.condition.clone().unwrap_or_default(),
+            brand: row_data.brand.as_deref(),
+            upc: row_data.upc.as_deref(),
+            size: None,
+            currency: &existing_item.currency,
+        });
 
         if let Ok(validation) = validation_result {
             if !validation.errors.is_empty() {
@@ -333,6 +374,7 @@ fn update_from_retry(file: String, conn: &Connection) -> anyhow::Result<()> {
             continue;
         }
 
+        previous.extend(snapshot_items(conn, &[row_data.id])?);
         queries::update_item(
             conn,
             row_data.id,
@@ -346,7 +388,10 @@ fn update_from_retry(file: String, conn: &Connection) -> anyhow::Result<()> {
         )?;
     }
 
-    // Save failed rows
+    record_operation(conn, OperationKind::Update, &previous)?;
+
+    // Save failed rows, failing the command once the summary is out
+    let failed = failed_row_count(&failed_rows);
     if !failed_rows.is_empty() {
         let timestamp = Utc::now().format("%Y-%m-%dT%H-%M-%S").to_string();
         let failed_dir = home_dir()
@@ -360,6 +405,9 @@ fn update_from_retry(file: String, conn: &Connection) -> anyhow::Result<()> {
     }
 
     println!("Retry update completed.");
+    if failed > 0 {
+        anyhow::bail!(validation_failed(format!("{} rows failed to update", failed)));
+    }
     Ok(())
 }
 
@@ -397,7 +445,7 @@ impl UpdateRow {
                 "title" => title = Some(trimmed_field.to_string()),
                 "price" => price = Some(trimmed_field.parse().map_err(|_| anyhow::anyhow!("Invalid price in row {}", row_num))?),
                 "quantity" => quantity = Some(trimmed_field.parse().map_err(|_| anyhow::anyhow!("Invalid quantity in row {}", row_num))?),
-                "condition" => condition = Some(trimmed_field.to_string()),
+                "condition" => condition = Some(normalize_condition(trimmed_field)),
                 "category" => category = Some(trimmed_field.to_string()),
                 "brand" => brand = Some(trimmed_field.to_string()),
                 "upc" => upc = Some(trimmed_field.to_string()),
@@ -0,0 +1,48 @@
+// Vacuum command implementation
+// Implements: inventory vacuum
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Bytes the database takes on disk: the main file plus its write-ahead log, if any
+fn database_size(db_path: &Path) -> u64 {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    [db_path.to_path_buf(), PathBuf::from(wal)].iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// A byte count in B, KiB or MiB, whichever reads best
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Rebuilds the database file to reclaim the space deleted rows left behind, then refreshes
+/// the query planner's statistics. VACUUM needs the database to itself: it waits for (and then
+/// blocks) other writers, and fails if one holds it too long. Returns the sizes before and after.
+pub fn handle_vacuum(conn: &Connection, db_path: &Path) -> Result<(u64, u64)> {
+    eprintln!("Warning: vacuum needs exclusive access; other openinv processes will wait until it finishes");
+    let before = database_size(db_path);
+
+    conn.execute_batch("VACUUM;")
+        .context("Failed to vacuum the database (is another process writing to it?)")?;
+    conn.execute_batch("ANALYZE;")?;
+    // In WAL mode the rebuilt pages sit in the log until a checkpoint writes them back
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+
+    let after = database_size(db_path);
+    println!(
+        "✓ Vacuumed {}: {} → {} ({} reclaimed)",
+        db_path.display(), format_size(before), format_size(after), format_size(before.saturating_sub(after))
+    );
+    log::info!("Vacuumed {}: {} bytes -> {} bytes", db_path.display(), before, after);
+    Ok((before, after))
+}
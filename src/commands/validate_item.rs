@@ -0,0 +1,79 @@
+// Validate item command implementation
+// Implements: inventory validate-item --title ... --platform all
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::json;
+use std::collections::HashMap;
+use std::str::FromStr;
+use crate::commands::list::OutputFormat;
+use crate::validation::{validate_item_all, validate_item_for, ItemFields, Platform, ValidationResult};
+use crate::error::error::validation_failed;
+
+/// Which platforms `validate-item` checks: one marketplace or `all` of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlatformChoice {
+    #[default]
+    All,
+    One(Platform),
+}
+
+impl FromStr for PlatformChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(PlatformChoice::All);
+        }
+        Platform::from_str(s, true)
+            .map(PlatformChoice::One)
+            .map_err(|_| format!("invalid platform '{}': expected all, ebay, stockx, poshmark or mercari", s))
+    }
+}
+
+/// JSON report with each platform's validity and errors nested under its name
+pub fn results_to_json(results: &HashMap<String, ValidationResult>) -> serde_json::Value {
+    let report: serde_json::Map<String, serde_json::Value> = results
+        .iter()
+        .map(|(platform, result)| {
+            (platform.clone(), json!({ "valid": result.is_valid(), "errors": result.errors }))
+        })
+        .collect();
+    serde_json::Value::Object(report)
+}
+
+pub fn handle_validate_item(item: &ItemFields, platform: PlatformChoice, format: Option<OutputFormat>) -> Result<()> {
+    let results = match platform {
+        PlatformChoice::All => validate_item_all(item)?,
+        PlatformChoice::One(platform) => {
+            let result = validate_item_for(platform, item)?;
+            HashMap::from([(platform.to_string(), result)])
+        }
+    };
+    let passed = results.values().filter(|result| result.is_valid()).count();
+
+    match format.unwrap_or(OutputFormat::Table) {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&results_to_json(&results))?),
+        OutputFormat::Table => {
+            println!("Validation results for '{}':", item.title);
+            for platform in Platform::ALL {
+                let Some(result) = results.get(&platform.to_string()) else { continue };
+                if result.is_valid() {
+                    println!("  ✓ {:<9} pass", platform.to_string());
+                } else {
+                    println!("  ✗ {:<9} fail", platform.to_string());
+                    for error in &result.errors {
+                        println!("      - {}: {}", error.field, error.message);
+                    }
+                }
+            }
+            println!("Passes {} of {} platforms", passed, results.len());
+        }
+        other => anyhow::bail!("validate-item supports json and table output, not {}", other),
+    }
+
+    if passed == 0 {
+        anyhow::bail!(validation_failed("Item fails validation for every selected platform"));
+    }
+    Ok(())
+}
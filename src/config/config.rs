@@ -1,2 +1,80 @@
 // Configuration implementation
-// TODO: Implement TOML configuration parsing 
\ No newline at end of file
+// Reads optional defaults from ~/.inventory/config.toml; CLI flags take precedence over them
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use dirs::home_dir;
+use crate::commands::list::OutputFormat;
+use crate::logging::logger::LogLevel;
+use crate::validation::{Platform, TitleLimits};
+
+pub const CONFIG_FILENAME: &str = "config.toml";
+
+/// Returns the default path of the config file (~/.inventory/config.toml)
+pub fn default_config_path() -> PathBuf {
+    let mut path = home_dir().expect("Could not determine home directory");
+    path.push(".inventory");
+    path.push(CONFIG_FILENAME);
+    path
+}
+
+/// Settings from the config file. Every key is optional; an unset key leaves the
+/// built-in default in place.
+///
+/// ```toml
+/// format = "table"
+/// platform = "ebay"
+/// db_path = "/data/inventory.db"
+/// cache_ttl = 120        # seconds
+/// log_level = "debug"
+/// low_stock_threshold = 3
+/// perf_report = true     # false stops filter and stats writing performance reports
+/// perf_report_dir = "/data/reports"
+/// busy_retries = 5       # times a write that finds the database locked is tried again
+///
+/// [title_limits]         # per platform, at most 80
+/// mercari = 40
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Output format used when a command gets no `--format`
+    pub format: Option<OutputFormat>,
+    /// Marketplace `add` validates items for when given no `--platform`
+    pub platform: Option<Platform>,
+    pub db_path: Option<PathBuf>,
+    /// How long cached query results stay valid, in seconds
+    pub cache_ttl: Option<u64>,
+    pub log_level: Option<LogLevel>,
+    /// Threshold `stats --low-stock` uses when given no value
+    pub low_stock_threshold: Option<i32>,
+    /// Whether `filter` and `stats` write performance reports (they do unless this is false)
+    pub perf_report: Option<bool>,
+    /// Where performance reports go when given no `--perf-report` [default: ~/.inventory/logs]
+    pub perf_report_dir: Option<PathBuf>,
+    /// How often a locked write is retried, with exponential backoff [default: 5]
+    pub busy_retries: Option<u32>,
+    /// Longest title each platform accepts [default: 80 everywhere]
+    pub title_limits: TitleLimits,
+}
+
+impl Config {
+    /// Loads `~/.inventory/config.toml`; a missing file means all defaults
+    pub fn load() -> Result<Self> {
+        Self::load_from(&default_config_path())
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        let config: Self = toml::from_str(&contents)
+            .with_context(|| format!("Invalid config file: {}", path.display()))?;
+        config.title_limits.check()
+            .with_context(|| format!("Invalid config file: {}", path.display()))?;
+        Ok(config)
+    }
+}
@@ -0,0 +1,182 @@
+// Currency support
+// ISO 4217 currency codes on items and exchange rate tables for converting stats totals
+
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Currency assumed for items that were stored without one
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// Highest price accepted in currencies with minor units (cents and the like)
+pub const MAX_PRICE: f64 = 999_999.99;
+
+/// Highest price accepted in zero-decimal currencies such as JPY, whose amounts run larger
+pub const MAX_PRICE_WHOLE_UNITS: f64 = 999_999_999.0;
+
+/// Active ISO 4217 currencies and their number of minor-unit digits, sorted by code
+const ISO_4217: &[(&str, u32)] = &[
+    ("AED", 2), ("AFN", 2), ("ALL", 2), ("AMD", 2), ("ANG", 2), ("AOA", 2), ("ARS", 2), ("AUD", 2),
+    ("AWG", 2), ("AZN", 2), ("BAM", 2), ("BBD", 2), ("BDT", 2), ("BGN", 2), ("BHD", 3), ("BIF", 0),
+    ("BMD", 2), ("BND", 2), ("BOB", 2), ("BOV", 2), ("BRL", 2), ("BSD", 2), ("BTN", 2), ("BWP", 2),
+    ("BYN", 2), ("BZD", 2), ("CAD", 2), ("CDF", 2), ("CHE", 2), ("CHF", 2), ("CHW", 2), ("CLF", 4),
+    ("CLP", 0), ("CNY", 2), ("COP", 2), ("COU", 2), ("CRC", 2), ("CUP", 2), ("CVE", 2), ("CZK", 2),
+    ("DJF", 0), ("DKK", 2), ("DOP", 2), ("DZD", 2), ("EGP", 2), ("ERN", 2), ("ETB", 2), ("EUR", 2),
+    ("FJD", 2), ("FKP", 2), ("GBP", 2), ("GEL", 2), ("GHS", 2), ("GIP", 2), ("GMD", 2), ("GNF", 0),
+    ("GTQ", 2), ("GYD", 2), ("HKD", 2), ("HNL", 2), ("HTG", 2), ("HUF", 2), ("IDR", 2), ("ILS", 2),
+    ("INR", 2), ("IQD", 3), ("IRR", 2), ("ISK", 0), ("JMD", 2), ("JOD", 3), ("JPY", 0), ("KES", 2),
+    ("KGS", 2), ("KHR", 2), ("KMF", 0), ("KPW", 2), ("KRW", 0), ("KWD", 3), ("KYD", 2), ("KZT", 2),
+    ("LAK", 2), ("LBP", 2), ("LKR", 2), ("LRD", 2), ("LSL", 2), ("LYD", 3), ("MAD", 2), ("MDL", 2),
+    ("MGA", 2), ("MKD", 2), ("MMK", 2), ("MNT", 2), ("MOP", 2), ("MRU", 2), ("MUR", 2), ("MVR", 2),
+    ("MWK", 2), ("MXN", 2), ("MXV", 2), ("MYR", 2), ("MZN", 2), ("NAD", 2), ("NGN", 2), ("NIO", 2),
+    ("NOK", 2), ("NPR", 2), ("NZD", 2), ("OMR", 3), ("PAB", 2), ("PEN", 2), ("PGK", 2), ("PHP", 2),
+    ("PKR", 2), ("PLN", 2), ("PYG", 0), ("QAR", 2), ("RON", 2), ("RSD", 2), ("RUB", 2), ("RWF", 0),
+    ("SAR", 2), ("SBD", 2), ("SCR", 2), ("SDG", 2), ("SEK", 2), ("SGD", 2), ("SHP", 2), ("SLE", 2),
+    ("SOS", 2), ("SRD", 2), ("SSP", 2), ("STN", 2), ("SVC", 2), ("SYP", 2), ("SZL", 2), ("THB", 2),
+    ("TJS", 2), ("TMT", 2), ("TND", 3), ("TOP", 2), ("TRY", 2), ("TTD", 2), ("TWD", 2), ("TZS", 2),
+    ("UAH", 2), ("UGX", 0), ("USD", 2), ("USN", 2), ("UYI", 0), ("UYU", 2), ("UYW", 4), ("UZS", 2),
+    ("VED", 2), ("VES", 2), ("VND", 0), ("VUV", 0), ("WST", 2), ("XAF", 0), ("XCD", 2), ("XCG", 2),
+    ("XOF", 0), ("XPF", 0), ("YER", 2), ("ZAR", 2), ("ZMW", 2), ("ZWG", 2),
+];
+
+/// A known ISO 4217 currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrencyInfo {
+    pub code: &'static str,
+    /// Digits after the decimal point, e.g. 2 for USD and 0 for JPY
+    pub minor_units: u32,
+}
+
+impl CurrencyInfo {
+    pub fn max_price(&self) -> f64 {
+        if self.minor_units == 0 { MAX_PRICE_WHOLE_UNITS } else { MAX_PRICE }
+    }
+}
+
+/// Looks up a currency by code, case-insensitively
+pub fn currency_info(code: &str) -> Option<CurrencyInfo> {
+    let code = normalize_currency(code)?;
+    ISO_4217
+        .binary_search_by(|(known, _)| (*known).cmp(code.as_str()))
+        .ok()
+        .map(|idx| CurrencyInfo { code: ISO_4217[idx].0, minor_units: ISO_4217[idx].1 })
+}
+
+/// Returns the upper-cased code if it has the shape of an ISO 4217 code (three letters)
+pub fn normalize_currency(code: &str) -> Option<String> {
+    let code = code.trim();
+    if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+        Some(code.to_ascii_uppercase())
+    } else {
+        None
+    }
+}
+
+/// Formats an amount with its currency, e.g. "$12.50" for USD and "1250 JPY" otherwise
+pub fn format_money(amount: f64, currency: &str) -> String {
+    let decimals = currency_info(currency).map_or(2, |info| info.minor_units as usize);
+    if currency == DEFAULT_CURRENCY {
+        format!("${:.*}", decimals, amount)
+    } else {
+        format!("{:.*} {}", decimals, amount, currency)
+    }
+}
+
+/// Exchange rates into a single base currency, loaded from a TOML file such as:
+///
+/// ```toml
+/// base = "USD"
+///
+/// [rates]
+/// EUR = 1.08   # 1 EUR = 1.08 USD
+/// GBP = 1.27
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExchangeRates {
+    pub base: String,
+    #[serde(default)]
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl ExchangeRates {
+    /// A table that converts nothing: only amounts already in `base` are counted
+    pub fn identity(base: &str) -> Self {
+        Self {
+            base: base.to_string(),
+            rates: BTreeMap::new(),
+        }
+    }
+
+    /// Parses a rate table, normalizing currency codes and rejecting unusable rates
+    pub fn parse(contents: &str) -> Result<Self> {
+        let raw: ExchangeRates = toml::from_str(contents).context("Invalid exchange rate table")?;
+
+        let base = normalize_currency(&raw.base)
+            .ok_or_else(|| anyhow!("Invalid base currency: {}", raw.base))?;
+
+        let mut rates = BTreeMap::new();
+        for (code, rate) in raw.rates {
+            let normalized = normalize_currency(&code)
+                .ok_or_else(|| anyhow!("Invalid currency code in rate table: {}", code))?;
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(anyhow!("Invalid exchange rate for {}: {}", normalized, rate));
+            }
+            rates.insert(normalized, rate);
+        }
+
+        Ok(Self { base, rates })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read exchange rate table: {}", path.display()))?;
+        Self::parse(&contents)
+    }
+
+    /// SQL expression for the multiplier of the `currency` column; NULL when there is no rate,
+    /// so SQLite's aggregates skip amounts that can't be converted.
+    /// Codes are normalized on parse, so they are safe to inline.
+    pub fn sql_multiplier(&self) -> String {
+        let mut sql = format!("(CASE currency WHEN '{}' THEN 1.0", self.base);
+        for (code, rate) in &self.rates {
+            if *code != self.base {
+                sql.push_str(&format!(" WHEN '{}' THEN {:?}", code, rate));
+            }
+        }
+        sql.push_str(" END)");
+        sql
+    }
+
+    /// Stable text form for cache keys
+    pub fn cache_key(&self) -> String {
+        let rates: Vec<String> = self.rates.iter().map(|(c, r)| format!("{}={}", c, r)).collect();
+        format!("{}:{}", self.base, rates.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency_table_lookup() {
+        assert!(ISO_4217.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert_eq!(currency_info("jpy").map(|info| info.minor_units), Some(0));
+        assert_eq!(currency_info("KWD").map(|info| info.minor_units), Some(3));
+        assert_eq!(currency_info("ABC"), None);
+        assert_eq!(format_money(1250.0, "JPY"), "1250 JPY");
+        assert_eq!(format_money(12.5, "USD"), "$12.50");
+    }
+
+    #[test]
+    fn test_parse_rate_table_normalizes_codes() {
+        let rates = ExchangeRates::parse("base = \"usd\"\n[rates]\neur = 1.08\n").unwrap();
+        assert_eq!(rates.base, "USD");
+        assert_eq!(rates.rates.get("EUR"), Some(&1.08));
+        assert_eq!(rates.sql_multiplier(), "(CASE currency WHEN 'USD' THEN 1.0 WHEN 'EUR' THEN 1.08 END)");
+
+        assert!(ExchangeRates::parse("base = \"US\"").is_err());
+        assert!(ExchangeRates::parse("base = \"USD\"\n[rates]\nEUR = -1.0\n").is_err());
+    }
+}
@@ -1,3 +1,4 @@
 // Configuration module - will contain TOML parsing and validation
 pub mod config;
-pub mod optimization; 
\ No newline at end of file
+pub mod currency;
+pub mod optimization;
\ No newline at end of file
@@ -1,26 +1,100 @@
 use anyhow::Result;
 use rusqlite::{Connection, params};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::{Duration, Instant};
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
 use chrono::{Utc, DateTime};
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
 use serde_json;
+use dirs::home_dir;
+use crate::db::retry::BUSY_TIMEOUT;
+use crate::logging::logger::default_log_dir;
+use crate::output::format::{render_table, to_json, OutputOptions};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry {
     pub data: String,
     pub timestamp: DateTime<Utc>,
     pub ttl: Duration,
+    /// The cache's use counter when the entry was last read or written; the lowest goes first
+    /// when the cache is full
+    #[serde(default)]
+    pub last_used: u64,
 }
 
+/// What `QueryCache::save_to` writes: the live entries and the hit and miss counts so far
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    entries: HashMap<String, CacheEntry>,
+    hits: u64,
+    misses: u64,
+}
+
+/// A summary of a `QueryCache`, as `cache --stats` prints it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    /// Lookups since the cache was last reset, over every run sharing the cache file
+    pub hits: u64,
+    pub misses: u64,
+    /// Approximate bytes the entries take in memory: keys, cached output and bookkeeping
+    pub approx_bytes: usize,
+}
+
+/// Default number of entries a `QueryCache` holds before evicting
+pub const DEFAULT_CACHE_CAPACITY: usize = 1000;
+
+pub const CACHE_FILENAME: &str = "cache.json";
+
+/// Returns the default path of the persisted query cache (~/.inventory/cache.json), or None
+/// when there's no home directory to keep it in
+pub fn default_cache_path() -> Option<PathBuf> {
+    let mut path = home_dir()?;
+    path.push(".inventory");
+    path.push(CACHE_FILENAME);
+    Some(path)
+}
+
+/// Picks where `filter` and `stats` write performance reports: nowhere with `--no-perf-report`,
+/// then the `--perf-report` directory, nowhere if the config file sets `perf_report = false`,
+/// then the config file's `perf_report_dir`, then ~/.inventory/logs
+pub fn resolve_perf_report_dir(disabled: bool, flag: Option<PathBuf>, enabled: Option<bool>, configured: Option<PathBuf>) -> Option<PathBuf> {
+    if disabled {
+        return None;
+    }
+    flag.or_else(|| {
+        if enabled == Some(false) {
+            return None;
+        }
+        Some(configured.unwrap_or_else(default_log_dir))
+    })
+}
+
+impl CacheEntry {
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        match chrono::Duration::from_std(self.ttl) {
+            Ok(ttl) => now >= self.timestamp + ttl,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Every recorded duration of each query, with when it was recorded
+pub type QueryTimes = HashMap<String, Vec<(DateTime<Utc>, Duration)>>;
+
 #[derive(Debug)]
 pub struct PerformanceMonitor {
-    pub query_times: Arc<Mutex<HashMap<String, Vec<Duration>>>>,
+    pub query_times: Arc<Mutex<QueryTimes>>,
     pub cache_hits: Arc<Mutex<u64>>,
     pub cache_misses: Arc<Mutex<u64>>,
+    pub verbose: bool,
+    /// Queries taking longer than this are logged as warnings and counted
+    pub slow_query_threshold: Option<Duration>,
+    pub slow_queries: Arc<Mutex<u64>>,
 }
 
 impl PerformanceMonitor {
@@ -29,14 +103,55 @@ impl PerformanceMonitor {
             query_times: Arc::new(Mutex::new(HashMap::new())),
             cache_hits: Arc::new(Mutex::new(0)),
             cache_misses: Arc::new(Mutex::new(0)),
+            verbose: false,
+            slow_query_threshold: None,
+            slow_queries: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Warn about any query slower than `threshold`, e.g. a filter scanning the whole table
+    /// because an index is missing
+    pub fn with_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_query_threshold = Some(threshold);
+        self
+    }
+
+    /// Enable or disable per-command timing output on stderr
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Print elapsed time, row count and cache status for a command when verbose is enabled
+    pub fn report_verbose(&self, operation: &str, elapsed: Duration, rows: Option<usize>, cache_hit: Option<bool>) {
+        if !self.verbose {
+            return;
+        }
+        let mut line = format!("[{}] query time: {:.2} ms", operation, elapsed.as_secs_f64() * 1000.0);
+        if let Some(rows) = rows {
+            line.push_str(&format!(", rows: {}", rows));
         }
+        if let Some(hit) = cache_hit {
+            line.push_str(if hit { ", cache: hit" } else { ", cache: miss" });
+        }
+        eprintln!("{}", line);
     }
 
     pub fn record_query(&self, query_name: &str, duration: Duration) {
         if let Ok(mut times) = self.query_times.lock() {
             times.entry(query_name.to_string())
                 .or_insert_with(Vec::new)
-                .push(duration);
+                .push((Utc::now(), duration));
+        }
+        
+        if let Some(threshold) = self.slow_query_threshold.filter(|&threshold| duration > threshold) {
+            log::warn!(
+                "Slow query {}: {:.2} ms (threshold {} ms)",
+                query_name, duration.as_secs_f64() * 1000.0, threshold.as_millis()
+            );
+            if let Ok(mut slow) = self.slow_queries.lock() {
+                *slow += 1;
+            }
         }
     }
 
@@ -52,11 +167,17 @@ impl PerformanceMonitor {
         }
     }
 
-    pub fn get_stats(&self) -> HashMap<String, f64> {
+    /// Average and count of each query's durations recorded at or after `since` and before
+    /// `until`, keyed `<query>_avg_ms` and `<query>_count`; queries with none are left out
+    fn query_stats(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> HashMap<String, f64> {
         let mut stats = HashMap::new();
         
         if let Ok(times) = self.query_times.lock() {
-            for (query, durations) in times.iter() {
+            for (query, recorded) in times.iter() {
+                let durations: Vec<Duration> = recorded.iter()
+                    .filter(|(at, _)| since.is_none_or(|since| *at >= since) && until.is_none_or(|until| *at < until))
+                    .map(|(_, duration)| *duration)
+                    .collect();
                 if !durations.is_empty() {
                     let avg = durations.iter().map(|d| d.as_millis() as f64).sum::<f64>() / durations.len() as f64;
                     stats.insert(format!("{}_avg_ms", query), avg);
@@ -64,6 +185,14 @@ impl PerformanceMonitor {
                 }
             }
         }
+        
+        stats
+    }
+    
+    /// Stats over the monitor's whole lifetime: each query's average and count, the cache
+    /// hits and misses, and how many queries were slow
+    pub fn get_stats(&self) -> HashMap<String, f64> {
+        let mut stats = self.query_stats(None, None);
 
         if let Ok(hits) = self.cache_hits.lock() {
             stats.insert("cache_hits".to_string(), *hits as f64);
@@ -73,74 +202,270 @@ impl PerformanceMonitor {
             stats.insert("cache_misses".to_string(), *misses as f64);
         }
 
+        if let Ok(slow) = self.slow_queries.lock() {
+            stats.insert("slow_query_count".to_string(), *slow as f64);
+        }
+
         stats
     }
 
-    /// Write performance stats to a file in the 'logs' directory as JSON
-    pub fn write_performance_report(&self, file_name: &str) -> Result<()> {
-        let stats = self.get_stats();
-        let logs_dir = Path::new("logs");
-        if !logs_dir.exists() {
-            fs::create_dir_all(logs_dir)?;
+    /// Stats for the queries recorded at or after `since`, e.g. to compare two runs of a
+    /// long-lived process. See `get_stats_window`.
+    pub fn get_stats_since(&self, since: DateTime<Utc>) -> HashMap<String, f64> {
+        self.get_stats_window(Some(since), None)
+    }
+    
+    /// Stats for the queries recorded from `since` (inclusive) until `until` (exclusive); either
+    /// end may be open. Cache hits and misses are only counted over the lifetime, so they're
+    /// left out. `slow_query_count` counts the window's queries over the slow-query threshold.
+    pub fn get_stats_window(&self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) -> HashMap<String, f64> {
+        let mut stats = self.query_stats(since, until);
+        let slow = match (self.slow_query_threshold, self.query_times.lock()) {
+            (Some(threshold), Ok(times)) => times.values()
+                .flatten()
+                .filter(|(at, duration)| {
+                    *duration > threshold
+                        && since.is_none_or(|since| *at >= since)
+                        && until.is_none_or(|until| *at < until)
+                })
+                .count(),
+            _ => 0,
+        };
+        stats.insert("slow_query_count".to_string(), slow as f64);
+        stats
+    }
+
+    /// The stats from `get_stats` for `--profile`: a JSON object, or a table of metrics
+    /// sorted by name. Timings keep two decimals; counts are whole numbers.
+    pub fn format_profile(&self, json: bool, options: &OutputOptions) -> Result<String> {
+        let stats: BTreeMap<String, f64> = self.get_stats().into_iter().collect();
+        if json {
+            return Ok(format!("{}\n", to_json(&stats, options)?));
         }
-        let file_path = logs_dir.join(file_name);
-        let json = serde_json::to_string_pretty(&stats)?;
-        let mut file = fs::File::create(&file_path)?;
-        file.write_all(json.as_bytes())?;
-        Ok(())
+        let rows = stats.into_iter()
+            .map(|(metric, value)| {
+                let value = if metric.ends_with("_ms") { format!("{:.2}", value) } else { format!("{}", value) };
+                (vec![metric, value], None)
+            })
+            .collect();
+        Ok(render_table(vec!["METRIC".to_string(), "VALUE".to_string()], rows, false))
+    }
+
+    /// Write performance stats as JSON to `file_name` in `dir`, creating the directory if
+    /// needed. Returns the report's path.
+    pub fn write_performance_report(&self, dir: &Path, file_name: &str) -> Result<PathBuf> {
+        write_report(&self.get_stats(), dir, file_name)
+    }
+
+    /// `write_performance_report` for only the queries recorded between `since` and `until`
+    /// (see `get_stats_window`). The report has the same shape as the lifetime one.
+    pub fn write_performance_report_window(
+        &self,
+        dir: &Path,
+        file_name: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<PathBuf> {
+        write_report(&self.get_stats_window(since, until), dir, file_name)
+    }
+}
+
+fn write_report(stats: &HashMap<String, f64>, dir: &Path, file_name: &str) -> Result<PathBuf> {
+    if !dir.exists() {
+        fs::create_dir_all(dir)?;
     }
+    let file_path = dir.join(file_name);
+    let json = serde_json::to_string_pretty(stats)?;
+    let mut file = fs::File::create(&file_path)?;
+    file.write_all(json.as_bytes())?;
+    Ok(file_path)
 }
 
 #[derive(Debug)]
 pub struct QueryCache {
     cache: Arc<Mutex<HashMap<String, CacheEntry>>>,
     monitor: Arc<PerformanceMonitor>,
+    max_entries: usize,
+    /// Overrides the TTL each caller passes to `set`
+    ttl: Option<Duration>,
+    /// Unlike the monitor's counts, these carry over between runs through the cache file
+    hits: AtomicU64,
+    misses: AtomicU64,
+    /// Counts every read and write, so recency doesn't depend on the wall clock
+    uses: AtomicU64,
 }
 
 impl QueryCache {
     pub fn new(monitor: Arc<PerformanceMonitor>) -> Self {
+        Self::with_capacity(monitor, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Create a cache that holds at most `max_entries`, evicting the least recently used entry
+    pub fn with_capacity(monitor: Arc<PerformanceMonitor>, max_entries: usize) -> Self {
         Self {
             cache: Arc::new(Mutex::new(HashMap::new())),
             monitor,
+            max_entries,
+            ttl: None,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            uses: AtomicU64::new(0),
         }
     }
 
+    fn next_use(&self) -> u64 {
+        self.uses.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Keep every entry for `ttl` (when set) instead of the TTL passed to `set`
+    pub fn with_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
     pub fn get(&self, key: &str) -> Option<String> {
-        if let Ok(cache) = self.cache.lock() {
-            if let Some(entry) = cache.get(key) {
-                if Utc::now() < entry.timestamp + chrono::Duration::from_std(entry.ttl).unwrap() {
+        if let Ok(mut cache) = self.cache.lock() {
+            if let Some(entry) = cache.get_mut(key) {
+                let now = Utc::now();
+                if !entry.is_expired(now) {
+                    entry.last_used = self.next_use();
+                    self.hits.fetch_add(1, Ordering::Relaxed);
                     self.monitor.record_cache_hit();
                     return Some(entry.data.clone());
                 } else {
                     // Expired entry, remove it
-                    drop(cache);
-                    if let Ok(mut cache) = self.cache.lock() {
-                        cache.remove(key);
-                    }
+                    cache.remove(key);
                 }
             }
         }
+        self.misses.fetch_add(1, Ordering::Relaxed);
         self.monitor.record_cache_miss();
         None
     }
 
     pub fn set(&self, key: String, data: String, ttl: Duration) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        let now = Utc::now();
         let entry = CacheEntry {
             data,
-            timestamp: Utc::now(),
-            ttl,
+            timestamp: now,
+            ttl: self.ttl.unwrap_or(ttl),
+            last_used: self.next_use(),
         };
         
         if let Ok(mut cache) = self.cache.lock() {
+            // Evict least recently used entries to make room for a new key
+            while !cache.contains_key(&key) && cache.len() >= self.max_entries {
+                let oldest = cache.iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(k, _)| k.clone());
+                match oldest {
+                    Some(oldest_key) => { cache.remove(&oldest_key); }
+                    None => break,
+                }
+            }
             cache.insert(key, entry);
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.cache.lock().map(|cache| cache.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every entry. The hit and miss counts are kept; see `reset`.
     pub fn clear(&self) {
         if let Ok(mut cache) = self.cache.lock() {
             cache.clear();
         }
     }
+
+    /// Drops every entry and zeroes the hit and miss counts
+    pub fn reset(&self) {
+        self.clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let (entries, approx_bytes) = match self.cache.lock() {
+            Ok(cache) => (
+                cache.len(),
+                cache.iter()
+                    .map(|(key, entry)| key.len() + entry.data.len() + std::mem::size_of::<(String, CacheEntry)>())
+                    .sum(),
+            ),
+            Err(_) => (0, 0),
+        };
+        CacheStats {
+            entries,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            approx_bytes,
+        }
+    }
+
+    /// Load entries and counts persisted by `save_to`. A missing or unreadable file leaves the
+    /// cache empty.
+    pub fn load_from(&self, path: &Path) {
+        let CacheFile { entries, hits, misses } = match fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+        {
+            Some(file) => file,
+            None => return,
+        };
+        self.hits.fetch_add(hits, Ordering::Relaxed);
+        self.misses.fetch_add(misses, Ordering::Relaxed);
+
+        let now = Utc::now();
+        let mut live: Vec<(String, CacheEntry)> = entries.into_iter()
+            .filter(|(_, entry)| !entry.is_expired(now))
+            .collect();
+        // Keep the most recently used entries when the file holds more than the cap
+        live.sort_by_key(|entry| std::cmp::Reverse(entry.1.last_used));
+        live.truncate(self.max_entries);
+        // Later uses in this run count as more recent than any in the file
+        if let Some((_, newest)) = live.first() {
+            self.uses.fetch_max(newest.last_used, Ordering::Relaxed);
+        }
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.extend(live);
+        }
+    }
+
+    /// Write unexpired entries and the counts to `path` as JSON, creating the parent directory
+    /// if needed
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        let now = Utc::now();
+        let entries: HashMap<String, CacheEntry> = match self.cache.lock() {
+            Ok(cache) => cache.iter()
+                .filter(|(_, entry)| !entry.is_expired(now))
+                .map(|(k, entry)| (k.clone(), entry.clone()))
+                .collect(),
+            Err(_) => HashMap::new(),
+        };
+
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        let file = CacheFile {
+            entries,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        };
+        fs::write(path, serde_json::to_string(&file)?)?;
+        Ok(())
+    }
 }
 
 pub fn create_database_indexes(conn: &Connection) -> Result<()> {
@@ -153,6 +478,7 @@ pub fn create_database_indexes(conn: &Connection) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_items_status ON items(status)",
         "CREATE INDEX IF NOT EXISTS idx_items_price_category ON items(price, category)",
         "CREATE INDEX IF NOT EXISTS idx_items_category_condition ON items(category, condition)",
+        "CREATE INDEX IF NOT EXISTS idx_items_last_import_run ON items(last_import_run)",
     ];
 
     for index_sql in &indexes {
@@ -174,6 +500,8 @@ pub fn optimize_database(conn: &Connection) -> Result<()> {
         PRAGMA cache_size = 10000;
         PRAGMA temp_store = MEMORY;"
     )?;
+    // Wait out another process's brief write lock rather than failing with "database is locked"
+    conn.busy_timeout(BUSY_TIMEOUT)?;
     
     Ok(())
 }
@@ -191,6 +519,15 @@ where
     Ok(result)
 }
 
+/// The database a cached result was read from, for its cache key. The cache file is shared by
+/// every database, so without it one database's results would be served for another's.
+pub fn database_cache_key(conn: &Connection) -> String {
+    match conn.path().filter(|path| !path.is_empty()) {
+        Some(path) => fs::canonicalize(path).map_or_else(|_| path.to_string(), |path| path.display().to_string()),
+        None => ":memory:".to_string(),
+    }
+}
+
 pub fn generate_cache_key(operation: &str, params: &HashMap<String, String>) -> String {
     let mut key = operation.to_string();
     let mut sorted_params: Vec<_> = params.iter().collect();
@@ -219,6 +556,83 @@ mod tests {
         let stats = monitor.get_stats();
         assert!(stats.contains_key("test_query_avg_ms"));
         assert_eq!(stats["test_query_count"], 2.0);
+        assert_eq!(stats["slow_query_count"], 0.0);
+    }
+
+    #[test]
+    fn test_slow_query_threshold() {
+        let monitor = PerformanceMonitor::new().with_threshold(Duration::from_millis(150));
+        monitor.record_query("filter_query", Duration::from_millis(100));
+        monitor.record_query("filter_query", Duration::from_millis(200));
+        
+        let slow = measure_query_performance(&monitor, "list_query", || {
+            std::thread::sleep(Duration::from_millis(160));
+            Ok(())
+        });
+        assert!(slow.is_ok());
+        assert_eq!(monitor.get_stats()["slow_query_count"], 2.0);
+    }
+
+    #[test]
+    fn test_stats_window_filters_by_recorded_time() {
+        let monitor = PerformanceMonitor::new().with_threshold(Duration::from_millis(150));
+        let start = Utc::now() - chrono::Duration::hours(1);
+        let at = |minutes| start + chrono::Duration::minutes(minutes);
+        monitor.query_times.lock().unwrap().insert("filter_query".to_string(), vec![
+            (at(0), Duration::from_millis(100)),
+            (at(10), Duration::from_millis(200)),
+            (at(20), Duration::from_millis(300)),
+        ]);
+        monitor.record_cache_hit();
+        
+        // Lifetime stats are unchanged
+        let stats = monitor.get_stats();
+        assert_eq!(stats["filter_query_count"], 3.0);
+        assert_eq!(stats["cache_hits"], 1.0);
+        
+        let stats = monitor.get_stats_since(at(10));
+        assert_eq!(stats["filter_query_count"], 2.0);
+        assert_eq!(stats["filter_query_avg_ms"], 250.0);
+        assert_eq!(stats["slow_query_count"], 2.0);
+        assert!(!stats.contains_key("cache_hits"));
+        
+        // The end of the window is exclusive
+        let stats = monitor.get_stats_window(Some(at(0)), Some(at(20)));
+        assert_eq!(stats["filter_query_count"], 2.0);
+        assert_eq!(stats["slow_query_count"], 1.0);
+        let stats = monitor.get_stats_window(None, Some(at(0)));
+        assert!(!stats.contains_key("filter_query_count"));
+        
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = monitor.write_performance_report_window(dir.path(), "window.json", Some(at(20)), None).unwrap();
+        let report: HashMap<String, f64> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(report["filter_query_count"], 1.0);
+        assert_eq!(report["filter_query_avg_ms"], 300.0);
+    }
+
+    #[test]
+    fn test_resolve_perf_report_dir() {
+        let flag = Some(PathBuf::from("/tmp/reports"));
+        let configured = Some(PathBuf::from("/data/reports"));
+        
+        assert_eq!(resolve_perf_report_dir(true, flag.clone(), None, configured.clone()), None);
+        assert_eq!(resolve_perf_report_dir(false, flag.clone(), Some(false), configured.clone()), flag);
+        assert_eq!(resolve_perf_report_dir(false, None, Some(false), configured.clone()), None);
+        assert_eq!(resolve_perf_report_dir(false, None, Some(true), configured.clone()), configured);
+        assert_eq!(resolve_perf_report_dir(false, None, None, None), Some(default_log_dir()));
+    }
+
+    #[test]
+    fn test_write_performance_report_creates_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let reports = dir.path().join("nested/reports");
+        let monitor = PerformanceMonitor::new();
+        monitor.record_query("test_query", Duration::from_millis(100));
+        
+        let path = monitor.write_performance_report(&reports, "performance_test.json").unwrap();
+        assert_eq!(path, reports.join("performance_test.json"));
+        let stats: HashMap<String, f64> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(stats["test_query_count"], 1.0);
     }
 
     #[test]
@@ -237,6 +651,140 @@ mod tests {
         assert_eq!(value, None);
     }
 
+    #[test]
+    fn test_query_cache_lru_eviction() {
+        let monitor = Arc::new(PerformanceMonitor::new());
+        let cache = QueryCache::with_capacity(monitor.clone(), 2);
+
+        cache.set("a".to_string(), "1".to_string(), Duration::from_secs(60));
+        cache.set("b".to_string(), "2".to_string(), Duration::from_secs(60));
+
+        // Touch "a" so "b" becomes the least recently used entry
+        assert_eq!(cache.get("a"), Some("1".to_string()));
+
+        cache.set("c".to_string(), "3".to_string(), Duration::from_secs(60));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some("1".to_string()));
+        assert_eq!(cache.get("c"), Some("3".to_string()));
+
+        // Overwriting an existing key must not evict anything
+        cache.set("a".to_string(), "10".to_string(), Duration::from_secs(60));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("c"), Some("3".to_string()));
+    }
+
+    #[test]
+    fn test_query_cache_evicts_in_use_order() {
+        let monitor = Arc::new(PerformanceMonitor::new());
+        let cache = QueryCache::with_capacity(monitor.clone(), 3);
+        for key in ["a", "b", "c"] {
+            cache.set(key.to_string(), key.to_string(), Duration::from_secs(60));
+        }
+
+        // Written and read back to back, so their wall-clock times would all tie
+        cache.get("a");
+        cache.get("c");
+        cache.get("b");
+        // Checked on the map directly, since a `get` would count as a use
+        let held = |key: &str| cache.cache.lock().unwrap().contains_key(key);
+        let mut evicted = Vec::new();
+        for key in ["d", "e", "f"] {
+            cache.set(key.to_string(), key.to_string(), Duration::from_secs(60));
+            let gone = ["a", "b", "c"].into_iter().find(|k| !evicted.contains(k) && !held(k));
+            evicted.push(gone.unwrap());
+        }
+        assert_eq!(evicted, vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_query_cache_persists_use_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let monitor = Arc::new(PerformanceMonitor::new());
+
+        let cache = QueryCache::new(monitor.clone());
+        for key in ["a", "b", "c"] {
+            cache.set(key.to_string(), key.to_string(), Duration::from_secs(60));
+        }
+        cache.get("a");
+        cache.save_to(&path).unwrap();
+
+        // A smaller cache keeps the most recently used, and carries on counting after them
+        let restored = QueryCache::with_capacity(monitor.clone(), 2);
+        restored.load_from(&path);
+        assert_eq!(restored.get("b"), None);
+        restored.get("c");
+        restored.set("d".to_string(), "d".to_string(), Duration::from_secs(60));
+        assert_eq!(restored.get("a"), None);
+        assert_eq!(restored.get("c"), Some("c".to_string()));
+    }
+
+    #[test]
+    fn test_query_cache_persistence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let monitor = Arc::new(PerformanceMonitor::new());
+
+        let cache = QueryCache::new(monitor.clone());
+        cache.set("live".to_string(), "fresh".to_string(), Duration::from_secs(60));
+        cache.set("stale".to_string(), "old".to_string(), Duration::from_secs(0));
+        cache.save_to(&path).unwrap();
+
+        let restored = QueryCache::new(monitor.clone());
+        restored.load_from(&path);
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored.get("live"), Some("fresh".to_string()));
+        assert_eq!(restored.get("stale"), None);
+    }
+
+    #[test]
+    fn test_query_cache_counts_persist_until_reset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.json");
+        let cache = QueryCache::new(Arc::new(PerformanceMonitor::new()));
+        cache.set("key".to_string(), "value".to_string(), Duration::from_secs(60));
+        cache.get("key");
+        cache.get("missing");
+        cache.save_to(&path).unwrap();
+
+        let restored = QueryCache::new(Arc::new(PerformanceMonitor::new()));
+        restored.load_from(&path);
+        restored.get("key");
+        let stats = restored.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 2, 1));
+        assert!(stats.approx_bytes >= "key".len() + "value".len());
+
+        // Invalidating after a write keeps the counts; a reset doesn't
+        restored.clear();
+        assert_eq!(restored.stats().hits, 2);
+        restored.reset();
+        assert_eq!(restored.stats(), CacheStats { entries: 0, hits: 0, misses: 0, approx_bytes: 0 });
+    }
+
+    #[test]
+    fn test_query_cache_ttl_override() {
+        let monitor = Arc::new(PerformanceMonitor::new());
+        let cache = QueryCache::new(monitor).with_ttl(Some(Duration::from_secs(0)));
+        cache.set("key".to_string(), "value".to_string(), Duration::from_secs(60));
+        assert_eq!(cache.get("key"), None);
+    }
+
+    #[test]
+    fn test_query_cache_load_missing_or_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let monitor = Arc::new(PerformanceMonitor::new());
+        let cache = QueryCache::new(monitor);
+
+        cache.load_from(&dir.path().join("missing.json"));
+        assert!(cache.is_empty());
+
+        let corrupt = dir.path().join("corrupt.json");
+        fs::write(&corrupt, "{not json").unwrap();
+        cache.load_from(&corrupt);
+        assert!(cache.is_empty());
+    }
+
     #[test]
     fn test_database_indexes() {
         let conn = initialize_database(None).unwrap();
@@ -0,0 +1,315 @@
+// Operation history backing the undo command, and per-item change history
+//
+// Every mutating command records the item rows it touched in `operations_log`
+// (see `schema::CREATE_OPERATIONS_LOG_TABLE_SQL`); `undo_last_operation` uses
+// those snapshots to put the items back the way they were. Separately, triggers
+// log each item's field changes in `item_history` for the history command.
+
+use anyhow::{Context, Result};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use serde_json::{Map, Value as JsonValue};
+use std::fmt;
+use std::str::FromStr;
+use crate::db::schema::HISTORY_FIELDS;
+use crate::db::timestamp::now_timestamp;
+
+/// A full items row, keyed by column name
+pub type ItemSnapshot = Map<String, JsonValue>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Add,
+    Update,
+    Delete,
+    Import,
+}
+
+impl fmt::Display for OperationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationKind::Add => write!(f, "add"),
+            OperationKind::Update => write!(f, "update"),
+            OperationKind::Delete => write!(f, "delete"),
+            OperationKind::Import => write!(f, "import"),
+        }
+    }
+}
+
+impl FromStr for OperationKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "add" => Ok(OperationKind::Add),
+            "update" => Ok(OperationKind::Update),
+            "delete" => Ok(OperationKind::Delete),
+            "import" => Ok(OperationKind::Import),
+            _ => anyhow::bail!("Unknown operation '{}' in operations log", s),
+        }
+    }
+}
+
+/// A logged operation and the item rows it recorded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    pub id: i64,
+    pub kind: OperationKind,
+    pub items: Vec<ItemSnapshot>,
+    pub created_at: String,
+}
+
+/// What undoing an operation changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoOutcome {
+    pub operation_id: i64,
+    pub kind: OperationKind,
+    /// Items removed, reverted or restored
+    pub items: usize,
+    /// Deleted items restored under a new ID because the original was taken: (original, new)
+    pub reassigned: Vec<(i64, i64)>,
+}
+
+fn to_json(value: Value) -> JsonValue {
+    match value {
+        Value::Null | Value::Blob(_) => JsonValue::Null,
+        Value::Integer(i) => JsonValue::from(i),
+        Value::Real(f) => JsonValue::from(f),
+        Value::Text(s) => JsonValue::from(s),
+    }
+}
+
+fn to_sql(value: &JsonValue) -> Value {
+    match value {
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        JsonValue::String(s) => Value::Text(s.clone()),
+        JsonValue::Bool(b) => Value::Integer(*b as i64),
+        _ => Value::Null,
+    }
+}
+
+fn snapshot_id(snapshot: &ItemSnapshot) -> Option<i64> {
+    snapshot.get("item_id").and_then(JsonValue::as_i64)
+}
+
+fn item_columns(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('items')")?;
+    let columns = stmt.query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>, rusqlite::Error>>()?;
+    Ok(columns)
+}
+
+/// Reads the full rows of the given items; IDs that aren't in the table are skipped
+pub fn snapshot_items(conn: &Connection, ids: &[i64]) -> Result<Vec<ItemSnapshot>> {
+    let mut stmt = conn.prepare("SELECT * FROM items WHERE item_id = ?")?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut snapshots = Vec::new();
+    for &id in ids {
+        let mut rows = stmt.query([id])?;
+        if let Some(row) = rows.next()? {
+            let mut snapshot = ItemSnapshot::new();
+            for (idx, column) in columns.iter().enumerate() {
+                snapshot.insert(column.clone(), to_json(row.get(idx)?));
+            }
+            snapshots.push(snapshot);
+        }
+    }
+    Ok(snapshots)
+}
+
+/// Logs an operation with the rows `undo` needs to reverse it. Nothing is logged without rows.
+pub fn record_operation(conn: &Connection, kind: OperationKind, items: &[ItemSnapshot]) -> Result<()> {
+    log_operation(conn, kind, None, items)
+}
+
+/// Logs an operation performed by an import run, which `revert_import_run` can reverse later
+pub fn record_import_operation(conn: &Connection, kind: OperationKind, run: &str, items: &[ItemSnapshot]) -> Result<()> {
+    log_operation(conn, kind, Some(run), items)
+}
+
+fn log_operation(conn: &Connection, kind: OperationKind, run: Option<&str>, items: &[ItemSnapshot]) -> Result<()> {
+    if items.is_empty() {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO operations_log (operation, items, created_at, import_run) VALUES (?, ?, ?, ?)",
+        params![kind.to_string(), serde_json::to_string(items)?, now_timestamp(), run],
+    ).context("Failed to record operation history")?;
+    Ok(())
+}
+
+/// The most recent operation that hasn't been undone yet
+pub fn last_operation(conn: &Connection) -> Result<Option<Operation>> {
+    let mut stmt = conn.prepare(
+        "SELECT operation_id, operation, items, created_at FROM operations_log \
+         WHERE undone_at IS NULL ORDER BY operation_id DESC LIMIT 1"
+    )?;
+    let mut rows = stmt.query([])?;
+    let Some(row) = rows.next()? else {
+        return Ok(None);
+    };
+    let id: i64 = row.get(0)?;
+    let items: String = row.get(2)?;
+    Ok(Some(Operation {
+        id,
+        kind: row.get::<_, String>(1)?.parse()?,
+        items: serde_json::from_str(&items)
+            .with_context(|| format!("Operation {} has unreadable item snapshots", id))?,
+        created_at: row.get(3)?,
+    }))
+}
+
+/// Puts a snapshot's values back into an existing row
+fn revert_item(conn: &Connection, columns: &[String], snapshot: &ItemSnapshot, id: i64) -> Result<usize> {
+    let (names, values): (Vec<&String>, Vec<Value>) = columns.iter()
+        .filter(|column| *column != "item_id")
+        .filter_map(|column| snapshot.get(column).map(|value| (column, to_sql(value))))
+        .unzip();
+    let assignments: Vec<String> = names.iter().map(|name| format!("{} = ?", name)).collect();
+    let sql = format!("UPDATE items SET {} WHERE item_id = ?", assignments.join(", "));
+    let params = values.into_iter().chain(std::iter::once(Value::Integer(id)));
+    Ok(conn.execute(&sql, params_from_iter(params))?)
+}
+
+/// Re-inserts a deleted row, under its original ID unless `keep_id` is false. Returns the row's ID.
+fn restore_item(conn: &Connection, columns: &[String], snapshot: &ItemSnapshot, keep_id: bool) -> Result<i64> {
+    let (names, values): (Vec<&String>, Vec<Value>) = columns.iter()
+        .filter(|column| keep_id || *column != "item_id")
+        .filter_map(|column| snapshot.get(column).map(|value| (column, to_sql(value))))
+        .unzip();
+    let names: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+    let placeholders = vec!["?"; names.len()].join(", ");
+    conn.execute(
+        &format!("INSERT INTO items ({}) VALUES ({})", names.join(", "), placeholders),
+        params_from_iter(values),
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn item_exists(conn: &Connection, id: i64) -> Result<bool> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items WHERE item_id = ?", [id], |row| row.get(0))?;
+    Ok(count > 0)
+}
+
+/// Reverses the most recent operation not yet undone, in one transaction: added and imported
+/// items are removed, updated items get their old values back and deleted items are re-inserted,
+/// keeping their original `item_id` unless another item has taken it since.
+pub fn undo_last_operation(conn: &Connection) -> Result<Option<UndoOutcome>> {
+    let tx = conn.unchecked_transaction()?;
+    let Some(operation) = last_operation(&tx)? else {
+        return Ok(None);
+    };
+    // Only columns the table still has; the snapshot may come from an older schema
+    let columns = item_columns(&tx)?;
+    let mut items = 0;
+    let mut reassigned = Vec::new();
+
+    for snapshot in &operation.items {
+        let id = snapshot_id(snapshot)
+            .with_context(|| format!("Operation {} has a snapshot without an item_id", operation.id))?;
+        match operation.kind {
+            OperationKind::Add | OperationKind::Import => {
+                items += tx.execute("DELETE FROM items WHERE item_id = ?", [id])?;
+            }
+            OperationKind::Update => {
+                items += revert_item(&tx, &columns, snapshot, id)
+                    .with_context(|| format!("Failed to revert item {}", id))?;
+            }
+            OperationKind::Delete => {
+                let keep_id = !item_exists(&tx, id)?;
+                let restored = restore_item(&tx, &columns, snapshot, keep_id)
+                    .with_context(|| format!("Failed to restore item {}", id))?;
+                if restored != id {
+                    reassigned.push((id, restored));
+                }
+                items += 1;
+            }
+        }
+    }
+
+    tx.execute(
+        "UPDATE operations_log SET undone_at = ? WHERE operation_id = ?",
+        params![now_timestamp(), operation.id],
+    )?;
+    tx.commit().context("Failed to commit undo")?;
+
+    Ok(Some(UndoOutcome { operation_id: operation.id, kind: operation.kind, items, reassigned }))
+}
+
+/// Puts the items an import run updated back the way they were, from the operations it logged
+/// that haven't been undone, and marks all of the run's operations undone. The items it
+/// inserted are left for the caller to remove. Returns how many items were reverted.
+pub fn revert_import_run(conn: &Connection, run: &str) -> Result<usize> {
+    let columns = item_columns(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT operation_id, items FROM operations_log \
+         WHERE import_run = ? AND operation = 'update' AND undone_at IS NULL ORDER BY operation_id DESC"
+    )?;
+    let updates = stmt.query_map([run], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+    
+    let mut reverted = 0;
+    for (operation_id, items) in updates {
+        let snapshots: Vec<ItemSnapshot> = serde_json::from_str(&items)
+            .with_context(|| format!("Operation {} has unreadable item snapshots", operation_id))?;
+        // Newest first, so an item the run updated twice ends up as it was before the first
+        for snapshot in snapshots.iter().rev() {
+            let id = snapshot_id(snapshot)
+                .with_context(|| format!("Operation {} has a snapshot without an item_id", operation_id))?;
+            reverted += revert_item(conn, &columns, snapshot, id)
+                .with_context(|| format!("Failed to revert item {}", id))?;
+        }
+    }
+    
+    conn.execute(
+        "UPDATE operations_log SET undone_at = ? WHERE import_run = ? AND undone_at IS NULL",
+        params![now_timestamp(), run],
+    )?;
+    Ok(reverted)
+}
+
+/// One field's values before and after a change; `old` is null for an insert
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: JsonValue,
+    pub new: JsonValue,
+}
+
+/// An entry in an item's history (see `schema::CREATE_ITEM_HISTORY_TABLE_SQL`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemChange {
+    /// `insert` or `update`
+    pub action: String,
+    pub changed_at: String,
+    /// In `HISTORY_FIELDS` order
+    pub changes: Vec<FieldChange>,
+}
+
+/// Every recorded change to an item, oldest first
+pub fn item_history(conn: &Connection, id: i64) -> Result<Vec<ItemChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT action, changes, changed_at FROM item_history WHERE item_id = ? ORDER BY history_id"
+    )?;
+    let rows = stmt.query_map([id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?)))?;
+
+    let mut history = Vec::new();
+    for row in rows {
+        let (action, changes, changed_at) = row?;
+        let changes: Map<String, JsonValue> = serde_json::from_str(&changes)
+            .with_context(|| format!("Item {} has an unreadable history entry from {}", id, changed_at))?;
+        let mut changes: Vec<FieldChange> = changes.into_iter()
+            .map(|(field, values)| FieldChange {
+                old: values.get(0).cloned().unwrap_or(JsonValue::Null),
+                new: values.get(1).cloned().unwrap_or(JsonValue::Null),
+                field,
+            })
+            .collect();
+        changes.sort_by_key(|change| HISTORY_FIELDS.iter().position(|field| *field == change.field));
+        history.push(ItemChange { action, changed_at, changes });
+    }
+    Ok(history)
+}
@@ -1,2 +1,101 @@
 // Database migration implementation
-// TODO: Implement schema migration logic 
\ No newline at end of file
+//
+// Schema changes are numbered migrations applied in order. The `schema_version` table records
+// each one that has run, so opening a database only applies the ones newer than its version.
+// Every migration must be safe on a database whose `CREATE_ITEMS_TABLE_SQL` already includes
+// the change, since new databases are created at the latest schema and still run them all.
+
+use rusqlite::{params, Connection, Result};
+use crate::db::schema::{ensure_currency_column, ensure_import_run_column, ensure_sku_column, ensure_tags_tables, ensure_item_history, ensure_categories_table, ensure_operations_import_run_column};
+use crate::db::timestamp::now_timestamp;
+
+/// SQL for the history of applied migrations
+pub const CREATE_SCHEMA_VERSION_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER PRIMARY KEY,
+    description TEXT NOT NULL,
+    applied_at TEXT NOT NULL
+);
+"#;
+
+/// One schema change, identified by its version
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub apply: fn(&Connection) -> Result<()>,
+}
+
+/// Every migration, oldest first. Append new ones with the next version; never renumber or
+/// edit one that has shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "Add currency column", apply: ensure_currency_column },
+    Migration { version: 2, description: "Add last_import_run column", apply: ensure_import_run_column },
+    Migration { version: 3, description: "Add sku column", apply: ensure_sku_column },
+    Migration { version: 4, description: "Add tags and item_tags tables", apply: ensure_tags_tables },
+    Migration { version: 5, description: "Add item_history table", apply: ensure_item_history },
+    Migration { version: 6, description: "Add categories table and category_id column", apply: ensure_categories_table },
+    Migration { version: 7, description: "Add import_run column to operations_log", apply: ensure_operations_import_run_column },
+];
+
+/// The newest version in `MIGRATIONS`
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// The database's schema version: the newest migration applied, or 0 for none
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    conn.execute(CREATE_SCHEMA_VERSION_TABLE_SQL, [])?;
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))
+}
+
+/// A migration recorded in `schema_version`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: String,
+    pub applied_at: String,
+}
+
+/// Every migration applied to the database, oldest first
+pub fn applied_migrations(conn: &Connection) -> Result<Vec<AppliedMigration>> {
+    conn.execute(CREATE_SCHEMA_VERSION_TABLE_SQL, [])?;
+    let mut stmt = conn.prepare("SELECT version, description, applied_at FROM schema_version ORDER BY version")?;
+    let applied = stmt.query_map([], |row| {
+        Ok(AppliedMigration { version: row.get(0)?, description: row.get(1)?, applied_at: row.get(2)? })
+    })?
+    .collect::<Result<Vec<_>>>()?;
+    Ok(applied)
+}
+
+/// Applies the migrations newer than the database's version in one transaction, so a failure
+/// leaves the schema as it was. Returns the migrations that ran; none when already up to date.
+pub fn run_migrations(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    let current = current_version(conn)?;
+    let pending: Vec<&Migration> = MIGRATIONS.iter().filter(|migration| migration.version > current).collect();
+    if pending.is_empty() {
+        return Ok(pending);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    for migration in &pending {
+        (migration.apply)(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?, ?, ?)",
+            params![migration.version, migration.description, now_timestamp()],
+        )?;
+        log::info!("Applied migration {}: {}", migration.version, migration.description);
+    }
+    tx.commit()?;
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_versions_are_ascending() {
+        assert!(MIGRATIONS.windows(2).all(|pair| pair[0].version < pair[1].version));
+        assert_eq!(MIGRATIONS[0].version, 1);
+    }
+}
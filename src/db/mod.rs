@@ -1,4 +1,9 @@
 // Database module - will contain schema and query implementations
 pub mod schema;
 pub mod queries;
-pub mod migrate; 
\ No newline at end of file
+pub mod migrate;
+pub mod timestamp;
+pub mod history;
+pub mod retry;
+#[cfg(feature = "pool")]
+pub mod pool; 
\ No newline at end of file
@@ -0,0 +1,38 @@
+// Connection pool for long-running embedders (the `pool` feature)
+//
+// The CLI opens one connection per invocation and doesn't use this. A service that calls the
+// command handlers concurrently can take connections from a `Pool` instead. Handlers take
+// `&Connection`, and a `PooledConnection` derefs to one, so `handle_list_inventory(&pooled, ..)`
+// works unchanged. The database is in WAL mode (see `optimize_database`), so readers don't block
+// the writer, and a writer waits out a held write lock for `BUSY_TIMEOUT` before failing.
+
+use anyhow::{Context, Result};
+use r2d2_sqlite::SqliteConnectionManager;
+use std::path::PathBuf;
+use crate::db::schema::initialize_database;
+
+pub use crate::db::retry::BUSY_TIMEOUT;
+
+pub type Pool = r2d2::Pool<SqliteConnectionManager>;
+pub type PooledConnection = r2d2::PooledConnection<SqliteConnectionManager>;
+
+/// Creates the database at `db_path` as `initialize_database` does, then pools up to `max_size`
+/// connections to it. A pool needs a file; an in-memory database can't be shared.
+pub fn initialize_pool(db_path: &PathBuf, max_size: u32) -> Result<Pool> {
+    // Schema, migrations and WAL mode are set once, by the first connection
+    drop(initialize_database(Some(db_path))?);
+
+    // `optimize_database`'s pragmas other than WAL only last for the connection that sets them
+    let manager = SqliteConnectionManager::file(db_path).with_init(|conn| {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.execute_batch(
+            "PRAGMA synchronous = NORMAL;
+            PRAGMA cache_size = 10000;
+            PRAGMA temp_store = MEMORY;"
+        )
+    });
+    Pool::builder()
+        .max_size(max_size)
+        .build(manager)
+        .with_context(|| format!("Failed to open a connection pool for {}", db_path.display()))
+}
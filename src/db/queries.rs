@@ -1,9 +1,13 @@
 // Database queries implementation
 // TODO: Implement CRUD operations 
 
-use rusqlite::{Connection, params, Result};
-use chrono::Utc;
-use crate::output::format::InventoryItem;
+use rusqlite::{Connection, OptionalExtension, params, Result};
+use serde::{Deserialize, Serialize};
+use crate::config::currency::DEFAULT_CURRENCY;
+use crate::db::retry::retry_on_busy;
+use crate::db::timestamp::now_timestamp;
+use crate::output::format::{InventoryItem, ITEM_FIELDS};
+use crate::error::error::not_found;
 
 /// Minimal struct for testing insertions
 pub struct NewItem<'a> {
@@ -26,64 +30,262 @@ pub struct NewItem<'a> {
     pub platform_status: Option<&'a str>,
     pub internal_notes: Option<&'a str>,
     pub status: &'a str,
+    /// ISO 4217 code of `price`; None stores the default currency
+    pub currency: Option<&'a str>,
+    /// Import run that created the item, for filtering and rolling back imports
+    pub import_run: Option<&'a str>,
+    /// Internal stock-keeping unit, unique across items
+    pub sku: Option<&'a str>,
 }
 
-/// Insert a new item into the items table
+/// SQL for `insert_item`, taking the `NewItem` fields in order with `last_updated` before `status`
+pub const INSERT_ITEM_SQL: &str = r#"INSERT INTO items (
+    title, description, price, quantity, photos, category, condition, brand, upc,
+    item_specifics, shipping_details, size, original_price, hashtags, colorway, release_date,
+    platform_status, internal_notes, last_updated, status, currency, last_import_run, sku
+) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)"#;
+
+/// Insert a new item into the items table. The statement is cached on the connection, so
+/// import loops prepare it once. A write that finds the database locked is retried.
 pub fn insert_item(conn: &Connection, item: &NewItem) -> Result<usize> {
-    let now = Utc::now().to_rfc3339();
-    conn.execute(
-        r#"INSERT INTO items (
-            title, description, price, quantity, photos, category, condition, brand, upc,
-            item_specifics, shipping_details, size, original_price, hashtags, colorway, release_date,
-            platform_status, internal_notes, last_updated, status
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)"#,
-        params![
-            item.title,
-            item.description,
-            item.price,
-            item.quantity,
-            item.photos,
-            item.category,
-            item.condition,
-            item.brand,
-            item.upc,
-            item.item_specifics,
-            item.shipping_details,
-            item.size,
-            item.original_price,
-            item.hashtags,
-            item.colorway,
-            item.release_date,
-            item.platform_status,
-            item.internal_notes,
-            now,
-            item.status,
-        ],
-    )
-}
-
-/// Retrieve all items from the database
+    retry_on_busy(|| {
+        let now = now_timestamp();
+        let category = canonical_category(conn, item.category)?;
+        conn.prepare_cached(INSERT_ITEM_SQL)?.execute(
+            params![
+                item.title,
+                item.description,
+                item.price,
+                item.quantity,
+                item.photos,
+                category,
+                item.condition,
+                item.brand,
+                item.upc,
+                item.item_specifics,
+                item.shipping_details,
+                item.size,
+                item.original_price,
+                item.hashtags,
+                item.colorway,
+                item.release_date,
+                item.platform_status,
+                item.internal_notes,
+                now,
+                item.status,
+                item.currency.unwrap_or(DEFAULT_CURRENCY),
+                item.import_run,
+                item.sku,
+            ],
+        )
+    })
+}
+
+/// Statuses allowed by the items table; anything but `active` counts as archived
+pub const ITEM_STATUSES: [&str; 3] = ["active", "sold", "draft"];
+
+/// The `ITEM_FIELDS` columns for a SELECT read by `map_item`, each after `qualifier` (such as
+/// "i." when `items` is joined under that alias)
+pub fn item_select_columns(qualifier: &str) -> String {
+    ITEM_FIELDS.iter().map(|field| format!("{}{}", qualifier, field)).collect::<Vec<_>>().join(", ")
+}
+
+/// Reads a row selected with `item_select_columns`
+pub fn map_item(row: &rusqlite::Row) -> Result<InventoryItem> {
+    let mut item = InventoryItem::new(
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+    ).with_currency(row.get(7)?);
+    item.description = row.get(8)?;
+    item.upc = row.get(9)?;
+    item.size = row.get(10)?;
+    item.original_price = row.get(11)?;
+    item.colorway = row.get(12)?;
+    item.release_date = row.get(13)?;
+    item.hashtags = row.get(14)?;
+    item.photos = row.get(15)?;
+    item.item_specifics = row.get(16)?;
+    item.shipping_details = row.get(17)?;
+    item.platform_status = row.get(18)?;
+    item.internal_notes = row.get(19)?;
+    item.status = row.get(20)?;
+    item.sku = row.get(21)?;
+    item.last_import_run = row.get(22)?;
+    item.last_updated = row.get(23)?;
+    Ok(item)
+}
+
+fn items_sql(include_archived: bool) -> String {
+    let where_clause = if include_archived { "" } else { "WHERE status = 'active' " };
+    format!("SELECT {} FROM items {}ORDER BY item_id", item_select_columns(""), where_clause)
+}
+
+/// Retrieve items, leaving out sold and draft items unless `include_archived` is set
+pub fn get_items(conn: &Connection, include_archived: bool) -> Result<Vec<InventoryItem>> {
+    let mut stmt = conn.prepare(&items_sql(include_archived))?;
+    
+    let items = stmt.query_map([], map_item)?
+        .collect::<Result<Vec<_>>>()?;
+    
+    Ok(items)
+}
+
+/// Retrieve active items; `get_items(conn, false)` under its original name for library callers.
+/// The CLI goes through `get_items`, so the binary never calls it.
+#[allow(dead_code)]
 pub fn get_all_items(conn: &Connection) -> Result<Vec<InventoryItem>> {
+    get_items(conn, false)
+}
+
+/// Hands the same items as `get_items` to `f` one at a time as they're read, without
+/// collecting them. Returns how many there were.
+pub fn for_each_item(conn: &Connection, include_archived: bool, mut f: impl FnMut(InventoryItem) -> anyhow::Result<()>) -> anyhow::Result<usize> {
+    let mut stmt = conn.prepare(&items_sql(include_archived))?;
+    let mut count = 0;
+    for item in stmt.query_map([], map_item)? {
+        f(item?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Retrieve active items with at most `threshold` units left, lowest quantity first
+pub fn get_low_stock_items(conn: &Connection, threshold: i32) -> Result<Vec<InventoryItem>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM items WHERE status = 'active' AND quantity <= ?1 ORDER BY quantity, item_id",
+        item_select_columns("")
+    ))?;
+    
+    let items = stmt.query_map([threshold], map_item)?
+        .collect::<Result<Vec<_>>>()?;
+    
+    Ok(items)
+}
+
+/// A UPC shared by more than one item, with the IDs of those items
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateUpc {
+    pub upc: String,
+    pub item_ids: Vec<i64>,
+}
+
+/// Every UPC that more than one item has, archived items included, by UPC with each one's
+/// item IDs ascending. Items without a UPC aren't duplicates of each other.
+pub fn get_duplicate_upcs(conn: &Connection) -> Result<Vec<DuplicateUpc>> {
     let mut stmt = conn.prepare(
-        "SELECT item_id, title, price, quantity, condition, category, brand FROM items ORDER BY item_id"
+        "SELECT upc, item_id FROM items WHERE upc IN \
+         (SELECT upc FROM items WHERE upc IS NOT NULL AND upc != '' GROUP BY upc HAVING COUNT(*) > 1) \
+         ORDER BY upc, item_id"
     )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+    let mut duplicates: Vec<DuplicateUpc> = Vec::new();
+    for row in rows {
+        let (upc, id) = row?;
+        match duplicates.last_mut() {
+            Some(last) if last.upc == upc => last.item_ids.push(id),
+            _ => duplicates.push(DuplicateUpc { upc, item_ids: vec![id] }),
+        }
+    }
+    Ok(duplicates)
+}
+
+/// Retrieve only the given columns of each item, in item_id order. Column names go into the
+/// SQL as is, so they must already have passed `validate_fields`.
+pub fn get_item_columns(conn: &Connection, columns: &[String], include_archived: bool) -> Result<Vec<Vec<rusqlite::types::Value>>> {
+    query_item_columns(conn, columns, &item_columns_sql(columns, include_archived, None))
+}
+
+/// `get_item_columns` for a random sample of at most `size` items, still in item_id order
+pub fn get_sample_item_columns(conn: &Connection, columns: &[String], include_archived: bool, size: usize) -> Result<Vec<Vec<rusqlite::types::Value>>> {
+    query_item_columns(conn, columns, &item_columns_sql(columns, include_archived, Some(size)))
+}
+
+fn query_item_columns(conn: &Connection, columns: &[String], sql: &str) -> Result<Vec<Vec<rusqlite::types::Value>>> {
+    let mut stmt = conn.prepare(sql)?;
     
-    let items = stmt.query_map([], |row| {
-        Ok(InventoryItem::new(
-            row.get(0)?,
-            row.get(1)?,
-            row.get(2)?,
-            row.get(3)?,
-            row.get(4)?,
-            row.get(5)?,
-            row.get(6)?,
-        ))
+    let rows = stmt.query_map([], |row| {
+        (0..columns.len()).map(|idx| row.get(idx)).collect::<Result<Vec<_>>>()
     })?
     .collect::<Result<Vec<_>>>()?;
     
+    Ok(rows)
+}
+
+fn item_columns_sql(columns: &[String], include_archived: bool, sample: Option<usize>) -> String {
+    let where_clause = if include_archived { "" } else { "WHERE status = 'active' " };
+    match sample {
+        // Sampled by ID, since the chosen columns needn't include it
+        Some(size) => format!(
+            "SELECT {} FROM items WHERE item_id IN (SELECT item_id FROM items {}ORDER BY RANDOM() LIMIT {}) ORDER BY item_id",
+            columns.join(", "), where_clause, size
+        ),
+        None => format!("SELECT {} FROM items {}ORDER BY item_id", columns.join(", "), where_clause),
+    }
+}
+
+/// Like `get_item_columns`, but hands each row to `f` as it's read instead of collecting them.
+/// Returns how many rows there were.
+pub fn for_each_item_columns(
+    conn: &Connection,
+    columns: &[String],
+    include_archived: bool,
+    mut f: impl FnMut(Vec<rusqlite::types::Value>) -> anyhow::Result<()>,
+) -> anyhow::Result<usize> {
+    let mut stmt = conn.prepare(&item_columns_sql(columns, include_archived, None))?;
+    let mut rows = stmt.query([])?;
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        f((0..columns.len()).map(|idx| row.get(idx)).collect::<Result<Vec<_>>>()?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Retrieve a random sample of at most `size` items, returned in item_id order.
+/// With a seed the sample is deterministic: items are ranked by a hash of their ID and the
+/// seed instead of `RANDOM()`, so the same seed picks the same items from the same data.
+pub fn get_sample_items(conn: &Connection, include_archived: bool, size: usize, seed: Option<u32>) -> Result<Vec<InventoryItem>> {
+    let where_clause = if include_archived { "" } else { "WHERE status = 'active' " };
+    // The hash scatters consecutive IDs, and squaring it (mod a prime below 2^31, so nothing
+    // overflows) keeps the ranking from being a mere rotation of the same order as the seed changes
+    let (hash, order) = match seed {
+        Some(_) => (", ((item_id + ?2) * 48271) % 2147483647 AS hash", "hash * hash % 2147483647, item_id"),
+        None => ("", "RANDOM()"),
+    };
+    let columns = item_select_columns("");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {columns} FROM (SELECT {columns}{} FROM items {}ORDER BY {} LIMIT ?1) ORDER BY item_id",
+        hash,
+        where_clause,
+        order
+    ))?;
+    
+    let limit = size as i64;
+    let items = match seed {
+        Some(seed) => stmt.query_map(params![limit, seed], map_item)?.collect::<Result<Vec<_>>>()?,
+        None => stmt.query_map(params![limit], map_item)?.collect::<Result<Vec<_>>>()?,
+    };
+    
     Ok(items)
 }
 
+/// Set an item's status without removing the row. Returns the number of rows updated,
+/// which is 0 when no item has the ID.
+pub fn set_item_status(conn: &Connection, id: i64, status: &str) -> anyhow::Result<usize> {
+    if !ITEM_STATUSES.contains(&status) {
+        anyhow::bail!("Invalid status '{}': expected one of {}", status, ITEM_STATUSES.join(", "));
+    }
+    Ok(conn.execute(
+        "UPDATE items SET status = ?, last_updated = ? WHERE item_id = ?",
+        params![status, now_timestamp(), id],
+    )?)
+}
+
 /// Count items in the table (for test validation)
 pub fn count_items(conn: &Connection) -> Result<i64> {
     let mut stmt = conn.prepare("SELECT COUNT(*) FROM items")?;
@@ -91,29 +293,237 @@ pub fn count_items(conn: &Connection) -> Result<i64> {
     Ok(count)
 }
 
+/// Item fields fetched by ID, with numeric columns kept as numbers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemRecord {
+    pub item_id: i64,
+    pub title: String,
+    pub price: f64,
+    pub quantity: i32,
+    pub category: String,
+    pub condition: String,
+    pub brand: Option<String>,
+    pub upc: Option<String>,
+    pub currency: String,
+}
+
+const ITEM_RECORD_COLUMNS: &str = "item_id, title, price, quantity, category, condition, brand, upc, currency";
+
+fn map_item_record(row: &rusqlite::Row) -> Result<ItemRecord> {
+    Ok(ItemRecord {
+        item_id: row.get(0)?,
+        title: row.get(1)?,
+        price: row.get(2)?,
+        quantity: row.get(3)?,
+        category: row.get(4)?,
+        condition: row.get(5)?,
+        brand: row.get(6)?,
+        upc: row.get(7)?,
+        currency: row.get(8)?,
+    })
+}
+
 /// Get an item by ID
-pub fn get_item_by_id(conn: &Connection, id: i64) -> Result<Option<std::collections::HashMap<String, String>>> {
-    let fields = ["item_id", "title", "price", "quantity", "category", "condition", "brand", "upc"];
-    let field_list = fields.join(", ");
-    let mut stmt = conn.prepare(&format!("SELECT {} FROM items WHERE item_id = ?", field_list))?;
-    let mut rows = stmt.query_map([id], |row| {
-        let mut item = std::collections::HashMap::new();
-        for (i, field) in fields.iter().enumerate() {
-            let value: String = match field {
-                &"price" => format!("{:.2}", row.get::<_, f64>(i)?),
-                &"quantity" => row.get::<_, i32>(i)?.to_string(),
-                &"item_id" => row.get::<_, i64>(i)?.to_string(),
-                _ => row.get::<_, Option<String>>(i)?.unwrap_or_default(),
-            };
-            item.insert(field.to_string(), value);
+pub fn get_item_by_id(conn: &Connection, id: i64) -> Result<Option<ItemRecord>> {
+    let mut stmt = conn.prepare_cached(&format!("SELECT {} FROM items WHERE item_id = ?", ITEM_RECORD_COLUMNS))?;
+    let mut rows = stmt.query_map([id], map_item_record)?;
+
+    rows.next().transpose()
+}
+
+/// Get the item with the given SKU, if any
+pub fn get_item_by_sku(conn: &Connection, sku: &str) -> Result<Option<ItemRecord>> {
+    // Cached, since imports check every row's SKU
+    let mut stmt = conn.prepare_cached(&format!("SELECT {} FROM items WHERE sku = ?", ITEM_RECORD_COLUMNS))?;
+    let mut rows = stmt.query_map([sku], map_item_record)?;
+
+    rows.next().transpose()
+}
+
+/// Every column of an item, for copying it
+#[derive(Debug, Clone, PartialEq)]
+pub struct FullItem {
+    pub item_id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub price: f64,
+    pub quantity: i32,
+    pub photos: Option<String>,
+    pub category: String,
+    pub condition: String,
+    pub brand: Option<String>,
+    pub upc: Option<String>,
+    pub item_specifics: Option<String>,
+    pub shipping_details: Option<String>,
+    pub size: Option<String>,
+    pub original_price: Option<f64>,
+    pub hashtags: Option<String>,
+    pub colorway: Option<String>,
+    pub release_date: Option<String>,
+    pub platform_status: Option<String>,
+    pub internal_notes: Option<String>,
+    pub last_updated: String,
+    pub status: String,
+    pub currency: String,
+    pub last_import_run: Option<String>,
+    pub sku: Option<String>,
+}
+
+impl FullItem {
+    /// The item's values ready to insert as a new row, which gets its own ID and timestamp,
+    /// belongs to no import run and has no SKU, since SKUs are unique
+    pub fn as_new_item(&self) -> NewItem<'_> {
+        NewItem {
+            title: &self.title,
+            description: self.description.as_deref(),
+            price: self.price,
+            quantity: self.quantity,
+            photos: self.photos.as_deref(),
+            category: &self.category,
+            condition: &self.condition,
+            brand: self.brand.as_deref(),
+            upc: self.upc.as_deref(),
+            item_specifics: self.item_specifics.as_deref(),
+            shipping_details: self.shipping_details.as_deref(),
+            size: self.size.as_deref(),
+            original_price: self.original_price,
+            hashtags: self.hashtags.as_deref(),
+            colorway: self.colorway.as_deref(),
+            release_date: self.release_date.as_deref(),
+            platform_status: self.platform_status.as_deref(),
+            internal_notes: self.internal_notes.as_deref(),
+            status: &self.status,
+            currency: Some(&self.currency),
+            import_run: None,
+            sku: None,
         }
-        Ok(item)
+    }
+}
+
+/// Get every column of an item by ID
+pub fn get_full_item(conn: &Connection, id: i64) -> Result<Option<FullItem>> {
+    let mut stmt = conn.prepare(
+        r#"SELECT item_id, title, description, price, quantity, photos, category, condition, brand, upc,
+            item_specifics, shipping_details, size, original_price, hashtags, colorway, release_date,
+            platform_status, internal_notes, last_updated, status, currency, last_import_run, sku
+        FROM items WHERE item_id = ?"#
+    )?;
+    let mut rows = stmt.query_map([id], |row| {
+        Ok(FullItem {
+            item_id: row.get(0)?,
+            title: row.get(1)?,
+            description: row.get(2)?,
+            price: row.get(3)?,
+            quantity: row.get(4)?,
+            photos: row.get(5)?,
+            category: row.get(6)?,
+            condition: row.get(7)?,
+            brand: row.get(8)?,
+            upc: row.get(9)?,
+            item_specifics: row.get(10)?,
+            shipping_details: row.get(11)?,
+            size: row.get(12)?,
+            original_price: row.get(13)?,
+            hashtags: row.get(14)?,
+            colorway: row.get(15)?,
+            release_date: row.get(16)?,
+            platform_status: row.get(17)?,
+            internal_notes: row.get(18)?,
+            last_updated: row.get(19)?,
+            status: row.get(20)?,
+            currency: row.get(21)?,
+            last_import_run: row.get(22)?,
+            sku: row.get(23)?,
+        })
     })?;
 
-    Ok(rows.next().transpose()?)
+    rows.next().transpose()
+}
+
+/// IDs of the items carrying a UPC, lowest first
+pub fn find_item_ids_by_upc(conn: &Connection, upc: &str) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT item_id FROM items WHERE upc = ? ORDER BY item_id")?;
+    let ids = stmt.query_map([upc], |row| row.get(0))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ids)
+}
+
+/// Every brand in the inventory, spelled as stored, alphabetically
+pub fn distinct_brands(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT brand FROM items WHERE brand IS NOT NULL AND brand != '' ORDER BY brand")?;
+    let brands = stmt.query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>>>()?;
+    Ok(brands)
+}
+
+/// Every category in the inventory, spelled as stored, alphabetically
+pub fn distinct_categories(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT category FROM items WHERE category != '' ORDER BY category")?;
+    let categories = stmt.query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>>>()?;
+    Ok(categories)
+}
+
+/// A row of the categories table with the number of items filed under it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Category {
+    pub id: i64,
+    pub name: String,
+    pub items: i64,
+}
+
+/// Every category with its item count, archived items included, alphabetically. Categories
+/// left without items (e.g. by `undo`) are listed with a count of 0.
+pub fn list_categories(conn: &Connection) -> Result<Vec<Category>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.category_id, c.name, COUNT(i.item_id) FROM categories AS c \
+         LEFT JOIN items AS i ON i.category_id = c.category_id \
+         GROUP BY c.category_id ORDER BY c.name"
+    )?;
+    let categories = stmt.query_map([], |row| Ok(Category { id: row.get(0)?, name: row.get(1)?, items: row.get(2)? }))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(categories)
+}
+
+/// A category name as items store it: spelled like the existing category it matches ignoring
+/// case, or as given for a new one
+pub fn canonical_category(conn: &Connection, name: &str) -> Result<String> {
+    // A bare items table, created without running the migrations, has no categories yet
+    let has_categories: bool = conn.prepare_cached(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'categories'"
+    )?.query_row([], |row| row.get(0))?;
+    if !has_categories {
+        return Ok(name.to_string());
+    }
+    let existing = conn.prepare_cached("SELECT name FROM categories WHERE name = ?")?
+        .query_row([name], |row| row.get(0))
+        .optional()?;
+    Ok(existing.unwrap_or_else(|| name.to_string()))
+}
+
+/// The category with this name, ignoring case
+pub fn find_category(conn: &Connection, name: &str) -> Result<Option<Category>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.category_id, c.name, (SELECT COUNT(*) FROM items WHERE category_id = c.category_id) \
+         FROM categories AS c WHERE c.name = ?"
+    )?;
+    let mut rows = stmt.query([name.trim()])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(Category { id: row.get(0)?, name: row.get(1)?, items: row.get(2)? })),
+        None => Ok(None),
+    }
+}
+
+/// IDs of the items filed under a category, in ID order
+pub fn items_in_category(conn: &Connection, category_id: i64) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT item_id FROM items WHERE category_id = ? ORDER BY item_id")?;
+    let ids = stmt.query_map([category_id], |row| row.get(0))?
+        .collect::<Result<Vec<i64>>>()?;
+    Ok(ids)
 }
 
-/// Update an item with partial updates (only update provided fields)
+/// Update an item with partial updates (only update provided fields), retrying if the
+/// database is locked
 pub fn update_item(
     conn: &Connection,
     id: i64,
@@ -124,10 +534,34 @@ pub fn update_item(
     condition: Option<&str>,
     brand: Option<&str>,
     upc: Option<&str>,
-) -> Result<()> {
+) -> anyhow::Result<()> {
+    update_item_columns(conn, id, &ItemChanges { title, price, quantity, category, condition, brand, upc, ..Default::default() })
+}
+
+/// Columns to change on an existing item; a `None` column keeps its value
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ItemChanges<'a> {
+    pub title: Option<&'a str>,
+    pub description: Option<&'a str>,
+    pub price: Option<f64>,
+    pub quantity: Option<i32>,
+    pub category: Option<&'a str>,
+    pub condition: Option<&'a str>,
+    pub brand: Option<&'a str>,
+    pub upc: Option<&'a str>,
+    pub release_date: Option<&'a str>,
+    pub currency: Option<&'a str>,
+    pub sku: Option<&'a str>,
+    /// The import run writing the changes, recorded as the item's `last_import_run`
+    pub import_run: Option<&'a str>,
+}
+
+/// Write `changes` to an item, retrying if the database is locked
+pub fn update_item_columns(conn: &Connection, id: i64, changes: &ItemChanges) -> anyhow::Result<()> {
+    let ItemChanges { title, description, price, quantity, category, condition, brand, upc, release_date, currency, sku, import_run } = *changes;
     let mut updates = Vec::new();
     let mut params = vec![];
-    let last_updated = Utc::now().to_rfc3339();
+    let last_updated = now_timestamp();
 
     if let Some(title) = title {
         updates.push("title = ?".to_string());
@@ -143,7 +577,7 @@ pub fn update_item(
     }
     if let Some(category) = category {
         updates.push("category = ?".to_string());
-        params.push(category.to_string());
+        params.push(canonical_category(conn, category)?);
     }
     if let Some(condition) = condition {
         updates.push("condition = ?".to_string());
@@ -157,6 +591,26 @@ pub fn update_item(
         updates.push("upc = ?".to_string());
         params.push(upc.to_string());
     }
+    if let Some(description) = description {
+        updates.push("description = ?".to_string());
+        params.push(description.to_string());
+    }
+    if let Some(release_date) = release_date {
+        updates.push("release_date = ?".to_string());
+        params.push(release_date.to_string());
+    }
+    if let Some(currency) = currency {
+        updates.push("currency = ?".to_string());
+        params.push(currency.to_string());
+    }
+    if let Some(sku) = sku {
+        updates.push("sku = ?".to_string());
+        params.push(sku.to_string());
+    }
+    if let Some(import_run) = import_run {
+        updates.push("last_import_run = ?".to_string());
+        params.push(import_run.to_string());
+    }
     updates.push("last_updated = ?".to_string());
     params.push(last_updated);
 
@@ -166,9 +620,79 @@ pub fn update_item(
 
     let query = format!("UPDATE items SET {} WHERE item_id = ?", updates.join(", "));
     params.push(id.to_string());
-    let affected = conn.execute(&query, rusqlite::params_from_iter(params))?;
+    // Cached so batch updates with the same columns reuse one prepared statement
+    let affected = retry_on_busy(|| conn.prepare_cached(&query)?.execute(rusqlite::params_from_iter(&params)))?;
     if affected == 0 {
-        return Err(rusqlite::Error::ExecuteReturnedResults);
+        anyhow::bail!(not_found(format!("No item with ID {}", id)));
     }
     Ok(())
-} 
\ No newline at end of file
+} 
+/// A tag name as stored: trimmed and lowercased. Blank names aren't tags.
+pub fn normalize_tag(name: &str) -> Option<String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() { None } else { Some(name) }
+}
+
+/// Normalizes tag names, dropping blanks and repeats but keeping the first-seen order
+pub fn normalize_tags(names: &[String]) -> Vec<String> {
+    let mut tags = Vec::new();
+    for tag in names.iter().filter_map(|name| normalize_tag(name)) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Tag an item, creating tags that don't exist yet. Returns how many tags were newly added;
+/// ones the item already has are left alone.
+pub fn add_tags(conn: &Connection, item_id: i64, names: &[String]) -> Result<usize> {
+    let mut added = 0;
+    for tag in normalize_tags(names) {
+        conn.execute("INSERT OR IGNORE INTO tags (name) VALUES (?)", [&tag])?;
+        added += conn.execute(
+            "INSERT OR IGNORE INTO item_tags (item_id, tag_id) SELECT ?, tag_id FROM tags WHERE name = ?",
+            params![item_id, tag],
+        )?;
+    }
+    Ok(added)
+}
+
+/// Remove tags from an item. Returns how many it actually had. A tag no item has any more
+/// is dropped.
+pub fn remove_tags(conn: &Connection, item_id: i64, names: &[String]) -> Result<usize> {
+    let mut removed = 0;
+    for tag in normalize_tags(names) {
+        removed += conn.execute(
+            "DELETE FROM item_tags WHERE item_id = ? AND tag_id = (SELECT tag_id FROM tags WHERE name = ?)",
+            params![item_id, tag],
+        )?;
+        if items_with_tag(conn, &tag)?.is_empty() {
+            conn.execute("DELETE FROM tags WHERE name = ?", [&tag])?;
+        }
+    }
+    Ok(removed)
+}
+
+/// An item's tags, alphabetically
+pub fn get_item_tags(conn: &Connection, item_id: i64) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.name FROM item_tags it JOIN tags t ON t.tag_id = it.tag_id WHERE it.item_id = ? ORDER BY t.name"
+    )?;
+    let tags = stmt.query_map([item_id], |row| row.get(0))?
+        .collect::<Result<Vec<String>>>()?;
+    Ok(tags)
+}
+
+/// IDs of the items with a tag, in item_id order
+pub fn items_with_tag(conn: &Connection, name: &str) -> Result<Vec<i64>> {
+    let Some(tag) = normalize_tag(name) else {
+        return Ok(Vec::new());
+    };
+    let mut stmt = conn.prepare(
+        "SELECT it.item_id FROM item_tags it JOIN tags t ON t.tag_id = it.tag_id WHERE t.name = ? ORDER BY it.item_id"
+    )?;
+    let ids = stmt.query_map([tag], |row| row.get(0))?
+        .collect::<Result<Vec<i64>>>()?;
+    Ok(ids)
+}
@@ -0,0 +1,65 @@
+// Retrying writes that hit a lock
+//
+// `busy_timeout` makes SQLite itself wait for another connection's write lock, but some lock
+// conflicts return SQLITE_BUSY straight away (two deferred transactions both upgrading to write,
+// say). `retry_on_busy` runs a write again after a short, doubling pause instead of failing it.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How long a connection waits on another's write lock before returning SQLITE_BUSY
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Retries after the first attempt when given no `busy_retries` in the config file
+pub const DEFAULT_BUSY_RETRIES: u32 = 5;
+
+/// Pause before the first retry; each further retry waits twice as long as the one before
+pub const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+
+static BUSY_RETRIES: AtomicU32 = AtomicU32::new(DEFAULT_BUSY_RETRIES);
+
+/// Set from the config file at startup; `retry_on_busy` reads it
+pub fn set_busy_retries(retries: u32) {
+    BUSY_RETRIES.store(retries, Ordering::Relaxed);
+}
+
+/// How many times a busy write is tried again before its error is returned
+pub fn busy_retries() -> u32 {
+    BUSY_RETRIES.load(Ordering::Relaxed)
+}
+
+/// Errors that can say whether SQLite gave up on a lock
+pub trait BusyError {
+    fn is_busy(&self) -> bool;
+}
+
+impl BusyError for rusqlite::Error {
+    fn is_busy(&self) -> bool {
+        self.sqlite_error_code() == Some(rusqlite::ErrorCode::DatabaseBusy)
+    }
+}
+
+impl BusyError for anyhow::Error {
+    fn is_busy(&self) -> bool {
+        self.chain()
+            .any(|cause| cause.downcast_ref::<rusqlite::Error>().is_some_and(BusyError::is_busy))
+    }
+}
+
+/// Runs `write`, running it again while it fails with SQLITE_BUSY, up to `busy_retries()` more
+/// times with exponential backoff. Any other error, or the last busy one, is returned as is.
+/// `write` must be safe to repeat, so it should be a whole transaction or a single statement.
+pub fn retry_on_busy<T, E: BusyError>(mut write: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    let mut backoff = INITIAL_BACKOFF;
+    for _ in 0..busy_retries() {
+        match write() {
+            Err(err) if err.is_busy() => {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+    write()
+}
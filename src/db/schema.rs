@@ -6,6 +6,7 @@ use std::path::PathBuf;
 use dirs::home_dir;
 use std::fs;
 use crate::config::optimization::optimize_database;
+use crate::db::migrate::run_migrations;
 
 pub const DB_FILENAME: &str = "inventory.db";
 
@@ -20,7 +21,19 @@ pub fn default_db_path() -> PathBuf {
     path
 }
 
-/// SQL for creating the items table with all constraints
+/// Environment variable naming the database file when `--db-path` isn't given
+pub const DB_PATH_ENV: &str = "INVENTORY_DB_PATH";
+
+/// Picks the database file: the `--db-path` flag, then `INVENTORY_DB_PATH`, then the config
+/// file's `db_path`, then `default_db_path()`
+pub fn resolve_db_path(flag: Option<PathBuf>, configured: Option<PathBuf>) -> PathBuf {
+    flag.or_else(|| std::env::var_os(DB_PATH_ENV).filter(|path| !path.is_empty()).map(PathBuf::from))
+        .or(configured)
+        .unwrap_or_else(default_db_path)
+}
+
+/// SQL for creating the items table with all constraints. The `category_id` column comes from
+/// the categories migration instead, so a table made from this alone doesn't need that table.
 pub const CREATE_ITEMS_TABLE_SQL: &str = r#"
 CREATE TABLE IF NOT EXISTS items (
     item_id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -43,10 +56,293 @@ CREATE TABLE IF NOT EXISTS items (
     platform_status TEXT,
     internal_notes TEXT,
     last_updated TEXT NOT NULL,
-    status TEXT NOT NULL CHECK(status IN ('active', 'sold', 'draft'))
+    status TEXT NOT NULL CHECK(status IN ('active', 'sold', 'draft')),
+    currency TEXT NOT NULL DEFAULT 'USD' CHECK(length(currency) = 3),
+    last_import_run TEXT,
+    sku TEXT UNIQUE
+);
+"#;
+
+/// SQL for the history of mutating commands, holding what `undo` needs to reverse each one:
+/// a JSON array of full item rows, taken before an update or delete and after an add or import.
+/// Operations an import logged carry its run id, so `rollback-import` can find them.
+pub const CREATE_OPERATIONS_LOG_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS operations_log (
+    operation_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    operation TEXT NOT NULL CHECK(operation IN ('add', 'update', 'delete', 'import')),
+    items TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    undone_at TEXT,
+    import_run TEXT
+);
+"#;
+
+/// SQL for tags and the many-to-many link between them and items. Tag names are stored
+/// normalized (see `queries::normalize_tag`); an item's links go when the item is deleted.
+pub const CREATE_TAGS_TABLES_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS tags (
+    tag_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS item_tags (
+    item_id INTEGER NOT NULL REFERENCES items(item_id),
+    tag_id INTEGER NOT NULL REFERENCES tags(tag_id),
+    PRIMARY KEY (item_id, tag_id)
+);
+CREATE INDEX IF NOT EXISTS idx_item_tags_tag ON item_tags(tag_id);
+CREATE TRIGGER IF NOT EXISTS items_tags_delete AFTER DELETE ON items BEGIN
+    DELETE FROM item_tags WHERE item_id = old.item_id;
+END;
+"#;
+
+/// SQL for the canonical category names and the triggers pointing each item's `category_id`
+/// at the category named by its `category`, which they create if it's new. Names are unique
+/// ignoring case, so "electronics" joins an existing "Electronics" (`insert_item` and
+/// `update_item` also store the existing spelling). Items with a blank category have none.
+pub const CREATE_CATEGORIES_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS categories (
+    category_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE
+);
+CREATE INDEX IF NOT EXISTS idx_items_category_id ON items(category_id);
+CREATE TRIGGER IF NOT EXISTS items_category_insert AFTER INSERT ON items
+WHEN new.category != ''
+BEGIN
+    INSERT OR IGNORE INTO categories (name) VALUES (new.category);
+    UPDATE items SET category_id = (SELECT category_id FROM categories WHERE name = new.category)
+    WHERE item_id = new.item_id;
+END;
+CREATE TRIGGER IF NOT EXISTS items_category_update AFTER UPDATE OF category ON items
+BEGIN
+    INSERT OR IGNORE INTO categories (name) SELECT new.category WHERE new.category != '';
+    UPDATE items SET category_id = (SELECT category_id FROM categories WHERE name = new.category)
+    WHERE item_id = new.item_id;
+END;
+"#;
+
+/// SQL for the per-item change log read by the history command. Triggers fill it on every
+/// insert and update (see `item_history_triggers_sql`), so `insert_item`, `update_item` and the
+/// bulk commands are all recorded. Entries outlive their item, so deleted items keep a history.
+pub const CREATE_ITEM_HISTORY_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS item_history (
+    history_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL,
+    action TEXT NOT NULL CHECK(action IN ('insert', 'update')),
+    changes TEXT NOT NULL,
+    changed_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_item_history_item ON item_history(item_id, history_id);
+"#;
+
+/// The item columns `item_history` tracks: all but the ID, `category_id`, which follows
+/// `category`, and `last_updated`, which moves with every change anyway. A migration adding a column should drop and recreate the history
+/// triggers with it included.
+pub const HISTORY_FIELDS: [&str; 22] = [
+    "title", "description", "price", "quantity", "photos", "category", "condition", "brand", "upc",
+    "item_specifics", "shipping_details", "size", "original_price", "hashtags", "colorway",
+    "release_date", "platform_status", "internal_notes", "status", "currency", "last_import_run", "sku",
+];
+
+/// SQL for the triggers filling `item_history`. An entry's `changes` maps each field that
+/// changed to `[old, new]`; an insert lists the fields it set, with null old values. Updates
+/// that only touch `last_updated` aren't recorded.
+pub fn item_history_triggers_sql() -> String {
+    let changed = |old: &str| HISTORY_FIELDS.iter()
+        .map(|field| format!(
+            "SELECT '{field}' AS field, {old} AS old_value, new.{field} AS new_value WHERE {old} IS NOT new.{field}",
+            field = field,
+            old = old.replace("{}", field),
+        ))
+        .collect::<Vec<_>>()
+        .join("\n        UNION ALL ");
+    let any_change = HISTORY_FIELDS.iter()
+        .map(|field| format!("old.{0} IS NOT new.{0}", field))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    let timestamp = "strftime('%Y-%m-%dT%H:%M:%f000+00:00', 'now')";
+    format!(
+        r#"
+CREATE TRIGGER IF NOT EXISTS items_history_insert AFTER INSERT ON items BEGIN
+    INSERT INTO item_history (item_id, action, changes, changed_at)
+    SELECT new.item_id, 'insert', json_group_object(field, json_array(old_value, new_value)), {timestamp}
+    FROM (
+        {inserted}
+    );
+END;
+CREATE TRIGGER IF NOT EXISTS items_history_update AFTER UPDATE ON items
+WHEN {any_change}
+BEGIN
+    INSERT INTO item_history (item_id, action, changes, changed_at)
+    SELECT new.item_id, 'update', json_group_object(field, json_array(old_value, new_value)), {timestamp}
+    FROM (
+        {updated}
+    );
+END;
+"#,
+        timestamp = timestamp,
+        inserted = changed("NULL"),
+        any_change = any_change,
+        updated = changed("old.{}"),
+    )
+}
+
+/// SQL for the FTS5 index over item titles and descriptions, kept in sync by triggers
+pub const CREATE_SEARCH_INDEX_SQL: &str = r#"
+CREATE VIRTUAL TABLE IF NOT EXISTS items_fts USING fts5(
+    title,
+    description,
+    content='items',
+    content_rowid='item_id'
 );
+CREATE TRIGGER IF NOT EXISTS items_fts_insert AFTER INSERT ON items BEGIN
+    INSERT INTO items_fts(rowid, title, description) VALUES (new.item_id, new.title, new.description);
+END;
+CREATE TRIGGER IF NOT EXISTS items_fts_delete AFTER DELETE ON items BEGIN
+    INSERT INTO items_fts(items_fts, rowid, title, description) VALUES ('delete', old.item_id, old.title, old.description);
+END;
+CREATE TRIGGER IF NOT EXISTS items_fts_update AFTER UPDATE ON items BEGIN
+    INSERT INTO items_fts(items_fts, rowid, title, description) VALUES ('delete', old.item_id, old.title, old.description);
+    INSERT INTO items_fts(rowid, title, description) VALUES (new.item_id, new.title, new.description);
+END;
 "#;
 
+/// SQL for triggers that rewrite SQLite-style `last_updated` values (e.g. from `datetime('now')`)
+/// into the canonical format from `db::timestamp` so every stored timestamp compares correctly
+pub const CREATE_TIMESTAMP_TRIGGERS_SQL: &str = r#"
+CREATE TRIGGER IF NOT EXISTS items_last_updated_insert AFTER INSERT ON items
+WHEN new.last_updated NOT LIKE '____-__-__T%'
+BEGIN
+    UPDATE items
+    SET last_updated = COALESCE(strftime('%Y-%m-%dT%H:%M:%f000+00:00', new.last_updated), new.last_updated)
+    WHERE item_id = new.item_id;
+END;
+CREATE TRIGGER IF NOT EXISTS items_last_updated_update AFTER UPDATE OF last_updated ON items
+WHEN new.last_updated NOT LIKE '____-__-__T%'
+BEGIN
+    UPDATE items
+    SET last_updated = COALESCE(strftime('%Y-%m-%dT%H:%M:%f000+00:00', new.last_updated), new.last_updated)
+    WHERE item_id = new.item_id;
+END;
+"#;
+
+/// Returns true if the items table has the given column
+pub fn has_column(conn: &Connection, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('items')")?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(names.iter().any(|name| name == column))
+}
+
+fn add_column_if_missing(conn: &Connection, column: &str, definition: &str) -> Result<()> {
+    if !has_column(conn, column)? {
+        conn.execute(&format!("ALTER TABLE items ADD COLUMN {} {}", column, definition), [])?;
+    }
+    Ok(())
+}
+
+/// Adds the currency column to databases created before items carried one;
+/// existing rows get the default currency
+pub fn ensure_currency_column(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "currency", "TEXT NOT NULL DEFAULT 'USD' CHECK(length(currency) = 3)")
+}
+
+/// Adds the column recording which import run created an item; older rows have none
+pub fn ensure_import_run_column(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "last_import_run", "TEXT")
+}
+
+/// Adds the SKU column to databases created before items carried one. SQLite can't add a
+/// UNIQUE column, so uniqueness comes from an index instead; existing rows have no SKU.
+pub fn ensure_sku_column(conn: &Connection) -> Result<()> {
+    if !has_column(conn, "sku")? {
+        conn.execute_batch(
+            "ALTER TABLE items ADD COLUMN sku TEXT;
+             CREATE UNIQUE INDEX IF NOT EXISTS idx_items_sku ON items(sku);"
+        )?;
+    }
+    Ok(())
+}
+
+/// Adds the column tying logged operations to the import run behind them; operations logged
+/// before it have none. Migrations run before the log is created, so this creates it if needed.
+pub fn ensure_operations_import_run_column(conn: &Connection) -> Result<()> {
+    conn.execute(CREATE_OPERATIONS_LOG_TABLE_SQL, [])?;
+    let exists: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('operations_log') WHERE name = 'import_run'",
+        [],
+        |row| row.get(0),
+    )?;
+    if exists == 0 {
+        conn.execute("ALTER TABLE operations_log ADD COLUMN import_run TEXT", [])?;
+    }
+    Ok(())
+}
+
+/// Creates the tags tables in databases created before items could be tagged
+pub fn ensure_tags_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(CREATE_TAGS_TABLES_SQL)
+}
+
+/// Starts recording item changes in databases created before the history command
+pub fn ensure_item_history(conn: &Connection) -> Result<()> {
+    conn.execute_batch(CREATE_ITEM_HISTORY_TABLE_SQL)?;
+    conn.execute_batch(&item_history_triggers_sql())
+}
+
+/// Adds `category_id` and the categories table it references; on databases created before
+/// them, also moves the existing categories onto the table. Each set of spellings that differ only
+/// in case becomes one category named after the most common of them, and the items are
+/// rewritten to that spelling.
+pub fn ensure_categories_table(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "category_id", "INTEGER REFERENCES categories(category_id)")?;
+    conn.execute_batch(CREATE_CATEGORIES_TABLE_SQL)?;
+    if !has_column(conn, "category")? {
+        return Ok(());
+    }
+    // The first spelling inserted claims the name; later ones differing in case are ignored
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO categories (name)
+             SELECT category FROM items WHERE category != ''
+             GROUP BY category ORDER BY COUNT(*) DESC, category;
+         UPDATE items
+         SET category = (SELECT name FROM categories WHERE name = items.category),
+             category_id = (SELECT category_id FROM categories WHERE name = items.category)
+         WHERE category != '' AND category_id IS NULL;"
+    )
+}
+
+/// Returns true if the linked SQLite library was built with FTS5
+pub fn fts5_available(conn: &Connection) -> bool {
+    conn.query_row("SELECT sqlite_compileoption_used('ENABLE_FTS5')", [], |row| row.get::<_, bool>(0))
+        .unwrap_or(false)
+}
+
+/// Returns true if the full-text search index exists in this database
+pub fn has_search_index(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'items_fts'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Creates the full-text search index if FTS5 is available, backfilling rows that predate it.
+/// Returns whether the index is usable.
+pub fn create_search_index(conn: &Connection) -> Result<bool> {
+    if !fts5_available(conn) {
+        return Ok(false);
+    }
+    
+    let existed = has_search_index(conn)?;
+    conn.execute_batch(CREATE_SEARCH_INDEX_SQL)?;
+    if !existed {
+        conn.execute("INSERT INTO items_fts(items_fts) VALUES ('rebuild')", [])?;
+    }
+    
+    Ok(true)
+}
+
 /// Initializes the database and creates the items table if it doesn't exist
 pub fn initialize_database(db_path: Option<&PathBuf>) -> Result<Connection> {
     let conn = match db_path {
@@ -56,13 +352,28 @@ pub fn initialize_database(db_path: Option<&PathBuf>) -> Result<Connection> {
                     fs::create_dir_all(parent).expect("Failed to create database directory");
                 }
             }
+            log::debug!("Opening database {}", path.display());
             Connection::open(path)?
         },
-        None => Connection::open_in_memory()?,
+        None => {
+            log::debug!("Opening in-memory database");
+            Connection::open_in_memory()?
+        }
     };
     
     // Create the items table
     conn.execute(CREATE_ITEMS_TABLE_SQL, [])?;
+    // Existing tables get the changes made since they were created
+    run_migrations(&conn)?;
+    conn.execute(CREATE_OPERATIONS_LOG_TABLE_SQL, [])?;
+    
+    // Keep last_updated in a single comparable format regardless of who writes it
+    conn.execute_batch(CREATE_TIMESTAMP_TRIGGERS_SQL)?;
+    
+    // Create the full-text search index; search falls back to LIKE without it
+    if let Err(e) = create_search_index(&conn) {
+        eprintln!("Warning: Failed to create search index: {}", e);
+    }
     
     // Apply database optimizations and create indexes
     if let Err(e) = optimize_database(&conn) {
@@ -0,0 +1,53 @@
+// Timestamp helpers shared by every path that writes a timestamp column
+//
+// All stored timestamps use RFC3339 in UTC with fixed microsecond precision, e.g.
+// "2025-06-27T14:59:00.000000+00:00", so they sort and compare correctly as plain text.
+// The triggers in `schema::CREATE_TIMESTAMP_TRIGGERS_SQL` produce the same format in SQL.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+
+/// Format a UTC datetime in the canonical stored format
+pub fn format_timestamp(dt: DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Micros, false)
+}
+
+/// The current time in the canonical stored format
+pub fn now_timestamp() -> String {
+    format_timestamp(Utc::now())
+}
+
+/// Parse a timestamp in any format we have seen in the wild and return it in the canonical format.
+/// Accepts RFC3339, SQLite's `YYYY-MM-DD HH:MM:SS[.fff]` (UTC) and bare `YYYY-MM-DD` dates.
+pub fn normalize_timestamp(value: &str) -> Option<String> {
+    let value = value.trim();
+    
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(format_timestamp(dt.with_timezone(&Utc)));
+    }
+    
+    for fmt in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(value, fmt) {
+            return Some(format_timestamp(naive.and_utc()));
+        }
+    }
+    
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|naive| format_timestamp(naive.and_utc()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_format_is_fixed_width() {
+        let a = normalize_timestamp("2024-01-02T03:04:05Z").unwrap();
+        let b = normalize_timestamp("2024-01-02T03:04:05.5Z").unwrap();
+        assert_eq!(a, "2024-01-02T03:04:05.000000+00:00");
+        assert_eq!(b, "2024-01-02T03:04:05.500000+00:00");
+        assert_eq!(a.len(), now_timestamp().len());
+        assert!(a < b);
+    }
+}
@@ -1,2 +1,108 @@
 // Error handling implementation
-// TODO: Implement custom error types 
\ No newline at end of file
+// Process exit codes, so scripts can tell a rejected item from a missing file or a database fault,
+// and the text or JSON report of an error on stderr
+
+use serde_json::json;
+use std::fmt;
+
+/// Exit code of each kind of failure. Commands report errors as `anyhow::Error`; `main` picks
+/// the code from what the error chain contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success = 0,
+    /// Anything not covered below
+    Failure = 1,
+    /// An item or file broke the validation rules, or the command line was malformed
+    Validation = 2,
+    /// An item, file, plugin or import run that was asked for doesn't exist
+    NotFound = 3,
+    /// Reading or writing a file failed
+    Io = 4,
+    /// SQLite reported an error
+    Database = 5,
+}
+
+impl ExitCode {
+    /// The code for an error: the first entry of its chain that identifies one, reading from
+    /// the outermost context inwards
+    pub fn of(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<CodedError>() {
+                return err.code;
+            }
+            if cause.is::<rusqlite::Error>() {
+                return ExitCode::Database;
+            }
+            if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+                return match err.kind() {
+                    std::io::ErrorKind::NotFound => ExitCode::NotFound,
+                    _ => ExitCode::Io,
+                };
+            }
+        }
+        ExitCode::Failure
+    }
+    
+    /// The kind of failure as `--error-format json` names it
+    pub fn kind(self) -> &'static str {
+        match self {
+            ExitCode::Success => "none",
+            ExitCode::Failure => "other",
+            ExitCode::Validation => "validation",
+            ExitCode::NotFound => "not_found",
+            ExitCode::Io => "io",
+            ExitCode::Database => "database",
+        }
+    }
+}
+
+impl From<ExitCode> for i32 {
+    fn from(code: ExitCode) -> Self {
+        code as i32
+    }
+}
+
+/// An error message tagged with the exit code it ends the process with
+#[derive(Debug)]
+pub struct CodedError {
+    pub code: ExitCode,
+    message: String,
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+/// An error for input that failed validation; it exits with `ExitCode::Validation`
+pub fn validation_failed(message: impl Into<String>) -> CodedError {
+    CodedError { code: ExitCode::Validation, message: message.into() }
+}
+
+/// An error for something asked for that doesn't exist; it exits with `ExitCode::NotFound`
+pub fn not_found(message: impl Into<String>) -> CodedError {
+    CodedError { code: ExitCode::NotFound, message: message.into() }
+}
+
+/// How `main` prints the error a command failed with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// `Error: ` and the message, with its causes on the lines below
+    #[default]
+    Text,
+    /// One line of `{"error": {"kind": ..., "message": ...}}`, the causes joined into the message
+    Json,
+}
+
+/// The report of an error for stderr, without a trailing newline
+pub fn format_error(err: &anyhow::Error, format: ErrorFormat) -> String {
+    match format {
+        ErrorFormat::Text => format!("Error: {:?}", err),
+        ErrorFormat::Json => json!({
+            "error": { "kind": ExitCode::of(err).kind(), "message": format!("{:#}", err) }
+        }).to_string(),
+    }
+}
@@ -1,2 +1,2 @@
-// Error module - will contain custom error types and handling
-pub mod error; 
\ No newline at end of file
+// Error module - exit codes and the errors that carry them
+pub mod error;
@@ -6,5 +6,6 @@ pub mod config;
 pub mod logging;
 pub mod output;
 pub mod error;
+pub mod bulk;
 pub use commands::validate;
 pub use config::optimization; 
\ No newline at end of file
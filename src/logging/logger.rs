@@ -1,2 +1,87 @@
 // Logging implementation
-// TODO: Implement log4rs configuration 
\ No newline at end of file
+// Console (stderr) and rolling file logging with log4rs, filtered by --log-level
+
+use anyhow::{Context, Result};
+use dirs::home_dir;
+use log::LevelFilter;
+use log4rs::append::console::{ConsoleAppender, Target};
+use log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
+use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
+use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
+use log4rs::append::rolling_file::RollingFileAppender;
+use log4rs::config::{Appender, Config, Root};
+use log4rs::encode::pattern::PatternEncoder;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+pub const LOG_FILENAME: &str = "openinv.log";
+
+/// Size at which the log file is rolled over
+const LOG_FILE_MAX_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rolled-over log files kept besides the current one
+const LOG_FILE_ARCHIVES: u32 = 3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    /// Only problems are logged unless a more verbose level is asked for
+    #[default]
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Returns the directory log files are written to (~/.inventory/logs)
+pub fn default_log_dir() -> PathBuf {
+    let mut path = home_dir().expect("Could not determine home directory");
+    path.push(".inventory");
+    path.push("logs");
+    path
+}
+
+/// Sends log records at `level` and above to stderr, keeping stdout for command output,
+/// and to ~/.inventory/logs/openinv.log, which rolls over at 5 MB
+pub fn init(level: LogLevel) -> Result<()> {
+    let log_dir = default_log_dir();
+    std::fs::create_dir_all(&log_dir)
+        .with_context(|| format!("Failed to create log directory: {}", log_dir.display()))?;
+
+    let console = ConsoleAppender::builder()
+        .target(Target::Stderr)
+        .encoder(Box::new(PatternEncoder::new("[{l}] {m}{n}")))
+        .build();
+
+    let archive_pattern = log_dir.join("openinv.{}.log");
+    let roller = FixedWindowRoller::builder()
+        .build(&archive_pattern.to_string_lossy(), LOG_FILE_ARCHIVES)
+        .map_err(|e| anyhow::anyhow!("Invalid log archive pattern: {}", e))?;
+    let policy = CompoundPolicy::new(Box::new(SizeTrigger::new(LOG_FILE_MAX_BYTES)), Box::new(roller));
+    let file = RollingFileAppender::builder()
+        .encoder(Box::new(PatternEncoder::new("{d(%Y-%m-%dT%H:%M:%S%.3f%:z)} {l} {t} - {m}{n}")))
+        .build(log_dir.join(LOG_FILENAME), Box::new(policy))
+        .context("Failed to open log file")?;
+
+    let config = Config::builder()
+        .appender(Appender::builder().build("console", Box::new(console)))
+        .appender(Appender::builder().build("file", Box::new(file)))
+        .build(Root::builder().appender("console").appender("file").build(level.into()))
+        .context("Invalid logging configuration")?;
+
+    log4rs::init_config(config).context("Logging is already initialized")?;
+    Ok(())
+}
@@ -1,2 +1,4 @@
-// Logging module - will contain log4rs configuration and setup
-pub mod logger; 
\ No newline at end of file
+// Logging module - log4rs configuration and setup
+pub mod logger;
+
+pub use logger::{init, LogLevel};
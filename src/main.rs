@@ -1,6 +1,9 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use anyhow::Result;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use anyhow::{Context, Result};
 use std::sync::Arc;
+use std::path::PathBuf;
+use std::time::Duration;
 use chrono;
 
 mod commands;
@@ -13,20 +16,46 @@ mod output;
 mod error;
 
 use commands::{
-    add::handle_add_item,
+    add::{complete_item, handle_add_item, ItemDraft},
     update::{Update, execute as handle_update},
-    delete::handle_delete_item,
+    delete::handle_delete_items,
     list::{handle_list_inventory, OutputFormat},
-    import::handle_import,
-    filter::handle_filter,
+    import::{handle_import, handle_import_retry, ColumnMap, DuplicateKey, ImportOptions, InputEncoding, LongTitlePolicy, UpsertKey},
+    filter::{handle_filter, handle_count, FilterArgs},
     migrate::handle_migrate,
     help::handle_help,
     commands::handle_commands,
-    stats::handle_stats,
+    stats::{handle_stats, handle_duplicate_upcs, handle_low_stock, StatsArgs, StatsFilters, StatsSection, DEFAULT_LOW_STOCK_THRESHOLD},
     validate::handle_validate,
+    validate_item::{handle_validate_item, PlatformChoice},
+    search::handle_search,
+    fix_timestamps::handle_fix_timestamps,
+    backup::{handle_backup, handle_restore},
+    rollback_import::handle_rollback_import,
+    archive::handle_archive,
+    db_diff::handle_db_diff,
+    export::{handle_export, handle_export_split, ExportFile, Sample, SplitBy},
+    plugins::handle_plugins,
+    undo::handle_undo,
+    completions::handle_completions,
+    clone::handle_clone,
+    reprice::{handle_reprice, PriceAdjustment},
+    tag::handle_tag,
+    history::handle_history,
+    vacuum::handle_vacuum,
+    categories::{handle_categories, CategoryAction},
+    brand::{handle_brand, BrandAction},
+    cache::handle_cache,
 };
-use db::schema::initialize_database;
-use config::optimization::{PerformanceMonitor, QueryCache, optimize_database};
+use db::schema::{initialize_database, resolve_db_path};
+use db::retry::DEFAULT_BUSY_RETRIES;
+use error::error::{format_error, ErrorFormat, ExitCode};
+use config::config::Config;
+use config::currency::ExchangeRates;
+use config::optimization::{PerformanceMonitor, QueryCache, optimize_database, default_cache_path, resolve_perf_report_dir};
+use logging::LogLevel;
+use validation::{ItemFields, Platform};
+use output::format::{parse_csv_delimiter, ColorChoice, OutputOptions};
 
 #[derive(Parser)]
 #[command(name = "inventory")]
@@ -36,16 +65,79 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
     
-    /// Output format (json, csv, table)
-    #[arg(long, value_enum, default_value = "json")]
+    /// Default output format for commands given no --format (json, ndjson, yaml, csv, table, or
+    /// custom:NAME for a formatter plugin); overrides `format` in ~/.inventory/config.toml
+    #[arg(long)]
     format: Option<OutputFormat>,
     
     /// Enable verbose output with performance metrics
     #[arg(long)]
     verbose: bool,
     
-    /// Log level
-    #[arg(long, value_enum, default_value = "info")]
+    /// Print only summaries and errors: no per-row lines, details or progress bars
+    #[arg(long, short = 'q', conflicts_with = "verbose")]
+    quiet: bool,
+    
+    /// Use CRLF line endings in CSV output (RFC 4180)
+    #[arg(long)]
+    crlf: bool,
+    
+    /// Character between CSV fields, e.g. ';' or '\t' for tab-separated output
+    #[arg(long, default_value = ",", value_parser = parse_csv_delimiter)]
+    delimiter: char,
+    
+    /// Leave the header record out of CSV output
+    #[arg(long)]
+    no_header: bool,
+    
+    /// Start ndjson output with a versioned schema line, so imports can check compatibility
+    #[arg(long)]
+    ndjson_schema: bool,
+    
+    /// Write JSON output on a single line instead of pretty-printed (other formats are unaffected)
+    #[arg(long)]
+    json_compact: bool,
+    
+    /// Wrap long table cells onto several lines, at WIDTH characters [default: 40], instead of truncating them
+    #[arg(long, value_name = "WIDTH", num_args = 0..=1, require_equals = true, default_missing_value = "40",
+          value_parser = clap::value_parser!(u16).range(1..))]
+    wrap: Option<u16>,
+    
+    /// How a failed command reports its error on stderr
+    #[arg(long, value_enum, default_value = "text")]
+    error_format: ErrorFormat,
+    
+    /// Color table output: bold headers, low-stock rows in red
+    #[arg(long, value_enum, default_value = "auto")]
+    color: ColorChoice,
+    
+    /// Database file [env: INVENTORY_DB_PATH] [default: ~/.inventory/inventory.db]
+    #[arg(long)]
+    db_path: Option<PathBuf>,
+    
+    /// After the command, print its query timings and cache hits to stderr (as JSON with
+    /// --format json, otherwise a table); --quiet suppresses it
+    #[arg(long)]
+    profile: bool,
+    
+    /// Warn about queries taking longer than this many milliseconds
+    #[arg(long, value_name = "MS")]
+    threshold_ms: Option<u64>,
+    
+    /// Directory for the performance reports `filter` and `stats` write [default: ~/.inventory/logs]
+    #[arg(long, value_name = "DIR")]
+    perf_report: Option<PathBuf>,
+    
+    /// Don't write performance reports, whatever the config file says
+    #[arg(long, conflicts_with = "perf_report")]
+    no_perf_report: bool,
+    
+    /// Always query the database: `filter` and `stats` neither read nor store cached results
+    #[arg(long)]
+    no_cache: bool,
+    
+    /// Log level for stderr and ~/.inventory/logs/openinv.log [default: warn]
+    #[arg(long, value_enum)]
     log_level: Option<LogLevel>,
 }
 
@@ -53,25 +145,34 @@ struct Cli {
 enum Commands {
     /// Add a new item to inventory
     Add {
+        /// Prompt for every field not given as a flag, optional ones included. Without this, only
+        /// missing required fields are asked for, and only on a terminal
+        #[arg(long)]
+        interactive: bool,
+        
+        /// Reject a brand that isn't already in the inventory, instead of warning about near misses
+        #[arg(long)]
+        strict_brands: bool,
+        
         /// Item title
         #[arg(short, long)]
-        title: String,
+        title: Option<String>,
         
         /// Item price
         #[arg(short, long)]
-        price: f64,
+        price: Option<f64>,
         
         /// Item quantity
         #[arg(short, long)]
-        quantity: i32,
+        quantity: Option<i32>,
         
         /// Item category
         #[arg(short, long)]
-        category: String,
+        category: Option<String>,
         
         /// Item condition
         #[arg(short = 'n', long)]
-        condition: String,
+        condition: Option<String>,
         
         /// Item brand
         #[arg(short, long)]
@@ -101,42 +202,220 @@ enum Commands {
         #[arg(long)]
         colorway: Option<String>,
         
-        /// Release date
+        /// Release date, as YYYY-MM-DD or MM/DD/YYYY (stored as YYYY-MM-DD)
         #[arg(long)]
         release_date: Option<String>,
         
         /// Internal notes
         #[arg(long)]
         internal_notes: Option<String>,
+        
+        /// ISO 4217 currency of the price (defaults to USD)
+        #[arg(long)]
+        currency: Option<String>,
+        
+        /// Internal SKU; must not already belong to another item
+        #[arg(long)]
+        sku: Option<String>,
+        
+        /// Marketplace to validate the item for [default: ebay, or `platform` in the config file]
+        #[arg(long, value_enum)]
+        platform: Option<Platform>,
     },
     
     /// Update items from CSV file
     Update(Update),
     
+    /// Mark items as sold or draft without deleting them
+    Archive {
+        /// Item IDs to archive (comma-separated or repeated)
+        #[arg(short, long, required = true, value_delimiter = ',')]
+        id: Vec<i32>,
+        
+        /// New status (sold, draft, or active to unarchive)
+        #[arg(short, long, default_value = "sold")]
+        status: String,
+    },
+    
+    /// Add or remove an item's tags
+    Tag {
+        /// Item ID
+        #[arg(short, long)]
+        id: i32,
+        
+        /// Tags to add (comma-separated or repeated)
+        #[arg(short, long, value_delimiter = ',')]
+        add: Vec<String>,
+        
+        /// Tags to remove (comma-separated or repeated)
+        #[arg(short, long, value_delimiter = ',')]
+        remove: Vec<String>,
+    },
+    
+    /// Show an item's recorded changes, oldest first
+    History {
+        /// Item ID
+        #[arg(short, long)]
+        id: i32,
+    },
+    
+    /// List, rename or merge categories; renaming or merging refiles their items
+    Categories {
+        #[command(subcommand)]
+        action: Option<CategoryAction>,
+    },
+    
+    /// Rename or merge brands across all items, e.g. "Addidas" into "Adidas"
+    Brand {
+        #[command(subcommand)]
+        action: BrandAction,
+    },
+    
     /// Delete an item by ID
     Delete {
-        /// Item ID to delete
+        /// Item IDs to delete (comma-separated or repeated)
+        #[arg(short, long, required = true, value_delimiter = ',')]
+        id: Vec<i32>,
+        
+        /// Delete without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    
+    /// Copy an item under a new ID, e.g. for another size or colorway
+    #[command(visible_alias = "duplicate")]
+    Clone {
+        /// ID of the item to copy
         #[arg(short, long)]
         id: i32,
+        
+        /// Size of the copy
+        #[arg(long)]
+        size: Option<String>,
+        
+        /// Colorway of the copy
+        #[arg(long)]
+        colorway: Option<String>,
+        
+        /// Quantity of the copy
+        #[arg(short, long)]
+        quantity: Option<i32>,
+        
+        /// Price of the copy
+        #[arg(short, long)]
+        price: Option<f64>,
+        
+        /// UPC of the copy
+        #[arg(long)]
+        upc: Option<String>,
+    },
+    
+    /// Raise or lower the prices of active items by a percentage or a fixed amount
+    Reprice {
+        /// Only items in this category
+        #[arg(short, long)]
+        category: Option<String>,
+        
+        /// Only items of this brand
+        #[arg(short, long)]
+        brand: Option<String>,
+        
+        /// Change prices by this many percent (e.g. -10)
+        #[arg(long, allow_hyphen_values = true, required_unless_present = "add", conflicts_with = "add")]
+        percent: Option<f64>,
+        
+        /// Add this amount to prices (e.g. 5.00, or -2.50 to lower them)
+        #[arg(long, allow_hyphen_values = true)]
+        add: Option<f64>,
     },
     
     /// List inventory items
     List {
         /// Output format
-        #[arg(short, long, value_enum)]
+        #[arg(short, long)]
         format: Option<OutputFormat>,
+        
+        /// Also show sold and draft items
+        #[arg(long)]
+        include_archived: bool,
+        
+        /// Comma-separated columns to show, with the same names and shortcuts as filter --fields
+        #[arg(long)]
+        columns: Option<String>,
+        
+        /// Show this many items picked at random instead of all of them
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        sample: Option<u64>,
     },
     
     /// Import items from CSV file
     Import {
-        /// CSV file to import
-        #[arg(short, long)]
-        file: String,
+        /// CSV, JSON, ndjson or Excel file to import (detected by the .json, .ndjson, .jsonl or .xlsx
+        /// extension), or - to read CSV from stdin without prompting for corrections
+        #[arg(short, long, required_unless_present = "retry")]
+        file: Option<String>,
+        
+        /// Failed import file to reprocess, prompting for corrections on a terminal
+        #[arg(long, conflicts_with_all = ["file", "dry_run", "comment_char", "fuzzy_headers", "encoding", "map", "upsert", "skip_duplicates", "validate_only"])]
+        retry: Option<String>,
+        
+        /// Validate the file and report the summary without writing to the database
+        #[arg(long)]
+        dry_run: bool,
+        
+        /// Only check the file's rows, as `validate` does, without opening a transaction or
+        /// checking the database for SKUs already in use
+        #[arg(long, conflicts_with_all = ["dry_run", "upsert", "skip_duplicates"])]
+        validate_only: bool,
+        
+        /// Skip CSV lines starting with this character (e.g. '#')
+        #[arg(long)]
+        comment_char: Option<char>,
+        
+        /// Match CSV headers ignoring case, spaces and underscores (e.g. "Item ID" for item_id)
+        #[arg(long)]
+        fuzzy_headers: bool,
+        
+        /// Text encoding of the file, e.g. windows-1252 or iso-8859-1 [default: utf-8]
+        #[arg(long, value_name = "ENCODING")]
+        encoding: Option<InputEncoding>,
+        
+        /// Read CSV or Excel columns as schema fields, e.g. "Product Name=title,Cost=price"
+        #[arg(long, value_name = "COLUMN=FIELD,...")]
+        map: Option<ColumnMap>,
+        
+        /// Reject rows whose title is over eBay's limit (80 characters unless configured lower), or truncate the title with a warning
+        #[arg(long, value_enum, default_value = "reject")]
+        on_long_title: LongTitlePolicy,
+        
+        /// Update the existing item a row matches (by --upsert-key) instead of adding a duplicate
+        #[arg(long)]
+        upsert: bool,
+        
+        /// Column matching rows to existing items in --upsert mode
+        #[arg(long, value_enum, default_value = "item_id", requires = "upsert")]
+        upsert_key: UpsertKey,
+        
+        /// Skip rows matching an item already in the inventory or earlier in the file, and count them
+        #[arg(long, conflicts_with = "upsert")]
+        skip_duplicates: bool,
+        
+        /// Comma-separated fields that identify a duplicate in --skip-duplicates mode
+        /// (title, description, upc, category, condition, brand)
+        #[arg(long, default_value = "title,brand,upc", requires = "skip_duplicates")]
+        duplicate_key: DuplicateKey,
+        
+        /// Stop after this many data rows, counting the rest as skipped [default: no limit]
+        #[arg(long, value_name = "N", conflicts_with_all = ["retry", "validate_only"])]
+        max_rows: Option<usize>,
     },
     
     /// Filter inventory items
-    Filter {
-        /// Price range (e.g., 10-50)
+    Filter(FilterArgs),
+    
+    /// Count the items matching the given filters
+    Count {
+        /// Price range (e.g., 10-50, >50, <=100)
         #[arg(short, long)]
         price: Option<String>,
         
@@ -152,12 +431,12 @@ enum Commands {
         #[arg(short, long)]
         brand: Option<String>,
         
-        /// Fields to display
-        #[arg(short = 'l', long)]
-        fields: Option<String>,
+        /// Also count sold and draft items
+        #[arg(long)]
+        include_archived: bool,
         
-        /// Output format
-        #[arg(short, long, value_enum)]
+        /// Output format (json prints {"count": N}; otherwise a bare integer)
+        #[arg(short, long)]
         format: Option<OutputFormat>,
     },
     
@@ -173,69 +452,377 @@ enum Commands {
     /// Show inventory statistics
     Stats {
         /// Output format
-        #[arg(short, long, value_enum)]
+        #[arg(short, long)]
         format: Option<OutputFormat>,
+        
+        /// Table to write as CSV: the summary figures or one breakdown; implies --format csv
+        /// [default: summary]
+        #[arg(long, value_enum)]
+        section: Option<StatsSection>,
+        
+        /// TOML exchange rate table for converting values into one currency
+        #[arg(long)]
+        rates: Option<PathBuf>,
+        
+        /// Only include items in this category
+        #[arg(short, long)]
+        category: Option<String>,
+        
+        /// Only include items in this condition
+        #[arg(short = 'n', long)]
+        condition: Option<String>,
+        
+        /// Only include items of this brand
+        #[arg(short, long)]
+        brand: Option<String>,
+        
+        /// Only include items in this price range (e.g. 10-50, <50, >=100)
+        #[arg(short, long)]
+        price: Option<String>,
+        
+        /// List items with at most this quantity instead of the aggregate stats
+        /// [default: 5, or `low_stock_threshold` in the config file]
+        #[arg(long, value_name = "THRESHOLD", num_args = 0..=1,
+              conflicts_with_all = ["section", "rates", "category", "condition", "brand", "price"])]
+        low_stock: Option<Option<i32>>,
+        
+        /// List the UPCs shared by more than one item, and which items share them, instead of
+        /// the aggregate stats
+        #[arg(long, conflicts_with_all = ["section", "rates", "category", "condition", "brand", "price", "low_stock"])]
+        duplicate_upc: bool,
     },
     
     /// Validate a CSV file against the inventory schema
     Validate {
-        /// CSV file to validate
+        /// CSV file to validate, or - to read it from stdin
         #[arg(short, long)]
         file: String,
     },
+    
+    /// Check an item against each marketplace's rules without adding it
+    ValidateItem {
+        /// Item title
+        #[arg(short, long)]
+        title: String,
+        
+        /// Item price
+        #[arg(short, long)]
+        price: f64,
+        
+        /// Item quantity
+        #[arg(short, long)]
+        quantity: i32,
+        
+        /// Item category
+        #[arg(short, long)]
+        category: String,
+        
+        /// Item condition
+        #[arg(short = 'n', long)]
+        condition: String,
+        
+        /// Item brand
+        #[arg(short, long)]
+        brand: Option<String>,
+        
+        /// UPC code
+        #[arg(long)]
+        upc: Option<String>,
+        
+        /// Item size
+        #[arg(long)]
+        size: Option<String>,
+        
+        /// ISO 4217 currency of the price
+        #[arg(long, default_value = "USD")]
+        currency: String,
+        
+        /// Marketplace to validate for (ebay, stockx, poshmark, mercari), or all of them
+        #[arg(long, default_value = "all")]
+        platform: PlatformChoice,
+        
+        /// Output format (table for a pass/fail summary, or json)
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+    
+    /// Full-text search over item titles and descriptions
+    Search {
+        /// Search terms
+        query: String,
+        
+        /// Output format
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+    
+    /// Normalize every last_updated value to RFC3339
+    FixTimestamps,
+    
+    /// Reclaim space left by deleted rows and refresh the query planner's statistics
+    #[command(visible_alias = "optimize")]
+    Vacuum,
+    
+    /// Show how the query cache behind `filter` and `stats` is doing, or empty it
+    Cache {
+        /// Delete every cached result and zero the hit and miss counts
+        #[arg(long)]
+        clear: bool,
+        
+        /// Print the entry count, hits, misses and approximate memory (the default without --clear)
+        #[arg(long)]
+        stats: bool,
+        
+        /// Output format for --stats (json, or text otherwise)
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+    
+    /// Back up the database to a timestamped .db file
+    Backup {
+        /// Backup directory, or a .db file path (defaults to ~/.inventory/backups)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Replace the database with the contents of a backup
+    Restore {
+        /// Backup file to restore from
+        #[arg(short, long)]
+        input: PathBuf,
+        
+        /// Confirm overwriting the live database
+        #[arg(long)]
+        force: bool,
+    },
+    
+    /// Remove the items an import run created and revert the ones it updated
+    RollbackImport {
+        /// Import run id printed in the import summary
+        #[arg(long)]
+        run: String,
+    },
+    
+    /// Compare the live database with another database file
+    DbDiff {
+        /// Database file to compare against, such as a backup
+        #[arg(long)]
+        other: PathBuf,
+    },
+    
+    /// Export items to a file or stdout
+    Export {
+        /// Output format
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+        
+        /// File to write, or the directory for --split-by; defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        
+        /// Add to the end of the --output file instead of replacing it (csv and ndjson only).
+        /// CSV rows go under the file's header, which must match.
+        #[arg(long, requires = "output")]
+        append: bool,
+        
+        /// Also export sold and draft items
+        #[arg(long)]
+        include_archived: bool,
+        
+        /// Export only a random sample of this many items
+        #[arg(long)]
+        sample: Option<usize>,
+        
+        /// Seed for a deterministic sample: the same seed picks the same items
+        #[arg(long, requires = "sample")]
+        seed: Option<u32>,
+        
+        /// Export through this exporter plugin instead of a built-in format (see `plugins`)
+        #[arg(long, conflicts_with = "format")]
+        exporter: Option<String>,
+        
+        /// Write one file per category or brand, export_<value>.<ext>, into the --output directory
+        #[arg(long, value_enum, requires = "output", conflicts_with_all = ["append", "exporter"])]
+        split_by: Option<SplitBy>,
+    },
+    
+    /// List the plugins in ~/.inventory/plugins (or $INVENTORY_PLUGIN_DIR)
+    Plugins,
+    
+    /// Reverse the most recent add, update, delete or import
+    Undo,
+    
+    /// Print a shell completion script, e.g. `openinv completions bash > ~/.local/share/bash-completion/completions/openinv`
+    #[command(hide = true)]
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
-enum LogLevel {
-    Error,
-    Warn,
-    Info,
-    Debug,
-    Trace,
+/// Runs the command, then exits with the code for how it went (see `error::error::ExitCode`)
+fn main() {
+    let cli = Cli::parse();
+    let error_format = cli.error_format;
+    if let Err(err) = run(cli) {
+        eprintln!("{}", format_error(&err, error_format));
+        std::process::exit(ExitCode::of(&err).into());
+    }
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn run(cli: Cli) -> Result<()> {
+    output::console::set_quiet(cli.quiet);
+    
+    // Completion scripts only need the command definitions, not the config or database
+    if let Commands::Completions { shell } = cli.command {
+        return handle_completions(shell, &mut Cli::command());
+    }
+    
+    // Config file values fill in whatever the command line leaves unset
+    let config = Config::load()?;
+    validation::set_title_limits(config.title_limits);
+    db::retry::set_busy_retries(config.busy_retries.unwrap_or(DEFAULT_BUSY_RETRIES));
+    let default_format = cli.format.clone().or(config.format.clone());
+    let profile_json = matches!(default_format, Some(OutputFormat::Json));
+    
+    // Logging comes up before anything worth logging happens; commands still run without it
+    let log_level = cli.log_level.or(config.log_level).unwrap_or_default();
+    if let Err(e) = logging::init(log_level) {
+        eprintln!("Failed to initialize logging: {:#}", e);
+    }
     
-    // Initialize database connection
-    let conn = initialize_database(None)?;
+    // Initialize database connection; every command shares this one
+    let db_path = resolve_db_path(cli.db_path.clone(), config.db_path.clone());
+    let mut conn = initialize_database(Some(&db_path))
+        .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
     
     // Initialize performance optimizations
     optimize_database(&conn)?;
-    let monitor = Arc::new(PerformanceMonitor::new());
-    let cache = Arc::new(QueryCache::new(monitor.clone()));
+    let mut monitor = PerformanceMonitor::new().with_verbose(cli.verbose);
+    if let Some(ms) = cli.threshold_ms {
+        monitor = monitor.with_threshold(Duration::from_millis(ms));
+    }
+    let monitor = Arc::new(monitor);
+    let cache = Arc::new(QueryCache::new(monitor.clone()).with_ttl(config.cache_ttl.map(Duration::from_secs)));
+    // Without a home directory the cache only lasts for this run
+    let cache_path = default_cache_path();
+    if let Some(path) = &cache_path {
+        cache.load_from(path);
+    }
+    // Handlers given no cache still time their queries with the monitor
+    let query_cache = (!cli.no_cache).then(|| cache.clone());
+    let output_options = OutputOptions {
+        crlf: cli.crlf,
+        csv_delimiter: cli.delimiter,
+        csv_header: !cli.no_header,
+        ndjson_schema: cli.ndjson_schema,
+        json_compact: cli.json_compact,
+        wrap: cli.wrap.map(usize::from),
+        color: cli.color.enabled(),
+    };
+    let perf_report_dir = resolve_perf_report_dir(cli.no_perf_report, cli.perf_report.clone(), config.perf_report, config.perf_report_dir.clone());
+    
     
-    // TODO: Initialize logging and config
-    // TODO: Handle subcommands
+    // Cached query results are stale once the inventory changes
+    let mutates_inventory = matches!(
+        cli.command,
+        Commands::Add { .. } | Commands::Update(_) | Commands::Delete { .. } | Commands::Clone { .. } | Commands::Reprice { .. } | Commands::Archive { .. } | Commands::Tag { .. } | Commands::Categories { action: Some(CategoryAction::Rename { .. } | CategoryAction::Merge { .. }) } | Commands::Brand { .. } | Commands::Import { dry_run: false, validate_only: false, .. } | Commands::Migrate | Commands::FixTimestamps | Commands::Restore { .. } | Commands::RollbackImport { .. } | Commands::Undo
+    );
     
-    match cli.command {
-        Commands::Add { title, price, quantity, category, condition, brand, description, upc, size, original_price, hashtags, colorway, release_date, internal_notes } => {
-            handle_add_item(title, price, quantity, category, condition, brand, description, upc, size, original_price, hashtags, colorway, release_date, internal_notes)
+    let result = match cli.command {
+        Commands::Add { interactive, strict_brands, title, price, quantity, category, condition, brand, description, upc, size, original_price, hashtags, colorway, release_date, internal_notes, currency, sku, platform } => {
+            let platform = platform.or(config.platform).unwrap_or_default();
+            let draft = ItemDraft { title, price, quantity, category, condition, brand, description, upc, size, currency, sku };
+            match complete_item(draft, interactive, platform, &conn) {
+                Ok(Some(item)) => {
+                    let (Some(title), Some(price), Some(quantity), Some(category), Some(condition)) =
+                        (item.title, item.price, item.quantity, item.category, item.condition) else {
+                        unreachable!("the wizard fills in every required field")
+                    };
+                    handle_add_item(title, price, quantity, category, condition, item.brand, item.description, item.upc, item.size, original_price, hashtags, colorway, release_date, internal_notes, item.currency, item.sku, strict_brands, platform, &conn)
+                }
+                Ok(None) => {
+                    println!("Cancelled; no item added.");
+                    Ok(())
+                }
+                Err(e) => Err(e),
+            }
         }
         Commands::Update(args) => {
             handle_update(args, &conn)
         }
-        Commands::Delete { id } => {
-            handle_delete_item(id, &conn)
+        Commands::Delete { id, yes } => {
+            handle_delete_items(&id, yes, &conn)
+        }
+        Commands::Clone { id, size, colorway, quantity, price, upc } => {
+            handle_clone(id, size, colorway, quantity, price, upc, &conn)
         }
-        Commands::List { format } => {
-            handle_list_inventory(&conn, format)
+        Commands::Reprice { category, brand, percent, add } => {
+            let adjustment = match (percent, add) {
+                (Some(percent), _) => PriceAdjustment::Percent(percent),
+                (None, Some(amount)) => PriceAdjustment::Add(amount),
+                (None, None) => unreachable!("clap requires --percent or --add"),
+            };
+            handle_reprice(&conn, category.as_deref(), brand.as_deref(), adjustment)
         }
-        Commands::Import { file } => {
-            handle_import(file, &conn, false)
+        Commands::Archive { id, status } => {
+            handle_archive(&id, &status, &conn)
         }
-        Commands::Filter { price, category, condition, brand, fields, format } => {
-            let result = handle_filter(&conn, price, category, condition, brand, fields, format, Some(monitor.clone()), Some(cache.clone()));
+        Commands::Tag { id, add, remove } => {
+            handle_tag(id, &add, &remove, &conn)
+        }
+        Commands::History { id } => {
+            handle_history(id, &conn)
+        }
+        Commands::Categories { action } => {
+            handle_categories(&conn, action, default_format, &output_options)
+        }
+        Commands::Brand { action } => {
+            handle_brand(&conn, action)
+        }
+        Commands::List { format, include_archived, columns, sample } => {
+            handle_list_inventory(&conn, format.or(default_format), include_archived, columns, sample.map(|n| n as usize), &output_options, Some(monitor.clone()))
+        }
+        Commands::Import { retry: Some(retry), .. } => {
+            handle_import_retry(&retry, &conn)
+        }
+        Commands::Import { file: Some(file), validate_only: true, comment_char, fuzzy_headers, map, encoding, .. } => {
+            handle_validate(&file, comment_char, fuzzy_headers, map.as_ref(), encoding)
+        }
+        Commands::Import { file, retry: None, dry_run, comment_char, fuzzy_headers, map, on_long_title, upsert, upsert_key, skip_duplicates, duplicate_key, max_rows, encoding, .. } => match file {
+            Some(file) => handle_import(file, &conn, &ImportOptions {
+                non_interactive: std::env::var("INVENTORY_NONINTERACTIVE").is_ok(),
+                dry_run,
+                comment_char,
+                fuzzy_headers,
+                on_long_title,
+                upsert: upsert.then_some(upsert_key),
+                skip_duplicates: skip_duplicates.then_some(&duplicate_key),
+                column_map: map.as_ref(),
+                max_rows,
+                encoding,
+            }),
+            None => Err(anyhow::anyhow!("Must specify either --file or --retry")),
+        },
+        Commands::Filter(mut args) => {
+            args.format = args.format.or(default_format);
+            let result = handle_filter(&conn, args, &output_options, Some(monitor.clone()), query_cache.clone());
             // Save performance report
-            let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S");
-            let filename = format!("performance_filter_{}.json", timestamp);
-            if let Err(e) = monitor.write_performance_report(&filename) {
-                eprintln!("Failed to write performance report: {}", e);
+            if let Some(dir) = &perf_report_dir {
+                let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S");
+                let filename = format!("performance_filter_{}.json", timestamp);
+                if let Err(e) = monitor.write_performance_report(dir, &filename) {
+                    eprintln!("Failed to write performance report: {}", e);
+                }
             }
             result
         }
+        Commands::Count { price, category, condition, brand, include_archived, format } => {
+            handle_count(&conn, price, category, condition, brand, include_archived, format.or(default_format))
+        }
         Commands::Migrate => {
-            handle_migrate()
+            handle_migrate(&conn)
         }
         Commands::Fields => {
             handle_help()
@@ -243,24 +830,105 @@ fn main() -> Result<()> {
         Commands::Commands => {
             handle_commands()
         }
-        Commands::Stats { format } => {
-            let result = handle_stats(&conn, format, Some(monitor.clone()), Some(cache.clone()));
+        Commands::Stats { format, duplicate_upc: true, .. } => {
+            handle_duplicate_upcs(&conn, format.or(default_format), &output_options)
+        }
+        Commands::Stats { format, low_stock: Some(threshold), .. } => {
+            let threshold = threshold.or(config.low_stock_threshold).unwrap_or(DEFAULT_LOW_STOCK_THRESHOLD);
+            handle_low_stock(&conn, threshold, format.or(default_format), &output_options)
+        }
+        Commands::Stats { format, section, rates, category, condition, brand, price, low_stock: None, .. } => {
+            let rates = rates.map(|path| ExchangeRates::load(&path)).transpose()?;
+            // A section is a CSV table, so asking for one without a format means CSV
+            let args = StatsArgs {
+                format: format.or_else(|| section.map(|_| OutputFormat::Csv)).or(default_format),
+                section,
+                rates: rates.as_ref(),
+                filters: StatsFilters { category, condition, brand, price },
+            };
+            let result = handle_stats(&conn, &args, &output_options, Some(monitor.clone()), query_cache.clone());
             // Save performance report
-            let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S");
-            let filename = format!("performance_stats_{}.json", timestamp);
-            if let Err(e) = monitor.write_performance_report(&filename) {
-                eprintln!("Failed to write performance report: {}", e);
+            if let Some(dir) = &perf_report_dir {
+                let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S");
+                let filename = format!("performance_stats_{}.json", timestamp);
+                if let Err(e) = monitor.write_performance_report(dir, &filename) {
+                    eprintln!("Failed to write performance report: {}", e);
+                }
             }
             result
         }
         Commands::Validate { file } => {
-            match handle_validate(&file) {
-                Ok(()) => Ok(()),
-                Err(e) => {
-                    eprintln!("{}", e);
-                    std::process::exit(1);
-                }
-            }
+            handle_validate(&file, None, false, None, None)
+        }
+        Commands::ValidateItem { title, price, quantity, category, condition, brand, upc, size, currency, platform, format } => {
+            // A global default this command can't render (e.g. csv) falls back to the summary
+            let default_format = default_format.filter(|f| matches!(f, OutputFormat::Json | OutputFormat::Table));
+            let item = ItemFields {
+                title: &title,
+                price,
+                quantity,
+                category: &category,
+                condition: &condition,
+                brand: brand.as_deref(),
+                upc: upc.as_deref(),
+                size: size.as_deref(),
+                currency: &currency,
+            };
+            handle_validate_item(&item, platform, format.or(default_format))
+        }
+        Commands::Search { query, format } => {
+            handle_search(&conn, &query, format.or(default_format), &output_options)
         }
+        Commands::FixTimestamps => {
+            handle_fix_timestamps(&mut conn)
+        }
+        Commands::Vacuum => {
+            handle_vacuum(&conn, &db_path).map(|_| ())
+        }
+        Commands::Cache { clear, stats, format } => {
+            handle_cache(&cache, cache_path.as_deref(), clear, stats, format.or(default_format), &output_options)
+        }
+        Commands::Backup { output } => {
+            handle_backup(&conn, output).map(|_| ())
+        }
+        Commands::Restore { input, force } => {
+            handle_restore(&mut conn, &input, force)
+        }
+        Commands::RollbackImport { run } => {
+            handle_rollback_import(&mut conn, &run)
+        }
+        Commands::DbDiff { other } => {
+            handle_db_diff(&conn, &other)
+        }
+        Commands::Export { format, output: Some(dir), include_archived, sample, seed, split_by: Some(split_by), .. } => {
+            let sample = sample.map(|size| Sample { size, seed });
+            handle_export_split(&conn, split_by, format.or(default_format), &dir, include_archived, sample, &output_options).map(|_| ())
+        }
+        Commands::Export { format, output, append, include_archived, sample, seed, exporter, .. } => {
+            let sample = sample.map(|size| Sample { size, seed });
+            let output = output.map(|path| ExportFile { path, append });
+            handle_export(&conn, format.or(default_format), output, include_archived, sample, exporter.as_deref(), &output_options)
+        }
+        Commands::Plugins => {
+            handle_plugins()
+        }
+        Commands::Undo => {
+            handle_undo(&conn)
+        }
+        Commands::Completions { .. } => unreachable!("completions are printed before the database is opened"),
+    };
+    
+    if mutates_inventory {
+        cache.clear();
     }
+    if let Some(path) = &cache_path {
+        if let Err(e) = cache.save_to(path) {
+            eprintln!("Failed to save query cache: {}", e);
+        }
+    }
+    if cli.profile && !output::console::is_quiet() {
+        eprint!("{}", monitor.format_profile(profile_json, &output_options)?);
+    }
+    
+    result
 } 
\ No newline at end of file
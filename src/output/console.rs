@@ -0,0 +1,24 @@
+// Console output shared by the commands: the global --quiet switch
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Set from the global `--quiet` flag: commands then print only their summary, with errors on stderr
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// `println!` for informational output that `--quiet` suppresses
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::output::console::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use status;
@@ -1,9 +1,80 @@
 // Output formatting implementation
 // TODO: Implement JSON, CSV, and table formatting 
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use comfy_table::{presets::ASCII_MARKDOWN, Attribute, Cell, Color, Table};
+use rusqlite::types::Value;
 use serde_json;
+use crate::config::currency::{format_money, DEFAULT_CURRENCY};
+use crate::plugins::loader::load_formatter;
 use std::collections::HashMap;
+use std::io::{self, BufWriter, IsTerminal, StdoutLock, Write};
+
+/// Output settings shared by the formatters, set from global CLI flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputOptions {
+    /// Terminate CSV records with CRLF as RFC 4180 specifies, instead of LF
+    pub crlf: bool,
+    /// Character between CSV fields; fields containing it are quoted
+    pub csv_delimiter: char,
+    /// Start CSV output with a header record
+    pub csv_header: bool,
+    /// Start ndjson output with an `NdjsonSchema` line
+    pub ndjson_schema: bool,
+    /// Write JSON on one line instead of pretty-printed
+    pub json_compact: bool,
+    /// Bold table headers and show low-stock rows in red (see `ColorChoice`)
+    pub color: bool,
+    /// Wrap long table cells onto lines of this width instead of truncating them
+    pub wrap: Option<usize>,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        Self { crlf: false, csv_delimiter: ',', csv_header: true, ndjson_schema: false, json_compact: false, color: false, wrap: None }
+    }
+}
+
+/// Parses `--delimiter`: a single character other than a quote or line break. `\t` (as typed,
+/// a backslash and a t) means tab, so it needn't be quoted in the shell.
+pub fn parse_csv_delimiter(value: &str) -> std::result::Result<char, String> {
+    let mut chars = value.chars();
+    let delimiter = match (value, chars.next(), chars.next()) {
+        ("\\t", _, _) => '\t',
+        (_, Some(c), None) => c,
+        _ => return Err(format!("'{}' isn't a single character", value)),
+    };
+    if matches!(delimiter, '"' | '\n' | '\r') {
+        return Err(format!("{:?} can't be used as a delimiter", delimiter));
+    }
+    Ok(delimiter)
+}
+
+/// When table output is colored
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Only when stdout is a terminal and `NO_COLOR` isn't set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl OutputOptions {
+    pub fn csv_line_ending(&self) -> &'static str {
+        if self.crlf { "\r\n" } else { "\n" }
+    }
+}
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct InventoryItem {
@@ -14,6 +85,78 @@ pub struct InventoryItem {
     pub condition: String,
     pub category: String,
     pub brand: Option<String>,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    // The rest of the item's columns; None when empty, or for an item built with `new`
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub upc: Option<String>,
+    #[serde(default)]
+    pub size: Option<String>,
+    #[serde(default)]
+    pub original_price: Option<f64>,
+    #[serde(default)]
+    pub colorway: Option<String>,
+    #[serde(default)]
+    pub release_date: Option<String>,
+    #[serde(default)]
+    pub hashtags: Option<String>,
+    #[serde(default)]
+    pub photos: Option<String>,
+    #[serde(default)]
+    pub item_specifics: Option<String>,
+    #[serde(default)]
+    pub shipping_details: Option<String>,
+    #[serde(default)]
+    pub platform_status: Option<String>,
+    #[serde(default)]
+    pub internal_notes: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub sku: Option<String>,
+    #[serde(default)]
+    pub last_import_run: Option<String>,
+    #[serde(default)]
+    pub last_updated: Option<String>,
+}
+
+/// Name identifying an ndjson stream of inventory items in its schema line
+pub const NDJSON_SCHEMA: &str = "openinv.items";
+
+/// Version of the ndjson item stream; bump when a field changes meaning or is removed
+pub const NDJSON_SCHEMA_VERSION: u32 = 1;
+
+/// Fields of an `InventoryItem` record, in output order: the compact set the table shows, then
+/// the rest of the items table's columns
+pub const ITEM_FIELDS: [&str; 24] = [
+    "item_id", "title", "price", "quantity", "condition", "category", "brand", "currency",
+    "description", "upc", "size", "original_price", "colorway", "release_date", "hashtags", "photos",
+    "item_specifics", "shipping_details", "platform_status", "internal_notes", "status", "sku",
+    "last_import_run", "last_updated",
+];
+
+/// The optional first line of an ndjson stream, describing the records that follow
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NdjsonSchema {
+    pub schema: String,
+    pub version: u32,
+    pub fields: Vec<String>,
+}
+
+impl NdjsonSchema {
+    pub fn current(fields: &[String]) -> Self {
+        Self {
+            schema: NDJSON_SCHEMA.to_string(),
+            version: NDJSON_SCHEMA_VERSION,
+            fields: fields.to_vec(),
+        }
+    }
+}
+
+fn default_currency() -> String {
+    DEFAULT_CURRENCY.to_string()
 }
 
 impl InventoryItem {
@@ -34,87 +177,370 @@ impl InventoryItem {
             condition,
             category,
             brand,
+            currency: default_currency(),
+            description: None,
+            upc: None,
+            size: None,
+            original_price: None,
+            colorway: None,
+            release_date: None,
+            hashtags: None,
+            photos: None,
+            item_specifics: None,
+            shipping_details: None,
+            platform_status: None,
+            internal_notes: None,
+            status: None,
+            sku: None,
+            last_import_run: None,
+            last_updated: None,
         }
     }
+    
+    pub fn with_currency(mut self, currency: String) -> Self {
+        self.currency = currency;
+        self
+    }
 }
 
-pub fn format_json(items: &[InventoryItem]) -> Result<String> {
-    Ok(serde_json::to_string_pretty(items)?)
+pub fn format_json(items: &[InventoryItem], options: &OutputOptions) -> Result<String> {
+    to_json(items, options)
 }
 
-pub fn format_csv(items: &[InventoryItem]) -> Result<String> {
-    let mut csv = String::new();
+/// JSON for any output, pretty-printed unless `json_compact` is set
+pub fn to_json<T: serde::Serialize + ?Sized>(value: &T, options: &OutputOptions) -> Result<String> {
+    Ok(if options.json_compact { serde_json::to_string(value)? } else { serde_json::to_string_pretty(value)? })
+}
+
+/// YAML with the same field names as `format_json`; an empty list is `[]`
+pub fn format_yaml(items: &[InventoryItem]) -> Result<String> {
+    Ok(serde_yaml::to_string(items)?)
+}
+
+/// Writes ndjson a record at a time, so a stream of any length is never held in memory.
+/// Nothing is written for no records unless a schema line is asked for.
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Starts the stream, with a schema line listing `fields` when enabled
+    pub fn new(mut writer: W, fields: &[String], options: &OutputOptions) -> Result<Self> {
+        if options.ndjson_schema {
+            serde_json::to_writer(&mut writer, &NdjsonSchema::current(fields))?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(Self { writer, written: 0 })
+    }
     
-    // Header
-    csv.push_str("item_id,title,price,quantity,condition,category,brand\n");
+    pub fn write<T: serde::Serialize>(&mut self, record: &T) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        self.written += 1;
+        Ok(())
+    }
     
-    // Data rows
+    /// Flushes the stream and returns how many records were written
+    pub fn finish(mut self) -> Result<usize> {
+        self.writer.flush()?;
+        Ok(self.written)
+    }
+}
+
+/// Streams ndjson to stdout, buffered but flushed as the writer finishes
+pub fn stdout_ndjson(fields: &[String], options: &OutputOptions) -> Result<NdjsonWriter<BufWriter<StdoutLock<'static>>>> {
+    NdjsonWriter::new(buffered_stdout(), fields, options)
+}
+
+/// One JSON object per line, preceded by a schema line listing `fields` when enabled
+pub fn format_ndjson_records<T: serde::Serialize>(records: &[T], fields: &[String], options: &OutputOptions) -> Result<String> {
+    let mut writer = NdjsonWriter::new(Vec::new(), fields, options)?;
+    for record in records {
+        writer.write(record)?;
+    }
+    Ok(String::from_utf8(writer.writer)?)
+}
+
+pub fn format_ndjson(items: &[InventoryItem], options: &OutputOptions) -> Result<String> {
+    let fields: Vec<String> = ITEM_FIELDS.iter().map(|f| f.to_string()).collect();
+    format_ndjson_records(items, &fields, options)
+}
+
+pub fn format_csv(items: &[InventoryItem]) -> Result<String> {
+    format_csv_with_options(items, &OutputOptions::default())
+}
+
+pub fn format_csv_with_options(items: &[InventoryItem], options: &OutputOptions) -> Result<String> {
+    let mut writer = CsvWriter::new(Vec::new(), &ITEM_FIELDS, options);
+    writer.write_header()?;
     for item in items {
-        csv.push_str(&format!(
-            "{},{},{:.2},{},{},{},{}\n",
-            item.item_id,
-            escape_csv_field(&item.title),
-            item.price,
-            item.quantity,
-            escape_csv_field(&item.condition),
-            escape_csv_field(&item.category),
-            item.brand.as_ref().map_or(String::new(), |b| escape_csv_field(b))
-        ));
+        writer.write_row(&item_csv_fields(item, options.csv_delimiter))?;
     }
-    
-    Ok(csv)
+    Ok(String::from_utf8(writer.writer)?)
+}
+
+/// An item's CSV fields in `ITEM_FIELDS` order, escaped for `delimiter`, with prices to two
+/// decimals and missing values empty
+pub fn item_csv_fields(item: &InventoryItem, delimiter: char) -> Vec<String> {
+    let text = |value: &Option<String>| value.as_ref().map_or(String::new(), |v| escape_csv_field(v, delimiter));
+    vec![
+        item.item_id.to_string(),
+        escape_csv_field(&item.title, delimiter),
+        format!("{:.2}", item.price),
+        item.quantity.to_string(),
+        escape_csv_field(&item.condition, delimiter),
+        escape_csv_field(&item.category, delimiter),
+        text(&item.brand),
+        item.currency.clone(),
+        text(&item.description),
+        text(&item.upc),
+        text(&item.size),
+        item.original_price.map_or(String::new(), |v| format!("{:.2}", v)),
+        text(&item.colorway),
+        text(&item.release_date),
+        text(&item.hashtags),
+        text(&item.photos),
+        text(&item.item_specifics),
+        text(&item.shipping_details),
+        text(&item.platform_status),
+        text(&item.internal_notes),
+        text(&item.status),
+        text(&item.sku),
+        text(&item.last_import_run),
+        text(&item.last_updated),
+    ]
 }
 
-pub fn format_table(items: &[InventoryItem]) -> Result<String> {
-    let mut table = String::new();
+/// Writes CSV a row at a time. The header goes out with the first row, or on `write_header`
+/// for output that has one even without rows; with `csv_header` off there's none.
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    eol: &'static str,
+    delimiter: String,
+    header: Option<String>,
+    written: usize,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new<S: AsRef<str>>(writer: W, header: &[S], options: &OutputOptions) -> Self {
+        let delimiter = options.csv_delimiter.to_string();
+        let header = options.csv_header.then(|| {
+            header.iter().map(|field| escape_csv_field(field.as_ref(), options.csv_delimiter)).collect::<Vec<_>>().join(&delimiter)
+        });
+        Self { writer, eol: options.csv_line_ending(), delimiter, header, written: 0 }
+    }
     
-    // Header
-    table.push_str("ID  | Title                                                | Price   | Qty | Condition | Category | Brand\n");
-    table.push_str("----|------------------------------------------------------|---------|-----|-----------|----------|-------\n");
+    pub fn write_header(&mut self) -> Result<()> {
+        if let Some(header) = self.header.take() {
+            write!(self.writer, "{}{}", header, self.eol)?;
+        }
+        Ok(())
+    }
     
-    // Data rows
-    for item in items {
-        let title = if item.title.len() > 50 {
-            format!("{}...", &item.title[..47])
-        } else {
-            item.title.clone()
-        };
-        
-        let brand = item.brand.as_deref().unwrap_or("");
-        let brand_display = if brand.len() > 30 {
-            format!("{}...", &brand[..27])
-        } else {
-            brand.to_string()
-        };
-        
-        table.push_str(&format!(
-            "{:<4} | {:<50} | ${:<7.2} | {:<3} | {:<9} | {:<8} | {}\n",
-            item.item_id,
-            title,
-            item.price,
-            item.quantity,
-            item.condition,
-            item.category,
-            brand_display
-        ));
+    /// Writes one record of already-escaped fields
+    pub fn write_row(&mut self, fields: &[String]) -> Result<()> {
+        self.write_header()?;
+        write!(self.writer, "{}{}", fields.join(&self.delimiter), self.eol)?;
+        self.written += 1;
+        Ok(())
+    }
+    
+    /// Flushes the output and returns how many rows were written
+    pub fn finish(mut self) -> Result<usize> {
+        self.writer.flush()?;
+        Ok(self.written)
+    }
+    
+    /// The underlying writer, for output collected in memory
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Stdout, buffered, for the streaming writers
+pub fn buffered_stdout() -> BufWriter<StdoutLock<'static>> {
+    BufWriter::new(io::stdout().lock())
+}
+
+/// Shortens a value to `max` characters, ending it with "..." when cut
+pub fn truncate_cell(value: &str, max: usize) -> String {
+    if value.chars().count() > max {
+        let kept: String = value.chars().take(max.saturating_sub(3)).collect();
+        format!("{}...", kept)
+    } else {
+        value.to_string()
+    }
+}
+
+/// A table cell: wrapped to `options.wrap` columns when set, otherwise cut to `max` characters
+pub fn fit_cell(value: &str, max: usize, options: &OutputOptions) -> String {
+    match options.wrap {
+        Some(width) => textwrap::fill(value, width),
+        None => truncate_cell(value, max),
+    }
+}
+
+/// Quantity at or below which colored tables show a row in red; out of stock is bold red
+pub const LOW_QUANTITY_HIGHLIGHT: i64 = 1;
+
+/// Renders rows as a grid whose columns size to their content; the header is printed
+/// even when there are no rows. Each row comes with its item's quantity, when known, for
+/// highlighting. Without `color` the output has no escape codes, even on a terminal.
+pub fn render_table(header: Vec<String>, rows: Vec<(Vec<String>, Option<i64>)>, color: bool) -> String {
+    let mut table = Table::new();
+    table.load_preset(ASCII_MARKDOWN)
+        .set_header(header.into_iter().map(|h| Cell::new(h).add_attribute(Attribute::Bold)));
+    if color {
+        table.enforce_styling();
+    } else {
+        table.force_no_tty();
+    }
+    for (row, quantity) in rows {
+        let cells = row.into_iter().map(|value| match quantity {
+            Some(0) => Cell::new(value).fg(Color::Red).add_attribute(Attribute::Bold),
+            Some(q) if q <= LOW_QUANTITY_HIGHLIGHT => Cell::new(value).fg(Color::Red),
+            _ => Cell::new(value),
+        });
+        table.add_row(cells.collect::<Vec<_>>());
     }
+    format!("{}\n", table)
+}
+
+pub fn format_table(items: &[InventoryItem], options: &OutputOptions) -> Result<String> {
+    let header = ["ID", "Title", "Price", "Qty", "Condition", "Category", "Brand"]
+        .iter()
+        .map(|h| h.to_string())
+        .collect();
+    
+    let rows = items.iter()
+        .map(|item| (vec![
+            item.item_id.to_string(),
+            fit_cell(&item.title, 50, options),
+            format_money(item.price, &item.currency),
+            item.quantity.to_string(),
+            item.condition.clone(),
+            item.category.clone(),
+            fit_cell(item.brand.as_deref().unwrap_or(""), 30, options),
+        ], Some(item.quantity as i64)))
+        .collect();
     
-    Ok(table)
+    Ok(render_table(header, rows, options.color))
 }
 
-fn escape_csv_field(field: &str) -> String {
-    if field.contains(',') || field.contains('"') || field.contains('\n') {
+/// Quotes a field that contains the delimiter, a quote or a line break, doubling its quotes
+pub fn escape_csv_field(field: &str, delimiter: char) -> String {
+    if field.contains(delimiter) || field.contains('"') || field.contains('\n') || field.contains('\r') {
         format!("\"{}\"", field.replace("\"", "\"\""))
     } else {
         field.to_string()
     }
 }
 
-pub fn format_items(items: &[InventoryItem], format: &str) -> Result<String> {
+/// One item restricted to chosen columns; serializes as an object with the columns in order
+pub struct ColumnRecord<'a> {
+    pub columns: &'a [String],
+    pub values: &'a [Value],
+}
+
+impl serde::Serialize for ColumnRecord<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for (column, value) in self.columns.iter().zip(self.values) {
+            match value {
+                Value::Null => map.serialize_entry(column, &None::<()>)?,
+                Value::Integer(i) => map.serialize_entry(column, i)?,
+                Value::Real(f) => map.serialize_entry(column, f)?,
+                Value::Text(s) => map.serialize_entry(column, s)?,
+                Value::Blob(b) => map.serialize_entry(column, b)?,
+            }
+        }
+        map.end()
+    }
+}
+
+fn column_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Integer(i) => i.to_string(),
+        Value::Real(f) => f.to_string(),
+        Value::Text(s) => s.clone(),
+        Value::Blob(b) => format!("<{} bytes>", b.len()),
+    }
+}
+
+/// A row of chosen columns as CSV fields escaped for `delimiter`; prices keep two decimals
+pub fn column_csv_fields(columns: &[String], values: &[Value], delimiter: char) -> Vec<String> {
+    columns.iter().zip(values)
+        .map(|(column, value)| match (column.as_str(), value) {
+            ("price", Value::Real(price)) => format!("{:.2}", price),
+            _ => escape_csv_field(&column_text(value), delimiter),
+        })
+        .collect()
+}
+
+/// Formats rows of chosen columns (as from `get_item_columns`) as json, ndjson, yaml, csv or
+/// table. CSV prices keep two decimals and table prices are shown as money, like `format_items`.
+pub fn format_columns(columns: &[String], rows: &[Vec<Value>], format: &str, options: &OutputOptions) -> Result<String> {
+    let records: Vec<ColumnRecord> = rows.iter()
+        .map(|values| ColumnRecord { columns, values })
+        .collect();
+    let currency_idx = columns.iter().position(|column| column == "currency");
+    let quantity_idx = columns.iter().position(|column| column == "quantity");
+    
+    match format.to_lowercase().as_str() {
+        "json" => to_json(&records, options),
+        "ndjson" => format_ndjson_records(&records, columns, options),
+        "yaml" | "yml" => Ok(serde_yaml::to_string(&records)?),
+        "csv" => {
+            let mut writer = CsvWriter::new(Vec::new(), columns, options);
+            writer.write_header()?;
+            for values in rows {
+                writer.write_row(&column_csv_fields(columns, values, options.csv_delimiter))?;
+            }
+            Ok(String::from_utf8(writer.writer)?)
+        }
+        "table" => {
+            let header = columns.iter().map(|column| column.to_uppercase()).collect();
+            let table_rows = rows.iter()
+                .map(|values| {
+                    let cells = columns.iter().zip(values)
+                        .map(|(column, value)| match (column.as_str(), value) {
+                            ("price", Value::Real(price)) => {
+                                let currency = currency_idx.map(|idx| column_text(&values[idx]));
+                                format_money(*price, currency.as_deref().unwrap_or(DEFAULT_CURRENCY))
+                            }
+                            ("title", _) => fit_cell(&column_text(value), 50, options),
+                            _ => fit_cell(&column_text(value), 30, options),
+                        })
+                        .collect();
+                    let quantity = quantity_idx.and_then(|idx| match values[idx] {
+                        Value::Integer(quantity) => Some(quantity),
+                        _ => None,
+                    });
+                    (cells, quantity)
+                })
+                .collect();
+            Ok(render_table(header, table_rows, options.color))
+        }
+        _ => Err(anyhow::anyhow!("Unsupported format with chosen columns: {}", format)),
+    }
+}
+
+/// Formats items as json, ndjson, yaml, csv, table, or with a formatter plugin given as `custom:NAME`
+pub fn format_items(items: &[InventoryItem], format: &str, options: &OutputOptions) -> Result<String> {
+    if let Some(name) = format.strip_prefix("custom:") {
+        let formatter = load_formatter(name)?;
+        return formatter.format(items)
+            .with_context(|| format!("Formatter plugin '{}' returned an error", formatter.name()));
+    }
     match format.to_lowercase().as_str() {
-        "json" => format_json(items),
-        "csv" => format_csv(items),
-        "table" => format_table(items),
+        "json" => format_json(items, options),
+        "ndjson" => format_ndjson(items, options),
+        "yaml" | "yml" => format_yaml(items),
+        "csv" => format_csv_with_options(items, options),
+        "table" => format_table(items, options),
         _ => Err(anyhow::anyhow!("Unsupported format: {}", format)),
     }
 } 
\ No newline at end of file
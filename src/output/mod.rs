@@ -1,2 +1,4 @@
 // Output module - will contain JSON, CSV, and table formatting logic
-pub mod format; 
\ No newline at end of file
+pub mod format;
+pub mod progress;
+pub mod console; 
\ No newline at end of file
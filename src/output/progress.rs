@@ -0,0 +1,90 @@
+// Progress bar for commands that work through a file row by row
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{self, IsTerminal};
+use crate::output::console::is_quiet;
+
+const TEMPLATE: &str = "{bar:40.cyan/blue} {pos}/{len} rows ({msg})";
+
+/// A progress bar over the rows of an import or update. The bar is only drawn when stdout is a
+/// terminal and `--quiet` is off; otherwise nothing is drawn and lines are printed as before.
+pub struct RowProgress {
+    bar: Option<ProgressBar>,
+    succeeded_label: &'static str,
+}
+
+impl RowProgress {
+    /// A bar over `total` rows, counting successful rows as `succeeded_label` (e.g. "imported")
+    pub fn new(total: usize, succeeded_label: &'static str) -> Self {
+        let bar = (io::stdout().is_terminal() && !is_quiet()).then(|| {
+            let bar = ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stdout());
+            bar.set_style(ProgressStyle::with_template(TEMPLATE).expect("valid progress template"));
+            bar
+        });
+        let progress = RowProgress { bar, succeeded_label };
+        progress.update(0, 0, 0);
+        progress
+    }
+
+    /// Sets how many rows are done and how they went
+    pub fn update(&self, done: usize, succeeded: usize, failed: usize) {
+        if let Some(bar) = &self.bar {
+            bar.set_position(done as u64);
+            bar.set_message(counts_message(succeeded, self.succeeded_label, failed));
+        }
+    }
+
+    /// Prints a line, above the bar when it's shown; `--quiet` drops it
+    pub fn println(&self, line: impl AsRef<str>) {
+        match &self.bar {
+            Some(bar) => bar.println(line),
+            None if !is_quiet() => println!("{}", line.as_ref()),
+            None => {}
+        }
+    }
+
+    /// Prints a per-row line the bar already accounts for, so only when there's no bar
+    pub fn detail(&self, line: impl AsRef<str>) {
+        if self.bar.is_none() && !is_quiet() {
+            println!("{}", line.as_ref());
+        }
+    }
+
+    /// Prints a row failure like `println`, but to stderr under `--quiet`
+    pub fn error(&self, line: impl AsRef<str>) {
+        if is_quiet() {
+            eprintln!("{}", line.as_ref());
+        } else {
+            self.println(line);
+        }
+    }
+
+    /// Hides the bar while `f` runs, for interactive prompts
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        match &self.bar {
+            Some(bar) => bar.suspend(f),
+            None => f(),
+        }
+    }
+
+    /// Removes the bar before the summary is printed
+    pub fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+fn counts_message(succeeded: usize, succeeded_label: &str, failed: usize) -> String {
+    format!("{} {}, {} failed", succeeded, succeeded_label, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_message() {
+        assert_eq!(counts_message(12, "imported", 3), "12 imported, 3 failed");
+    }
+}
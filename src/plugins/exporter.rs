@@ -0,0 +1,81 @@
+// Exporter plugins
+// Platform exporters, used with `export --exporter NAME`
+//
+// An exporter plugin is a shared library exporting two C functions:
+//
+//     char *openinv_export(const char *items_json);
+//     void openinv_free_string(char *output);
+//
+// `openinv_export` receives the item list as a JSON array (the same shape as `--format json`)
+// and returns the exported document, such as a marketplace's bulk upload file, as a
+// NUL-terminated UTF-8 string, or NULL on failure. The string is handed back to
+// `openinv_free_string` once it has been copied. A library may be both an exporter and a
+// formatter plugin, sharing `openinv_free_string`.
+
+use anyhow::{anyhow, Context, Result};
+use libloading::{Library, Symbol};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::path::Path;
+use crate::output::format::InventoryItem;
+use crate::plugins::formatter::FREE_SYMBOL;
+
+pub const EXPORT_SYMBOL: &[u8] = b"openinv_export\0";
+
+type ExportFn = unsafe extern "C" fn(items_json: *const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(output: *mut c_char);
+
+/// Exports an item list for an external platform
+pub trait Plugin {
+    fn name(&self) -> &str;
+    fn export(&self, items: &[InventoryItem]) -> Result<String>;
+}
+
+/// An exporter plugin backed by a dynamically loaded library
+pub struct DynamicExporter {
+    name: String,
+    library: Library,
+}
+
+impl DynamicExporter {
+    pub fn load(name: &str, path: &Path) -> Result<Self> {
+        // Safety: loading runs the library's initializers; plugins are trusted like the binary itself
+        let library = unsafe { Library::new(path) }
+            .with_context(|| format!("Failed to load exporter plugin '{}' from {}", name, path.display()))?;
+
+        // Check the exports now so a bad plugin fails at load time rather than mid-export
+        unsafe {
+            library.get::<ExportFn>(EXPORT_SYMBOL)
+                .with_context(|| format!("Plugin '{}' does not export openinv_export", name))?;
+            library.get::<FreeFn>(FREE_SYMBOL)
+                .with_context(|| format!("Plugin '{}' does not export openinv_free_string", name))?;
+        }
+
+        Ok(Self { name: name.to_string(), library })
+    }
+}
+
+impl Plugin for DynamicExporter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn export(&self, items: &[InventoryItem]) -> Result<String> {
+        let items_json = CString::new(serde_json::to_string(items)?)?;
+
+        // Safety: the signatures are the documented plugin ABI, and the returned
+        // string is only read before being passed back to the plugin to free
+        unsafe {
+            let export: Symbol<ExportFn> = self.library.get(EXPORT_SYMBOL)?;
+            let free: Symbol<FreeFn> = self.library.get(FREE_SYMBOL)?;
+
+            let raw = export(items_json.as_ptr());
+            if raw.is_null() {
+                return Err(anyhow!("Exporter plugin '{}' failed to export items", self.name));
+            }
+            let output = CStr::from_ptr(raw).to_string_lossy().into_owned();
+            free(raw);
+            Ok(output)
+        }
+    }
+}
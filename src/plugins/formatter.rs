@@ -0,0 +1,79 @@
+// Formatter plugins
+// Custom output formats, selected with `--format custom:NAME`
+//
+// A formatter plugin is a shared library exporting two C functions:
+//
+//     char *openinv_format(const char *items_json);
+//     void openinv_free_string(char *output);
+//
+// `openinv_format` receives the item list as a JSON array (the same shape as `--format json`)
+// and returns the formatted output as a NUL-terminated UTF-8 string, or NULL on failure.
+// The string is handed back to `openinv_free_string` once it has been copied.
+
+use anyhow::{anyhow, Context, Result};
+use libloading::{Library, Symbol};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::path::Path;
+use crate::output::format::InventoryItem;
+
+pub const FORMAT_SYMBOL: &[u8] = b"openinv_format\0";
+pub const FREE_SYMBOL: &[u8] = b"openinv_free_string\0";
+
+type FormatFn = unsafe extern "C" fn(items_json: *const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(output: *mut c_char);
+
+/// Turns an item list into output text
+pub trait FormatterPlugin {
+    fn name(&self) -> &str;
+    fn format(&self, items: &[InventoryItem]) -> Result<String>;
+}
+
+/// A formatter plugin backed by a dynamically loaded library
+pub struct DynamicFormatter {
+    name: String,
+    library: Library,
+}
+
+impl DynamicFormatter {
+    pub fn load(name: &str, path: &Path) -> Result<Self> {
+        // Safety: loading runs the library's initializers; plugins are trusted like the binary itself
+        let library = unsafe { Library::new(path) }
+            .with_context(|| format!("Failed to load formatter plugin '{}' from {}", name, path.display()))?;
+
+        // Check the exports now so a bad plugin fails at load time rather than mid-output
+        unsafe {
+            library.get::<FormatFn>(FORMAT_SYMBOL)
+                .with_context(|| format!("Formatter plugin '{}' does not export openinv_format", name))?;
+            library.get::<FreeFn>(FREE_SYMBOL)
+                .with_context(|| format!("Formatter plugin '{}' does not export openinv_free_string", name))?;
+        }
+
+        Ok(Self { name: name.to_string(), library })
+    }
+}
+
+impl FormatterPlugin for DynamicFormatter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn format(&self, items: &[InventoryItem]) -> Result<String> {
+        let items_json = CString::new(serde_json::to_string(items)?)?;
+
+        // Safety: the signatures are the documented plugin ABI, and the returned
+        // string is only read before being passed back to the plugin to free
+        unsafe {
+            let format: Symbol<FormatFn> = self.library.get(FORMAT_SYMBOL)?;
+            let free: Symbol<FreeFn> = self.library.get(FREE_SYMBOL)?;
+
+            let raw = format(items_json.as_ptr());
+            if raw.is_null() {
+                return Err(anyhow!("Formatter plugin '{}' failed to format items", self.name));
+            }
+            let output = CStr::from_ptr(raw).to_string_lossy().into_owned();
+            free(raw);
+            Ok(output)
+        }
+    }
+}
@@ -1,2 +1,118 @@
 // Plugin loader implementation
-// TODO: Implement dynamic plugin loading 
\ No newline at end of file
+// Plugins are shared libraries found by name in the plugin directory
+
+use anyhow::{anyhow, Result};
+use dirs::home_dir;
+use libloading::Library;
+use std::path::{Path, PathBuf};
+use crate::plugins::exporter::{DynamicExporter, Plugin, EXPORT_SYMBOL};
+use crate::plugins::formatter::{DynamicFormatter, FormatterPlugin, FORMAT_SYMBOL};
+use crate::error::error::not_found;
+
+/// Overrides the plugin directory, mainly for testing plugins before installing them
+pub const PLUGIN_DIR_ENV: &str = "INVENTORY_PLUGIN_DIR";
+
+/// Returns the plugin directory ($INVENTORY_PLUGIN_DIR or ~/.inventory/plugins)
+pub fn plugin_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os(PLUGIN_DIR_ENV) {
+        return PathBuf::from(dir);
+    }
+    let mut dir = home_dir().expect("Could not determine home directory");
+    dir.push(".inventory/plugins");
+    dir
+}
+
+/// Resolves a plugin name to its platform-specific library path, e.g. `myfmt` to
+/// `libmyfmt.so` on Linux. Names are restricted so they can't point outside the plugin directory.
+pub fn plugin_library_path(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(anyhow!("Invalid plugin name '{}': use letters, digits, '-' and '_'", name));
+    }
+    Ok(plugin_dir().join(libloading::library_filename(name)))
+}
+
+/// Loads the formatter plugin with the given name from the plugin directory
+pub fn load_formatter(name: &str) -> Result<Box<dyn FormatterPlugin>> {
+    let path = plugin_library_path(name)?;
+    if !path.exists() {
+        return Err(not_found(format!("Formatter plugin '{}' not found at {}", name, path.display())).into());
+    }
+    Ok(Box::new(DynamicFormatter::load(name, &path)?))
+}
+
+/// Loads the exporter plugin with the given name from the plugin directory
+pub fn load_exporter(name: &str) -> Result<Box<dyn Plugin>> {
+    let path = plugin_library_path(name)?;
+    if !path.exists() {
+        return Err(not_found(format!("Exporter plugin '{}' not found at {}", name, path.display())).into());
+    }
+    Ok(Box::new(DynamicExporter::load(name, &path)?))
+}
+
+/// Library extensions picked up when scanning the plugin directory
+pub const PLUGIN_EXTENSIONS: [&str; 3] = ["so", "dll", "dylib"];
+
+/// A library found in the plugin directory and what it provides
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredPlugin {
+    /// The name to select it by, e.g. `myexp` for `libmyexp.so`
+    pub name: String,
+    pub path: PathBuf,
+    pub exporter: bool,
+    pub formatter: bool,
+    /// Why the library couldn't be loaded, if it couldn't
+    pub error: Option<String>,
+}
+
+/// The plugin name a library file is selected by: its file name without the
+/// platform's library prefix and extension (the inverse of `plugin_library_path`)
+fn plugin_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let name = stem.strip_prefix(std::env::consts::DLL_PREFIX).filter(|name| !name.is_empty()).unwrap_or(stem);
+    Some(name.to_string())
+}
+
+fn inspect_plugin(name: String, path: PathBuf) -> DiscoveredPlugin {
+    // Safety: loading runs the library's initializers; plugins are trusted like the binary itself
+    match unsafe { Library::new(&path) } {
+        Ok(library) => {
+            let exports = |symbol: &[u8]| unsafe { library.get::<*const ()>(symbol).is_ok() };
+            DiscoveredPlugin {
+                exporter: exports(EXPORT_SYMBOL),
+                formatter: exports(FORMAT_SYMBOL),
+                name,
+                path,
+                error: None,
+            }
+        }
+        Err(e) => DiscoveredPlugin { name, path, exporter: false, formatter: false, error: Some(e.to_string()) },
+    }
+}
+
+/// Finds the plugin libraries (*.so, *.dll, *.dylib) in `dir`, sorted by name.
+/// A missing directory has no plugins.
+pub fn discover_plugins_in(dir: &Path) -> Result<Vec<DiscoveredPlugin>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut plugins = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_library = path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| PLUGIN_EXTENSIONS.contains(&ext));
+        if !path.is_file() || !is_library {
+            continue;
+        }
+        if let Some(name) = plugin_name(&path) {
+            plugins.push(inspect_plugin(name, path));
+        }
+    }
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(plugins)
+}
+
+/// Finds the plugin libraries in the plugin directory
+pub fn discover_plugins() -> Result<Vec<DiscoveredPlugin>> {
+    discover_plugins_in(&plugin_dir())
+}
@@ -1,3 +1,5 @@
 // Plugins module - will contain plugin loading and platform-specific implementations
+pub mod exporter;
+pub mod formatter;
 pub mod loader;
 pub mod platforms; 
\ No newline at end of file
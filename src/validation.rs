@@ -1,5 +1,11 @@
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use rusqlite::Connection;
+use crate::config::currency::{currency_info, DEFAULT_CURRENCY, MAX_PRICE};
+use crate::db::queries::get_item_by_sku;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ValidationError {
@@ -37,75 +43,149 @@ impl ValidationResult {
     }
 }
 
-// Platform-specific validation functions
+/// Longest title the items table stores, and every platform's limit unless configured lower
+pub const TITLE_MAX_LEN: usize = 80;
 
-pub fn validate_title_ebay(title: &str) -> Result<ValidationResult> {
-    let mut result = ValidationResult::new();
-    
-    if title.len() > 80 {
-        result.add_error("title", "Exceeds eBay's 80-character limit", None, Some(title));
+/// Longest title each platform accepts, from `[title_limits]` in the config file.
+/// The items table stores at most `TITLE_MAX_LEN` characters, so a limit can only be lowered.
+///
+/// ```toml
+/// [title_limits]
+/// poshmark = 60
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TitleLimits {
+    pub ebay: usize,
+    pub stockx: usize,
+    pub poshmark: usize,
+    pub mercari: usize,
+}
+
+impl TitleLimits {
+    pub const DEFAULT: TitleLimits = TitleLimits {
+        ebay: TITLE_MAX_LEN,
+        stockx: TITLE_MAX_LEN,
+        poshmark: TITLE_MAX_LEN,
+        mercari: TITLE_MAX_LEN,
+    };
+    
+    pub fn get(&self, platform: Platform) -> usize {
+        match platform {
+            Platform::Ebay => self.ebay,
+            Platform::Stockx => self.stockx,
+            Platform::Poshmark => self.poshmark,
+            Platform::Mercari => self.mercari,
+        }
     }
     
-    if title.trim().is_empty() {
-        result.add_error("title", "Title cannot be empty", None, Some(title));
+    /// Fails naming the first limit that's zero or longer than the items table allows
+    pub fn check(&self) -> Result<()> {
+        for platform in Platform::ALL {
+            let limit = self.get(platform);
+            if limit == 0 || limit > TITLE_MAX_LEN {
+                anyhow::bail!("title_limits.{} must be between 1 and {}, got {}", platform, TITLE_MAX_LEN, limit);
+            }
+        }
+        Ok(())
     }
-    
-    Ok(result)
 }
 
-pub fn validate_title_stockx(title: &str) -> Result<ValidationResult> {
-    let mut result = ValidationResult::new();
-    
-    if title.len() > 80 {
-        result.add_error("title", "Exceeds StockX's 80-character limit", None, Some(title));
+impl Default for TitleLimits {
+    fn default() -> Self {
+        Self::DEFAULT
     }
-    
-    if title.trim().is_empty() {
-        result.add_error("title", "Title cannot be empty", None, Some(title));
-    }
-    
-    Ok(result)
 }
 
-pub fn validate_title_poshmark(title: &str) -> Result<ValidationResult> {
-    let mut result = ValidationResult::new();
-    
-    if title.len() > 80 {
-        result.add_error("title", "Exceeds Poshmark's 80-character limit", None, Some(title));
+static TITLE_LIMITS: RwLock<TitleLimits> = RwLock::new(TitleLimits::DEFAULT);
+
+/// Set from the config file at startup; the title validators read it
+pub fn set_title_limits(limits: TitleLimits) {
+    *TITLE_LIMITS.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = limits;
+}
+
+/// The longest title `platform` currently accepts
+pub fn title_limit(platform: Platform) -> usize {
+    TITLE_LIMITS.read().unwrap_or_else(|poisoned| poisoned.into_inner()).get(platform)
+}
+
+/// Shortens a title to at most `limit` bytes, cutting at the last word break that fits
+/// (or the last whole character when a single word is too long)
+pub fn truncate_title(title: &str, limit: usize) -> String {
+    if title.len() <= limit {
+        return title.to_string();
     }
-    
-    if title.trim().is_empty() {
-        result.add_error("title", "Title cannot be empty", None, Some(title));
+    let mut end = limit;
+    while !title.is_char_boundary(end) {
+        end -= 1;
     }
-    
-    Ok(result)
+    let head = &title[..end];
+    // Only cut at a space if the next character doesn't continue the same word
+    let cut = if title[end..].starts_with(char::is_whitespace) {
+        head
+    } else {
+        head.rfind(char::is_whitespace).map_or(head, |space| &head[..space])
+    };
+    let cut = cut.trim_end();
+    if cut.is_empty() { head.to_string() } else { cut.to_string() }
 }
 
-pub fn validate_title_mercari(title: &str) -> Result<ValidationResult> {
+// Platform-specific validation functions
+
+/// Checks a title against the platform's configured length limit
+fn check_title(title: &str, platform: Platform, name: &str) -> ValidationResult {
     let mut result = ValidationResult::new();
     
-    if title.len() > 80 {
-        result.add_error("title", "Exceeds Mercari's 80-character limit", None, Some(title));
+    let limit = title_limit(platform);
+    if title.len() > limit {
+        result.add_error("title", &format!("Exceeds {}'s {}-character limit", name, limit), None, Some(title));
     }
     
     if title.trim().is_empty() {
         result.add_error("title", "Title cannot be empty", None, Some(title));
     }
     
-    Ok(result)
+    result
+}
+
+pub fn validate_title_ebay(title: &str) -> Result<ValidationResult> {
+    Ok(check_title(title, Platform::Ebay, "eBay"))
+}
+
+pub fn validate_title_stockx(title: &str) -> Result<ValidationResult> {
+    Ok(check_title(title, Platform::Stockx, "StockX"))
+}
+
+pub fn validate_title_poshmark(title: &str) -> Result<ValidationResult> {
+    Ok(check_title(title, Platform::Poshmark, "Poshmark"))
+}
+
+pub fn validate_title_mercari(title: &str) -> Result<ValidationResult> {
+    Ok(check_title(title, Platform::Mercari, "Mercari"))
 }
 
-pub fn validate_price(price: f64) -> Result<ValidationResult> {
+/// Validates a price against its currency's cap and number of decimal places.
+/// Unknown currencies are reported by `validate_currency` and bounded like USD here.
+pub fn validate_price(price: f64, currency: &str) -> Result<ValidationResult> {
     let mut result = ValidationResult::new();
+    let info = currency_info(currency);
+    let max_price = info.map_or(MAX_PRICE, |info| info.max_price());
+    let minor_units = info.map_or(2, |info| info.minor_units);
     
     if price < 0.0 {
         result.add_error("price", "Price must be non-negative", None, Some(&price.to_string()));
     }
     
-    if price > 999999.99 {
+    if price > max_price {
         result.add_error("price", "Price exceeds maximum allowed value", None, Some(&price.to_string()));
     }
     
+    let scaled = price * 10f64.powi(minor_units as i32);
+    if (scaled - scaled.round()).abs() > 1e-6 * scaled.abs().max(1.0) {
+        let message = format!("{} prices allow at most {} decimal places", currency.to_ascii_uppercase(), minor_units);
+        result.add_error("price", &message, None, Some(&price.to_string()));
+    }
+    
     Ok(result)
 }
 
@@ -123,6 +203,90 @@ pub fn validate_quantity(quantity: i32) -> Result<ValidationResult> {
     Ok(result)
 }
 
+/// Product code formats accepted in the `upc` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeKind {
+    Ean8,
+    Isbn10,
+    UpcA,
+    Ean13,
+    /// An EAN-13 in the 978/979 "Bookland" range
+    Isbn13,
+}
+
+impl std::fmt::Display for CodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodeKind::Ean8 => write!(f, "EAN-8"),
+            CodeKind::Isbn10 => write!(f, "ISBN-10"),
+            CodeKind::UpcA => write!(f, "UPC-A"),
+            CodeKind::Ean13 => write!(f, "EAN-13"),
+            CodeKind::Isbn13 => write!(f, "ISBN-13"),
+        }
+    }
+}
+
+/// The code's digits (and an ISBN-10's trailing 'X'), ignoring separators such as hyphens
+fn code_chars(code: &str) -> Vec<char> {
+    code.chars()
+        .filter(|c| c.is_ascii_digit() || c.eq_ignore_ascii_case(&'x'))
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Detects a product code's format from its length; 'X' is only allowed as an ISBN-10 check digit
+pub fn code_kind(code: &str) -> Option<CodeKind> {
+    let chars = code_chars(code);
+    let digits_before_check = chars[..chars.len().saturating_sub(1)].iter().all(char::is_ascii_digit);
+    let check_is_digit = chars.last().is_some_and(char::is_ascii_digit);
+    match chars.len() {
+        8 if digits_before_check && check_is_digit => Some(CodeKind::Ean8),
+        10 if digits_before_check => Some(CodeKind::Isbn10),
+        12 if digits_before_check && check_is_digit => Some(CodeKind::UpcA),
+        13 if digits_before_check && check_is_digit => {
+            let prefix: String = chars[..3].iter().collect();
+            if prefix == "978" || prefix == "979" { Some(CodeKind::Isbn13) } else { Some(CodeKind::Ean13) }
+        }
+        _ => None,
+    }
+}
+
+/// EAN-8 weights digits 3,1,3,1,... from the left; the total must be a multiple of 10
+fn ean8_checksum_ok(chars: &[char]) -> bool {
+    let total: u32 = chars.iter()
+        .enumerate()
+        .map(|(i, c)| c.to_digit(10).unwrap_or(0) * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    total.is_multiple_of(10)
+}
+
+/// ISBN-10 weights digits 10 down to 1, with 'X' worth 10; the total must be a multiple of 11
+fn isbn10_checksum_ok(chars: &[char]) -> bool {
+    let total: u32 = chars.iter()
+        .enumerate()
+        .map(|(i, c)| c.to_digit(10).unwrap_or(10) * (10 - i as u32))
+        .sum();
+    total.is_multiple_of(11)
+}
+
+/// Adds an error unless the code is a known format. EAN-8 and ISBN-10 check digits are verified;
+/// 12- and 13-digit codes keep the length-only check existing listings were accepted under.
+fn check_product_code(result: &mut ValidationResult, upc: &str) {
+    let Some(kind) = code_kind(upc) else {
+        result.add_error("upc", "UPC must be 8, 10, 12 or 13 digits (EAN-8, ISBN-10, UPC-A or EAN-13/ISBN-13)", None, Some(upc));
+        return;
+    };
+    let chars = code_chars(upc);
+    let checksum_ok = match kind {
+        CodeKind::Ean8 => ean8_checksum_ok(&chars),
+        CodeKind::Isbn10 => isbn10_checksum_ok(&chars),
+        CodeKind::UpcA | CodeKind::Ean13 | CodeKind::Isbn13 => true,
+    };
+    if !checksum_ok {
+        result.add_error("upc", &format!("Invalid {} check digit", kind), None, Some(upc));
+    }
+}
+
 pub fn validate_upc_stockx(upc: &str) -> Result<ValidationResult> {
     let mut result = ValidationResult::new();
     
@@ -131,11 +295,7 @@ pub fn validate_upc_stockx(upc: &str) -> Result<ValidationResult> {
         return Ok(result);
     }
     
-    // Basic UPC validation (12 or 13 digits)
-    let digits: Vec<char> = upc.chars().filter(|c| c.is_ascii_digit()).collect();
-    if digits.len() != 12 && digits.len() != 13 {
-        result.add_error("upc", "UPC must be 12 or 13 digits", None, Some(upc));
-    }
+    check_product_code(&mut result, upc);
     
     Ok(result)
 }
@@ -144,11 +304,7 @@ pub fn validate_upc_ebay(upc: &str) -> Result<ValidationResult> {
     let mut result = ValidationResult::new();
     
     if !upc.trim().is_empty() {
-        // Basic UPC validation (12 or 13 digits)
-        let digits: Vec<char> = upc.chars().filter(|c| c.is_ascii_digit()).collect();
-        if digits.len() != 12 && digits.len() != 13 {
-            result.add_error("upc", "UPC must be 12 or 13 digits", None, Some(upc));
-        }
+        check_product_code(&mut result, upc);
     }
     
     Ok(result)
@@ -174,19 +330,157 @@ pub fn validate_size_stockx(size: &str) -> Result<ValidationResult> {
     Ok(result)
 }
 
+/// The sizes a platform accepts for some kinds of item: every `step` from `min` to `max`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeChart {
+    pub platform: Platform,
+    /// What the sizes are, for error messages, e.g. "US sneaker"
+    pub name: &'static str,
+    /// The chart applies to items whose category contains one of these, ignoring case
+    pub categories: &'static [&'static str],
+    /// Region marker a size may be written with, as in "US 10" or "10us"
+    pub region: &'static str,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+impl SizeChart {
+    /// Whether items in `category` are sized by this chart
+    pub fn applies_to(&self, category: &str) -> bool {
+        let category = category.to_lowercase();
+        self.categories.iter().any(|keyword| category.contains(keyword))
+    }
+    
+    /// The size `size` stands for, without its region marker, if it's a number
+    fn parse(&self, size: &str) -> Option<f64> {
+        let size = size.trim().to_lowercase();
+        let size = size.strip_prefix(self.region).or_else(|| size.strip_suffix(self.region)).unwrap_or(&size);
+        size.trim().parse().ok().filter(|size: &f64| size.is_finite())
+    }
+    
+    /// Every size on the chart, smallest first
+    pub fn sizes(&self) -> impl Iterator<Item = f64> + '_ {
+        let steps = ((self.max - self.min) / self.step).round() as usize;
+        (0..=steps).map(move |i| self.min + i as f64 * self.step)
+    }
+    
+    pub fn contains(&self, size: f64) -> bool {
+        self.sizes().any(|valid| (valid - size).abs() < 1e-9)
+    }
+    
+    /// The one or two chart sizes closest to `size`: both neighbours when it falls between them
+    pub fn nearest(&self, size: f64) -> Vec<f64> {
+        let below = self.sizes().filter(|&valid| valid < size).last();
+        let above = self.sizes().find(|&valid| valid > size);
+        below.into_iter().chain(above).collect()
+    }
+}
+
+/// US men's sneaker sizes StockX lists, 3.5 to 18 in half sizes
+pub const US_SNEAKER_SIZES: SizeChart = SizeChart {
+    platform: Platform::Stockx,
+    name: "US sneaker",
+    categories: &["sneaker", "shoe", "footwear"],
+    region: "us",
+    min: 3.5,
+    max: 18.0,
+    step: 0.5,
+};
+
+/// Size charts platforms check sizes against; an item no chart applies to only needs a size
+pub const SIZE_CHARTS: &[SizeChart] = &[US_SNEAKER_SIZES];
+
+/// The chart `platform` sizes an item in `category` by, if it has one
+pub fn size_chart(platform: Platform, category: &str) -> Option<&'static SizeChart> {
+    SIZE_CHARTS.iter().find(|chart| chart.platform == platform && chart.applies_to(category))
+}
+
+/// Checks a non-empty size is on `chart`, naming the nearest sizes that are when it isn't
+pub fn validate_size_on_chart(size: &str, chart: &SizeChart) -> Result<ValidationResult> {
+    let mut result = ValidationResult::new();
+    
+    let join = |sizes: Vec<f64>| sizes.iter().map(f64::to_string).collect::<Vec<_>>().join(" or ");
+    match chart.parse(size) {
+        Some(value) if chart.contains(value) => {}
+        Some(value) => {
+            let message = format!("'{}' isn't a {} size. Nearest valid: {}", size, chart.name, join(chart.nearest(value)));
+            result.add_error("size", &message, None, Some(size));
+        }
+        None => {
+            let message = format!(
+                "'{}' isn't a {} size; expected a number from {} to {} in steps of {}",
+                size, chart.name, chart.min, chart.max, chart.step
+            );
+            result.add_error("size", &message, None, Some(size));
+        }
+    }
+    
+    Ok(result)
+}
+
+fn validate_size_stockx_charted(size: &str, chart: Option<&SizeChart>) -> Result<ValidationResult> {
+    let mut result = validate_size_stockx(size)?;
+    if let (true, Some(chart)) = (result.is_valid(), chart) {
+        result.errors.extend(validate_size_on_chart(size, chart)?.errors);
+    }
+    Ok(result)
+}
+
+/// Requires a size from the US sneaker chart, as StockX does for footwear
+pub fn validate_size_stockx_sneaker(size: &str) -> Result<ValidationResult> {
+    validate_size_stockx_charted(size, Some(&US_SNEAKER_SIZES))
+}
+
+/// Conditions an item can be in, compared case-insensitively
+pub const CONDITIONS: [&str; 6] = ["new", "used", "deadstock", "like new", "good", "fair"];
+
+/// Common ways of writing a condition, and the one in `CONDITIONS` each means
+const CONDITION_ALIASES: [(&str, &str); 9] = [
+    ("brand new", "new"),
+    ("new with tags", "new"),
+    ("nwt", "new"),
+    ("pre-owned", "used"),
+    ("pre owned", "used"),
+    ("preowned", "used"),
+    ("ds", "deadstock"),
+    ("like-new", "like new"),
+    ("excellent", "like new"),
+];
+
+/// Furthest (in edits) a typo can be from a known value and still be suggested
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// The candidate closest to `value`, ignoring case, if it's near enough to be a typo of it
+pub fn closest_match<'a>(value: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let value = value.to_lowercase();
+    candidates.into_iter()
+        .map(|candidate| (strsim::levenshtein(&value, &candidate.to_lowercase()), candidate))
+        .filter(|&(distance, _)| distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Replaces an alias such as "pre-owned" with the condition it stands for. Anything else is
+/// returned as given, for `validate_condition` to accept or reject.
+pub fn normalize_condition(condition: &str) -> String {
+    let lower = condition.trim().to_lowercase();
+    CONDITION_ALIASES.iter()
+        .find(|(alias, _)| *alias == lower)
+        .map_or_else(|| condition.to_string(), |(_, canonical)| canonical.to_string())
+}
+
 pub fn validate_condition(condition: &str) -> Result<ValidationResult> {
     let mut result = ValidationResult::new();
     
-    let valid_conditions = ["new", "used", "deadstock", "like new", "good", "fair"];
-    let condition_lower = condition.to_lowercase();
+    let condition_lower = normalize_condition(condition).to_lowercase();
     
-    if !valid_conditions.contains(&condition_lower.as_str()) {
-        result.add_error(
-            "condition", 
-            &format!("Invalid condition. Must be one of: {}", valid_conditions.join(", ")), 
-            None, 
-            Some(condition)
-        );
+    if !CONDITIONS.contains(&condition_lower.as_str()) {
+        let message = match closest_match(&condition_lower, CONDITIONS) {
+            Some(suggestion) => format!("Invalid condition '{}'. Did you mean '{}'?", condition, suggestion),
+            None => format!("Invalid condition '{}'. Must be one of: {}", condition, CONDITIONS.join(", ")),
+        };
+        result.add_error("condition", &message, None, Some(condition));
     }
     
     Ok(result)
@@ -212,108 +506,209 @@ pub fn validate_brand(brand: &str) -> Result<ValidationResult> {
     Ok(result)
 }
 
+pub fn validate_currency(currency: &str) -> Result<ValidationResult> {
+    let mut result = ValidationResult::new();
+    
+    if currency_info(currency).is_none() {
+        result.add_error("currency", "Unknown currency; expected an ISO 4217 code such as USD", None, Some(currency));
+    }
+    
+    Ok(result)
+}
+
+/// Formats a release date is accepted in: ISO, then US month/day/year
+const RELEASE_DATE_FORMATS: [&str; 2] = ["%Y-%m-%d", "%m/%d/%Y"];
+
+/// A release date as stored, `YYYY-MM-DD`, or None if it isn't a real date in an accepted format
+pub fn normalize_release_date(date: &str) -> Option<String> {
+    let date = date.trim();
+    RELEASE_DATE_FORMATS.iter()
+        .find_map(|format| NaiveDate::parse_from_str(date, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+/// The release date is optional, but one that's given has to be a real date
+pub fn validate_release_date(date: &str) -> Result<ValidationResult> {
+    let mut result = ValidationResult::new();
+    
+    if !date.trim().is_empty() && normalize_release_date(date).is_none() {
+        result.add_error("release_date", "Invalid release date; expected YYYY-MM-DD or MM/DD/YYYY", None, Some(date));
+    }
+    
+    Ok(result)
+}
+
+/// SKUs are unique, so one another item already has is an error (checked here rather than left
+/// to the items table's constraint, which only reports a raw SQLite failure)
+pub fn validate_sku(conn: &Connection, sku: &str) -> Result<ValidationResult> {
+    validate_sku_except(conn, sku, None)
+}
+
+/// Like [`validate_sku`], but the item `except` may already have the SKU, as when it's
+/// written back to that item
+pub fn validate_sku_except(conn: &Connection, sku: &str, except: Option<i64>) -> Result<ValidationResult> {
+    let mut result = ValidationResult::new();
+    
+    if get_item_by_sku(conn, sku)?.is_some_and(|item| Some(item.item_id) != except) {
+        result.add_error("sku", "SKU already exists", None, Some(sku));
+    }
+    
+    Ok(result)
+}
+
+/// Marketplaces items can be validated for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    #[default]
+    Ebay,
+    Stockx,
+    Poshmark,
+    Mercari,
+}
+
+impl Platform {
+    /// Every supported platform, in the order results are reported
+    pub const ALL: [Platform; 4] = [Platform::Ebay, Platform::Stockx, Platform::Poshmark, Platform::Mercari];
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Platform::Ebay => write!(f, "ebay"),
+            Platform::Stockx => write!(f, "stockx"),
+            Platform::Poshmark => write!(f, "poshmark"),
+            Platform::Mercari => write!(f, "mercari"),
+        }
+    }
+}
+
+/// Validates a complete item for one platform
+pub fn validate_item_for(platform: Platform, item: &ItemFields) -> Result<ValidationResult> {
+    match platform {
+        Platform::Ebay => validate_item_ebay(item),
+        Platform::Stockx => validate_item_stockx(item),
+        Platform::Poshmark => validate_item_poshmark(item),
+        Platform::Mercari => validate_item_mercari(item),
+    }
+}
+
+/// Validates a complete item for every platform, keyed by platform name (e.g. "stockx")
+pub fn validate_item_all(item: &ItemFields) -> Result<HashMap<String, ValidationResult>> {
+    let mut results = HashMap::new();
+    for platform in Platform::ALL {
+        let result = validate_item_for(platform, item)?;
+        results.insert(platform.to_string(), result);
+    }
+    Ok(results)
+}
+
+/// A complete item's fields, as the platform validators check them. Platforms that require a
+/// UPC or size report a missing one as empty.
+#[derive(Debug, Clone, Copy)]
+pub struct ItemFields<'a> {
+    pub title: &'a str,
+    pub price: f64,
+    pub quantity: i32,
+    pub category: &'a str,
+    pub condition: &'a str,
+    pub brand: Option<&'a str>,
+    pub upc: Option<&'a str>,
+    pub size: Option<&'a str>,
+    pub currency: &'a str,
+}
+
+impl Default for ItemFields<'_> {
+    fn default() -> Self {
+        Self {
+            title: "",
+            price: 0.0,
+            quantity: 0,
+            category: "",
+            condition: "",
+            brand: None,
+            upc: None,
+            size: None,
+            currency: DEFAULT_CURRENCY,
+        }
+    }
+}
+
 // Platform-specific validation for complete items
-pub fn validate_item_ebay(
-    title: &str,
-    price: f64,
-    quantity: i32,
-    category: &str,
-    condition: &str,
-    brand: Option<&str>,
-    upc: Option<&str>,
-) -> Result<ValidationResult> {
+pub fn validate_item_ebay(item: &ItemFields) -> Result<ValidationResult> {
     let mut result = ValidationResult::new();
     
     // Validate individual fields
-    result.errors.extend(validate_title_ebay(title)?.errors);
-    result.errors.extend(validate_price(price)?.errors);
-    result.errors.extend(validate_quantity(quantity)?.errors);
-    result.errors.extend(validate_category(category)?.errors);
-    result.errors.extend(validate_condition(condition)?.errors);
-    
-    if let Some(brand_name) = brand {
+    result.errors.extend(validate_title_ebay(item.title)?.errors);
+    result.errors.extend(validate_price(item.price, item.currency)?.errors);
+    result.errors.extend(validate_currency(item.currency)?.errors);
+    result.errors.extend(validate_quantity(item.quantity)?.errors);
+    result.errors.extend(validate_category(item.category)?.errors);
+    result.errors.extend(validate_condition(item.condition)?.errors);
+    
+    if let Some(brand_name) = item.brand {
         result.errors.extend(validate_brand(brand_name)?.errors);
     }
     
-    if let Some(upc_code) = upc {
+    if let Some(upc_code) = item.upc {
         result.errors.extend(validate_upc_ebay(upc_code)?.errors);
     }
     
     Ok(result)
 }
 
-pub fn validate_item_stockx(
-    title: &str,
-    price: f64,
-    quantity: i32,
-    category: &str,
-    condition: &str,
-    brand: Option<&str>,
-    upc: &str,
-    size: &str,
-) -> Result<ValidationResult> {
+pub fn validate_item_stockx(item: &ItemFields) -> Result<ValidationResult> {
     let mut result = ValidationResult::new();
     
     // Validate individual fields
-    result.errors.extend(validate_title_stockx(title)?.errors);
-    result.errors.extend(validate_price(price)?.errors);
-    result.errors.extend(validate_quantity(quantity)?.errors);
-    result.errors.extend(validate_category(category)?.errors);
-    result.errors.extend(validate_condition(condition)?.errors);
-    result.errors.extend(validate_upc_stockx(upc)?.errors);
-    result.errors.extend(validate_size_stockx(size)?.errors);
-    
-    if let Some(brand_name) = brand {
+    result.errors.extend(validate_title_stockx(item.title)?.errors);
+    result.errors.extend(validate_price(item.price, item.currency)?.errors);
+    result.errors.extend(validate_currency(item.currency)?.errors);
+    result.errors.extend(validate_quantity(item.quantity)?.errors);
+    result.errors.extend(validate_category(item.category)?.errors);
+    result.errors.extend(validate_condition(item.condition)?.errors);
+    result.errors.extend(validate_upc_stockx(item.upc.unwrap_or(""))?.errors);
+    // Footwear sizes have to be on the chart; anything else just needs a size
+    result.errors.extend(validate_size_stockx_charted(item.size.unwrap_or(""), size_chart(Platform::Stockx, item.category))?.errors);
+    
+    if let Some(brand_name) = item.brand {
         result.errors.extend(validate_brand(brand_name)?.errors);
     }
     
     Ok(result)
 }
 
-pub fn validate_item_poshmark(
-    title: &str,
-    price: f64,
-    quantity: i32,
-    category: &str,
-    condition: &str,
-    brand: Option<&str>,
-    size: &str,
-) -> Result<ValidationResult> {
+pub fn validate_item_poshmark(item: &ItemFields) -> Result<ValidationResult> {
     let mut result = ValidationResult::new();
     
     // Validate individual fields
-    result.errors.extend(validate_title_poshmark(title)?.errors);
-    result.errors.extend(validate_price(price)?.errors);
-    result.errors.extend(validate_quantity(quantity)?.errors);
-    result.errors.extend(validate_category(category)?.errors);
-    result.errors.extend(validate_condition(condition)?.errors);
-    result.errors.extend(validate_size_poshmark(size)?.errors);
-    
-    if let Some(brand_name) = brand {
+    result.errors.extend(validate_title_poshmark(item.title)?.errors);
+    result.errors.extend(validate_price(item.price, item.currency)?.errors);
+    result.errors.extend(validate_currency(item.currency)?.errors);
+    result.errors.extend(validate_quantity(item.quantity)?.errors);
+    result.errors.extend(validate_category(item.category)?.errors);
+    result.errors.extend(validate_condition(item.condition)?.errors);
+    result.errors.extend(validate_size_poshmark(item.size.unwrap_or(""))?.errors);
+    
+    if let Some(brand_name) = item.brand {
         result.errors.extend(validate_brand(brand_name)?.errors);
     }
     
     Ok(result)
 }
 
-pub fn validate_item_mercari(
-    title: &str,
-    price: f64,
-    quantity: i32,
-    category: &str,
-    condition: &str,
-    brand: Option<&str>,
-) -> Result<ValidationResult> {
+pub fn validate_item_mercari(item: &ItemFields) -> Result<ValidationResult> {
     let mut result = ValidationResult::new();
     
     // Validate individual fields
-    result.errors.extend(validate_title_mercari(title)?.errors);
-    result.errors.extend(validate_price(price)?.errors);
-    result.errors.extend(validate_quantity(quantity)?.errors);
-    result.errors.extend(validate_category(category)?.errors);
-    result.errors.extend(validate_condition(condition)?.errors);
-    
-    if let Some(brand_name) = brand {
+    result.errors.extend(validate_title_mercari(item.title)?.errors);
+    result.errors.extend(validate_price(item.price, item.currency)?.errors);
+    result.errors.extend(validate_currency(item.currency)?.errors);
+    result.errors.extend(validate_quantity(item.quantity)?.errors);
+    result.errors.extend(validate_category(item.category)?.errors);
+    result.errors.extend(validate_condition(item.condition)?.errors);
+    
+    if let Some(brand_name) = item.brand {
         result.errors.extend(validate_brand(brand_name)?.errors);
     }
     
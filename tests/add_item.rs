@@ -1,7 +1,11 @@
+use std::io::Cursor;
 use std::process::Command;
 use rusqlite::Connection;
 use inventory::db::schema::CREATE_ITEMS_TABLE_SQL;
-use inventory::db::queries::count_items;
+use inventory::db::queries::{count_items, distinct_brands, distinct_categories, get_item_by_sku};
+use inventory::db::schema::initialize_database;
+use inventory::commands::add::{handle_add_item, prompt_for_item, ItemDraft};
+use inventory::validation::Platform;
 
 fn setup_test_db() -> Connection {
     let conn = Connection::open_in_memory().unwrap();
@@ -64,8 +68,7 @@ fn test_add_item_validation_error_title_too_long() {
         .output()
         .expect("Failed to execute command");
     
-    // Command should still exit successfully (validation error is not a program error)
-    assert!(output.status.success(), "Command should exit successfully even with validation errors");
+    assert_eq!(output.status.code(), Some(2), "Validation errors should exit with code 2");
     
     let stderr = String::from_utf8_lossy(&output.stderr);
     
@@ -90,7 +93,7 @@ fn test_add_item_validation_error_negative_price() {
         .output()
         .expect("Failed to execute command");
     
-    assert!(output.status.success(), "Command should exit successfully even with validation errors");
+    assert_eq!(output.status.code(), Some(2), "Validation errors should exit with code 2");
     
     let stderr = String::from_utf8_lossy(&output.stderr);
     
@@ -115,7 +118,7 @@ fn test_add_item_validation_error_invalid_condition() {
         .output()
         .expect("Failed to execute command");
     
-    assert!(output.status.success(), "Command should exit successfully even with validation errors");
+    assert_eq!(output.status.code(), Some(2), "Validation errors should exit with code 2");
     
     let stderr = String::from_utf8_lossy(&output.stderr);
     
@@ -139,7 +142,7 @@ fn test_add_item_validation_error_empty_title() {
         .output()
         .expect("Failed to execute command");
     
-    assert!(output.status.success(), "Command should exit successfully even with validation errors");
+    assert_eq!(output.status.code(), Some(2), "Validation errors should exit with code 2");
     
     let stderr = String::from_utf8_lossy(&output.stderr);
     
@@ -163,7 +166,7 @@ fn test_add_item_validation_error_empty_category() {
         .output()
         .expect("Failed to execute command");
     
-    assert!(output.status.success(), "Command should exit successfully even with validation errors");
+    assert_eq!(output.status.code(), Some(2), "Validation errors should exit with code 2");
     
     let stderr = String::from_utf8_lossy(&output.stderr);
     
@@ -187,7 +190,7 @@ fn test_add_item_validation_error_multiple_errors() {
         .output()
         .expect("Failed to execute command");
     
-    assert!(output.status.success(), "Command should exit successfully even with validation errors");
+    assert_eq!(output.status.code(), Some(2), "Validation errors should exit with code 2");
     
     let stderr = String::from_utf8_lossy(&output.stderr);
     
@@ -264,4 +267,150 @@ fn test_add_item_with_optional_fields() {
     assert!(stdout.contains("A test item with optional fields"), "Should show description");
     assert!(stdout.contains("UPC: 987654321098"), "Should show UPC");
     assert!(stdout.contains("Size: L"), "Should show size");
-} 
\ No newline at end of file
+} 
+
+#[test]
+fn test_add_item_validates_for_platform() {
+    let conn = initialize_database(None).unwrap();
+    let add = |platform, size: Option<&str>| handle_add_item(
+        "Air Jordan 1".to_string(), 150.0, 1, "sneakers".to_string(), "new".to_string(),
+        None, None, Some("123456789012".to_string()), size.map(str::to_string),
+        None, None, None, None, None, None, None,
+        false, platform, &conn,
+    );
+    
+    // StockX needs a size, which eBay doesn't
+    assert!(add(Platform::Stockx, None).is_err());
+    assert_eq!(count_items(&conn).unwrap(), 0);
+    add(Platform::Ebay, None).unwrap();
+    assert_eq!(count_items(&conn).unwrap(), 1);
+    add(Platform::Stockx, Some("10")).unwrap();
+    assert_eq!(count_items(&conn).unwrap(), 2);
+}
+
+#[test]
+fn test_add_item_normalizes_release_date() {
+    let conn = initialize_database(None).unwrap();
+    let add = |release_date: &str| handle_add_item(
+        "Air Jordan 1".to_string(), 150.0, 1, "sneakers".to_string(), "new".to_string(),
+        None, None, None, None, None, None, None, Some(release_date.to_string()), None, None, None,
+        false, Platform::Ebay, &conn,
+    );
+    
+    assert!(add("2023-13-45").is_err());
+    assert!(add("next tuesday").is_err());
+    assert_eq!(count_items(&conn).unwrap(), 0);
+    add("03/15/2023").unwrap();
+    let stored: String = conn.query_row("SELECT release_date FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(stored, "2023-03-15");
+}
+
+#[test]
+fn test_add_item_rejects_duplicate_sku() {
+    let conn = initialize_database(None).unwrap();
+    let add = |sku: &str| handle_add_item(
+        "Air Jordan 1".to_string(), 150.0, 1, "sneakers".to_string(), "new".to_string(),
+        None, None, None, None, None, None, None, None, None, None, Some(sku.to_string()),
+        false, Platform::Ebay, &conn,
+    );
+    
+    add("AJ1-CHI-10").unwrap();
+    assert!(add("AJ1-CHI-10").is_err());
+    assert_eq!(count_items(&conn).unwrap(), 1);
+    assert_eq!(get_item_by_sku(&conn, "AJ1-CHI-10").unwrap().unwrap().title, "Air Jordan 1");
+    add("AJ1-CHI-11").unwrap();
+    assert_eq!(count_items(&conn).unwrap(), 2);
+}
+
+#[test]
+fn test_add_wizard_prompts_for_missing_fields() {
+    let conn = initialize_database(None).unwrap();
+    let draft = ItemDraft { title: Some("Air Jordan 1".to_string()), category: Some("sneakers".to_string()), ..Default::default() };
+    
+    // A bad price and condition are asked again; StockX also needs a UPC and size
+    let mut input = Cursor::new("-5\n150\n2\nmint\nUsed\n123456789012\n10\n\n");
+    let mut output = Vec::new();
+    let item = prompt_for_item(&mut input, &mut output, draft, false, Platform::Stockx, &conn).unwrap().unwrap();
+    assert_eq!((item.price, item.quantity), (Some(150.0), Some(2)));
+    assert_eq!(item.condition.as_deref(), Some("used"));
+    assert_eq!((item.upc.as_deref(), item.size.as_deref()), (Some("123456789012"), Some("10")));
+    assert_eq!(item.brand, None);
+    
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Price (USD): "), "Title was given, so it isn't asked for");
+    assert!(output.contains("Condition (new, used, deadstock"));
+    assert_eq!(output.matches("Price (USD): ").count(), 2);
+    assert!(output.contains("Add this item? [Y/n]"));
+}
+
+#[test]
+fn test_add_wizard_declined_or_cut_short() {
+    let conn = initialize_database(None).unwrap();
+    let draft = ItemDraft { title: Some("Hoodie".to_string()), price: Some(40.0), quantity: Some(1), category: Some("apparel".to_string()), ..Default::default() };
+    
+    let mut output = Vec::new();
+    let mut declined = Cursor::new("new\nn\n");
+    assert!(prompt_for_item(&mut declined, &mut output, draft.clone(), false, Platform::Ebay, &conn).unwrap().is_none());
+    
+    let mut empty = Cursor::new("");
+    let err = prompt_for_item(&mut empty, &mut output, draft, false, Platform::Ebay, &conn).unwrap_err();
+    assert!(err.to_string().contains("Input ended"));
+}
+
+#[test]
+fn test_add_missing_fields_fails_without_terminal() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    initialize_database(Some(&db_path)).unwrap();
+    
+    let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .env("HOME", home.path())
+        .env("INVENTORY_DB_PATH", &db_path)
+        .args(["add", "--title", "Hoodie", "--price", "40"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Missing required options: --quantity, --category, --condition"), "{}", stderr);
+    
+    let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .env("HOME", home.path())
+        .env("INVENTORY_DB_PATH", &db_path)
+        .args(["add", "--interactive", "-t", "Hoodie", "-p", "40", "-q", "1", "-c", "apparel", "-n", "new"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--interactive needs a terminal"));
+}
+
+#[test]
+fn test_add_item_stores_condition_alias_as_canonical() {
+    let conn = initialize_database(None).unwrap();
+    handle_add_item(
+        "Air Jordan 1".to_string(), 150.0, 1, "sneakers".to_string(), "Pre-Owned".to_string(),
+        None, None, None, None, None, None, None, None, None, None, Some("AJ1".to_string()),
+        false, Platform::Ebay, &conn,
+    ).unwrap();
+    assert_eq!(get_item_by_sku(&conn, "AJ1").unwrap().unwrap().condition, "used");
+}
+
+#[test]
+fn test_add_item_strict_brands() {
+    let conn = initialize_database(None).unwrap();
+    let add = |brand: &str, strict| handle_add_item(
+        "Samba OG".to_string(), 100.0, 1, "sneakers".to_string(), "new".to_string(),
+        Some(brand.to_string()), None, None, None, None, None, None, None, None, None, None, strict, Platform::Ebay, &conn,
+    );
+    
+    add("Adidas", false).unwrap();
+    // A near miss is only a warning without --strict-brands
+    add("Addidas", false).unwrap();
+    assert_eq!(distinct_brands(&conn).unwrap(), ["Addidas", "Adidas"]);
+    assert_eq!(distinct_categories(&conn).unwrap(), ["sneakers"]);
+    
+    assert!(add("Adidass", true).is_err());
+    assert!(add("Puma", true).is_err());
+    assert_eq!(count_items(&conn).unwrap(), 2);
+    add("Adidas", true).unwrap();
+    assert_eq!(count_items(&conn).unwrap(), 3);
+}
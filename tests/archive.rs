@@ -0,0 +1,47 @@
+use inventory::db::schema::initialize_database;
+use inventory::db::queries::{count_items, get_all_items, get_items, set_item_status};
+use inventory::commands::archive::handle_archive;
+use rusqlite::Connection;
+
+fn insert_item(conn: &Connection, title: &str) -> i32 {
+    conn.execute(
+        "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+         VALUES (?, 10.0, 1, 'sneakers', 'new', datetime('now'), 'active')",
+        [title],
+    ).unwrap();
+    conn.last_insert_rowid() as i32
+}
+
+fn titles(conn: &Connection, include_archived: bool) -> Vec<String> {
+    get_items(conn, include_archived).unwrap().into_iter().map(|item| item.title).collect()
+}
+
+#[test]
+fn test_archive_keeps_rows_but_hides_them() {
+    let conn = initialize_database(None).unwrap();
+    let sold = insert_item(&conn, "Sold Item");
+    insert_item(&conn, "Active Item");
+
+    handle_archive(&[sold], "sold", &conn).unwrap();
+
+    assert_eq!(count_items(&conn).unwrap(), 2);
+    assert_eq!(titles(&conn, false), vec!["Active Item".to_string()]);
+    assert_eq!(titles(&conn, true), vec!["Sold Item".to_string(), "Active Item".to_string()]);
+    let active: Vec<String> = get_all_items(&conn).unwrap().into_iter().map(|item| item.title).collect();
+    assert_eq!(active, titles(&conn, false));
+
+    // Setting the status back to active unarchives it
+    handle_archive(&[sold], "active", &conn).unwrap();
+    assert_eq!(titles(&conn, false).len(), 2);
+}
+
+#[test]
+fn test_set_item_status_validation() {
+    let conn = initialize_database(None).unwrap();
+    let id = insert_item(&conn, "Item") as i64;
+
+    let err = set_item_status(&conn, id, "archived").unwrap_err();
+    assert!(err.to_string().contains("expected one of active, sold, draft"));
+    assert_eq!(set_item_status(&conn, 999, "draft").unwrap(), 0);
+    assert!(handle_archive(&[999], "draft", &conn).is_err());
+}
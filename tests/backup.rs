@@ -0,0 +1,85 @@
+use inventory::db::schema::initialize_database;
+use inventory::commands::backup::{backup_path, handle_backup, handle_restore};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn insert_item(conn: &Connection, title: &str) {
+    conn.execute(
+        "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+         VALUES (?, 10.0, 1, 'sneakers', 'new', datetime('now'), 'active')",
+        [title],
+    ).unwrap();
+}
+
+fn titles(conn: &Connection) -> Vec<String> {
+    let mut stmt = conn.prepare("SELECT title FROM items ORDER BY item_id").unwrap();
+    stmt.query_map([], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+}
+
+#[test]
+fn test_backup_path_naming() {
+    let dir = TempDir::new().unwrap();
+    let explicit = dir.path().join("snapshot.db");
+    assert_eq!(backup_path(Some(&explicit)), explicit);
+
+    let timestamped = backup_path(Some(dir.path()));
+    assert_eq!(timestamped.parent(), Some(dir.path()));
+    let name = timestamped.file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with("inventory_") && name.ends_with(".db"));
+}
+
+#[test]
+fn test_backup_and_restore_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("inventory.db");
+    let mut conn = initialize_database(Some(&db_path)).unwrap();
+    conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+    insert_item(&conn, "Kept");
+
+    let backup_file = handle_backup(&conn, Some(dir.path().join("backups"))).unwrap();
+    assert!(backup_file.exists());
+
+    // Backing up to the same file again must not overwrite it
+    assert!(handle_backup(&conn, Some(backup_file.clone())).is_err());
+
+    insert_item(&conn, "Added after backup");
+    assert_eq!(titles(&conn).len(), 2);
+
+    handle_restore(&mut conn, &backup_file, true).unwrap();
+    assert_eq!(titles(&conn), vec!["Kept".to_string()]);
+
+    // The search index comes back with the data
+    let hits: i64 = conn.query_row("SELECT COUNT(*) FROM items_fts WHERE items_fts MATCH 'kept'", [], |row| row.get(0)).unwrap();
+    assert_eq!(hits, 1);
+}
+
+#[test]
+fn test_restore_requires_force() {
+    let dir = TempDir::new().unwrap();
+    let source = initialize_database(None).unwrap();
+    insert_item(&source, "From backup");
+    let backup_file = handle_backup(&source, Some(dir.path().join("backup.db"))).unwrap();
+
+    let mut conn = initialize_database(None).unwrap();
+    insert_item(&conn, "Live");
+
+    let err = handle_restore(&mut conn, &backup_file, false).unwrap_err();
+    assert!(err.to_string().contains("--force"));
+    assert_eq!(titles(&conn), vec!["Live".to_string()]);
+}
+
+#[test]
+fn test_restore_rejects_non_inventory_database() {
+    let dir = TempDir::new().unwrap();
+    let other = dir.path().join("other.db");
+    Connection::open(&other).unwrap()
+        .execute("CREATE TABLE notes (body TEXT)", []).unwrap();
+
+    let mut conn = initialize_database(None).unwrap();
+    insert_item(&conn, "Live");
+
+    let err = handle_restore(&mut conn, &other, true).unwrap_err();
+    assert!(format!("{:#}", err).contains("no items table"));
+    assert!(handle_restore(&mut conn, &dir.path().join("missing.db"), true).is_err());
+    assert_eq!(titles(&conn), vec!["Live".to_string()]);
+}
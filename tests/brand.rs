@@ -0,0 +1,106 @@
+use inventory::commands::brand::rebrand_items;
+use inventory::db::history::undo_last_operation;
+use inventory::db::queries::{insert_item, NewItem};
+use inventory::db::schema::initialize_database;
+use inventory::error::error::ExitCode;
+use rusqlite::Connection;
+use std::process::Command;
+
+fn insert(conn: &Connection, title: &str, brand: &str) -> i64 {
+    insert_item(conn, &NewItem {
+        title,
+        description: None,
+        price: 100.0,
+        quantity: 1,
+        photos: None,
+        category: "sneakers",
+        condition: "new",
+        brand: Some(brand),
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    }).unwrap();
+    conn.last_insert_rowid()
+}
+
+fn brands(conn: &Connection) -> Vec<String> {
+    conn.prepare("SELECT brand FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .collect::<rusqlite::Result<_>>().unwrap()
+}
+
+fn names(brands: &[&str]) -> Vec<String> {
+    brands.iter().map(|brand| brand.to_string()).collect()
+}
+
+#[test]
+fn test_rebrand_matches_exactly_unless_ignoring_case() {
+    let conn = initialize_database(None).unwrap();
+    for (title, brand) in [("Superstar", "Addidas"), ("Samba", "addidas"), ("Gazelle", "Adidas"), ("Dunk", "Nike")] {
+        insert(&conn, title, brand);
+    }
+
+    assert_eq!(rebrand_items(&conn, &names(&["Addidas"]), "Adidas", false).unwrap(), 1);
+    assert_eq!(brands(&conn), ["Adidas", "addidas", "Adidas", "Nike"]);
+
+    // Items already spelled like the new brand don't count as changed
+    assert_eq!(rebrand_items(&conn, &names(&["ADDIDAS", "adidas"]), "Adidas", true).unwrap(), 1);
+    assert_eq!(brands(&conn), ["Adidas", "Adidas", "Adidas", "Nike"]);
+
+    // The whole change is one operation for undo
+    undo_last_operation(&conn).unwrap();
+    assert_eq!(brands(&conn), ["Adidas", "addidas", "Adidas", "Nike"]);
+}
+
+#[test]
+fn test_rebrand_rejects_unknown_or_blank_brands() {
+    let conn = initialize_database(None).unwrap();
+    insert(&conn, "Superstar", "Addidas");
+    insert(&conn, "Dunk", "Nike");
+
+    // A brand nobody has is probably a typo, so nothing changes
+    let err = rebrand_items(&conn, &names(&["Nike", "Reebok"]), "Adidas", false).unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::NotFound);
+    assert_eq!(err.to_string(), "No items have brand 'Reebok'");
+    let err = rebrand_items(&conn, &names(&["addidas"]), "Adidas", false).unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::NotFound);
+    assert_eq!(brands(&conn), ["Addidas", "Nike"]);
+
+    let err = rebrand_items(&conn, &names(&["Addidas"]), "  ", false).unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::Validation);
+}
+
+#[test]
+fn test_brand_command() {
+    let home = tempfile::tempdir().unwrap();
+    let openinv = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env_remove("INVENTORY_DB_PATH")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    for (title, brand) in [("Superstar", "Addidas"), ("Samba", "adiddas"), ("Dunk", "Nike")] {
+        openinv(&["add", "-t", title, "-p", "100", "-q", "1", "-c", "sneakers", "-n", "new", "-b", brand]);
+    }
+    let renamed = openinv(&["brand", "rename", "addidas", "Adidas", "--ignore-case"]);
+    assert!(renamed.starts_with("✓ Renamed brand 'addidas' to 'Adidas' on 1 item(s)"), "{}", renamed);
+    let merged = openinv(&["brand", "merge", "adiddas", "Nike", "--into", "Adidas"]);
+    assert!(merged.starts_with("✓ Merged adiddas, Nike into 'Adidas' on 2 item(s)"), "{}", merged);
+    assert_eq!(openinv(&["count", "--brand", "Adidas"]).trim(), "3");
+}
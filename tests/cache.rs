@@ -0,0 +1,61 @@
+use std::process::Command;
+
+#[test]
+fn test_cache_command_reports_and_clears() {
+    let home = tempfile::tempdir().unwrap();
+    let openinv = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env_remove("INVENTORY_DB_PATH")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let stats = || serde_json::from_str::<serde_json::Value>(&openinv(&["cache", "--stats", "--format", "json"])).unwrap();
+    
+    openinv(&["add", "-t", "Laptop", "-p", "999.99", "-q", "1", "-c", "electronics", "-n", "new"]);
+    // A miss that stores the result, then a hit in a later run
+    openinv(&["filter", "--format", "json"]);
+    openinv(&["filter", "--format", "json"]);
+    let before = stats();
+    assert_eq!((&before["entries"], &before["hits"], &before["misses"]), (&1.into(), &1.into(), &1.into()), "{}", before);
+    assert!(before["approx_bytes"].as_u64().unwrap() > 0);
+    
+    let text = openinv(&["cache"]);
+    assert!(text.starts_with("Entries: 1\nHits: 1\nMisses: 1\nApproximate memory: "), "{}", text);
+    
+    let cleared = openinv(&["cache", "--clear"]);
+    assert_eq!(cleared, "✓ Cleared 1 cached result(s)\n");
+    assert_eq!(stats(), serde_json::json!({"entries": 0, "hits": 0, "misses": 0, "approx_bytes": 0}));
+}
+
+#[test]
+fn test_cached_results_stay_with_their_database() {
+    let home = tempfile::tempdir().unwrap();
+    let openinv = |db: &str, args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .arg("--db-path")
+            .arg(home.path().join(db))
+            .args(args)
+            .env("HOME", home.path())
+            .env_remove("INVENTORY_DB_PATH")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    
+    openinv("a.db", &["add", "-t", "Only in A", "-p", "10", "-q", "1", "-c", "misc", "-n", "new"]);
+    openinv("b.db", &["add", "-t", "Only in B", "-p", "20", "-q", "2", "-c", "misc", "-n", "new"]);
+    
+    // Both databases share ~/.inventory/cache.json, but not each other's results
+    assert!(openinv("a.db", &["filter", "--format", "json"]).contains("Only in A"));
+    let filtered = openinv("b.db", &["filter", "--format", "json"]);
+    assert!(filtered.contains("Only in B") && !filtered.contains("Only in A"), "{}", filtered);
+    
+    let total = |db: &str| serde_json::from_str::<serde_json::Value>(&openinv(db, &["stats", "--format", "json"])).unwrap()["total_value"].clone();
+    assert_eq!(total("a.db"), 10.0);
+    assert_eq!(total("b.db"), 40.0);
+}
@@ -0,0 +1,169 @@
+use inventory::commands::categories::{handle_merge_categories, handle_rename_category};
+use inventory::db::history::undo_last_operation;
+use inventory::db::queries::{find_category, insert_item, list_categories, update_item, NewItem};
+use inventory::db::schema::{ensure_categories_table, initialize_database};
+use inventory::error::error::ExitCode;
+use rusqlite::Connection;
+use std::process::Command;
+
+fn insert(conn: &Connection, title: &str, category: &str) -> i64 {
+    insert_item(conn, &NewItem {
+        title,
+        description: None,
+        price: 100.0,
+        quantity: 1,
+        photos: None,
+        category,
+        condition: "new",
+        brand: None,
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    }).unwrap();
+    conn.last_insert_rowid()
+}
+
+fn filed_under(conn: &Connection, id: i64) -> (String, Option<i64>) {
+    conn.query_row("SELECT category, category_id FROM items WHERE item_id = ?", [id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+}
+
+fn counts(conn: &Connection) -> Vec<(String, i64)> {
+    list_categories(conn).unwrap().into_iter().map(|category| (category.name, category.items)).collect()
+}
+
+#[test]
+fn test_items_join_categories_ignoring_case() {
+    let conn = initialize_database(None).unwrap();
+    let first = insert(&conn, "Laptop", "Electronics");
+    let second = insert(&conn, "Phone", "electronics");
+    let third = insert(&conn, "Cap", "hats");
+
+    let electronics = find_category(&conn, "ELECTRONICS").unwrap().unwrap();
+    assert_eq!(filed_under(&conn, first), ("Electronics".to_string(), Some(electronics.id)));
+    // The existing spelling wins, so grouped stats see one category
+    assert_eq!(filed_under(&conn, second), ("Electronics".to_string(), Some(electronics.id)));
+
+    update_item(&conn, third, None, None, None, Some("ELECTRONICS"), None, None, None).unwrap();
+    assert_eq!(filed_under(&conn, third), ("Electronics".to_string(), Some(electronics.id)));
+    assert_eq!(counts(&conn), [("Electronics".to_string(), 3), ("hats".to_string(), 0)]);
+
+    // A blank category isn't one
+    let blank = insert(&conn, "Mystery box", "");
+    assert_eq!(filed_under(&conn, blank), (String::new(), None));
+}
+
+#[test]
+fn test_categories_migrated_from_existing_spellings() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(
+        "CREATE TABLE items (item_id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, category TEXT NOT NULL)",
+        [],
+    ).unwrap();
+    for (title, category) in [("A", "electronics"), ("B", "Electronics"), ("C", "electronics"), ("D", "hats"), ("E", "")] {
+        conn.execute("INSERT INTO items (title, category) VALUES (?, ?)", [title, category]).unwrap();
+    }
+
+    ensure_categories_table(&conn).unwrap();
+    // Running again on an up-to-date table is a no-op
+    ensure_categories_table(&conn).unwrap();
+
+    // The most common spelling names the category and every item takes it
+    assert_eq!(counts(&conn), [("electronics".to_string(), 3), ("hats".to_string(), 1)]);
+    let categories: Vec<String> = conn.prepare("SELECT category FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .collect::<rusqlite::Result<_>>().unwrap();
+    assert_eq!(categories, ["electronics", "electronics", "electronics", "hats", ""]);
+}
+
+#[test]
+fn test_rename_category_refiles_items() {
+    let conn = initialize_database(None).unwrap();
+    let laptop = insert(&conn, "Laptop", "electronic");
+    insert(&conn, "Phone", "electronic");
+    insert(&conn, "Cap", "hats");
+
+    assert_eq!(handle_rename_category(&conn, "Electronic", "Electronics").unwrap(), 2);
+    let renamed = find_category(&conn, "electronics").unwrap().unwrap();
+    assert_eq!(filed_under(&conn, laptop), ("Electronics".to_string(), Some(renamed.id)));
+    assert!(find_category(&conn, "electronic").unwrap().is_none());
+
+    // Only the case of a name can match another category's
+    assert_eq!(handle_rename_category(&conn, "electronics", "ELECTRONICS").unwrap(), 2);
+    let err = handle_rename_category(&conn, "hats", "electronics").unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::Validation);
+    assert!(err.to_string().contains("merge"), "{}", err);
+    let err = handle_rename_category(&conn, "shoes", "sneakers").unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::NotFound);
+
+    // Undo puts the items back under the old name
+    undo_last_operation(&conn).unwrap();
+    assert_eq!(filed_under(&conn, laptop).0, "Electronics");
+}
+
+#[test]
+fn test_merge_categories_moves_items_and_deletes_sources() {
+    let conn = initialize_database(None).unwrap();
+    let laptop = insert(&conn, "Laptop", "Electronics");
+    let phone = insert(&conn, "Phone", "electronic");
+    let radio = insert(&conn, "Radio", "electronica");
+    insert(&conn, "Cap", "hats");
+
+    assert_eq!(handle_merge_categories(&conn, &["electronic".to_string(), "ELECTRONICA".to_string()], "electronics").unwrap(), 2);
+    let target = find_category(&conn, "Electronics").unwrap().unwrap();
+    for id in [laptop, phone, radio] {
+        assert_eq!(filed_under(&conn, id), ("Electronics".to_string(), Some(target.id)));
+    }
+    assert_eq!(counts(&conn), [("Electronics".to_string(), 3), ("hats".to_string(), 1)]);
+
+    let err = handle_merge_categories(&conn, &["hats".to_string()], "HATS").unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::Validation);
+    let err = handle_merge_categories(&conn, &["hats".to_string()], "shoes").unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::NotFound);
+
+    // The merge is one operation, so one undo restores both source categories
+    undo_last_operation(&conn).unwrap();
+    assert_eq!(filed_under(&conn, phone).0, "electronic");
+    assert_eq!(filed_under(&conn, radio).0, "electronica");
+    assert_eq!(find_category(&conn, "electronic").unwrap().unwrap().items, 1);
+}
+
+#[test]
+fn test_categories_command() {
+    let home = tempfile::tempdir().unwrap();
+    let openinv = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env_remove("INVENTORY_DB_PATH")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    for (title, category) in [("Laptop", "Electronics"), ("Phone", "electronic"), ("Cap", "hats")] {
+        openinv(&["add", "-t", title, "-p", "100", "-q", "1", "-c", category, "-n", "new"]);
+    }
+    let table = openinv(&["categories"]);
+    assert!(table.contains("| electronic ") && table.contains("| Electronics "), "{}", table);
+
+    openinv(&["categories", "merge", "electronic", "--into", "Electronics"]);
+    openinv(&["categories", "rename", "hats", "Hats"]);
+    let listed: serde_json::Value = serde_json::from_str(&openinv(&["categories", "list", "--format", "json"])).unwrap();
+    assert_eq!(listed, serde_json::json!([
+        {"category": "Electronics", "items": 2},
+        {"category": "Hats", "items": 1},
+    ]));
+}
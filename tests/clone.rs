@@ -0,0 +1,66 @@
+use inventory::commands::clone::handle_clone;
+use inventory::db::history::{undo_last_operation, OperationKind};
+use inventory::db::queries::{get_full_item, insert_item, set_item_status, NewItem};
+use inventory::db::schema::initialize_database;
+use rusqlite::Connection;
+
+fn insert_sneaker(conn: &Connection) -> i64 {
+    insert_item(conn, &NewItem {
+        title: "Air Jordan 1",
+        description: Some("Deadstock"),
+        price: 180.5,
+        quantity: 1,
+        photos: Some("front.jpg"),
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        upc: Some("123456789012"),
+        item_specifics: None,
+        shipping_details: Some("USPS"),
+        size: Some("10"),
+        original_price: Some(170.0),
+        hashtags: Some("#jordan"),
+        colorway: Some("Chicago"),
+        release_date: Some("2015-05-30"),
+        platform_status: Some("ebay:listed"),
+        internal_notes: Some("Box damaged"),
+        status: "active",
+        currency: Some("EUR"),
+        import_run: None,
+        sku: None,
+    }).unwrap();
+    conn.last_insert_rowid()
+}
+
+#[test]
+fn test_clone_copies_item_with_overrides() {
+    let conn = initialize_database(None).unwrap();
+    let id = insert_sneaker(&conn);
+    set_item_status(&conn, id, "sold").unwrap();
+
+    handle_clone(id as i32, Some("11".to_string()), Some("Bred".to_string()), Some(3), None, None, &conn).unwrap();
+    let source = get_full_item(&conn, id).unwrap().unwrap();
+    let copy = get_full_item(&conn, id + 1).unwrap().unwrap();
+
+    assert_eq!((copy.size.as_deref(), copy.colorway.as_deref(), copy.quantity), (Some("11"), Some("Bred"), 3));
+    // Everything else is the original's, except that the copy is a fresh, unlisted item
+    assert_eq!((copy.status.as_str(), copy.platform_status.as_deref()), ("active", None));
+    assert_eq!(
+        (&copy.title, &copy.description, copy.price, &copy.brand, &copy.upc, &copy.currency, &copy.internal_notes),
+        (&source.title, &source.description, source.price, &source.brand, &source.upc, &source.currency, &source.internal_notes),
+    );
+
+    let outcome = undo_last_operation(&conn).unwrap().unwrap();
+    assert_eq!((outcome.kind, outcome.items), (OperationKind::Add, 1));
+    assert!(get_full_item(&conn, id + 1).unwrap().is_none());
+}
+
+#[test]
+fn test_clone_missing_item() {
+    let conn = initialize_database(None).unwrap();
+    let err = handle_clone(42, None, None, None, None, None, &conn).unwrap_err();
+    assert_eq!(err.to_string(), "Item with ID 42 does not exist");
+
+    let id = insert_sneaker(&conn);
+    assert!(handle_clone(id as i32, None, None, Some(-1), None, None, &conn).is_err());
+}
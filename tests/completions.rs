@@ -0,0 +1,22 @@
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn test_completions_bash() {
+    let home = TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(["completions", "bash"])
+        .env("HOME", home.path())
+        .env_remove("INVENTORY_DB_PATH")
+        .output()
+        .unwrap();
+    
+    assert!(output.status.success());
+    let script = String::from_utf8(output.stdout).unwrap();
+    assert!(!script.is_empty());
+    assert!(script.contains("openinv"));
+    assert!(script.contains("import"));
+    
+    // Printing completions doesn't create the inventory database
+    assert!(!home.path().join(".inventory/inventory.db").exists());
+}
@@ -0,0 +1,87 @@
+use inventory::config::config::Config;
+use inventory::commands::list::OutputFormat;
+use inventory::logging::logger::LogLevel;
+use inventory::validation::Platform;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+#[test]
+fn test_missing_config_file_is_all_defaults() {
+    let dir = TempDir::new().unwrap();
+    let config = Config::load_from(&dir.path().join("config.toml")).unwrap();
+    assert_eq!(config, Config::default());
+}
+
+#[test]
+fn test_load_config_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, r#"
+format = "table"
+platform = "stockx"
+db_path = "/data/inventory.db"
+cache_ttl = 120
+log_level = "debug"
+low_stock_threshold = 3
+perf_report = false
+perf_report_dir = "/data/reports"
+busy_retries = 2
+
+[title_limits]
+mercari = 40
+"#).unwrap();
+    
+    let config = Config::load_from(&path).unwrap();
+    assert_eq!(config.format, Some(OutputFormat::Table));
+    assert_eq!(config.platform, Some(Platform::Stockx));
+    assert_eq!(config.db_path, Some(PathBuf::from("/data/inventory.db")));
+    assert_eq!(config.cache_ttl, Some(120));
+    assert_eq!(config.log_level, Some(LogLevel::Debug));
+    assert_eq!(config.low_stock_threshold, Some(3));
+    assert_eq!(config.perf_report, Some(false));
+    assert_eq!(config.perf_report_dir, Some(PathBuf::from("/data/reports")));
+    assert_eq!(config.busy_retries, Some(2));
+    assert_eq!(config.title_limits.get(Platform::Mercari), 40);
+    assert_eq!(config.title_limits.get(Platform::Ebay), 80);
+    
+    // Keys left out keep their defaults
+    std::fs::write(&path, "format = \"custom:MyFmt\"\n").unwrap();
+    let config = Config::load_from(&path).unwrap();
+    assert_eq!(config.format, Some(OutputFormat::Custom("MyFmt".to_string())));
+    assert_eq!(config.db_path, None);
+}
+
+#[test]
+fn test_invalid_config_values() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    
+    for (contents, expected) in [
+        ("format = \"xml\"", "invalid format 'xml'"),
+        ("platform = \"etsy\"", "unknown variant `etsy`"),
+        ("colour = \"blue\"", "unknown field `colour`"),
+        ("[title_limits]\nebay = 100", "title_limits.ebay must be between 1 and 80, got 100"),
+        ("[title_limits]\netsy = 60", "unknown field `etsy`"),
+    ] {
+        std::fs::write(&path, contents).unwrap();
+        let err = format!("{:#}", Config::load_from(&path).unwrap_err());
+        assert!(err.contains(expected), "{}: {}", contents, err);
+    }
+}
+
+#[test]
+fn test_title_limits_apply_to_validation() {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir_all(home.path().join(".inventory")).unwrap();
+    std::fs::write(home.path().join(".inventory/config.toml"), "[title_limits]\nmercari = 10\n").unwrap();
+    
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(["validate-item", "-t", "Air Jordan 1", "-p", "150", "-q", "1", "-c", "sneakers", "-n", "new", "--format", "json"])
+        .env("HOME", home.path())
+        .env_remove("INVENTORY_DB_PATH")
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["ebay"]["valid"], true);
+    assert_eq!(report["mercari"]["errors"][0]["message"], "Exceeds Mercari's 10-character limit");
+}
@@ -0,0 +1,56 @@
+use inventory::db::schema::initialize_database;
+use inventory::commands::backup::handle_backup;
+use inventory::commands::db_diff::{diff_databases, FieldDifference, MissingItem};
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn insert_item(conn: &Connection, title: &str, price: f64) -> i64 {
+    conn.execute(
+        "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+         VALUES (?, ?, 1, 'sneakers', 'new', '2024-01-01T00:00:00.000000+00:00', 'active')",
+        rusqlite::params![title, price],
+    ).unwrap();
+    conn.last_insert_rowid()
+}
+
+#[test]
+fn test_db_diff_against_backup() {
+    let dir = TempDir::new().unwrap();
+    let conn = initialize_database(None).unwrap();
+    let changed = insert_item(&conn, "Changed", 10.0);
+    let removed = insert_item(&conn, "Removed", 20.0);
+    let backup = handle_backup(&conn, Some(dir.path().join("snapshot.db"))).unwrap();
+
+    // Identical right after the backup
+    assert!(diff_databases(&conn, &backup).unwrap().is_empty());
+
+    conn.execute("UPDATE items SET price = 12.5, last_updated = '2024-01-01T00:00:00.000000+00:00' WHERE item_id = ?", [changed]).unwrap();
+    conn.execute("DELETE FROM items WHERE item_id = ?", [removed]).unwrap();
+    let added = insert_item(&conn, "Added", 30.0);
+
+    let diff = diff_databases(&conn, &backup).unwrap();
+    assert_eq!(diff.only_in_live, vec![MissingItem { item_id: added, title: "Added".to_string() }]);
+    assert_eq!(diff.only_in_other, vec![MissingItem { item_id: removed, title: "Removed".to_string() }]);
+    assert_eq!(diff.changed, vec![FieldDifference {
+        item_id: changed,
+        field: "price".to_string(),
+        live: "12.5".to_string(),
+        other: "10".to_string(),
+    }]);
+
+    // The other database is detached afterwards, so diffing again works
+    assert!(diff_databases(&conn, &backup).is_ok());
+}
+
+#[test]
+fn test_db_diff_rejects_non_inventory_database() {
+    let dir = TempDir::new().unwrap();
+    let other = dir.path().join("other.db");
+    Connection::open(&other).unwrap()
+        .execute("CREATE TABLE notes (body TEXT)", []).unwrap();
+
+    let conn = initialize_database(None).unwrap();
+    let err = diff_databases(&conn, &other).unwrap_err();
+    assert!(format!("{:#}", err).contains("no items table"));
+    assert!(diff_databases(&conn, &dir.path().join("missing.db")).is_err());
+}
@@ -0,0 +1,62 @@
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// Runs the CLI with HOME pointed at `home`, so the default database, cache and config stay inside it
+fn openinv(home: &Path, args: &[&str], db_env: Option<&Path>) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_openinv"));
+    command.args(args).env("HOME", home).env_remove("INVENTORY_DB_PATH");
+    if let Some(path) = db_env {
+        command.env("INVENTORY_DB_PATH", path);
+    }
+    let output = command.output().unwrap();
+    assert!(output.status.success(), "{:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+fn add_args(title: &str) -> Vec<&str> {
+    vec!["add", "--title", title, "--price", "10.99", "--quantity", "1", "--category", "sneakers", "--condition", "new"]
+}
+
+fn listed_titles(output: &Output) -> Vec<String> {
+    let items: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    items.iter().map(|item| item["title"].as_str().unwrap().to_string()).collect()
+}
+
+#[test]
+fn test_items_persist_between_runs() {
+    let home = TempDir::new().unwrap();
+    let db = home.path().join("custom.db");
+    let db_arg = db.to_str().unwrap();
+    
+    let mut args = vec!["--db-path", db_arg];
+    args.extend(add_args("Flag Item"));
+    openinv(home.path(), &args, None);
+    
+    let listed = openinv(home.path(), &["--db-path", db_arg, "list"], None);
+    assert_eq!(listed_titles(&listed), vec!["Flag Item".to_string()]);
+    
+    // The environment variable selects the same file
+    let listed = openinv(home.path(), &["list"], Some(&db));
+    assert_eq!(listed_titles(&listed), vec!["Flag Item".to_string()]);
+    
+    // Without either, the default database under HOME is used and starts out empty
+    let listed = openinv(home.path(), &["list"], None);
+    assert!(listed_titles(&listed).is_empty());
+    assert!(home.path().join(".inventory/inventory.db").exists());
+}
+
+#[test]
+fn test_flag_overrides_env() {
+    let home = TempDir::new().unwrap();
+    let flag_db = home.path().join("flag.db");
+    let env_db = home.path().join("env.db");
+    
+    let mut args = vec!["--db-path", flag_db.to_str().unwrap()];
+    args.extend(add_args("Flag Item"));
+    openinv(home.path(), &args, Some(&env_db));
+    
+    assert!(flag_db.exists());
+    let listed = openinv(home.path(), &["list"], Some(&env_db));
+    assert!(listed_titles(&listed).is_empty());
+}
@@ -1,6 +1,7 @@
 use rusqlite::Connection;
-use inventory::db::schema::CREATE_ITEMS_TABLE_SQL;
+use inventory::db::schema::{CREATE_ITEMS_TABLE_SQL, ensure_currency_column, ensure_sku_column, has_column};
 use inventory::db::queries::{insert_item, NewItem, count_items};
+use inventory::db::migrate::{applied_migrations, current_version, latest_version, run_migrations, CREATE_SCHEMA_VERSION_TABLE_SQL, MIGRATIONS};
 
 fn setup_in_memory_db() -> Connection {
     let conn = Connection::open_in_memory().unwrap();
@@ -39,6 +40,9 @@ fn test_insert_valid_item() {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     };
     let rows = insert_item(&conn, &item).unwrap();
     assert_eq!(rows, 1);
@@ -70,6 +74,9 @@ fn test_title_length_constraint() {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     };
     let result = insert_item(&conn, &item);
     assert!(result.is_err(), "Should fail due to title length constraint");
@@ -98,6 +105,9 @@ fn test_price_nonnegative_constraint() {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     };
     let result = insert_item(&conn, &item);
     assert!(result.is_err(), "Should fail due to price >= 0 constraint");
@@ -126,6 +136,9 @@ fn test_quantity_nonnegative_constraint() {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     };
     let result = insert_item(&conn, &item);
     assert!(result.is_err(), "Should fail due to quantity >= 0 constraint");
@@ -154,7 +167,79 @@ fn test_status_constraint() {
         platform_status: None,
         internal_notes: None,
         status: "invalid_status",
+        currency: None,
+        import_run: None,
+        sku: None,
     };
     let result = insert_item(&conn, &item);
     assert!(result.is_err(), "Should fail due to status constraint");
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_currency_column_added_to_existing_table() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(
+        "CREATE TABLE items (item_id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, price REAL NOT NULL)",
+        [],
+    ).unwrap();
+    conn.execute("INSERT INTO items (title, price) VALUES ('Old Item', 5.0)", []).unwrap();
+    
+    ensure_currency_column(&conn).unwrap();
+    // Running again on an up-to-date table is a no-op
+    ensure_currency_column(&conn).unwrap();
+    
+    assert!(has_column(&conn, "currency").unwrap());
+    let currency: String = conn.query_row("SELECT currency FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(currency, "USD");
+}
+
+#[test]
+fn test_sku_column_added_to_existing_table_is_unique() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(
+        "CREATE TABLE items (item_id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, price REAL NOT NULL)",
+        [],
+    ).unwrap();
+    conn.execute("INSERT INTO items (title, price) VALUES ('Old Item', 5.0)", []).unwrap();
+    
+    ensure_sku_column(&conn).unwrap();
+    ensure_sku_column(&conn).unwrap();
+    
+    assert!(has_column(&conn, "sku").unwrap());
+    conn.execute("INSERT INTO items (title, price, sku) VALUES ('New Item', 5.0, 'SKU-1')", []).unwrap();
+    assert!(conn.execute("INSERT INTO items (title, price, sku) VALUES ('Copy', 5.0, 'SKU-1')", []).is_err());
+    // Items without a SKU don't collide
+    conn.execute("INSERT INTO items (title, price) VALUES ('Another Old Item', 5.0)", []).unwrap();
+}
+
+#[test]
+fn test_migrations_apply_once_in_order() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(
+        "CREATE TABLE items (item_id INTEGER PRIMARY KEY AUTOINCREMENT, title TEXT NOT NULL, price REAL NOT NULL)",
+        [],
+    ).unwrap();
+    assert_eq!(current_version(&conn).unwrap(), 0);
+    
+    let ran: Vec<u32> = run_migrations(&conn).unwrap().iter().map(|m| m.version).collect();
+    assert_eq!(ran, (1..=latest_version()).collect::<Vec<_>>());
+    assert!(has_column(&conn, "currency").unwrap() && has_column(&conn, "sku").unwrap());
+    assert_eq!(current_version(&conn).unwrap(), latest_version());
+    
+    // Running again is a no-op
+    assert!(run_migrations(&conn).unwrap().is_empty());
+    assert_eq!(applied_migrations(&conn).unwrap().len(), MIGRATIONS.len());
+}
+
+#[test]
+fn test_migrations_skip_versions_already_applied() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(CREATE_ITEMS_TABLE_SQL, []).unwrap();
+    conn.execute(CREATE_SCHEMA_VERSION_TABLE_SQL, []).unwrap();
+    conn.execute("INSERT INTO schema_version VALUES (1, 'Add currency column', '2024-01-01T00:00:00.000000+00:00')", []).unwrap();
+    
+    // The full table already has every column, so the later migrations only record themselves
+    let ran: Vec<u32> = run_migrations(&conn).unwrap().iter().map(|m| m.version).collect();
+    assert_eq!(ran, (2..=latest_version()).collect::<Vec<_>>());
+    assert_eq!(current_version(&conn).unwrap(), latest_version());
+}
@@ -1,6 +1,7 @@
 use std::process::Command;
 use rusqlite::Connection;
-use inventory::db::schema::{CREATE_ITEMS_TABLE_SQL, default_db_path};
+use inventory::db::schema::{CREATE_ITEMS_TABLE_SQL, default_db_path, initialize_database};
+use inventory::commands::delete::{delete_items, handle_delete_items, DeleteOutcome};
 use inventory::db::queries::{insert_item, NewItem, count_items};
 use std::fs;
 
@@ -36,6 +37,9 @@ fn insert_test_item(conn: &Connection, title: &str, price: f64, quantity: i32, c
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     };
     
     insert_item(conn, &item).unwrap();
@@ -150,4 +154,21 @@ fn test_delete_item_database_integration() {
     // Verify item no longer exists
     let result: Result<String, _> = stmt.query_row([item_id], |row| row.get(0));
     assert!(result.is_err(), "Item should no longer exist in database");
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_delete_multiple_ids_with_missing() {
+    let conn = initialize_database(None).unwrap();
+    let first = insert_test_item(&conn, "First", 10.0, 1, "test");
+    let second = insert_test_item(&conn, "Second", 20.0, 1, "test");
+    let kept = insert_test_item(&conn, "Kept", 30.0, 1, "test");
+    
+    let outcome = delete_items(&conn, &[first, 999, second]).unwrap();
+    assert_eq!(outcome, DeleteOutcome { deleted: 2, missing: vec![999] });
+    assert_eq!(count_items(&conn).unwrap(), 1);
+    
+    // --yes skips the prompt; unknown IDs are warnings as long as something exists
+    handle_delete_items(&[kept, 998], true, &conn).unwrap();
+    assert_eq!(count_items(&conn).unwrap(), 0);
+    assert!(handle_delete_items(&[kept], true, &conn).is_err());
+}
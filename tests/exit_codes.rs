@@ -0,0 +1,100 @@
+use inventory::db::schema::initialize_database;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn run(home: &Path, db_path: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(args)
+        .env("HOME", home)
+        .env("INVENTORY_DB_PATH", db_path)
+        .env("INVENTORY_NONINTERACTIVE", "1")
+        .output()
+        .unwrap()
+}
+
+fn assert_exit_code(output: &Output, code: i32) {
+    assert_eq!(output.status.code(), Some(code), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_exit_codes_by_failure_kind() {
+    let home = TempDir::new().unwrap();
+    let db_path = home.path().join("inventory.db");
+    initialize_database(Some(&db_path)).unwrap();
+    let run = |args: &[&str]| run(home.path(), &db_path, args);
+    
+    assert_exit_code(&run(&["add", "-t", "Air Jordan 1", "-p", "150", "-q", "1", "-c", "sneakers", "-n", "new"]), 0);
+    
+    // Validation: a rejected item, a file with bad rows, or a malformed command line
+    assert_exit_code(&run(&["add", "-t", "Air Jordan 1", "-p", "-5", "-q", "1", "-c", "sneakers", "-n", "new"]), 2);
+    let csv = home.path().join("items.csv");
+    std::fs::write(&csv, "item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,,shoes,mint,Nike\n").unwrap();
+    assert_exit_code(&run(&["validate", "--file", csv.to_str().unwrap()]), 2);
+    assert_exit_code(&run(&["list", "--sample", "0"]), 2);
+    
+    // Not found: an item ID or input file that doesn't exist
+    assert_exit_code(&run(&["history", "--id", "999"]), 3);
+    assert_exit_code(&run(&["delete", "--id", "998,999", "--yes"]), 3);
+    assert_exit_code(&run(&["archive", "--id", "998,999"]), 3);
+    assert_exit_code(&run(&["import", "--file", "missing.csv"]), 3);
+    
+    // IO: the export destination is a directory
+    assert_exit_code(&run(&["export", "-f", "csv", "-o", home.path().to_str().unwrap()]), 4);
+}
+
+#[test]
+fn test_failed_rows_exit_with_validation_code() {
+    let home = TempDir::new().unwrap();
+    let db_path = home.path().join("inventory.db");
+    initialize_database(Some(&db_path)).unwrap();
+    let run = |args: &[&str]| run(home.path(), &db_path, args);
+    
+    // Every row failing, or only some: the valid rows are committed, but the exit code says not all were
+    let csv = home.path().join("items.csv");
+    std::fs::write(&csv, "item_id,title,description,price,quantity,upc,category,condition,brand\n1,Bad Item,Desc,abc,5,,shoes,new,Nike\n").unwrap();
+    assert_exit_code(&run(&["import", "--file", csv.to_str().unwrap()]), 2);
+    std::fs::write(&csv, "item_id,title,description,price,quantity,upc,category,condition,brand\n1,Good Item,Desc,10.0,5,,shoes,new,Nike\n2,Bad Item,Desc,abc,5,,shoes,new,Nike\n").unwrap();
+    let output = run(&["import", "--file", csv.to_str().unwrap()]);
+    assert_exit_code(&output, 2);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Successfully imported: 1 items"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 rows failed to import"));
+    // A dry run exits the same way, so it works as a pre-flight check
+    let output = run(&["import", "--file", csv.to_str().unwrap(), "--dry-run"]);
+    assert_exit_code(&output, 2);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 rows would fail to import"));
+    std::fs::write(&csv, "item_id,title,description,price,quantity,upc,category,condition,brand\n3,Other Item,Desc,10.0,5,,shoes,new,Nike\n").unwrap();
+    assert_exit_code(&run(&["import", "--file", csv.to_str().unwrap(), "--dry-run"]), 0);
+    
+    let updates = home.path().join("updates.csv");
+    std::fs::write(&updates, "id,title\n999,Missing Item\n").unwrap();
+    assert_exit_code(&run(&["update", "--file", updates.to_str().unwrap()]), 2);
+}
+
+#[test]
+fn test_database_error_exit_code() {
+    let home = TempDir::new().unwrap();
+    let db_path = home.path().join("inventory.db");
+    std::fs::write(&db_path, "this is not a database, just some text that is long enough to read").unwrap();
+    
+    let output = run(home.path(), &db_path, &["list"]);
+    assert_exit_code(&output, 5);
+}
+
+#[test]
+fn test_json_error_format() {
+    let home = TempDir::new().unwrap();
+    let db_path = home.path().join("inventory.db");
+    initialize_database(Some(&db_path)).unwrap();
+    
+    let output = run(home.path(), &db_path, &["--error-format", "json", "history", "--id", "999"]);
+    assert_exit_code(&output, 3);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.lines().count(), 1, "{}", stderr);
+    let report: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(report, serde_json::json!({"error": {"kind": "not_found", "message": "Item with ID 999 does not exist"}}));
+    
+    // Text stays the default
+    let output = run(home.path(), &db_path, &["history", "--id", "999"]);
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("Error: Item with ID 999 does not exist\n"));
+}
@@ -0,0 +1,147 @@
+use inventory::db::schema::initialize_database;
+use inventory::db::queries::get_sample_items;
+use inventory::commands::export::{handle_export, handle_export_split, safe_file_stem, ExportFile, Sample, SplitBy};
+use inventory::commands::list::OutputFormat;
+use inventory::output::format::OutputOptions;
+use rusqlite::Connection;
+use tempfile::TempDir;
+
+fn setup_items(count: usize) -> Connection {
+    let conn = initialize_database(None).unwrap();
+    for i in 0..count {
+        conn.execute(
+            "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+             VALUES (?, 10.0, 1, 'sneakers', 'new', datetime('now'), 'active')",
+            [format!("Item {}", i)],
+        ).unwrap();
+    }
+    conn
+}
+
+fn ids(conn: &Connection, size: usize, seed: Option<u32>) -> Vec<i32> {
+    get_sample_items(conn, false, size, seed).unwrap().into_iter().map(|item| item.item_id).collect()
+}
+
+#[test]
+fn test_sample_size_and_order() {
+    let conn = setup_items(50);
+    
+    let sample = ids(&conn, 10, None);
+    assert_eq!(sample.len(), 10);
+    assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+    
+    // Asking for more than there is exports everything
+    assert_eq!(ids(&conn, 100, None).len(), 50);
+}
+
+#[test]
+fn test_seeded_sample_is_deterministic() {
+    let conn = setup_items(50);
+    
+    assert_eq!(ids(&conn, 10, Some(42)), ids(&conn, 10, Some(42)));
+    assert_ne!(ids(&conn, 10, Some(42)), ids(&conn, 10, Some(7)));
+    assert_ne!(ids(&conn, 10, Some(42)), (1..=10).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_export_sample_to_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sample.json");
+    let conn = setup_items(20);
+    
+    let sample = Sample { size: 5, seed: Some(1) };
+    handle_export(&conn, Some(OutputFormat::Json), Some(ExportFile { path: path.clone(), append: false }), false, Some(sample), None, &OutputOptions::default()).unwrap();
+    
+    let exported: Vec<serde_json::Value> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let exported_ids: Vec<i64> = exported.iter().map(|item| item["item_id"].as_i64().unwrap()).collect();
+    let expected: Vec<i64> = ids(&conn, 5, Some(1)).into_iter().map(i64::from).collect();
+    assert_eq!(exported_ids, expected);
+}
+
+#[test]
+fn test_export_append_csv_and_ndjson() {
+    let dir = TempDir::new().unwrap();
+    let conn = setup_items(2);
+    let append = |format: OutputFormat, path: &std::path::Path, options: &OutputOptions| {
+        handle_export(&conn, Some(format), Some(ExportFile { path: path.to_path_buf(), append: true }), false, None, None, options)
+    };
+    
+    // The first run creates the file with a header; later ones add rows under it
+    let csv = dir.path().join("master.csv");
+    append(OutputFormat::Csv, &csv, &OutputOptions::default()).unwrap();
+    append(OutputFormat::Csv, &csv, &OutputOptions::default()).unwrap();
+    let lines: Vec<String> = std::fs::read_to_string(&csv).unwrap().lines().map(String::from).collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("item_id,title"));
+    assert_eq!(lines[1], lines[3]);
+    
+    // Appended ndjson keeps the one schema line at the top
+    let ndjson = dir.path().join("master.ndjson");
+    let options = OutputOptions { ndjson_schema: true, ..Default::default() };
+    append(OutputFormat::Ndjson, &ndjson, &options).unwrap();
+    append(OutputFormat::Ndjson, &ndjson, &options).unwrap();
+    let contents = std::fs::read_to_string(&ndjson).unwrap();
+    assert_eq!(contents.lines().count(), 5);
+    assert_eq!(contents.matches("\"schema\"").count(), 1);
+    
+    // A CSV with other columns isn't extended with mismatched rows
+    let other = dir.path().join("other.csv");
+    std::fs::write(&other, "sku,price\nA1,10\n").unwrap();
+    let err = append(OutputFormat::Csv, &other, &OutputOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("doesn't match"), "{}", err);
+    assert_eq!(std::fs::read_to_string(&other).unwrap(), "sku,price\nA1,10\n");
+    
+    let json = dir.path().join("master.json");
+    let err = append(OutputFormat::Json, &json, &OutputOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("Can't append a JSON export"), "{}", err);
+    assert!(!json.exists());
+}
+
+#[test]
+fn test_safe_file_stem() {
+    assert_eq!(safe_file_stem("sneakers"), "sneakers");
+    assert_eq!(safe_file_stem(" Trading Cards/Pokémon "), "Trading_Cards_Pokémon");
+    assert_eq!(safe_file_stem("../etc"), "___etc");
+    assert_eq!(safe_file_stem("///"), "unnamed");
+}
+
+#[test]
+fn test_export_split_by_category_and_brand() {
+    let conn = initialize_database(None).unwrap();
+    for (title, category, brand) in [
+        ("Air Jordan 1", "sneakers", Some("Nike")),
+        ("Samba", "sneakers", Some("Adidas")),
+        ("Charizard", "Trading Cards/Pokémon", None),
+        ("Trading tin", "trading cards pokémon", Some("Nike")),
+    ] {
+        conn.execute(
+            "INSERT INTO items (title, price, quantity, category, condition, brand, last_updated, status) \
+             VALUES (?, 10.0, 1, ?, 'new', ?, datetime('now'), 'active')",
+            rusqlite::params![title, category, brand],
+        ).unwrap();
+    }
+    let dir = TempDir::new().unwrap();
+    let out = dir.path().join("by_category");
+    
+    let written = handle_export_split(&conn, SplitBy::Category, Some(OutputFormat::Csv), &out, false, None, &OutputOptions::default()).unwrap();
+    let names: Vec<(String, usize)> = written.iter()
+        .map(|(path, count)| (path.file_name().unwrap().to_string_lossy().into_owned(), *count))
+        .collect();
+    // Categories that clean up to the same name (ignoring case) get a suffix
+    assert_eq!(names, [
+        ("export_Trading_Cards_Pokémon.csv".to_string(), 1),
+        ("export_sneakers.csv".to_string(), 2),
+        ("export_trading_cards_pokémon_2.csv".to_string(), 1),
+    ]);
+    let sneakers = std::fs::read_to_string(out.join("export_sneakers.csv")).unwrap();
+    assert_eq!(sneakers.lines().count(), 3);
+    assert!(sneakers.contains("Air Jordan 1") && sneakers.contains("Samba"));
+    
+    let out = dir.path().join("by_brand");
+    let written = handle_export_split(&conn, SplitBy::Brand, Some(OutputFormat::Json), &out, false, None, &OutputOptions::default()).unwrap();
+    let counts: Vec<usize> = written.iter().map(|(_, count)| *count).collect();
+    assert_eq!(counts, [1, 2, 1]);
+    assert!(out.join("export_no_brand.json").exists());
+    let nike: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(out.join("export_Nike.json")).unwrap()).unwrap();
+    assert_eq!(nike.as_array().unwrap().len(), 2);
+}
@@ -1,9 +1,10 @@
 use inventory::db::schema::initialize_database;
-use inventory::commands::filter::handle_filter;
+use inventory::commands::filter::{count_items, handle_filter, FilterArgs};
 use inventory::commands::list::OutputFormat;
 use rusqlite::Connection;
 use std::sync::Arc;
 use inventory::config::optimization::{PerformanceMonitor, QueryCache};
+use inventory::output::format::OutputOptions;
 
 fn setup_test_db() -> Connection {
     let conn = initialize_database(None).unwrap();
@@ -63,12 +64,13 @@ fn test_filter_by_price_range() {
     // Test price range filter
     let result = handle_filter(
         &conn,
-        Some("10-50".to_string()),
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("10-50".to_string()),
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -96,12 +98,14 @@ fn test_filter_by_category_and_condition() {
     // Test category and condition filter
     let result = handle_filter(
         &conn,
-        None,
-        Some("electronics".to_string()),
-        Some("new".to_string()),
-        None,
-        Some("item_id,title,category,condition".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            category: Some("electronics".to_string()),
+            condition: Some("new".to_string()),
+            fields: Some("item_id,title,category,condition".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -127,12 +131,12 @@ fn test_filter_field_shortcuts() {
     // Test field shortcuts
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        Some("id,t,p,q,c,cat,b".to_string()), // Using shortcuts
-        Some(OutputFormat::Json),
+        FilterArgs {
+            fields: Some("id,t,p,q,c,cat,b".to_string()), // Using shortcuts
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -142,12 +146,12 @@ fn test_filter_field_shortcuts() {
     // Test with mixed shortcuts and full names
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        Some("item_id,t,price,q,condition,cat,brand".to_string()), // Mixed
-        Some(OutputFormat::Json),
+        FilterArgs {
+            fields: Some("item_id,t,price,q,condition,cat,brand".to_string()), // Mixed
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -166,12 +170,12 @@ fn test_filter_output_formats() {
     // Test JSON format
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -180,12 +184,12 @@ fn test_filter_output_formats() {
     // Test table format
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Table),
+        FilterArgs {
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Table),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -194,12 +198,12 @@ fn test_filter_output_formats() {
     // Test CSV format
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Csv),
+        FilterArgs {
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Csv),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -217,12 +221,16 @@ fn test_filter_empty_results() {
     // Test filter that should return no results
     let result = handle_filter(
         &conn,
-        Some("100-200".to_string()), // Price range with no matches
-        Some("books".to_string()),   // Category with no matches
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("100-200".to_string()), // Price range with no matches
+            quantity: None,
+            category: Some("books".to_string()), // Category with no matches
+            condition: None,
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -245,12 +253,13 @@ fn test_filter_invalid_price_range() {
     // Test invalid price range format
     let result = handle_filter(
         &conn,
-        Some("invalid-price".to_string()),
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("invalid-price".to_string()),
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -260,12 +269,13 @@ fn test_filter_invalid_price_range() {
     // Test invalid price values
     let result = handle_filter(
         &conn,
-        Some("abc-def".to_string()),
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("abc-def".to_string()),
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -280,12 +290,12 @@ fn test_filter_unknown_fields() {
     // Test unknown field
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        Some("unknown_field".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            fields: Some("unknown_field".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -295,12 +305,12 @@ fn test_filter_unknown_fields() {
     // Test mixed valid and invalid fields
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        Some("item_id,unknown_field,title".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            fields: Some("item_id,unknown_field,title".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -321,12 +331,16 @@ fn test_filter_complex_combination() {
     // Test complex filter: Nike brand, clothing category, price under 100
     let result = handle_filter(
         &conn,
-        Some("-100".to_string()), // Price under 100
-        Some("clothing".to_string()),
-        None,
-        Some("Nike".to_string()),
-        Some("id,t,p,cat,b".to_string()), // Using shortcuts
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("-100".to_string()), // Price under 100
+            quantity: None,
+            category: Some("clothing".to_string()),
+            brand: Some("Nike".to_string()),
+            fields: Some("id,t,p,cat,b".to_string()), // Using shortcuts
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -352,12 +366,11 @@ fn test_filter_default_fields() {
     // Test without specifying fields (should use defaults)
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        None, // No fields specified
-        Some(OutputFormat::Json),
+        FilterArgs {
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -375,12 +388,12 @@ fn test_filter_empty_fields() {
     // Test with empty fields string
     let result = handle_filter(
         &conn,
-        None,
-        None,
-        None,
-        None,
-        Some("".to_string()), // Empty fields
-        Some(OutputFormat::Json),
+        FilterArgs {
+            fields: Some("".to_string()), // Empty fields
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -400,12 +413,14 @@ fn test_filter_price_range_edge_cases() {
     // Test open-ended ranges
     let result = handle_filter(
         &conn,
-        Some("20-".to_string()), // Min price only
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("20-".to_string()), // Min price only
+            quantity: None,
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -413,12 +428,14 @@ fn test_filter_price_range_edge_cases() {
     
     let result = handle_filter(
         &conn,
-        Some("-30".to_string()), // Max price only
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("-30".to_string()), // Max price only
+            quantity: None,
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
@@ -427,14 +444,172 @@ fn test_filter_price_range_edge_cases() {
     // Test exact price match
     let result = handle_filter(
         &conn,
-        Some("25.99".to_string()), // Exact price
-        None,
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("25.99".to_string()), // Exact price
+            quantity: None,
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         None,
         None,
     );
     assert!(result.is_ok());
-} 
\ No newline at end of file
+} 
+
+#[test]
+fn test_count_items_matches_filters() {
+    let conn = setup_test_db();
+    add_test_item(&conn, "Air Jordan 1", 180.0, 1, "sneakers", "new", Some("Nike"));
+    add_test_item(&conn, "Dunk Low", 110.0, 2, "sneakers", "used", Some("Nike"));
+    add_test_item(&conn, "Yeezy 350", 230.0, 1, "sneakers", "new", Some("Adidas"));
+    add_test_item(&conn, "Tech Fleece", 90.0, 3, "apparel", "new", Some("Nike"));
+    conn.execute("UPDATE items SET status = 'sold' WHERE title = 'Dunk Low'", []).unwrap();
+
+    assert_eq!(count_items(&conn, None, None, None, None, false).unwrap(), 3);
+    assert_eq!(count_items(&conn, None, None, None, None, true).unwrap(), 4);
+    assert_eq!(count_items(&conn, None, Some("sneakers"), None, Some("Nike"), true).unwrap(), 2);
+    assert_eq!(count_items(&conn, Some(">100"), None, Some("new"), None, false).unwrap(), 2);
+    assert_eq!(count_items(&conn, None, Some("electronics"), None, None, false).unwrap(), 0);
+    assert!(count_items(&conn, Some("abc"), None, None, None, false).is_err());
+}
+
+#[test]
+fn test_filter_on_sale() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    for (title, price, original_price) in [("Marked down", 80.0, Some(100.0)), ("No original", 30.0, None), ("Free original", 5.0, Some(0.0)), ("Marked up", 60.0, Some(50.0))] {
+        add_test_item(&conn, title, price, 1, "test", "new", None);
+        conn.execute("UPDATE items SET original_price = ? WHERE title = ?", rusqlite::params![original_price, title]).unwrap();
+    }
+    
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(["filter", "--on-sale", "-f", "csv", "--fields", "title,price,original_price"])
+        .env("HOME", home.path())
+        .env("INVENTORY_DB_PATH", &db_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "TITLE,PRICE,ORIGINAL_PRICE\nMarked down,80.00,100.00\n");
+}
+
+#[test]
+fn test_filter_expr() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    add_test_item(&conn, "Laptop", 999.99, 1, "electronics", "new", Some("Dell"));
+    add_test_item(&conn, "Paperback", 12.99, 2, "books", "used", None);
+    add_test_item(&conn, "Box Set", 45.00, 1, "books", "new", None);
+    add_test_item(&conn, "Running Shoes", 89.99, 1, "sneakers", "new", Some("Nike"));
+    add_test_item(&conn, "Cap", 15.00, 1, "apparel", "new", Some("Adidas"));
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap()
+    };
+    let titles = |args: &[&str]| {
+        let output = run(args);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    
+    assert_eq!(
+        titles(&["filter", "--filter-expr", "category = 'electronics' OR category = 'books' AND price < 20", "-f", "csv", "--fields", "title"]),
+        "TITLE\nLaptop\nPaperback\n"
+    );
+    assert_eq!(
+        titles(&["filter", "--filter-expr", "(category = 'electronics' OR category = 'books') AND price < 20", "-f", "csv", "--fields", "title"]),
+        "TITLE\nPaperback\n"
+    );
+    // The expression narrows the other filters rather than replacing them
+    assert_eq!(
+        titles(&["filter", "--condition", "new", "--filter-expr", "brand = 'Nike' OR title LIKE 'box%'", "-f", "csv", "--fields", "title"]),
+        "TITLE\nBox Set\nRunning Shoes\n"
+    );
+    
+    let output = run(&["filter", "--filter-expr", "notes = 'x'"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: Unknown column 'notes' in --filter-expr"));
+}
+
+#[test]
+fn test_filter_line_value() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    add_test_item(&conn, "Paperback", 12.50, 2, "books", "used", None);
+    add_test_item(&conn, "Box Set", 45.00, 3, "books", "new", None);
+    let filter = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .arg("filter")
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    
+    assert_eq!(
+        filter(&["-f", "csv", "--fields", "title,line_value"]),
+        "TITLE,LINE_VALUE\nPaperback,25.00\nBox Set,135.00\nTOTAL,160.00\n"
+    );
+    assert!(filter(&["-f", "table", "--fields", "title,line_value"]).ends_with("Total value: $160.00\n"));
+    
+    // JSON has the field only when it's asked for
+    let json: serde_json::Value = serde_json::from_str(&filter(&["--fields", "title,line_value"])).unwrap();
+    assert_eq!(json[1]["line_value"], 135.0);
+    let json: serde_json::Value = serde_json::from_str(&filter(&["--fields", "title"])).unwrap();
+    assert!(json[0].get("line_value").is_none());
+    assert!(!filter(&["-f", "csv", "--fields", "title"]).contains("TOTAL"));
+}
+
+#[test]
+fn test_filter_group_by() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    add_test_item(&conn, "Dunk", 100.0, 2, "sneakers", "new", Some("Nike"));
+    add_test_item(&conn, "Air Max", 150.0, 1, "sneakers", "used", Some("Nike"));
+    add_test_item(&conn, "Samba", 90.0, 1, "sneakers", "new", Some("Adidas"));
+    add_test_item(&conn, "Tee", 20.0, 3, "apparel", "new", None);
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .arg("filter")
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap()
+    };
+    let filter = |args: &[&str]| {
+        let output = run(args);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    
+    assert_eq!(
+        filter(&["--group-by", "brand", "-f", "csv"]),
+        "BRAND,COUNT,TOTAL VALUE\n,1,60.00\nAdidas,1,90.00\nNike,2,350.00\n"
+    );
+    // The other filters pick the items that are grouped
+    assert_eq!(
+        filter(&["--category", "sneakers", "--group-by", "condition", "-f", "csv"]),
+        "CONDITION,COUNT,TOTAL VALUE\nnew,2,290.00\nused,1,150.00\n"
+    );
+    let json: serde_json::Value = serde_json::from_str(&filter(&["--group-by", "category"])).unwrap();
+    assert_eq!(json[1]["group"], "sneakers");
+    assert_eq!(json[1]["count"], 3);
+    assert!(filter(&["--group-by", "brand", "-f", "table"]).contains("(none)"));
+    
+    let output = run(&["--group-by", "title"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: Can't group by 'title'"));
+}
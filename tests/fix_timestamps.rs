@@ -0,0 +1,58 @@
+use inventory::db::schema::{initialize_database, CREATE_ITEMS_TABLE_SQL};
+use inventory::commands::fix_timestamps::handle_fix_timestamps;
+use inventory::db::timestamp::normalize_timestamp;
+use chrono::DateTime;
+use rusqlite::Connection;
+
+fn insert_raw(conn: &Connection, title: &str, last_updated_sql: &str) {
+    conn.execute(
+        &format!(
+            "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+             VALUES (?, 10.0, 1, 'sneakers', 'new', {}, 'active')",
+            last_updated_sql
+        ),
+        [title],
+    ).unwrap();
+}
+
+fn all_timestamps(conn: &Connection) -> Vec<String> {
+    let mut stmt = conn.prepare("SELECT last_updated FROM items ORDER BY item_id").unwrap();
+    stmt.query_map([], |row| row.get(0)).unwrap().map(|r| r.unwrap()).collect()
+}
+
+#[test]
+fn test_normalize_timestamp_formats() {
+    assert_eq!(normalize_timestamp("2024-01-02 03:04:05").unwrap(), "2024-01-02T03:04:05.000000+00:00");
+    assert_eq!(normalize_timestamp("2024-01-02").unwrap(), "2024-01-02T00:00:00.000000+00:00");
+    assert_eq!(normalize_timestamp("2024-01-02T05:04:05+02:00").unwrap(), "2024-01-02T03:04:05.000000+00:00");
+    assert!(normalize_timestamp("next tuesday").is_none());
+}
+
+#[test]
+fn test_fix_timestamps_rewrites_legacy_rows() {
+    // A database without the normalizing triggers, as created by older versions
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.execute(CREATE_ITEMS_TABLE_SQL, []).unwrap();
+    
+    insert_raw(&conn, "Legacy", "datetime('now')");
+    insert_raw(&conn, "Current", "'2024-05-01T12:00:00+00:00'");
+    insert_raw(&conn, "Garbage", "'not a date'");
+    
+    handle_fix_timestamps(&mut conn).unwrap();
+    
+    let timestamps = all_timestamps(&conn);
+    assert!(DateTime::parse_from_rfc3339(&timestamps[0]).is_ok(), "Legacy value should be RFC3339: {}", timestamps[0]);
+    assert_eq!(timestamps[1], "2024-05-01T12:00:00.000000+00:00");
+    assert_eq!(timestamps[2], "not a date", "Unparseable values are left unchanged");
+}
+
+#[test]
+fn test_triggers_normalize_raw_inserts_and_updates() {
+    let conn = initialize_database(None).unwrap();
+    
+    insert_raw(&conn, "Raw insert", "datetime('now')");
+    conn.execute("UPDATE items SET last_updated = '2024-01-02 03:04:05' WHERE item_id = 1", []).unwrap();
+    
+    let timestamps = all_timestamps(&conn);
+    assert_eq!(timestamps[0], "2024-01-02T03:04:05.000000+00:00");
+}
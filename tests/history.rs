@@ -0,0 +1,64 @@
+use inventory::db::history::item_history;
+use inventory::db::queries::{insert_item, update_item, NewItem};
+use inventory::db::schema::initialize_database;
+use serde_json::json;
+
+fn insert_sneaker(conn: &rusqlite::Connection) -> i64 {
+    insert_item(conn, &NewItem {
+        title: "Air Jordan 1",
+        description: None,
+        price: 180.5,
+        quantity: 2,
+        photos: None,
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    }).unwrap();
+    conn.last_insert_rowid()
+}
+
+#[test]
+fn test_history_records_insert_then_changed_fields() {
+    let conn = initialize_database(None).unwrap();
+    let id = insert_sneaker(&conn);
+    update_item(&conn, id, None, Some(150.0), Some(1), None, None, None, None).unwrap();
+    // Setting a field to the value it already has is not a change
+    update_item(&conn, id, None, None, None, Some("sneakers"), None, None, None).unwrap();
+
+    let history = item_history(&conn, id).unwrap();
+    assert_eq!(history.len(), 2);
+
+    assert_eq!(history[0].action, "insert");
+    let inserted: Vec<&str> = history[0].changes.iter().map(|change| change.field.as_str()).collect();
+    assert_eq!(inserted, ["title", "price", "quantity", "category", "condition", "brand", "status", "currency"]);
+    assert!(history[0].changes.iter().all(|change| change.old.is_null()));
+
+    assert_eq!(history[1].action, "update");
+    let updated: Vec<_> = history[1].changes.iter().map(|change| (change.field.as_str(), &change.old, &change.new)).collect();
+    assert_eq!(updated, [("price", &json!(180.5), &json!(150.0)), ("quantity", &json!(2), &json!(1))]);
+    assert!(history[0].changed_at <= history[1].changed_at);
+}
+
+#[test]
+fn test_history_outlives_deleted_item() {
+    let conn = initialize_database(None).unwrap();
+    let id = insert_sneaker(&conn);
+    conn.execute("DELETE FROM items WHERE item_id = ?", [id]).unwrap();
+
+    assert_eq!(item_history(&conn, id).unwrap().len(), 1);
+    assert!(item_history(&conn, id + 1).unwrap().is_empty());
+}
@@ -1,6 +1,10 @@
 use inventory::db::schema::initialize_database;
-use inventory::commands::import::handle_import;
+use inventory::commands::import::{handle_import, handle_import_retry, ColumnMap, DuplicateKey, ImportOptions, InputEncoding, LongTitlePolicy, UpsertKey};
+use inventory::output::format::{format_ndjson, InventoryItem, OutputOptions};
+use inventory::commands::rollback_import::{count_import_run_items, handle_rollback_import};
+use inventory::error::error::ExitCode;
 use rusqlite::Connection;
+use rust_xlsxwriter::{Format, Workbook};
 use std::fs;
 use std::io::Write;
 use tempfile::NamedTempFile;
@@ -11,6 +15,25 @@ fn create_test_csv(content: &str) -> NamedTempFile {
     file
 }
 
+fn create_test_json(content: &str) -> NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+    write!(file, "{}", content).unwrap();
+    file
+}
+
+fn create_test_ndjson(content: &str) -> NamedTempFile {
+    let mut file = tempfile::Builder::new().suffix(".ndjson").tempfile().unwrap();
+    write!(file, "{}", content).unwrap();
+    file
+}
+
+/// Valid rows are committed even when others fail, but the import still ends in a validation error
+fn assert_rows_failed(result: anyhow::Result<()>, failed: usize) {
+    let err = result.unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::Validation);
+    assert_eq!(err.to_string(), format!("{} rows failed to import", failed));
+}
+
 fn setup_test_db() -> Connection {
     let conn = initialize_database(None).unwrap();
     // Clear any existing data
@@ -30,7 +53,7 @@ fn test_import_valid_csv() {
     let file_path = csv_file.path().to_str().unwrap();
     
     // Mock stdin for non-interactive test
-    let result = handle_import(file_path.to_string(), &conn, true);
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
     assert!(result.is_ok());
     
     // Verify items were imported
@@ -68,7 +91,7 @@ fn test_import_missing_required_fields() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
     assert!(result.is_err());
     assert!(result.unwrap_err().to_string().contains("Missing required field"));
 }
@@ -83,8 +106,8 @@ fn test_import_invalid_price() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
-    assert!(result.is_ok());
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
     
     // Should have failed rows due to negative price
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
@@ -101,8 +124,8 @@ fn test_import_invalid_quantity() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
-    assert!(result.is_ok());
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
     
     // Should have failed rows due to negative quantity
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
@@ -119,8 +142,8 @@ fn test_import_invalid_condition() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
-    assert!(result.is_ok());
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
     
     // Should have failed rows due to invalid condition
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
@@ -137,8 +160,8 @@ fn test_import_empty_title() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
-    assert!(result.is_ok());
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
     
     // Should have failed rows due to empty title
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
@@ -156,8 +179,8 @@ fn test_import_title_too_long() {
     let csv_file = create_test_csv(&csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
-    assert!(result.is_ok());
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
     
     // Should have failed rows due to title too long
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
@@ -176,8 +199,8 @@ fn test_import_mixed_valid_invalid() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
-    assert!(result.is_ok());
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
     
     // Should have imported 2 valid items, skipped 1 invalid
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
@@ -191,6 +214,288 @@ fn test_import_mixed_valid_invalid() {
     assert_eq!(titles, vec!["Valid Item", "Another Valid"]);
 }
 
+#[test]
+fn test_import_dry_run_writes_nothing() {
+    let conn = setup_test_db();
+    
+    let csv_content = r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,Valid Item,Valid Description,29.99,5,123456789012,electronics,new,TestBrand
+2,Invalid Item,Invalid Description,-10.00,5,123456789013,electronics,new,TestBrand"#;
+    
+    let csv_file = create_test_csv(csv_content);
+    let file_path = csv_file.path().to_str().unwrap();
+    
+    let err = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, dry_run: true, ..Default::default() }).unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::Validation);
+    assert_eq!(err.to_string(), "1 rows would fail to import");
+    
+    // Valid rows are only reported, never inserted
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_import_currency_column() {
+    let conn = setup_test_db();
+    
+    let csv_content = r#"item_id,title,description,price,quantity,upc,category,condition,brand,currency
+1,Euro Item,Desc,29.99,5,123456789012,electronics,new,TestBrand,eur
+2,Default Item,Desc,15.50,3,123456789013,clothing,used,AnotherBrand,
+3,Bad Currency,Desc,15.50,3,123456789014,clothing,used,AnotherBrand,EURO"#;
+    
+    let csv_file = create_test_csv(csv_content);
+    let file_path = csv_file.path().to_str().unwrap();
+    
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
+    
+    let mut stmt = conn.prepare("SELECT title, currency FROM items ORDER BY item_id").unwrap();
+    let rows: Vec<(String, String)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+        .map(|r| r.unwrap()).collect();
+    
+    assert_eq!(rows, vec![
+        ("Euro Item".to_string(), "EUR".to_string()),
+        ("Default Item".to_string(), "USD".to_string()),
+    ]);
+}
+
+#[test]
+fn test_import_release_date_column() {
+    let conn = setup_test_db();
+    
+    let csv_content = r#"item_id,title,description,price,quantity,upc,category,condition,brand,release_date
+1,ISO Date,Desc,29.99,5,123456789012,sneakers,new,Nike,2023-03-15
+2,US Date,Desc,15.50,3,123456789013,sneakers,new,Nike,12/01/2022
+3,No Date,Desc,15.50,3,123456789014,sneakers,new,Nike,
+4,Bad Date,Desc,15.50,3,123456789015,sneakers,new,Nike,2023-13-45"#;
+    
+    let csv_file = create_test_csv(csv_content);
+    let file_path = csv_file.path().to_str().unwrap();
+    
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
+    
+    let mut stmt = conn.prepare("SELECT title, release_date FROM items ORDER BY item_id").unwrap();
+    let rows: Vec<(String, Option<String>)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+        .map(|r| r.unwrap()).collect();
+    
+    assert_eq!(rows, vec![
+        ("ISO Date".to_string(), Some("2023-03-15".to_string())),
+        ("US Date".to_string(), Some("2022-12-01".to_string())),
+        ("No Date".to_string(), None),
+    ]);
+}
+
+#[test]
+fn test_import_skips_comments_and_empty_records() {
+    let conn = setup_test_db();
+    
+    let csv_content = r#"item_id,title,description,price,quantity,upc,category,condition,brand
+# Supplier feed, week 12
+1,Test Item,Test Description,29.99,5,123456789012,electronics,new,TestBrand
+
+,,,,,,,,
+# Clearance
+2,Another Item,Another Description,15.50,3,123456789013,clothing,used,AnotherBrand"#;
+    
+    let csv_file = create_test_csv(csv_content);
+    let file_path = csv_file.path().to_str().unwrap();
+    
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, comment_char: Some('#'), ..Default::default() });
+    assert!(result.is_ok());
+    
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 2);
+    
+    let titles: Vec<String> = conn.prepare("SELECT title FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Test Item".to_string(), "Another Item".to_string()]);
+}
+
+#[test]
+fn test_import_fuzzy_headers() {
+    let conn = setup_test_db();
+    
+    let csv_content = r#"Item ID,TITLE,Description,PRICE,Quantity,UPC,Category,condition,Brand_,Currency
+1,Test Item,Test Description,29.99,5,123456789012,electronics,new,TestBrand,EUR"#;
+    
+    let csv_file = create_test_csv(csv_content);
+    let file_path = csv_file.path().to_str().unwrap();
+    
+    // Exact matching still wants the canonical names
+    assert!(handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).is_err());
+    
+    handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, fuzzy_headers: true, ..Default::default() }).unwrap();
+    let (title, brand, currency): (String, String, String) = conn.query_row(
+        "SELECT title, brand, currency FROM items",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).unwrap();
+    assert_eq!((title.as_str(), brand.as_str(), currency.as_str()), ("Test Item", "TestBrand", "EUR"));
+}
+
+#[test]
+fn test_import_column_map_and_reordered_headers() {
+    let conn = setup_test_db();
+    
+    let csv_content = r#"Product Name,Cost,Quantity,Item_ID,UPC,Category,Condition,Brand,Description
+Test Item,29.99,5,1,123456789012,electronics,new,TestBrand,Test Description"#;
+    
+    let csv_file = create_test_csv(csv_content);
+    let file_path = csv_file.path().to_str().unwrap();
+    
+    // Case differences are matched, but the renamed columns need a mapping
+    let err = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap_err();
+    assert!(err.to_string().contains("Missing required field: title"), "{}", err);
+    
+    let map: ColumnMap = "Product Name=title,Cost=price".parse().unwrap();
+    let err = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, column_map: Some(&"Product Name=title".parse().unwrap()), ..Default::default() }).unwrap_err();
+    assert!(err.to_string().contains("Missing required field: price"), "{}", err);
+    
+    handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, column_map: Some(&map), ..Default::default() }).unwrap();
+    let (title, price, brand): (String, f64, String) = conn.query_row(
+        "SELECT title, price, brand FROM items",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).unwrap();
+    assert_eq!((title.as_str(), price, brand.as_str()), ("Test Item", 29.99, "TestBrand"));
+    
+    assert!("Product Name=titel".parse::<ColumnMap>().unwrap_err().contains("isn't an import field"));
+    assert!("Cost".parse::<ColumnMap>().is_err());
+}
+
+#[test]
+fn test_import_sku_column_and_duplicates() {
+    let conn = setup_test_db();
+    
+    let csv_content = r#"item_id,title,description,price,quantity,upc,category,condition,brand,sku
+1,First Item,Desc,10.0,1,,electronics,new,Acme,SKU-1
+2,Second Item,Desc,12.0,1,,electronics,new,Acme,SKU-1
+3,Third Item,Desc,14.0,1,,electronics,new,Acme,"#;
+    let csv_file = create_test_csv(csv_content);
+    assert_rows_failed(handle_import(csv_file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }), 1);
+    
+    // The second row repeats a SKU, so it fails validation instead of hitting the constraint
+    let rows: Vec<(String, Option<String>)> = conn.prepare("SELECT title, sku FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(rows, vec![("First Item".to_string(), Some("SKU-1".to_string())), ("Third Item".to_string(), None)]);
+}
+
+#[test]
+fn test_import_xlsx() {
+    let conn = setup_test_db();
+    let file = tempfile::Builder::new().suffix(".xlsx").tempfile().unwrap();
+    
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    let money = Format::new().set_num_format("$#,##0.00");
+    for (col, header) in ["Title", "item_id", "description", "price", "quantity", "upc", "category", "condition", "brand"].iter().enumerate() {
+        sheet.write_string(0, col as u16, *header).unwrap();
+    }
+    sheet.write_string(1, 0, "Test Item").unwrap();
+    sheet.write_string(1, 2, "Test Description").unwrap();
+    sheet.write_number_with_format(1, 3, 1234.5, &money).unwrap();
+    sheet.write_number(1, 4, 5).unwrap();
+    sheet.write_string(1, 5, "123456789012").unwrap();
+    sheet.write_string(1, 6, "electronics").unwrap();
+    sheet.write_string(1, 7, "new").unwrap();
+    sheet.write_string(1, 8, "TestBrand").unwrap();
+    // Fails validation: a fractional quantity
+    sheet.write_string(2, 0, "Another Item").unwrap();
+    sheet.write_number(2, 3, 10).unwrap();
+    sheet.write_number(2, 4, 2.5).unwrap();
+    sheet.write_string(2, 6, "electronics").unwrap();
+    sheet.write_string(2, 7, "new").unwrap();
+    workbook.save(file.path()).unwrap();
+    
+    assert_rows_failed(handle_import(file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }), 1);
+    let items: Vec<(String, f64, i64)> = conn.prepare("SELECT title, price, quantity FROM items").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(items, vec![("Test Item".to_string(), 1234.5, 5)]);
+}
+
+#[test]
+fn test_import_retry_failed_rows() {
+    std::env::set_var("INVENTORY_NONINTERACTIVE", "1");
+    let conn = setup_test_db();
+    
+    // Row 2 was fixed by hand in the file; row 3 comes from an older file with errors only
+    let failed = r#"{
+  "errors": [
+    {"field": "price", "message": "Price must be non-negative", "row": 2, "value": "abc"},
+    {"field": "title", "message": "Title cannot be empty", "row": 3, "value": ""}
+  ],
+  "rows": [
+    {"row": 2, "item_id": "", "title": "Fixed Item", "description": "Desc", "price": "19.99", "quantity": "2",
+     "upc": "", "category": "electronics", "condition": "new", "brand": "TestBrand", "currency": ""}
+  ]
+}"#;
+    let failed_file = create_test_json(failed);
+    
+    // Row 3 has no fields to retry, so it still fails
+    let err = handle_import_retry(failed_file.path().to_str().unwrap(), &conn).unwrap_err();
+    assert_eq!(ExitCode::of(&err), ExitCode::Validation);
+    let items: Vec<(String, f64)> = conn.prepare("SELECT title, price FROM items").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(items, vec![("Fixed Item".to_string(), 19.99)]);
+    
+    let bad_file = create_test_json(r#"{"items": []}"#);
+    assert!(handle_import_retry(bad_file.path().to_str().unwrap(), &conn).is_err());
+}
+
+#[test]
+fn test_import_quiet_prints_only_summary_and_errors() {
+    let home = tempfile::TempDir::new().unwrap();
+    let csv_file = create_test_csv("item_id,title,description,price,quantity,upc,category,condition,brand
+1,Test Item,Test Description,29.99,5,123456789012,electronics,new,TestBrand
+2,Another Item,Another Description,abc,3,,electronics,new,TestBrand");
+    
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(["--quiet", "import", "--file", csv_file.path().to_str().unwrap()])
+        .env("HOME", home.path())
+        .env("INVENTORY_NONINTERACTIVE", "1")
+        .env_remove("INVENTORY_DB_PATH")
+        .output()
+        .unwrap();
+    
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Importing from"), "{}", stdout);
+    assert!(!stdout.contains("✓ Row"), "{}", stdout);
+    assert!(stdout.contains("Successfully imported: 1 items"), "{}", stdout);
+    assert!(stdout.contains("Skipped/Failed: 1 items"), "{}", stdout);
+}
+
+#[test]
+fn test_import_truncates_long_titles() {
+    let conn = setup_test_db();
+    let long_title = "Vintage Nike Air Jordan 1 Retro High OG Chicago Lost and Found Sneakers Size 10 Men";
+    
+    let csv_content = format!("item_id,title,description,price,quantity,upc,category,condition,brand
+1,{},Test Description,29.99,5,123456789012,electronics,new,TestBrand", long_title);
+    
+    let csv_file = create_test_csv(&csv_content);
+    let file_path = csv_file.path().to_str().unwrap();
+    
+    assert_rows_failed(handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }), 1);
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 0);
+    
+    handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, on_long_title: LongTitlePolicy::Truncate, ..Default::default() }).unwrap();
+    let title: String = conn.query_row("SELECT title FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(title, "Vintage Nike Air Jordan 1 Retro High OG Chicago Lost and Found Sneakers Size 10");
+}
+
 #[test]
 fn test_import_csv_parse_error() {
     let conn = setup_test_db();
@@ -202,8 +507,8 @@ fn test_import_csv_parse_error() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
-    assert!(result.is_ok());
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 1);
     
     // Should have failed due to CSV parse error
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
@@ -219,7 +524,7 @@ fn test_import_empty_csv() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
     assert!(result.is_ok());
     
     // Should have imported 0 items
@@ -231,7 +536,7 @@ fn test_import_empty_csv() {
 fn test_import_file_not_found() {
     let conn = setup_test_db();
     
-    let result = handle_import("nonexistent_file.csv".to_string(), &conn, true);
+    let result = handle_import("nonexistent_file.csv".to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
     assert!(result.is_err());
     assert!(result.unwrap_err().to_string().contains("File not found"));
 }
@@ -246,7 +551,7 @@ fn test_import_with_optional_fields_empty() {
     let csv_file = create_test_csv(csv_content);
     let file_path = csv_file.path().to_str().unwrap();
     
-    let result = handle_import(file_path.to_string(), &conn, true);
+    let result = handle_import(file_path.to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
     assert!(result.is_ok());
     
     // Should import successfully with empty optional fields
@@ -264,4 +569,436 @@ fn test_import_with_optional_fields_empty() {
     
     assert_eq!(row.0, None); // upc should be NULL
     assert_eq!(row.1, None); // brand should be NULL
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_import_json_array() {
+    let conn = setup_test_db();
+    
+    let json_file = create_test_json(r#"[
+        {"title": "Json Item", "description": "From upstream", "price": 29.99, "quantity": 5,
+         "upc": "123456789012", "category": "electronics", "condition": "new", "brand": "TestBrand"},
+        {"title": "Invalid Json Item", "price": -1, "quantity": 1, "category": "electronics", "condition": "new"},
+        {"title": "Wrong Types", "price": "cheap", "quantity": 1, "category": "electronics", "condition": "new"}
+    ]"#);
+    
+    let result = handle_import(json_file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert_rows_failed(result, 2);
+    
+    // Only the valid item is imported; the others are skipped per row
+    let (title, price): (String, f64) = conn.query_row("SELECT title, price FROM items", [], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    }).unwrap();
+    assert_eq!(title, "Json Item");
+    assert_eq!(price, 29.99);
+    
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_import_json_items_object() {
+    let conn = setup_test_db();
+    
+    let json_file = create_test_json(r#"{"items": [
+        {"title": "Wrapped Item", "price": 10.0, "quantity": 2, "category": "clothing", "condition": "used", "currency": "GBP"}
+    ]}"#);
+    
+    let result = handle_import(json_file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert!(result.is_ok());
+    
+    let currency: String = conn.query_row("SELECT currency FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(currency, "GBP");
+}
+
+#[test]
+fn test_import_malformed_json() {
+    let conn = setup_test_db();
+    
+    let json_file = create_test_json(r#"[{"title": "Broken", "price": 10.0"#);
+    let result = handle_import(json_file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert!(result.unwrap_err().to_string().contains("Malformed JSON"));
+    
+    let json_file = create_test_json(r#"{"products": []}"#);
+    let result = handle_import(json_file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() });
+    assert!(result.unwrap_err().to_string().contains("\"items\""));
+}
+
+#[test]
+fn test_import_run_rollback() {
+    let mut conn = setup_test_db();
+    
+    let first = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,Good Feed Item,Desc,29.99,5,123456789012,electronics,new,TestBrand"#);
+    handle_import(first.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    let second = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,Bad Feed Item,Desc,9.99,1,123456789013,electronics,new,TestBrand
+2,Bad Feed Item 2,Desc,19.99,2,123456789014,clothing,used,TestBrand"#);
+    handle_import(second.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    let bad_run: String = conn.query_row(
+        "SELECT last_import_run FROM items WHERE title = 'Bad Feed Item'",
+        [],
+        |row| row.get(0),
+    ).unwrap();
+    assert_eq!(count_import_run_items(&conn, &bad_run).unwrap(), 2);
+    
+    handle_rollback_import(&mut conn, &bad_run).unwrap();
+    
+    let titles: Vec<String> = conn.prepare("SELECT title FROM items").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(titles, vec!["Good Feed Item".to_string()]);
+    
+    // Rolling back the same run twice finds nothing to undo
+    assert!(handle_rollback_import(&mut conn, &bad_run).is_err());
+}
+
+#[test]
+fn test_import_run_rollback_reverts_upserted_items() {
+    let mut conn = setup_test_db();
+    let first = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,Test Item,Desc,29.99,5,,electronics,new,"#);
+    handle_import(first.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    let first_run: String = conn.query_row("SELECT last_import_run FROM items WHERE item_id = 1", [], |row| row.get(0)).unwrap();
+    
+    // The upsert tags the item it updates with its own run, as well as the one it inserts
+    let feed = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,Repriced Item,,24.99,,,,,
+2,New Item,Desc,9.99,1,,toys,new,"#);
+    handle_import(feed.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::ItemId), ..Default::default() }).unwrap();
+    let feed_run: String = conn.query_row("SELECT last_import_run FROM items WHERE item_id = 2", [], |row| row.get(0)).unwrap();
+    assert_eq!(count_import_run_items(&conn, &feed_run).unwrap(), 2);
+    
+    handle_rollback_import(&mut conn, &feed_run).unwrap();
+    
+    let items: Vec<(i64, String, f64, String)> = conn.prepare("SELECT item_id, title, price, last_import_run FROM items").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))).unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(items, vec![(1, "Test Item".to_string(), 29.99, first_run)]);
+}
+
+#[test]
+fn test_import_ndjson_export_roundtrip() {
+    let conn = setup_test_db();
+    let items = vec![
+        InventoryItem::new(7, "Exported Item".to_string(), 42.0, 3, "new".to_string(), "shoes".to_string(), Some("Nike".to_string())),
+        InventoryItem::new(8, "Euro Item".to_string(), 10.5, 1, "used".to_string(), "shoes".to_string(), None)
+            .with_currency("EUR".to_string()),
+    ];
+    let export = format_ndjson(&items, &OutputOptions { ndjson_schema: true, ..Default::default() }).unwrap();
+    assert!(export.starts_with(r#"{"schema":"openinv.items","version":1,"fields":["item_id","title""#));
+    
+    let file = create_test_ndjson(&export);
+    handle_import(file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    let rows: Vec<(String, String)> = conn.prepare("SELECT title, currency FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(rows, vec![
+        ("Exported Item".to_string(), "USD".to_string()),
+        ("Euro Item".to_string(), "EUR".to_string()),
+    ]);
+}
+
+#[test]
+fn test_import_ndjson_rejects_incompatible_schema() {
+    let conn = setup_test_db();
+    let item = r#"{"title":"Item","price":1.0,"quantity":1,"category":"shoes","condition":"new"}"#;
+    
+    let newer = create_test_ndjson(&format!(
+        "{}\n{}\n",
+        r#"{"schema":"openinv.items","version":99,"fields":["title","price","quantity","category","condition"]}"#,
+        item
+    ));
+    let err = handle_import(newer.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap_err();
+    assert!(err.to_string().contains("Unsupported ndjson schema version 99"));
+    
+    let missing_field = create_test_ndjson(&format!(
+        "{}\n{}\n",
+        r#"{"schema":"openinv.items","version":1,"fields":["title","price"]}"#,
+        item
+    ));
+    assert!(handle_import(missing_field.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).is_err());
+    
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 0);
+    
+    // Streams without a schema line are read as plain ndjson
+    let plain = create_test_ndjson(&format!("{}\n", item));
+    handle_import(plain.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_import_upsert_updates_matching_items() {
+    let conn = setup_test_db();
+    let first = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,Test Item,Desc,29.99,5,123456789012,electronics,new,TestBrand
+2,Other Item,Desc,15.50,3,987654321098,clothing,used,OtherBrand"#);
+    handle_import(first.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    // Item 1 gets a new price, keeping its other fields; item 3 doesn't exist yet and is inserted
+    let feed = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,,,24.99,,,,,
+3,New Item,Desc,9.99,1,,toys,new,"#);
+    handle_import(feed.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::ItemId), ..Default::default() }).unwrap();
+    
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 3);
+    let (title, price, quantity): (String, f64, i32) = conn.query_row(
+        "SELECT title, price, quantity FROM items WHERE item_id = 1", [], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).unwrap();
+    assert_eq!((title.as_str(), price, quantity), ("Test Item", 24.99, 5));
+    
+    // Matching by UPC; the merged record is validated, so a bad quantity leaves the item alone
+    let by_upc = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+,,,,7,987654321098,,,
+,,,,-1,123456789012,,,"#);
+    assert_rows_failed(handle_import(by_upc.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::Upc), ..Default::default() }), 1);
+    let quantities: Vec<i32> = conn.prepare("SELECT quantity FROM items WHERE item_id IN (1, 2) ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap().map(|q| q.unwrap()).collect();
+    assert_eq!(quantities, vec![5, 7]);
+    
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn test_import_upsert_writes_description_and_currency() {
+    let conn = setup_test_db();
+    let items = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand,currency
+1,Test Item,Old description,29.99,5,,electronics,new,,EUR
+2,Other Item,Kept description,15.50,3,,clothing,used,,EUR"#);
+    handle_import(items.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    // The currency is spelled differently but is the item's own, so it doesn't block the update
+    let feed = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand,currency
+1,,New description,,,,,,,eur
+2,,,12.00,,,,,,"#);
+    handle_import(feed.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::ItemId), ..Default::default() }).unwrap();
+    
+    let rows: Vec<(String, f64, String)> = conn.prepare("SELECT description, price, currency FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).unwrap().map(|r| r.unwrap()).collect();
+    assert_eq!(rows, vec![
+        ("New description".to_string(), 29.99, "EUR".to_string()),
+        ("Kept description".to_string(), 12.0, "EUR".to_string()),
+    ]);
+}
+
+#[test]
+fn test_import_upsert_keeps_skus_unique_across_items() {
+    let conn = setup_test_db();
+    let items = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand,sku
+1,Test Item,Desc,29.99,5,,electronics,new,,
+2,Other Item,Desc,15.50,3,,clothing,used,,SHIRT-1"#);
+    handle_import(items.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    // Item 1 gets a SKU, and item 2's own SKU doesn't count as taken when it's written back
+    let feed = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand,sku
+1,,,,,,,,,GADGET-1
+2,,,12.00,,,,,,SHIRT-1"#);
+    handle_import(feed.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::ItemId), ..Default::default() }).unwrap();
+    
+    // Another item's SKU is still rejected
+    let clash = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand,sku
+1,,,19.99,,,,,,SHIRT-1"#);
+    assert_rows_failed(handle_import(clash.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::ItemId), ..Default::default() }), 1);
+    
+    let rows: Vec<(f64, Option<String>)> = conn.prepare("SELECT price, sku FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap().map(|r| r.unwrap()).collect();
+    assert_eq!(rows, vec![(29.99, Some("GADGET-1".to_string())), (12.0, Some("SHIRT-1".to_string()))]);
+}
+
+#[test]
+fn test_import_upsert_normalizes_and_keeps_release_dates() {
+    let conn = setup_test_db();
+    let items = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand,release_date
+1,Test Item,Desc,29.99,5,,electronics,new,,
+2,Other Item,Desc,15.50,3,,clothing,used,,2021-03-04"#);
+    handle_import(items.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    // Item 1's date is stored as YYYY-MM-DD; item 2 leaves its date out, so it's kept
+    let feed = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand,release_date
+1,,,,,,,,,12/25/2022
+2,,,12.00,,,,,,"#);
+    handle_import(feed.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::ItemId), ..Default::default() }).unwrap();
+    
+    let dates: Vec<Option<String>> = conn.prepare("SELECT release_date FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap().map(|d| d.unwrap()).collect();
+    assert_eq!(dates, vec![Some("2022-12-25".to_string()), Some("2021-03-04".to_string())]);
+}
+
+#[test]
+fn test_import_upsert_rejects_ambiguous_or_currency_changing_rows() {
+    let conn = setup_test_db();
+    let items = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,First Copy,Desc,10.00,1,123456789012,books,new,
+2,Second Copy,Desc,10.00,1,123456789012,books,new,"#);
+    handle_import(items.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    let ambiguous = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+,,,12.00,,123456789012,,,"#);
+    assert_rows_failed(handle_import(ambiguous.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::Upc), ..Default::default() }), 1);
+    
+    let recurrency = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand,currency
+1,,,12.00,,,,,,EUR"#);
+    assert_rows_failed(handle_import(recurrency.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, upsert: Some(UpsertKey::ItemId), ..Default::default() }), 1);
+    
+    let prices: Vec<f64> = conn.prepare("SELECT price FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap().map(|p| p.unwrap()).collect();
+    assert_eq!(prices, vec![10.0, 10.0]);
+}
+
+#[test]
+fn test_import_skip_duplicates() {
+    let conn = setup_test_db();
+    let existing = create_test_csv(r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,Air Jordan 1,Desc,150.00,1,123456789012,sneakers,new,Nike"#);
+    handle_import(existing.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    // One row repeats an item in the database, one repeats an earlier row (ignoring case and spacing)
+    let feed = r#"item_id,title,description,price,quantity,upc,category,condition,brand
+1,Air Jordan 1,Desc,140.00,2,123456789012,sneakers,new,Nike
+2,Dunk Low,Desc,110.00,1,,sneakers,new,Nike
+3, dunk low ,Other,115.00,1,,sneakers,used,NIKE
+4,Dunk Low,Desc,110.00,1,,sneakers,new,"#;
+    let key = DuplicateKey::default();
+    let file = create_test_csv(feed);
+    handle_import(file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, skip_duplicates: Some(&key), ..Default::default() }).unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 3, "the two duplicates are skipped; the unbranded Dunk Low has a different key");
+    
+    // A key of just the title also catches the unbranded row
+    let title_only: DuplicateKey = "title".parse().unwrap();
+    let again = create_test_csv(feed);
+    handle_import(again.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, skip_duplicates: Some(&title_only), ..Default::default() }).unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 3);
+    
+    // Without the flag, duplicates are imported as before
+    let unchecked = create_test_csv(feed);
+    handle_import(unchecked.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 7);
+    
+    assert!("title,price".parse::<DuplicateKey>().unwrap_err().contains("'price' can't be part of a duplicate key"));
+}
+
+#[test]
+fn test_import_and_validate_from_stdin() {
+    use std::process::{Command, Stdio};
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let pipe = |args: &[&str], input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .env_remove("INVENTORY_NONINTERACTIVE")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        child.wait_with_output().unwrap()
+    };
+    let csv = "item_id,title,description,price,quantity,upc,category,condition,brand\n\
+               1,Test Item,Desc,29.99,5,123456789012,electronics,new,TestBrand\n\
+               2,Bad Item,Desc,abc,3,,electronics,new,TestBrand\n";
+    
+    let output = pipe(&["validate", "--file", "-"], csv);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("✗ Row 2: price"));
+    
+    // The bad row is skipped rather than prompted for, since stdin holds the data
+    let output = pipe(&["import", "--file", "-"], csv);
+    assert_eq!(output.status.code(), Some(2), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Importing from: standard input"), "{}", stdout);
+    assert!(!stdout.contains("Enter new value"), "{}", stdout);
+    let conn = Connection::open(&db_path).unwrap();
+    let titles: Vec<String> = conn.prepare("SELECT title FROM items").unwrap()
+        .query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(titles, ["Test Item"]);
+}
+
+#[test]
+fn test_import_max_rows_stops_early() {
+    let home = tempfile::TempDir::new().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let mut csv = String::from("item_id,title,description,price,quantity,upc,category,condition,brand\n");
+    for i in 1..=5 {
+        csv.push_str(&format!("{},Item {},Desc,10.0,1,,shoes,new,Acme\n", i, i));
+    }
+    let csv_file = create_test_csv(&csv);
+    
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(["import", "--max-rows", "2", "--file", csv_file.path().to_str().unwrap()])
+        .env("HOME", home.path())
+        .env("INVENTORY_DB_PATH", &db_path)
+        .env("INVENTORY_NONINTERACTIVE", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Stopped after 2 rows (--max-rows); the remaining 3 rows were skipped"), "{}", stdout);
+    assert!(stdout.contains("Successfully imported: 2 items"), "{}", stdout);
+    assert!(stdout.contains("Skipped/Failed: 3 items"), "{}", stdout);
+    
+    let conn = Connection::open(&db_path).unwrap();
+    let titles: Vec<String> = conn.prepare("SELECT title FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(titles, ["Item 1", "Item 2"]);
+    
+    // A limit the file stays under changes nothing
+    let conn = initialize_database(None).unwrap();
+    handle_import(csv_file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, max_rows: Some(5), ..Default::default() }).unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 5);
+    
+    // JSON and ndjson items past the limit are never read as rows, so a bad one there fails nothing
+    let json_file = create_test_json(r#"[
+        {"title": "Json Item", "price": 29.99, "quantity": 5, "category": "electronics", "condition": "new"},
+        {"title": "Wrong Types", "price": "cheap", "quantity": 1, "category": "electronics", "condition": "new"}
+    ]"#);
+    let ndjson_file = create_test_ndjson("{\"title\": \"Ndjson Item\", \"price\": 9.99, \"quantity\": 1, \"category\": \"toys\", \"condition\": \"new\"}\nnot json\n");
+    let conn = initialize_database(None).unwrap();
+    for file in [&json_file, &ndjson_file] {
+        handle_import(file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, max_rows: Some(1), ..Default::default() }).unwrap();
+    }
+    let titles: Vec<String> = conn.prepare("SELECT title FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(titles, ["Json Item", "Ndjson Item"]);
+}
+
+#[test]
+fn test_import_with_encoding() {
+    // "Citroën Chemise" by "Société Générale" in Windows-1252: ë is 0xEB and é is 0xE9
+    let mut csv_file = tempfile::Builder::new().suffix(".csv").tempfile().unwrap();
+    csv_file.write_all(b"item_id,title,description,price,quantity,upc,category,condition,brand\n").unwrap();
+    csv_file.write_all(b"1,Citro\xEBn Chemise,Vintage,29.99,1,,clothing,used,Soci\xE9t\xE9 G\xE9n\xE9rale\n").unwrap();
+    let file_path = csv_file.path().to_str().unwrap().to_string();
+    
+    let encoding: InputEncoding = "windows-1252".parse().unwrap();
+    let conn = setup_test_db();
+    handle_import(file_path.clone(), &conn, &ImportOptions { non_interactive: true, encoding: Some(encoding), ..Default::default() }).unwrap();
+    let (title, brand): (String, String) = conn.query_row("SELECT title, brand FROM items", [], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+    assert_eq!(title, "Citroën Chemise");
+    assert_eq!(brand, "Société Générale");
+    
+    // As UTF-8 the same bytes are reported, not replaced
+    let conn = setup_test_db();
+    let err = handle_import(file_path, &conn, &ImportOptions { non_interactive: true, encoding: Some("utf-8".parse().unwrap()), ..Default::default() }).unwrap_err();
+    assert!(err.to_string().contains("isn't valid UTF-8: byte 0xEB on line 2 can't be decoded"), "{}", err);
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 0);
+    
+    assert!("klingon".parse::<InputEncoding>().unwrap_err().starts_with("unknown encoding 'klingon'"));
+}
@@ -1,26 +1,34 @@
 use inventory::db::schema::initialize_database;
-use inventory::db::queries::{insert_item, NewItem, get_all_items};
-use inventory::output::format::{format_json, format_csv, format_table, InventoryItem};
+use inventory::db::queries::{for_each_item, insert_item, NewItem, get_item_columns, get_items};
+use inventory::commands::list::{handle_list_inventory, OutputFormat};
+use inventory::output::format::{format_columns, format_json, format_csv, format_csv_with_options, format_items, format_table, parse_csv_delimiter, render_table, InventoryItem, NdjsonWriter, OutputOptions};
+use inventory::plugins::loader::PLUGIN_DIR_ENV;
+use std::process::Command;
+use std::str::FromStr;
+
+/// CSV columns after the compact set, and their cells for an item built with `InventoryItem::new`
+const DETAIL_HEADER: &str = ",description,upc,size,original_price,colorway,release_date,hashtags,photos,item_specifics,shipping_details,platform_status,internal_notes,status,sku,last_import_run,last_updated";
+const NO_DETAILS: &str = ",,,,,,,,,,,,,,,,";
 
 #[test]
 fn test_list_inventory_empty_database() {
     let conn = initialize_database(None).unwrap();
     
-    let items = get_all_items(&conn).unwrap();
+    let items = get_items(&conn, true).unwrap();
     assert_eq!(items.len(), 0);
     
     // Test JSON formatting for empty list
-    let json_output = format_json(&items).unwrap();
+    let json_output = format_json(&items, &OutputOptions::default()).unwrap();
     assert_eq!(json_output, "[]");
     
     // Test CSV formatting for empty list
     let csv_output = format_csv(&items).unwrap();
-    assert_eq!(csv_output, "item_id,title,price,quantity,condition,category,brand\n");
+    assert_eq!(csv_output, format!("item_id,title,price,quantity,condition,category,brand,currency{}\n", DETAIL_HEADER));
     
     // Test table formatting for empty list
-    let table_output = format_table(&items).unwrap();
-    assert!(table_output.contains("ID  | Title"));
-    assert!(table_output.contains("----|"));
+    let table_output = format_table(&items, &OutputOptions::default()).unwrap();
+    assert!(table_output.contains("| ID | Title | Price | Qty | Condition | Category | Brand |"));
+    assert!(table_output.contains("|----|"));
 }
 
 #[test]
@@ -48,6 +56,9 @@ fn test_list_inventory_with_items() {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     };
     
     let item2 = NewItem {
@@ -70,12 +81,15 @@ fn test_list_inventory_with_items() {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     };
     
     insert_item(&conn, &item1).unwrap();
     insert_item(&conn, &item2).unwrap();
     
-    let items = get_all_items(&conn).unwrap();
+    let items = get_items(&conn, true).unwrap();
     assert_eq!(items.len(), 2);
     
     // Verify item data
@@ -86,6 +100,17 @@ fn test_list_inventory_with_items() {
     assert_eq!(items[0].condition, "new");
     assert_eq!(items[0].category, "sneakers");
     assert_eq!(items[0].brand, Some("Nike".to_string()));
+    // The rest of the columns come along too
+    assert_eq!(items[0].description.as_deref(), Some("Classic sneaker in Chicago colorway"));
+    assert_eq!(items[0].size.as_deref(), Some("10"));
+    assert_eq!(items[0].original_price, Some(170.0));
+    assert_eq!(items[0].status.as_deref(), Some("active"));
+    assert!(items[0].last_updated.is_some());
+    
+    // Missing optionals are null in JSON
+    let json: serde_json::Value = serde_json::from_str(&format_json(&items, &OutputOptions::default()).unwrap()).unwrap();
+    assert_eq!(json[0]["colorway"], "Chicago");
+    assert!(json[0]["internal_notes"].is_null());
     
     assert_eq!(items[1].item_id, 2);
     assert_eq!(items[1].title, "Vintage Denim Jacket");
@@ -103,7 +128,7 @@ fn test_json_formatting() {
         InventoryItem::new(2, "Test Item 2".to_string(), 50.0, 1, "used".to_string(), "test".to_string(), None),
     ];
     
-    let json_output = format_json(&items).unwrap();
+    let json_output = format_json(&items, &OutputOptions::default()).unwrap();
     println!("JSON OUTPUT: {}", json_output);
     // Verify JSON structure
     assert!(json_output.contains("\"item_id\": 1"));
@@ -123,21 +148,44 @@ fn test_json_formatting() {
 }
 
 #[test]
-fn test_csv_formatting() {
+fn test_yaml_formatting_mirrors_json() {
     let items = vec![
         InventoryItem::new(1, "Test Item 1".to_string(), 100.0, 2, "new".to_string(), "test".to_string(), Some("Brand1".to_string())),
         InventoryItem::new(2, "Test Item 2".to_string(), 50.0, 1, "used".to_string(), "test".to_string(), None),
     ];
     
+    let yaml_output = format_items(&items, "yaml", &OutputOptions::default()).unwrap();
+    let from_yaml: serde_json::Value = serde_yaml::from_str(&yaml_output).unwrap();
+    let from_json: serde_json::Value = serde_json::from_str(&format_json(&items, &OutputOptions::default()).unwrap()).unwrap();
+    assert_eq!(from_yaml, from_json);
+    
+    // Empty results are still a valid (empty) YAML list
+    assert_eq!(format_items(&[], "yaml", &OutputOptions::default()).unwrap().trim(), "[]");
+    assert_eq!(OutputFormat::from_str("YAML").unwrap(), OutputFormat::Yaml);
+}
+
+#[test]
+fn test_csv_formatting() {
+    let mut first = InventoryItem::new(1, "Test Item 1".to_string(), 100.0, 2, "new".to_string(), "test".to_string(), Some("Brand1".to_string()));
+    first.description = Some("Boxed, unworn".to_string());
+    first.size = Some("10.5".to_string());
+    first.original_price = Some(120.0);
+    first.status = Some("active".to_string());
+    let items = vec![
+        first,
+        InventoryItem::new(2, "Test Item 2".to_string(), 50.0, 1, "used".to_string(), "test".to_string(), None)
+            .with_currency("EUR".to_string()),
+    ];
+    
     let csv_output = format_csv(&items).unwrap();
     let lines: Vec<&str> = csv_output.lines().collect();
     
     // Verify header
-    assert_eq!(lines[0], "item_id,title,price,quantity,condition,category,brand");
+    assert_eq!(lines[0], format!("item_id,title,price,quantity,condition,category,brand,currency{}", DETAIL_HEADER));
     
-    // Verify data rows
-    assert_eq!(lines[1], "1,Test Item 1,100.00,2,new,test,Brand1");
-    assert_eq!(lines[2], "2,Test Item 2,50.00,1,used,test,");
+    // Verify data rows; missing values are empty
+    assert_eq!(lines[1], "1,Test Item 1,100.00,2,new,test,Brand1,USD,\"Boxed, unworn\",,10.5,120.00,,,,,,,,,active,,,");
+    assert_eq!(lines[2], format!("2,Test Item 2,50.00,1,used,test,,EUR{}", NO_DETAILS));
 }
 
 #[test]
@@ -147,23 +195,25 @@ fn test_table_formatting() {
         InventoryItem::new(2, "This is a very long title that should be truncated at 50 characters".to_string(), 50.0, 1, "used".to_string(), "test".to_string(), None),
     ];
     
-    let table_output = format_table(&items).unwrap();
+    let table_output = format_table(&items, &OutputOptions::default()).unwrap();
     let lines: Vec<&str> = table_output.lines().collect();
     println!("TABLE OUTPUT:\n{}", table_output);
     // Verify header
-    assert!(lines[0].contains("ID  | Title"));
-    assert!(lines[0].contains("Price   | Qty | Condition | Category | Brand"));
-    assert!(lines[1].contains("----|"));
+    assert!(lines[0].starts_with("| ID | Title "));
+    assert!(lines[0].contains("| Price   | Qty | Condition | Category | Brand  |"));
+    assert!(lines[1].starts_with("|----|"));
     
     // Verify short title (not truncated)
-    assert!(lines[2].contains("1    | Short Title"));
-    assert!(lines[2].contains("$100.00"));
-    assert!(lines[2].contains("2   | new"));
+    assert!(lines[2].starts_with("| 1  | Short Title "));
+    assert!(lines[2].contains("| $100.00 | 2   | new "));
     
     // Verify long title (truncated)
-    assert!(lines[3].contains("2    | This is a very long title that should be trunca..."));
-    assert!(lines[3].contains("$50.00"));
-    assert!(lines[3].contains("1   | used"));
+    assert!(lines[3].starts_with("| 2  | This is a very long title that should be trunca... |"));
+    assert!(lines[3].contains("| $50.00  | 1   | used "));
+    
+    // Columns line up: every line has its borders in the same places
+    let borders = |line: &str| line.char_indices().filter(|(_, c)| *c == '|').map(|(i, _)| i).collect::<Vec<_>>();
+    assert!(lines.iter().all(|line| borders(line) == borders(lines[0])));
 }
 
 #[test]
@@ -176,5 +226,409 @@ fn test_csv_escaping() {
     let lines: Vec<&str> = csv_output.lines().collect();
     
     // Verify escaped fields
-    assert_eq!(lines[1], "1,\"Item with, comma\",100.00,2,new,test,\"Brand with \"\"quotes\"\"\"");
-} 
\ No newline at end of file
+    assert_eq!(lines[1], format!("1,\"Item with, comma\",100.00,2,new,test,\"Brand with \"\"quotes\"\"\",USD{}", NO_DETAILS));
+} 
+#[test]
+fn test_csv_crlf_line_endings() {
+    let items = vec![
+        InventoryItem::new(1, "First".to_string(), 10.0, 1, "new".to_string(), "test".to_string(), None),
+        InventoryItem::new(2, "Second".to_string(), 20.0, 2, "used".to_string(), "test".to_string(), Some("Brand".to_string())),
+    ];
+    
+    let csv_output = format_csv_with_options(&items, &OutputOptions { crlf: true, ..Default::default() }).unwrap();
+    
+    assert_eq!(
+        csv_output,
+        format!(
+            "item_id,title,price,quantity,condition,category,brand,currency{0}\r\n1,First,10.00,1,new,test,,USD{1}\r\n2,Second,20.00,2,used,test,Brand,USD{1}\r\n",
+            DETAIL_HEADER, NO_DETAILS
+        )
+    );
+    assert_eq!(csv_output.matches('\n').count(), csv_output.matches("\r\n").count());
+}
+
+#[test]
+fn test_csv_delimiter_and_no_header() {
+    let items = vec![
+        InventoryItem::new(1, "Semi; colon".to_string(), 10.0, 1, "new".to_string(), "test".to_string(), Some("Comma, Inc".to_string())),
+    ];
+    
+    // Fields are quoted for the delimiter in use, so the comma no longer needs it
+    let options = OutputOptions { csv_delimiter: ';', ..Default::default() };
+    assert_eq!(
+        format_csv_with_options(&items, &options).unwrap(),
+        format!(
+            "item_id;title;price;quantity;condition;category;brand;currency{}\n1;\"Semi; colon\";10.00;1;new;test;Comma, Inc;USD{}\n",
+            DETAIL_HEADER.replace(',', ";"), NO_DETAILS.replace(',', ";")
+        )
+    );
+    
+    let options = OutputOptions { csv_delimiter: '\t', csv_header: false, ..Default::default() };
+    assert_eq!(
+        format_csv_with_options(&items, &options).unwrap(),
+        format!("1\tSemi; colon\t10.00\t1\tnew\ttest\tComma, Inc\tUSD{}\n", NO_DETAILS.replace(',', "\t"))
+    );
+}
+
+#[test]
+fn test_table_wraps_long_cells_when_asked() {
+    let title = "Nike Air Jordan 1 Retro High OG Chicago Lost and Found 2022";
+    let items = vec![InventoryItem::new(1, title.to_string(), 10.0, 1, "new".to_string(), "sneakers".to_string(), None)];
+    
+    // Truncation stays the default
+    let table = format_table(&items, &OutputOptions::default()).unwrap();
+    assert!(table.contains("| Nike Air Jordan 1 Retro High OG Chicago Lost an... |"), "{}", table);
+    
+    let table = format_table(&items, &OutputOptions { wrap: Some(20), ..Default::default() }).unwrap();
+    let lines: Vec<&str> = table.lines().collect();
+    assert!(lines.len() > 3, "{}", table);
+    // The item's other cells stay on its first line, and every line keeps the grid's width
+    assert!(lines[2].starts_with("| 1  | Nike Air Jordan") && lines[2].contains("| $10.00 |"), "{}", table);
+    assert!(lines[3..].iter().all(|line| line.starts_with("|    | ") && !line.contains('$')), "{}", table);
+    assert!(lines.iter().all(|line| line.chars().count() == lines[0].chars().count()), "{}", table);
+    let wrapped: Vec<&str> = lines[2..].iter()
+        .map(|line| line.split('|').nth(2).unwrap().trim())
+        .collect();
+    assert!(wrapped.iter().all(|part| part.chars().count() <= 20), "{}", table);
+    assert_eq!(wrapped.join(" "), title);
+    
+    // Chosen columns wrap the same way
+    let columns = vec!["title".to_string()];
+    let rows = vec![vec![rusqlite::types::Value::Text(title.to_string())]];
+    let table = format_columns(&columns, &rows, "table", &OutputOptions { wrap: Some(30), ..Default::default() }).unwrap();
+    assert_eq!(table.lines().count(), 4, "{}", table);
+}
+
+#[test]
+fn test_parse_csv_delimiter() {
+    assert_eq!(parse_csv_delimiter(";"), Ok(';'));
+    assert_eq!(parse_csv_delimiter("\t"), Ok('\t'));
+    assert_eq!(parse_csv_delimiter("\\t"), Ok('\t'));
+    assert!(parse_csv_delimiter("").is_err());
+    assert!(parse_csv_delimiter(";;").is_err());
+    assert!(parse_csv_delimiter("\"").is_err());
+    assert!(parse_csv_delimiter("\n").is_err());
+}
+
+#[test]
+fn test_list_chosen_columns() {
+    let conn = initialize_database(None).unwrap();
+    conn.execute(
+        "INSERT INTO items (title, price, quantity, category, condition, upc, last_updated, status) \
+         VALUES ('First, Edition', 10.5, 1, 'books', 'used', '123456789012', datetime('now'), 'active')",
+        [],
+    ).unwrap();
+    
+    let columns = vec!["title".to_string(), "price".to_string(), "upc".to_string()];
+    let rows = get_item_columns(&conn, &columns, false).unwrap();
+    let options = OutputOptions::default();
+    
+    let json = format_columns(&columns, &rows, "json", &options).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, serde_json::json!([{"title": "First, Edition", "price": 10.5, "upc": "123456789012"}]));
+    assert!(json.find("\"title\"").unwrap() < json.find("\"price\"").unwrap());
+    
+    let csv = format_columns(&columns, &rows, "csv", &options).unwrap();
+    assert_eq!(csv, "title,price,upc\n\"First, Edition\",10.50,123456789012\n");
+    
+    let table = format_columns(&columns, &rows, "table", &options).unwrap();
+    assert!(table.starts_with("| TITLE          | PRICE  | UPC          |"));
+    assert!(table.contains("| First, Edition | $10.50 | 123456789012 |"));
+    
+    // The empty table still has its header
+    let table = format_columns(&columns, &[], "table", &options).unwrap();
+    assert!(table.contains("| TITLE | PRICE | UPC |"));
+}
+
+#[test]
+fn test_list_unknown_column() {
+    let conn = initialize_database(None).unwrap();
+    let err = handle_list_inventory(&conn, None, false, Some("t,sku".to_string()), None, &OutputOptions::default(), None).unwrap_err();
+    assert_eq!(err.to_string(), "Unknown field: sku");
+}
+
+#[test]
+fn test_parse_custom_output_format() {
+    assert_eq!(OutputFormat::from_str("CSV").unwrap(), OutputFormat::Csv);
+    assert_eq!(OutputFormat::from_str("custom:MyFmt").unwrap(), OutputFormat::Custom("MyFmt".to_string()));
+    assert_eq!(OutputFormat::Custom("MyFmt".to_string()).to_string(), "custom:MyFmt");
+    assert!(OutputFormat::from_str("custom:").is_err());
+    assert!(OutputFormat::from_str("xml").is_err());
+}
+
+#[test]
+fn test_custom_format_missing_plugin() {
+    let plugin_dir = tempfile::tempdir().unwrap();
+    std::env::set_var(PLUGIN_DIR_ENV, plugin_dir.path());
+    let items = vec![
+        InventoryItem::new(1, "First".to_string(), 10.0, 1, "new".to_string(), "test".to_string(), None),
+    ];
+    
+    let err = format_items(&items, "custom:myfmt", &OutputOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("Formatter plugin 'myfmt' not found"));
+    
+    // Names can't reach outside the plugin directory
+    let err = format_items(&items, "custom:../myfmt", &OutputOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("Invalid plugin name"));
+}
+
+#[test]
+fn test_ndjson_writer_streams_items() {
+    let conn = initialize_database(None).unwrap();
+    let fields = vec!["item_id".to_string(), "title".to_string()];
+    
+    // No items, no lines
+    let mut writer = NdjsonWriter::new(Vec::new(), &fields, &OutputOptions::default()).unwrap();
+    assert_eq!(for_each_item(&conn, true, |item| writer.write(&item)).unwrap(), 0);
+    assert_eq!(writer.finish().unwrap(), 0);
+    
+    for title in ["First", "Second"] {
+        insert_item(&conn, &NewItem {
+            title,
+            description: None,
+            price: 10.0,
+            quantity: 1,
+            photos: None,
+            category: "misc",
+            condition: "new",
+            brand: None,
+            upc: None,
+            item_specifics: None,
+            shipping_details: None,
+            size: None,
+            original_price: None,
+            hashtags: None,
+            colorway: None,
+            release_date: None,
+            platform_status: None,
+            internal_notes: None,
+            status: "active",
+            currency: None,
+            import_run: None,
+            sku: None,
+        }).unwrap();
+    }
+    let mut output = Vec::new();
+    let mut writer = NdjsonWriter::new(&mut output, &fields, &OutputOptions { ndjson_schema: true, ..Default::default() }).unwrap();
+    for_each_item(&conn, true, |item| writer.write(&item)).unwrap();
+    assert_eq!(writer.finish().unwrap(), 2);
+    
+    let lines: Vec<serde_json::Value> = String::from_utf8(output).unwrap().lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["schema"], "openinv.items");
+    assert_eq!((lines[1]["title"].as_str(), lines[2]["title"].as_str()), (Some("First"), Some("Second")));
+}
+
+#[test]
+fn test_list_and_filter_ndjson_empty_output() {
+    let home = tempfile::tempdir().unwrap();
+    for args in [&["list", "-f", "ndjson"][..], &["list", "-f", "ndjson", "--columns", "id,title"], &["filter", "-f", "ndjson"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env_remove("INVENTORY_DB_PATH")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        assert!(output.stdout.is_empty(), "{:?} printed {:?}", args, String::from_utf8_lossy(&output.stdout));
+    }
+}
+
+#[test]
+fn test_list_and_filter_stream_csv() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    
+    assert_eq!(run(&["filter", "-f", "csv"]), "No items found matching the filter criteria.\n");
+    
+    for title in ["Plain", "Comma, quoted"] {
+        insert_item(&conn, &NewItem {
+            title,
+            description: None,
+            price: 10.0,
+            quantity: 1,
+            photos: None,
+            category: "misc",
+            condition: "new",
+            brand: None,
+            upc: None,
+            item_specifics: None,
+            shipping_details: None,
+            size: None,
+            original_price: None,
+            hashtags: None,
+            colorway: None,
+            release_date: None,
+            platform_status: None,
+            internal_notes: None,
+            status: "active",
+            currency: None,
+            import_run: None,
+            sku: None,
+        }).unwrap();
+    }
+    
+    // Streaming writes exactly what the collected formatter builds
+    assert_eq!(run(&["list", "-f", "csv"]), format_csv(&get_items(&conn, false).unwrap()).unwrap());
+    assert_eq!(
+        run(&["filter", "-f", "csv", "--fields", "item_id,title"]),
+        "ITEM_ID,TITLE\n1,Plain\n2,\"Comma, quoted\"\n"
+    );
+}
+
+#[test]
+fn test_table_color_highlights_low_stock() {
+    let rows = || vec![
+        (vec!["Plenty".to_string()], Some(5)),
+        (vec!["Last one".to_string()], Some(1)),
+        (vec!["Sold out".to_string()], Some(0)),
+        (vec!["Unknown".to_string()], None),
+    ];
+    let colored = render_table(vec!["Title".to_string()], rows(), true);
+    let lines: Vec<&str> = colored.lines().collect();
+    assert!(lines[0].contains("\x1b[1m"), "bold header: {:?}", lines[0]);
+    assert!(!lines[2].contains('\x1b'));
+    assert!(lines[3].contains("\x1b[38;5;9m") && !lines[3].contains("\x1b[1m"));
+    assert!(lines[4].contains("\x1b[38;5;9m\x1b[1m"));
+    assert!(!lines[5].contains('\x1b'));
+    
+    let plain = render_table(vec!["Title".to_string()], rows(), false);
+    assert!(!plain.contains('\x1b'));
+    assert_eq!(plain, render_table(vec!["Title".to_string()], rows().into_iter().map(|(row, _)| (row, None)).collect(), false));
+}
+
+#[test]
+fn test_piped_table_output_has_no_escape_codes() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    conn.execute(
+        "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+         VALUES ('Sold out', 5, 0, 'misc', 'new', '2024-01-01T00:00:00.000000+00:00', 'active')",
+        [],
+    ).unwrap();
+    for args in [&["list", "-f", "table"][..], &["--color", "never", "filter", "-f", "table"], &["--color", "auto", "list", "-f", "table"]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Sold out"));
+        assert!(!output.stdout.contains(&0x1b), "{:?} printed {:?}", args, String::from_utf8_lossy(&output.stdout));
+    }
+}
+
+#[test]
+fn test_filter_csv_delimiter_flags() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    conn.execute(
+        "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+         VALUES ('Tab\there', 10.0, 1, 'shoes', 'new', '2024-01-01T00:00:00.000000+00:00', 'active')",
+        [],
+    ).unwrap();
+    let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(args)
+        .env("HOME", home.path())
+        .env("INVENTORY_DB_PATH", &db_path)
+        .output()
+        .unwrap();
+    
+    let output = run(&["--delimiter", "\\t", "--no-header", "filter", "-f", "csv", "--fields", "title,price"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "\"Tab\there\"\t10.00\n");
+    
+    let output = run(&["--delimiter", "\"", "filter", "-f", "csv"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("can't be used as a delimiter"));
+}
+
+#[test]
+fn test_json_compact_output() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    for title in ["First", "Second"] {
+        conn.execute(
+            "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+             VALUES (?, 10.0, 1, 'shoes', 'new', '2024-01-01T00:00:00.000000+00:00', 'active')",
+            [title],
+        ).unwrap();
+    }
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    
+    for args in [&["list", "-f", "json"][..], &["filter", "-f", "json"], &["stats", "-f", "json"]] {
+        let pretty = run(args);
+        let compact = run(&[&["--json-compact"], args].concat());
+        assert_eq!(compact.trim_end().lines().count(), 1, "{:?}: {}", args, compact);
+        assert!(pretty.trim_end().lines().count() > 1);
+        let parse = |s: &str| serde_json::from_str::<serde_json::Value>(s).unwrap();
+        assert_eq!(parse(&compact), parse(&pretty), "{:?}", args);
+    }
+    
+    // Other formats don't change
+    assert_eq!(run(&["--json-compact", "list", "-f", "csv"]), run(&["list", "-f", "csv"]));
+}
+
+#[test]
+fn test_list_and_filter_sample() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    for i in 0..10 {
+        conn.execute(
+            "INSERT INTO items (title, price, quantity, category, condition, last_updated, status) \
+             VALUES (?, 10.0, 1, ?, 'new', '2024-01-01T00:00:00.000000+00:00', 'active')",
+            rusqlite::params![format!("Item {}", i), if i % 2 == 0 { "shoes" } else { "hats" }],
+        ).unwrap();
+    }
+    let run = |args: &[&str]| Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(args)
+        .env("HOME", home.path())
+        .env("INVENTORY_DB_PATH", &db_path)
+        .output()
+        .unwrap();
+    let lines = |args: &[&str]| {
+        let output = run(args);
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap().lines().map(str::to_string).collect::<Vec<_>>()
+    };
+    
+    assert_eq!(lines(&["list", "-f", "ndjson", "--sample", "3"]).len(), 3);
+    assert_eq!(lines(&["list", "-f", "ndjson", "--columns", "title", "--sample", "4"]).len(), 4);
+    
+    // The sample is drawn from the matching items only
+    let sampled = lines(&["filter", "-f", "ndjson", "--category", "hats", "--fields", "category", "--sample", "2"]);
+    assert_eq!(sampled.len(), 2);
+    assert!(sampled.iter().all(|line| line.contains("\"category\":\"hats\"")), "{:?}", sampled);
+    // Asking for more than there are returns them all
+    assert_eq!(lines(&["filter", "-f", "ndjson", "--category", "shoes", "--sample", "50"]).len(), 5);
+    
+    let output = run(&["list", "--sample", "0"]);
+    assert!(!output.status.success());
+}
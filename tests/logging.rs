@@ -0,0 +1,39 @@
+use inventory::logging::LogLevel;
+use log::LevelFilter;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+fn openinv(home: &TempDir, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(args)
+        .env("HOME", home.path())
+        .env_remove("INVENTORY_DB_PATH")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+#[test]
+fn test_log_level_controls_output() {
+    let home = TempDir::new().unwrap();
+    let log_file = home.path().join(".inventory/logs/openinv.log");
+    
+    // The default level keeps routine diagnostics out of stderr
+    let output = openinv(&home, &["list"]);
+    assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+    
+    let output = openinv(&home, &["--log-level", "debug", "list"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[DEBUG] Opening database"), "{}", stderr);
+    assert!(std::fs::read_to_string(&log_file).unwrap().contains("DEBUG openinv::db::schema - Opening database"));
+    
+    // Command output on stdout is unaffected
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "[]");
+}
+
+#[test]
+fn test_log_level_filters() {
+    assert_eq!(LevelFilter::from(LogLevel::default()), LevelFilter::Warn);
+    assert_eq!(LevelFilter::from(LogLevel::Trace), LevelFilter::Trace);
+}
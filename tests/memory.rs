@@ -0,0 +1,83 @@
+//! Heap use of streamed versus collected output. Kept in its own test binary with a single test,
+//! since the counting allocator sees every thread's allocations.
+
+use inventory::db::queries::{for_each_item, get_items};
+use inventory::db::schema::initialize_database;
+use inventory::output::format::{format_csv_with_options, item_csv_fields, CsvWriter, OutputOptions, ITEM_FIELDS};
+use rusqlite::Connection;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// System allocator that tracks live and peak heap bytes. SQLite allocates through its own
+/// malloc, so only Rust-side buffers (items, rows, output) are counted.
+struct PeakAllocator;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(live, Ordering::SeqCst);
+        }
+        ptr
+    }
+    
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+/// Heap bytes `f` holds at its peak, above what was live when it started
+fn peak_heap_during(f: impl FnOnce()) -> usize {
+    let base = LIVE.load(Ordering::SeqCst);
+    PEAK.store(base, Ordering::SeqCst);
+    f();
+    PEAK.load(Ordering::SeqCst) - base
+}
+
+fn add_items(conn: &Connection, count: usize) {
+    conn.execute(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?)
+         INSERT INTO items (title, description, price, quantity, category, condition, brand, last_updated, status, currency)
+         SELECT 'Item ' || i, 'Description ' || i, i % 500 + 0.99, i % 7, 'sneakers', 'new', 'Nike',
+                '2024-01-01T00:00:00.000000+00:00', 'active', 'USD' FROM n",
+        [count],
+    ).unwrap();
+}
+
+/// What `list -f csv` does, written to a sink instead of stdout
+fn stream_csv(conn: &Connection, options: &OutputOptions) -> usize {
+    let mut writer = CsvWriter::new(io::sink(), &ITEM_FIELDS, options);
+    writer.write_header().unwrap();
+    for_each_item(conn, false, |item| writer.write_row(&item_csv_fields(&item, options.csv_delimiter))).unwrap();
+    writer.finish().unwrap()
+}
+
+#[test]
+fn test_streamed_csv_heap_stays_flat_as_items_grow() {
+    let conn = initialize_database(None).unwrap();
+    let options = OutputOptions::default();
+    
+    add_items(&conn, 1000);
+    let streamed_small = peak_heap_during(|| assert_eq!(stream_csv(&conn, &options), 1000));
+    
+    add_items(&conn, 19000);
+    let streamed_large = peak_heap_during(|| assert_eq!(stream_csv(&conn, &options), 20000));
+    let collected_large = peak_heap_during(|| {
+        let csv = format_csv_with_options(&get_items(&conn, false).unwrap(), &options).unwrap();
+        assert_eq!(csv.lines().count(), 20001);
+    });
+    
+    // Streaming holds one row at a time, so twenty times the items needs no more heap;
+    // collecting holds every item and the whole document
+    assert!(streamed_large <= streamed_small * 2, "streamed peak grew from {} to {} bytes", streamed_small, streamed_large);
+    assert!(collected_large > streamed_large * 100, "collected peak {} bytes vs streamed {}", collected_large, streamed_large);
+}
@@ -1,9 +1,14 @@
 use inventory::config::optimization::{PerformanceMonitor, QueryCache, optimize_database, measure_query_performance};
 use inventory::db::schema::initialize_database;
-use inventory::commands::filter::handle_filter;
-use inventory::commands::stats::handle_stats;
+use inventory::commands::filter::{handle_filter, FilterArgs};
+use inventory::commands::stats::{handle_stats, StatsArgs};
 use inventory::commands::list::OutputFormat;
+use inventory::commands::import::{handle_import, ImportOptions};
+use inventory::bulk::bulk_insert;
+use inventory::db::queries::{insert_item, NewItem, INSERT_ITEM_SQL};
+use inventory::output::format::OutputOptions;
 use rusqlite::Connection;
+use std::io::Write;
 use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
@@ -95,12 +100,14 @@ fn test_filter_performance_with_cache() {
     let start = std::time::Instant::now();
     let result = handle_filter(
         &conn,
-        Some("10-50".to_string()),
-        Some("electronics".to_string()),
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("10-50".to_string()),
+            category: Some("electronics".to_string()),
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         Some(monitor.clone()),
         Some(cache.clone()),
     );
@@ -111,12 +118,14 @@ fn test_filter_performance_with_cache() {
     let start = std::time::Instant::now();
     let result = handle_filter(
         &conn,
-        Some("10-50".to_string()),
-        Some("electronics".to_string()),
-        None,
-        None,
-        Some("item_id,title,price".to_string()),
-        Some(OutputFormat::Json),
+        FilterArgs {
+            price: Some("10-50".to_string()),
+            category: Some("electronics".to_string()),
+            fields: Some("item_id,title,price".to_string()),
+            format: Some(OutputFormat::Json),
+            ..Default::default()
+        },
+        &OutputOptions::default(),
         Some(monitor.clone()),
         Some(cache.clone()),
     );
@@ -143,7 +152,7 @@ fn test_stats_performance() {
     // Measure stats query performance
     let start = std::time::Instant::now();
     let result = measure_query_performance(&monitor, "stats_query", || {
-        handle_stats(&conn, Some(OutputFormat::Json), Some(monitor.clone()), Some(cache.clone()))
+        handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), Some(monitor.clone()), Some(cache.clone()))
     });
     let duration = start.elapsed();
     
@@ -235,12 +244,15 @@ fn test_large_dataset_performance() {
     let result = measure_query_performance(&monitor, "large_filter", || {
         handle_filter(
             &conn,
-            Some("10-100".to_string()),
-            Some("electronics".to_string()),
-            Some("new".to_string()),
-            None,
-            Some("item_id,title,price".to_string()),
-            Some(OutputFormat::Json),
+            FilterArgs {
+                price: Some("10-100".to_string()),
+                category: Some("electronics".to_string()),
+                condition: Some("new".to_string()),
+                fields: Some("item_id,title,price".to_string()),
+                format: Some(OutputFormat::Json),
+                ..Default::default()
+            },
+            &OutputOptions::default(),
             Some(monitor.clone()),
             Some(cache.clone()),
         )
@@ -253,4 +265,324 @@ fn test_large_dataset_performance() {
     let stats = monitor.get_stats();
     assert!(stats.contains_key("large_filter_avg_ms"));
     assert!(stats["large_filter_count"] == 1.0);
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_import_large_file_commits_every_row() {
+    let row_count = 10000;
+    let dir = tempfile::tempdir().unwrap();
+    
+    let mut csv_file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(csv_file, "item_id,title,description,price,quantity,upc,category,condition,brand").unwrap();
+    for i in 0..row_count {
+        writeln!(csv_file, "{},Item {},Description {},19.99,3,,electronics,new,Brand", i, i, i).unwrap();
+    }
+    
+    let conn = initialize_database(Some(&dir.path().join("import.db"))).unwrap();
+    handle_import(csv_file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    // The import's transaction is committed, not left open, and holds every row
+    assert!(conn.is_autocommit());
+    let reopened = Connection::open(dir.path().join("import.db")).unwrap();
+    let count: i64 = reopened.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, row_count as i64);
+}
+
+/// Wall-clock comparison, so it's left out of the default run: `cargo test -- --ignored`
+#[test]
+#[ignore = "timing comparison; run on a quiet machine with --ignored"]
+fn test_import_large_file_in_single_transaction() {
+    let row_count = 10000;
+    let dir = tempfile::tempdir().unwrap();
+    
+    let mut csv_file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(csv_file, "item_id,title,description,price,quantity,upc,category,condition,brand").unwrap();
+    for i in 0..row_count {
+        writeln!(csv_file, "{},Item {},Description {},19.99,3,,electronics,new,Brand", i, i, i).unwrap();
+    }
+    
+    // Transactional import into a file-backed database
+    let import_conn = initialize_database(Some(&dir.path().join("import.db"))).unwrap();
+    let start = std::time::Instant::now();
+    handle_import(csv_file.path().to_str().unwrap().to_string(), &import_conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    let import_duration = start.elapsed();
+    
+    let count: i64 = import_conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, row_count as i64);
+    
+    // Row-by-row autocommit inserts of the same data, without any CSV parsing or validation
+    let autocommit_conn = initialize_database(Some(&dir.path().join("autocommit.db"))).unwrap();
+    let start = std::time::Instant::now();
+    for i in 0..row_count {
+        let title = format!("Item {}", i);
+        let description = format!("Description {}", i);
+        insert_item(&autocommit_conn, &NewItem {
+            title: &title,
+            description: Some(&description),
+            price: 19.99,
+            quantity: 3,
+            photos: None,
+            category: "electronics",
+            condition: "new",
+            brand: Some("Brand"),
+            upc: None,
+            item_specifics: None,
+            shipping_details: None,
+            size: None,
+            original_price: None,
+            hashtags: None,
+            colorway: None,
+            release_date: None,
+            platform_status: None,
+            internal_notes: None,
+            status: "active",
+            currency: None,
+            import_run: None,
+            sku: None,
+        }).unwrap();
+    }
+    let autocommit_duration = start.elapsed();
+    
+    println!("Transactional import: {:?}, row-by-row inserts: {:?}", import_duration, autocommit_duration);
+    // The import does strictly more work per row, so it only wins by avoiding per-row commits
+    assert!(import_duration < autocommit_duration, "Import should be faster than committing every row");
+}
+
+fn insert_titled_item(conn: &Connection, title: &str) {
+    insert_item(conn, &NewItem {
+        title,
+        description: None,
+        price: 19.99,
+        quantity: 3,
+        photos: None,
+        category: "electronics",
+        condition: "new",
+        brand: Some("Brand"),
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    }).unwrap();
+}
+
+/// Runs of the cached insert after a single insert; trigger programs count towards them
+fn runs_per_insert() -> i32 {
+    let conn = initialize_database(None).unwrap();
+    insert_titled_item(&conn, "Probe");
+    let runs = conn.prepare_cached(INSERT_ITEM_SQL).unwrap().get_status(rusqlite::StatementStatus::Run);
+    assert!(runs > 0, "insert_item did not cache its statement");
+    runs
+}
+
+#[test]
+fn test_insert_item_reuses_cached_statement() {
+    let row_count = 50;
+    let conn = initialize_database(None).unwrap();
+    for i in 0..row_count {
+        insert_titled_item(&conn, &format!("Item {}", i));
+    }
+    
+    // A statement prepared per row would never get past a single insert's runs
+    let stmt = conn.prepare_cached(INSERT_ITEM_SQL).unwrap();
+    assert_eq!(stmt.get_status(rusqlite::StatementStatus::Run), row_count * runs_per_insert());
+}
+
+#[test]
+fn test_import_reuses_cached_insert_statement() {
+    let row_count = 200;
+    let mut csv_file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(csv_file, "item_id,title,description,price,quantity,upc,category,condition,brand").unwrap();
+    for i in 0..row_count {
+        writeln!(csv_file, "{},Item {},Description {},19.99,3,,electronics,new,Brand", i, i, i).unwrap();
+    }
+    
+    let conn = initialize_database(None).unwrap();
+    handle_import(csv_file.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    
+    let stmt = conn.prepare_cached(INSERT_ITEM_SQL).unwrap();
+    assert_eq!(stmt.get_status(rusqlite::StatementStatus::Run), row_count * runs_per_insert());
+}
+
+/// Wall-clock comparison, so it's left out of the default run: `cargo test -- --ignored`
+#[test]
+#[ignore = "timing comparison; run on a quiet machine with --ignored"]
+fn test_cached_insert_beats_reprepared_insert() {
+    let row_count = 20000;
+    let titles: Vec<String> = (0..row_count).map(|i| format!("Item {}", i)).collect();
+    
+    // Preparing the insert for every row, as insert_item did before it cached the statement
+    let prepared_conn = initialize_database(None).unwrap();
+    let tx = prepared_conn.unchecked_transaction().unwrap();
+    let start = std::time::Instant::now();
+    for title in &titles {
+        tx.prepare(INSERT_ITEM_SQL).unwrap().execute(rusqlite::params![
+            title, None::<String>, 19.99, 3, None::<String>, "electronics", "new", "Brand", None::<String>,
+            None::<String>, None::<String>, None::<String>, None::<f64>, None::<String>, None::<String>, None::<String>,
+            None::<String>, None::<String>, "2024-01-01T00:00:00.000000+00:00", "active", "USD", None::<String>, None::<String>,
+        ]).unwrap();
+    }
+    let prepare_duration = start.elapsed();
+    tx.commit().unwrap();
+    
+    let cached_conn = initialize_database(None).unwrap();
+    let tx = cached_conn.unchecked_transaction().unwrap();
+    let start = std::time::Instant::now();
+    for title in &titles {
+        insert_item(&tx, &NewItem {
+            title,
+            description: None,
+            price: 19.99,
+            quantity: 3,
+            photos: None,
+            category: "electronics",
+            condition: "new",
+            brand: Some("Brand"),
+            upc: None,
+            item_specifics: None,
+            shipping_details: None,
+            size: None,
+            original_price: None,
+            hashtags: None,
+            colorway: None,
+            release_date: None,
+            platform_status: None,
+            internal_notes: None,
+            status: "active",
+            currency: None,
+            import_run: None,
+            sku: None,
+        }).unwrap();
+    }
+    let cached_duration = start.elapsed();
+    tx.commit().unwrap();
+    
+    let count: i64 = cached_conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, row_count as i64);
+    
+    println!("{} inserts with prepare: {:?}, with prepare_cached: {:?}", row_count, prepare_duration, cached_duration);
+    assert!(cached_duration < prepare_duration, "Cached statements should be faster than re-preparing every row");
+}
+
+#[test]
+fn test_profile_prints_stats_to_stderr() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    initialize_database(Some(&db_path)).unwrap();
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+    };
+    
+    let (stdout, stderr) = run(&["--profile", "list"]);
+    assert!(stderr.contains("| list_query_count  | 1     |"), "{}", stderr);
+    assert!(!stdout.contains("list_query_count"));
+    
+    let (_, stderr) = run(&["--profile", "--format", "json", "filter"]);
+    let stats: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(stats["filter_query_count"], 1.0);
+    assert!(stats.get("cache_misses").is_some());
+    
+    let (_, stderr) = run(&["--profile", "--quiet", "list"]);
+    assert!(stderr.is_empty(), "{}", stderr);
+}
+
+#[test]
+fn test_bulk_insert_is_all_or_nothing() {
+    let titles: Vec<String> = (0..1000).map(|i| format!("Item {}", i)).collect();
+    let skus: Vec<String> = (0..1000).map(|i| format!("SKU-{}", i)).collect();
+    let new_item = |i: usize| NewItem {
+        title: &titles[i],
+        description: None,
+        price: 19.99,
+        quantity: 3,
+        photos: None,
+        category: "electronics",
+        condition: "new",
+        brand: Some("Brand"),
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: Some(&skus[i]),
+    };
+    let count = |conn: &Connection| conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get::<_, i64>(0)).unwrap();
+    
+    let conn = initialize_database(None).unwrap();
+    let items: Vec<NewItem> = (0..1000).map(new_item).collect();
+    assert_eq!(bulk_insert(&conn, &items).unwrap(), 1000);
+    assert_eq!(count(&conn), 1000);
+    
+    // Item 500 repeats item 10's SKU, so none of the batch goes in
+    let conn = initialize_database(None).unwrap();
+    let mut items: Vec<NewItem> = (0..1000).map(new_item).collect();
+    items[500].sku = Some(&skus[10]);
+    let err = bulk_insert(&conn, &items).unwrap_err();
+    assert_eq!(err.to_string(), "Item 500 ('Item 500') could not be inserted");
+    assert!(format!("{:#}", err).contains("UNIQUE constraint failed"), "{:#}", err);
+    assert_eq!(count(&conn), 0);
+    // The connection is usable afterwards, not stuck in the failed transaction
+    assert_eq!(bulk_insert(&conn, &items[..500]).unwrap(), 500);
+}
+
+#[test]
+fn test_no_cache_skips_cached_results() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    add_test_items(&conn, 1);
+    let run = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        (String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap())
+    };
+    let filtered = |args: &[&str]| serde_json::from_str::<Vec<serde_json::Value>>(&run(args).0).unwrap().len();
+    let total_items = |args: &[&str]| serde_json::from_str::<serde_json::Value>(&run(args).0).unwrap()["total_items"].clone();
+    
+    assert_eq!(filtered(&["filter", "--format", "json"]), 1);
+    assert_eq!(total_items(&["stats", "--format", "json"]), 1);
+    // Written behind the CLI's back, so nothing clears the cached results
+    add_test_items(&conn, 1);
+    assert_eq!(filtered(&["filter", "--format", "json"]), 1);
+    assert_eq!(total_items(&["stats", "--format", "json"]), 1);
+    
+    assert_eq!(filtered(&["--no-cache", "filter", "--format", "json"]), 2);
+    assert_eq!(total_items(&["--no-cache", "stats", "--format", "json"]), 2);
+    // Fresh results aren't stored either, so the stale ones are still what's cached
+    assert_eq!(filtered(&["filter", "--format", "json"]), 1);
+    
+    // The query is still timed, but there's no cache to hit
+    let (_, stderr) = run(&["--profile", "--format", "json", "--no-cache", "filter"]);
+    let stats: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(stats["filter_query_count"], 1.0);
+    assert_eq!(stats["cache_hits"], 0.0);
+}
@@ -0,0 +1,37 @@
+use inventory::commands::export::handle_export;
+use inventory::db::schema::initialize_database;
+use inventory::output::format::OutputOptions;
+use inventory::plugins::loader::{discover_plugins_in, load_exporter, PLUGIN_DIR_ENV};
+use tempfile::TempDir;
+
+#[test]
+fn test_discover_plugins() {
+    let dir = TempDir::new().unwrap();
+    assert!(discover_plugins_in(&dir.path().join("missing")).unwrap().is_empty());
+    assert!(discover_plugins_in(dir.path()).unwrap().is_empty());
+    
+    // Only library files are picked up; one that isn't a real library is reported, not fatal
+    let library = dir.path().join(format!("{}broken.so", std::env::consts::DLL_PREFIX));
+    std::fs::write(&library, b"not a shared library").unwrap();
+    std::fs::write(dir.path().join("README.txt"), b"notes").unwrap();
+    
+    let plugins = discover_plugins_in(dir.path()).unwrap();
+    assert_eq!(plugins.len(), 1);
+    assert_eq!(plugins[0].name, "broken");
+    assert_eq!(plugins[0].path, library);
+    assert!(!plugins[0].exporter && !plugins[0].formatter);
+    assert!(plugins[0].error.is_some());
+}
+
+#[test]
+fn test_export_with_missing_exporter() {
+    let dir = TempDir::new().unwrap();
+    std::env::set_var(PLUGIN_DIR_ENV, dir.path());
+    let conn = initialize_database(None).unwrap();
+    
+    let err = handle_export(&conn, None, None, false, None, Some("shopify"), &OutputOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("Exporter plugin 'shopify' not found"));
+    
+    let err = load_exporter("../shopify").err().unwrap();
+    assert!(err.to_string().contains("Invalid plugin name"));
+}
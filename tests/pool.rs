@@ -0,0 +1,68 @@
+#![cfg(feature = "pool")]
+
+use inventory::commands::filter::count_items;
+use inventory::db::pool::initialize_pool;
+use inventory::db::queries::{get_items, insert_item, NewItem};
+use std::thread;
+
+fn new_item(title: &str) -> NewItem<'_> {
+    NewItem {
+        title,
+        description: None,
+        price: 25.0,
+        quantity: 1,
+        photos: None,
+        category: "misc",
+        condition: "new",
+        brand: None,
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    }
+}
+
+#[test]
+fn test_pooled_connections_read_concurrently() {
+    let dir = tempfile::tempdir().unwrap();
+    let pool = initialize_pool(&dir.path().join("inventory.db"), 4).unwrap();
+    insert_item(&pool.get().unwrap(), &new_item("Pooled")).unwrap();
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let pool = pool.clone();
+            thread::spawn(move || get_items(&pool.get().unwrap(), false).unwrap().len())
+        })
+        .collect();
+    for reader in readers {
+        assert_eq!(reader.join().unwrap(), 1);
+    }
+}
+
+#[test]
+fn test_readers_are_not_blocked_by_an_open_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let pool = initialize_pool(&dir.path().join("inventory.db"), 2).unwrap();
+    let writer = pool.get().unwrap();
+    insert_item(&writer, &new_item("Committed")).unwrap();
+
+    writer.execute_batch("BEGIN IMMEDIATE").unwrap();
+    insert_item(&writer, &new_item("Uncommitted")).unwrap();
+
+    // WAL lets the reader see the last commit while the write is still open
+    let reader = pool.get().unwrap();
+    assert_eq!(count_items(&reader, None, None, None, None, false).unwrap(), 1);
+
+    writer.execute_batch("COMMIT").unwrap();
+    assert_eq!(count_items(&reader, None, None, None, None, false).unwrap(), 2);
+}
@@ -0,0 +1,73 @@
+use inventory::commands::reprice::{handle_reprice, PriceAdjustment};
+use inventory::db::history::undo_last_operation;
+use inventory::db::queries::{insert_item, set_item_status, NewItem};
+use inventory::db::schema::initialize_database;
+use rusqlite::Connection;
+
+fn insert(conn: &Connection, title: &str, price: f64, category: &str, brand: &str) -> i64 {
+    insert_item(conn, &NewItem {
+        title,
+        description: None,
+        price,
+        quantity: 1,
+        photos: None,
+        category,
+        condition: "new",
+        brand: Some(brand),
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    }).unwrap();
+    conn.last_insert_rowid()
+}
+
+fn prices(conn: &Connection) -> Vec<f64> {
+    conn.prepare("SELECT price FROM items ORDER BY item_id").unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect()
+}
+
+#[test]
+fn test_reprice_by_percent_within_filters() {
+    let conn = initialize_database(None).unwrap();
+    insert(&conn, "Air Jordan 1", 200.0, "sneakers", "Nike");
+    insert(&conn, "Dunk Low", 19.99, "sneakers", "Nike");
+    insert(&conn, "Yeezy 350", 300.0, "sneakers", "Adidas");
+    insert(&conn, "Tech Fleece", 100.0, "apparel", "Nike");
+    let sold = insert(&conn, "Air Max 90", 150.0, "sneakers", "Nike");
+    set_item_status(&conn, sold, "sold").unwrap();
+
+    handle_reprice(&conn, Some("sneakers"), Some("Nike"), PriceAdjustment::Percent(-10.0)).unwrap();
+    // Other brands, categories and sold items keep their prices
+    assert_eq!(prices(&conn), vec![180.0, 17.99, 300.0, 100.0, 150.0]);
+
+    // The whole repricing is one step for undo
+    undo_last_operation(&conn).unwrap().unwrap();
+    assert_eq!(prices(&conn), vec![200.0, 19.99, 300.0, 100.0, 150.0]);
+}
+
+#[test]
+fn test_reprice_skips_prices_that_would_go_negative() {
+    let conn = initialize_database(None).unwrap();
+    insert(&conn, "Socks", 3.0, "apparel", "Nike");
+    insert(&conn, "Hoodie", 60.0, "apparel", "Nike");
+
+    handle_reprice(&conn, None, None, PriceAdjustment::Add(-5.0)).unwrap();
+    assert_eq!(prices(&conn), vec![3.0, 55.0]);
+
+    // Nothing matching isn't an error
+    handle_reprice(&conn, Some("electronics"), None, PriceAdjustment::Add(1.0)).unwrap();
+}
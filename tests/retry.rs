@@ -0,0 +1,86 @@
+use inventory::db::queries::{insert_item, NewItem};
+use inventory::db::retry::{retry_on_busy, set_busy_retries};
+use inventory::db::schema::initialize_database;
+use rusqlite::{ffi, Connection};
+use std::time::Duration;
+
+// Both tests set the same retry count, so running them in parallel doesn't change either
+const RETRIES: u32 = 3;
+
+fn sqlite_error(code: i32) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(ffi::Error::new(code), None)
+}
+
+#[test]
+fn test_retry_on_busy_stops_after_configured_attempts() {
+    set_busy_retries(RETRIES);
+    
+    let mut attempts = 0;
+    let result: rusqlite::Result<()> = retry_on_busy(|| { attempts += 1; Err(sqlite_error(ffi::SQLITE_BUSY)) });
+    assert_eq!(result.unwrap_err().sqlite_error_code(), Some(rusqlite::ErrorCode::DatabaseBusy));
+    assert_eq!(attempts, RETRIES + 1);
+    
+    // A busy error wrapped in context still counts
+    let mut attempts = 0;
+    let result = retry_on_busy(|| {
+        attempts += 1;
+        if attempts < 3 {
+            return Err(anyhow::Error::new(sqlite_error(ffi::SQLITE_BUSY)).context("Failed to delete item"));
+        }
+        Ok(attempts)
+    });
+    assert_eq!(result.unwrap(), 3);
+    
+    // Other errors aren't retried
+    let mut attempts = 0;
+    let result: rusqlite::Result<()> = retry_on_busy(|| { attempts += 1; Err(sqlite_error(ffi::SQLITE_CONSTRAINT)) });
+    assert!(result.is_err());
+    assert_eq!(attempts, 1);
+}
+
+#[test]
+fn test_insert_waits_out_a_brief_lock() {
+    set_busy_retries(RETRIES);
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    // Without SQLite's own wait, only the retries can get past the lock
+    conn.busy_timeout(Duration::ZERO).unwrap();
+    
+    let (locked, wait_for_lock) = std::sync::mpsc::channel();
+    let holder = std::thread::spawn(move || {
+        let other = Connection::open(&db_path).unwrap();
+        other.execute_batch("BEGIN IMMEDIATE").unwrap();
+        locked.send(()).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        other.execute_batch("COMMIT").unwrap();
+    });
+    wait_for_lock.recv().unwrap();
+    
+    let inserted = insert_item(&conn, &NewItem {
+        title: "Laptop",
+        description: None,
+        price: 999.99,
+        quantity: 1,
+        photos: None,
+        category: "electronics",
+        condition: "new",
+        brand: None,
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    });
+    holder.join().unwrap();
+    assert_eq!(inserted.unwrap(), 1);
+}
@@ -0,0 +1,95 @@
+use inventory::db::schema::{initialize_database, has_search_index, CREATE_ITEMS_TABLE_SQL};
+use inventory::db::queries::{insert_item, NewItem};
+use inventory::commands::search::{search_items, handle_search};
+use inventory::commands::list::OutputFormat;
+use inventory::output::format::OutputOptions;
+use rusqlite::Connection;
+
+fn add_test_item(conn: &Connection, title: &str, description: Option<&str>) {
+    let item = NewItem {
+        title,
+        description,
+        price: 100.0,
+        quantity: 1,
+        photos: None,
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    };
+    insert_item(conn, &item).unwrap();
+}
+
+#[test]
+fn test_search_matches_title_and_description() {
+    let conn = initialize_database(None).unwrap();
+    assert!(has_search_index(&conn).unwrap());
+    
+    add_test_item(&conn, "Air Jordan 1 Retro High OG", Some("Chicago colorway"));
+    add_test_item(&conn, "Vintage Denim Jacket", Some("Classic 90s denim"));
+    add_test_item(&conn, "Dunk Low", Some("Pairs well with any Jordan fan's closet"));
+    
+    let items = search_items(&conn, "jordan").unwrap();
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|i| i.title != "Vintage Denim Jacket"));
+    
+    let items = search_items(&conn, "denim").unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].title, "Vintage Denim Jacket");
+}
+
+#[test]
+fn test_search_index_stays_in_sync() {
+    let conn = initialize_database(None).unwrap();
+    add_test_item(&conn, "Air Jordan 1", None);
+    
+    conn.execute("UPDATE items SET title = 'Yeezy Boost 350' WHERE item_id = 1", []).unwrap();
+    assert!(search_items(&conn, "jordan").unwrap().is_empty());
+    assert_eq!(search_items(&conn, "yeezy").unwrap().len(), 1);
+    
+    conn.execute("DELETE FROM items WHERE item_id = 1", []).unwrap();
+    assert!(search_items(&conn, "yeezy").unwrap().is_empty());
+}
+
+#[test]
+fn test_search_special_characters_are_literal() {
+    let conn = initialize_database(None).unwrap();
+    add_test_item(&conn, "Air Jordan 1 \"Chicago\"", None);
+    
+    let result = search_items(&conn, "\"chicago\" AND -");
+    assert!(result.is_ok(), "FTS syntax in user input should not cause an error");
+}
+
+#[test]
+fn test_search_falls_back_without_index() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(CREATE_ITEMS_TABLE_SQL, []).unwrap();
+    assert!(!has_search_index(&conn).unwrap());
+    
+    add_test_item(&conn, "Air Jordan 1", Some("retro"));
+    add_test_item(&conn, "Denim Jacket", None);
+    
+    let items = search_items(&conn, "jordan").unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].title, "Air Jordan 1");
+}
+
+#[test]
+fn test_search_empty_query() {
+    let conn = initialize_database(None).unwrap();
+    assert!(search_items(&conn, "   ").is_err());
+    assert!(handle_search(&conn, "", Some(OutputFormat::Json), &OutputOptions::default()).is_err());
+}
@@ -1,11 +1,14 @@
 use inventory::db::schema::initialize_database;
-use inventory::commands::stats::handle_stats;
+use inventory::commands::stats::{handle_stats, handle_low_stock, compute_stats, StatsArgs, StatsFilters};
+use inventory::db::queries::{get_duplicate_upcs, get_low_stock_items, DuplicateUpc};
+use inventory::config::currency::ExchangeRates;
 use inventory::commands::list::OutputFormat;
 use rusqlite::Connection;
 use std::io::Write;
 use tempfile::NamedTempFile;
 use std::sync::Arc;
 use inventory::config::optimization::{PerformanceMonitor, QueryCache};
+use inventory::output::format::OutputOptions;
 
 fn setup_test_db() -> Connection {
     let conn = initialize_database(None).unwrap();
@@ -58,15 +61,15 @@ fn test_stats_empty_database() {
     let conn = setup_test_db();
     
     // Test table format (default)
-    let result = handle_stats(&conn, None, None, None);
+    let result = handle_stats(&conn, &StatsArgs::default(), &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Test JSON format
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Test CSV format
-    let result = handle_stats(&conn, Some(OutputFormat::Csv), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Csv), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
 }
 
@@ -77,7 +80,7 @@ fn test_stats_single_item() {
     // Add a single item
     add_test_item(&conn, "Test Item", 29.99, 2, "electronics", "new", Some("TestBrand"));
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Verify the item was added
@@ -103,7 +106,7 @@ fn test_stats_multiple_categories() {
     add_test_item(&conn, "Book", 12.99, 2, "books", "used", Some("Penguin"));
     add_test_item(&conn, "Phone", 599.99, 1, "electronics", "new", Some("Apple"));
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Verify we have 3 categories
@@ -123,6 +126,42 @@ fn test_stats_multiple_categories() {
     assert_eq!(electronics_count, 2);
 }
 
+#[test]
+fn test_stats_category_price_spread() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    add_test_item(&conn, "Laptop", 999.99, 1, "electronics", "new", Some("Dell"));
+    add_test_item(&conn, "Cable", 4.50, 10, "electronics", "new", None);
+    add_test_item(&conn, "Phone", 599.99, 1, "electronics", "used", Some("Apple"));
+    add_test_item(&conn, "Book", 12.99, 2, "books", "used", Some("Penguin"));
+    
+    let stats = compute_stats(&conn, None, &StatsFilters::default()).unwrap();
+    let spread: Vec<(&str, f64, f64)> = stats.categories.iter()
+        .map(|category| (category.category.as_str(), category.min_price, category.max_price))
+        .collect();
+    assert_eq!(spread, vec![("electronics", 4.50, 999.99), ("books", 12.99, 12.99)]);
+    
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(["stats", "--format", "table"])
+        .env("HOME", home.path())
+        .env("INVENTORY_DB_PATH", &db_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let table = String::from_utf8(output.stdout).unwrap();
+    let section: Vec<&str> = table.lines()
+        .skip_while(|line| *line != "=== BY CATEGORY ===")
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .collect();
+    assert_eq!(section.len(), 4, "{}", table);
+    assert_eq!(section[2], "electronics       | 3     | $1644.98    | $534.83     | $4.50       | $999.99");
+    // Every row puts its column separators where the header does
+    let pipes = |line: &str| line.char_indices().filter(|(_, c)| *c == '|').map(|(i, _)| i).collect::<Vec<_>>();
+    assert!(section.iter().all(|line| pipes(line) == pipes(section[0])), "{}", table);
+}
+
 #[test]
 fn test_stats_different_conditions() {
     let conn = setup_test_db();
@@ -133,7 +172,7 @@ fn test_stats_different_conditions() {
     add_test_item(&conn, "Like New", 75.0, 1, "test", "like new", Some("Brand3"));
     add_test_item(&conn, "Good Item", 25.0, 1, "test", "good", Some("Brand4"));
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Verify we have 4 different conditions
@@ -154,7 +193,7 @@ fn test_stats_brands_with_nulls() {
     add_test_item(&conn, "No Brand Item", 50.0, 1, "test", "new", None);
     add_test_item(&conn, "Another Branded", 75.0, 1, "test", "new", Some("Adidas"));
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Verify we have 3 brands (including "Unknown" for NULL)
@@ -177,7 +216,7 @@ fn test_stats_price_ranges() {
     add_test_item(&conn, "Expensive", 150.99, 1, "test", "new", Some("Brand4")); // Under $250
     add_test_item(&conn, "Premium", 500.99, 1, "test", "new", Some("Brand5")); // Over $250
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Verify we have items in different price ranges
@@ -205,7 +244,7 @@ fn test_stats_average_price_calculation() {
     add_test_item(&conn, "Item 2", 20.0, 1, "test", "new", Some("Brand2"));
     add_test_item(&conn, "Item 3", 30.0, 1, "test", "new", Some("Brand3"));
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Verify average price calculation
@@ -217,6 +256,25 @@ fn test_stats_average_price_calculation() {
     assert_eq!(avg_price, 20.0); // (10 + 20 + 30) / 3 = 20
 }
 
+#[test]
+fn test_stats_median_and_p90_price() {
+    let conn = setup_test_db();
+    let stats = compute_stats(&conn, None, &StatsFilters::default()).unwrap();
+    assert_eq!((stats.median_price, stats.p90_price), (0.0, 0.0));
+    
+    // One expensive item skews the average but not the median
+    for price in [10.0, 20.0, 30.0, 40.0, 1000.0] {
+        add_test_item(&conn, "Item", price, 1, "test", "new", None);
+    }
+    let stats = compute_stats(&conn, None, &StatsFilters::default()).unwrap();
+    assert_eq!(stats.average_price, 220.0);
+    assert_eq!(stats.median_price, 30.0);
+    assert!((stats.p90_price - 616.0).abs() < 1e-9);
+    
+    add_test_item(&conn, "Item", 50.0, 1, "test", "new", None);
+    assert_eq!(compute_stats(&conn, None, &StatsFilters::default()).unwrap().median_price, 35.0);
+}
+
 #[test]
 fn test_stats_total_value_calculation() {
     let conn = setup_test_db();
@@ -226,7 +284,7 @@ fn test_stats_total_value_calculation() {
     add_test_item(&conn, "Item 2", 15.0, 3, "test", "new", Some("Brand2")); // 15 * 3 = 45
     add_test_item(&conn, "Item 3", 25.0, 1, "test", "new", Some("Brand3")); // 25 * 1 = 25
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Verify total value calculation (price * quantity)
@@ -245,7 +303,7 @@ fn test_stats_table_format() {
     // Add some test data
     add_test_item(&conn, "Test Item", 29.99, 2, "electronics", "new", Some("TestBrand"));
     
-    let result = handle_stats(&conn, Some(OutputFormat::Table), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Table), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
 }
 
@@ -256,7 +314,7 @@ fn test_stats_csv_format() {
     // Add some test data
     add_test_item(&conn, "Test Item", 29.99, 2, "electronics", "new", Some("TestBrand"));
     
-    let result = handle_stats(&conn, Some(OutputFormat::Csv), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Csv), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
 }
 
@@ -296,7 +354,7 @@ fn test_stats_large_dataset() {
         ).unwrap();
     }
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), None, None);
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
     
     // Verify we have 1000 items
@@ -364,6 +422,207 @@ fn test_stats_with_performance_monitoring() {
     let monitor = Arc::new(PerformanceMonitor::new());
     let cache = Arc::new(QueryCache::new(monitor.clone()));
     
-    let result = handle_stats(&conn, Some(OutputFormat::Json), Some(monitor), Some(cache));
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), ..Default::default() }, &OutputOptions::default(), Some(monitor), Some(cache));
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_stats_do_not_sum_mixed_currencies() {
+    let conn = setup_test_db();
+    
+    add_test_item(&conn, "US Item", 10.0, 2, "test", "new", None);
+    add_test_item(&conn, "Another US Item", 30.0, 1, "test", "new", None);
+    add_test_item(&conn, "EU Item", 100.0, 1, "test", "new", None);
+    conn.execute("UPDATE items SET currency = 'EUR' WHERE title = 'EU Item'", []).unwrap();
+    
+    // Without rates, totals cover only the most common currency
+    let stats = compute_stats(&conn, None, &StatsFilters::default()).unwrap();
+    assert_eq!(stats.total_items, 3);
+    assert_eq!(stats.currency, "USD");
+    assert!((stats.total_value - 50.0).abs() < 0.001);
+    assert!((stats.average_price - 20.0).abs() < 0.001);
+    assert_eq!(stats.unconverted_items, 1);
+    assert_eq!(stats.currencies.len(), 2);
+    assert_eq!(stats.currencies[1].currency, "EUR");
+    assert!((stats.currencies[1].total_value - 100.0).abs() < 0.001);
+    
+    // With a rate table, everything is converted into the base currency
+    let rates = ExchangeRates::parse("base = \"USD\"\n[rates]\nEUR = 1.5\n").unwrap();
+    let stats = compute_stats(&conn, Some(&rates), &StatsFilters::default()).unwrap();
+    assert!((stats.total_value - 200.0).abs() < 0.001);
+    assert_eq!(stats.unconverted_items, 0);
+    assert_eq!(stats.price_ranges.over_250, 0);
+    assert_eq!(stats.price_ranges.under_250, 3);
+    
+    let result = handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Table), rates: Some(&rates), ..Default::default() }, &OutputOptions::default(), None, None);
     assert!(result.is_ok());
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_stats_scoped_by_filters() {
+    let conn = setup_test_db();
+    add_test_item(&conn, "Cheap Gadget", 20.0, 1, "electronics", "new", Some("Acme"));
+    add_test_item(&conn, "Budget Gadget", 40.0, 2, "electronics", "used", Some("Acme"));
+    add_test_item(&conn, "Pricey Gadget", 300.0, 1, "electronics", "new", Some("Acme"));
+    add_test_item(&conn, "Shirt", 15.0, 1, "clothing", "new", None);
+    
+    let filters = StatsFilters { category: Some("electronics".to_string()), price: Some("<50".to_string()), ..Default::default() };
+    let stats = compute_stats(&conn, None, &filters).unwrap();
+    assert_eq!(stats.total_items, 2);
+    assert_eq!(stats.total_value, 100.0);
+    assert_eq!(stats.median_price, 30.0);
+    assert_eq!(stats.categories.len(), 1);
+    assert_eq!(stats.price_ranges.under_50, 2);
+    
+    // Nothing matching gives zeroed stats rather than an error
+    let filters = StatsFilters { brand: Some("Nobody".to_string()), ..Default::default() };
+    let stats = compute_stats(&conn, None, &filters).unwrap();
+    assert_eq!((stats.total_items, stats.total_value, stats.average_price, stats.median_price), (0, 0.0, 0.0, 0.0));
+    assert!(stats.categories.is_empty());
+    
+    let bad_price = StatsFilters { price: Some("cheap".to_string()), ..Default::default() };
+    assert!(compute_stats(&conn, None, &bad_price).is_err());
+}
+
+#[test]
+fn test_stats_cache_keeps_scoped_results_apart() {
+    let conn = setup_test_db();
+    add_test_item(&conn, "Gadget", 20.0, 1, "electronics", "new", None);
+    add_test_item(&conn, "Shirt", 15.0, 1, "clothing", "new", None);
+    
+    let cache = Arc::new(QueryCache::new(Arc::new(PerformanceMonitor::new())));
+    let scoped = StatsFilters { category: Some("clothing".to_string()), ..Default::default() };
+    for filters in [StatsFilters::default(), scoped] {
+        handle_stats(&conn, &StatsArgs { format: Some(OutputFormat::Json), filters, ..Default::default() }, &OutputOptions::default(), None, Some(cache.clone())).unwrap();
+    }
+    assert_eq!(cache.len(), 2);
+}
+
+#[test]
+fn test_stats_low_stock_items() {
+    let conn = setup_test_db();
+    add_test_item(&conn, "Plenty", 10.0, 20, "test", "new", None);
+    add_test_item(&conn, "Two Left", 10.0, 2, "test", "new", None);
+    add_test_item(&conn, "Sold Out", 10.0, 0, "test", "new", None);
+    add_test_item(&conn, "At Threshold", 10.0, 5, "test", "new", None);
+    add_test_item(&conn, "Archived", 10.0, 1, "test", "new", None);
+    conn.execute("UPDATE items SET status = 'sold' WHERE title = 'Archived'", []).unwrap();
+    
+    // Most urgent first; sold items aren't running low
+    let items = get_low_stock_items(&conn, 5).unwrap();
+    let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+    assert_eq!(titles, vec!["Sold Out", "Two Left", "At Threshold"]);
+    assert!(get_low_stock_items(&conn, -1).unwrap().is_empty());
+    
+    assert!(handle_low_stock(&conn, 5, Some(OutputFormat::Json), &OutputOptions::default()).is_ok());
+    assert!(handle_low_stock(&conn, 5, None, &OutputOptions::default()).is_ok());
+}
+
+#[test]
+fn test_stats_duplicate_upcs() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    for title in ["Laptop", "Laptop (copy)", "Phone", "Tee", "Archived Laptop", "Mug", "Cup"] {
+        add_test_item(&conn, title, 10.0, 1, "test", "new", None);
+    }
+    let stats = |args: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(["stats", "--duplicate-upc"])
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    
+    assert!(get_duplicate_upcs(&conn).unwrap().is_empty());
+    assert_eq!(stats(&[]), "No duplicate UPCs found.\n");
+    assert_eq!(stats(&["--format", "json"]).trim(), "[]");
+    
+    // Archived items count; items without a UPC (here Mug and Cup) don't
+    for (title, upc) in [("Laptop", "012345678905"), ("Laptop (copy)", "012345678905"), ("Archived Laptop", "012345678905"), ("Phone", "036000291452"), ("Tee", "036000291452"), ("Mug", "")] {
+        conn.execute("UPDATE items SET upc = ? WHERE title = ?", [upc, title]).unwrap();
+    }
+    conn.execute("UPDATE items SET status = 'sold' WHERE title = 'Archived Laptop'", []).unwrap();
+    assert_eq!(get_duplicate_upcs(&conn).unwrap(), vec![
+        DuplicateUpc { upc: "012345678905".to_string(), item_ids: vec![1, 2, 5] },
+        DuplicateUpc { upc: "036000291452".to_string(), item_ids: vec![3, 4] },
+    ]);
+    
+    assert_eq!(stats(&["--format", "csv"]), "upc,item_ids\n012345678905,1 2 5\n036000291452,3 4\n");
+    let json: serde_json::Value = serde_json::from_str(&stats(&["--format", "json"])).unwrap();
+    assert_eq!(json, serde_json::json!([
+        {"upc": "012345678905", "item_ids": [1, 2, 5]},
+        {"upc": "036000291452", "item_ids": [3, 4]},
+    ]));
+    assert!(stats(&[]).contains("| 036000291452 | 3 4      |"));
+}
+
+#[test]
+fn test_stats_csv_sections() {
+    let home = tempfile::tempdir().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    add_test_item(&conn, "Laptop", 1000.0, 1, "electronics", "new", Some("Dell"));
+    add_test_item(&conn, "Phone", 500.0, 1, "electronics", "used", Some("Apple"));
+    add_test_item(&conn, "Tee", 20.0, 1, "clothing, basics", "new", None);
+    let stats = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .arg("stats")
+            .args(args)
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap()
+    };
+    let csv = |args: &[&str]| {
+        let output = stats(args);
+        assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+    
+    // The summary is the default, so plain CSV output is unchanged
+    let summary = csv(&["--format", "csv"]);
+    assert!(summary.starts_with("metric,value\ntotal_items,3\n"));
+    assert_eq!(csv(&["--section", "summary"]), summary);
+    
+    assert_eq!(
+        csv(&["--section", "categories"]),
+        "category,count,total_value,average_price\nelectronics,2,1500.00,750.00\n\"clothing, basics\",1,20.00,20.00\n"
+    );
+    let conditions = csv(&["--format", "csv", "--section", "conditions"]);
+    assert!(conditions.starts_with("condition,count,total_value,average_price\n"));
+    assert!(conditions.contains("used,1,500.00,500.00\n"));
+    assert!(csv(&["--section", "brands"]).contains("\nDell,1,1000.00,1000.00\n"));
+    assert!(csv(&["--section", "price_ranges"]).starts_with("range,count\nunder_10,0\n"));
+    
+    let table = stats(&["--format", "table", "--section", "brands"]);
+    assert!(!table.status.success());
+    assert!(String::from_utf8_lossy(&table.stderr).contains("--section picks the table for CSV output"));
+}
+
+#[test]
+fn test_stats_discounts() {
+    let conn = setup_test_db();
+    let stats = compute_stats(&conn, None, &StatsFilters::default()).unwrap();
+    assert_eq!((stats.discounted_items, stats.total_discount, stats.average_discount_percent), (0, 0.0, 0.0));
+    
+    // Only A and E are marked down: B's original price is zero, C has none and D's is lower
+    for (title, price, quantity, original_price) in [
+        ("A", 80.0, 2, Some(100.0)),
+        ("B", 50.0, 1, Some(0.0)),
+        ("C", 30.0, 1, None),
+        ("D", 60.0, 1, Some(50.0)),
+        ("E", 45.0, 1, Some(90.0)),
+    ] {
+        add_test_item(&conn, title, price, quantity, "test", "new", None);
+        conn.execute("UPDATE items SET original_price = ? WHERE title = ?", rusqlite::params![original_price, title]).unwrap();
+    }
+    let stats = compute_stats(&conn, None, &StatsFilters::default()).unwrap();
+    assert_eq!(stats.discounted_items, 2);
+    assert_eq!(stats.total_discount, 85.0);
+    assert_eq!(stats.average_discount_percent, 35.0);
+}
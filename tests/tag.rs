@@ -0,0 +1,105 @@
+use inventory::commands::tag::handle_tag;
+use inventory::db::queries::{add_tags, get_item_tags, insert_item, items_with_tag, normalize_tags, remove_tags, NewItem};
+use inventory::db::schema::initialize_database;
+use rusqlite::Connection;
+use std::process::Command;
+
+fn insert(conn: &Connection, title: &str) -> i64 {
+    insert_item(conn, &NewItem {
+        title,
+        description: None,
+        price: 100.0,
+        quantity: 1,
+        photos: None,
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    }).unwrap();
+    conn.last_insert_rowid()
+}
+
+fn tags(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_tag_names_are_normalized_and_deduplicated() {
+    assert_eq!(normalize_tags(&tags(&[" Grail", "grail", "", "VINTAGE "])), tags(&["grail", "vintage"]));
+
+    let conn = initialize_database(None).unwrap();
+    let first = insert(&conn, "Air Jordan 1");
+    let second = insert(&conn, "Dunk Low");
+
+    assert_eq!(add_tags(&conn, first, &tags(&["Grail", "vintage", " grail"])).unwrap(), 2);
+    // Adding a tag the item already has changes nothing
+    assert_eq!(add_tags(&conn, first, &tags(&["GRAIL"])).unwrap(), 0);
+    add_tags(&conn, second, &tags(&["grail"])).unwrap();
+
+    assert_eq!(get_item_tags(&conn, first).unwrap(), tags(&["grail", "vintage"]));
+    assert_eq!(items_with_tag(&conn, "Grail").unwrap(), vec![first, second]);
+
+    assert_eq!(remove_tags(&conn, first, &tags(&["grail", "missing"])).unwrap(), 1);
+    assert_eq!(items_with_tag(&conn, "grail").unwrap(), vec![second]);
+
+    // Deleting an item drops its tags with it
+    conn.execute("DELETE FROM items WHERE item_id = ?", [second]).unwrap();
+    assert!(items_with_tag(&conn, "grail").unwrap().is_empty());
+}
+
+#[test]
+fn test_tag_command_checks_item_and_lists() {
+    let conn = initialize_database(None).unwrap();
+    let err = handle_tag(7, &tags(&["grail"]), &[], &conn).unwrap_err();
+    assert_eq!(err.to_string(), "Item with ID 7 does not exist");
+
+    let id = insert(&conn, "Air Jordan 1");
+    assert!(handle_tag(id as i32, &tags(&["Sale"]), &tags(&["sale"]), &conn).is_err());
+    handle_tag(id as i32, &tags(&["sale", "grail"]), &[], &conn).unwrap();
+    handle_tag(id as i32, &[], &tags(&["sale"]), &conn).unwrap();
+    assert_eq!(get_item_tags(&conn, id).unwrap(), tags(&["grail"]));
+}
+
+#[test]
+fn test_filter_by_tag() {
+    let home = tempfile::tempdir().unwrap();
+    let openinv = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(args)
+            .env("HOME", home.path())
+            .env_remove("INVENTORY_DB_PATH")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    for title in ["Air Jordan 1", "Dunk Low", "Yeezy 350"] {
+        openinv(&["add", "-t", title, "-p", "100", "-q", "1", "-c", "sneakers", "-n", "new"]);
+    }
+    openinv(&["tag", "--id", "1", "--add", "Grail,vintage"]);
+    openinv(&["tag", "--id", "3", "--add", "grail"]);
+
+    let stdout = openinv(&["filter", "--tag", " GRAIL", "-l", "title", "-f", "json"]);
+    let items: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let titles: Vec<&str> = items.as_array().unwrap().iter().map(|item| item["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, vec!["Air Jordan 1", "Yeezy 350"]);
+
+    // Tagging invalidates cached filter results
+    openinv(&["tag", "--id", "1", "--remove", "grail"]);
+    let stdout = openinv(&["filter", "--tag", "grail", "-l", "title", "-f", "json"]);
+    assert!(!stdout.contains("Air Jordan 1"), "{}", stdout);
+}
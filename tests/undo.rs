@@ -0,0 +1,102 @@
+use inventory::commands::archive::handle_archive;
+use inventory::commands::delete::delete_items;
+use inventory::commands::import::{handle_import, ImportOptions};
+use inventory::db::history::{last_operation, snapshot_items, undo_last_operation, OperationKind};
+use inventory::db::queries::{insert_item, NewItem};
+use inventory::db::schema::initialize_database;
+use rusqlite::Connection;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+fn insert_full_item(conn: &Connection, title: &str) -> i64 {
+    insert_item(conn, &NewItem {
+        title,
+        description: Some("Worn twice"),
+        price: 180.5,
+        quantity: 2,
+        photos: Some("front.jpg"),
+        category: "sneakers",
+        condition: "used",
+        brand: Some("Nike"),
+        upc: Some("123456789012"),
+        item_specifics: None,
+        shipping_details: Some("USPS"),
+        size: Some("10"),
+        original_price: Some(170.0),
+        hashtags: Some("#jordan"),
+        colorway: Some("Chicago"),
+        release_date: Some("2015-05-30"),
+        platform_status: None,
+        internal_notes: Some("Box damaged"),
+        status: "active",
+        currency: Some("EUR"),
+        import_run: None,
+        sku: None,
+    }).unwrap();
+    conn.last_insert_rowid()
+}
+
+fn count_items(conn: &Connection) -> i64 {
+    conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn test_undo_delete_restores_full_row_and_id() {
+    let conn = initialize_database(None).unwrap();
+    let first = insert_full_item(&conn, "Air Jordan 1");
+    let second = insert_full_item(&conn, "Air Jordan 4");
+    let before = snapshot_items(&conn, &[first, second]).unwrap();
+
+    delete_items(&conn, &[first as i32, second as i32]).unwrap();
+    assert_eq!(count_items(&conn), 0);
+    assert_eq!(last_operation(&conn).unwrap().unwrap().kind, OperationKind::Delete);
+
+    let outcome = undo_last_operation(&conn).unwrap().unwrap();
+    assert_eq!((outcome.kind, outcome.items), (OperationKind::Delete, 2));
+    assert!(outcome.reassigned.is_empty());
+    assert_eq!(snapshot_items(&conn, &[first, second]).unwrap(), before);
+
+    // The delete has been undone, so there's nothing left to undo
+    assert!(undo_last_operation(&conn).unwrap().is_none());
+}
+
+#[test]
+fn test_undo_delete_when_id_is_taken() {
+    let conn = initialize_database(None).unwrap();
+    let id = insert_full_item(&conn, "Original");
+    delete_items(&conn, &[id as i32]).unwrap();
+    conn.execute(
+        "INSERT INTO items (item_id, title, price, quantity, category, condition, last_updated, status) \
+         VALUES (?, 'Newcomer', 5, 1, 'misc', 'new', '2024-01-01T00:00:00.000000+00:00', 'active')",
+        [id],
+    ).unwrap();
+
+    let outcome = undo_last_operation(&conn).unwrap().unwrap();
+    let (original, restored) = outcome.reassigned[0];
+    assert_eq!(original, id);
+    let title: String = conn.query_row("SELECT title FROM items WHERE item_id = ?", [restored], |row| row.get(0)).unwrap();
+    assert_eq!(title, "Original");
+    assert_eq!(count_items(&conn), 2);
+}
+
+#[test]
+fn test_undo_update_then_import_in_turn() {
+    let conn = initialize_database(None).unwrap();
+    let id = insert_full_item(&conn, "Air Jordan 1");
+
+    let mut csv = NamedTempFile::new().unwrap();
+    writeln!(csv, "item_id,title,description,price,quantity,upc,category,condition,brand\n1,Imported,Desc,10.0,1,,shoes,new,Acme").unwrap();
+    handle_import(csv.path().to_str().unwrap().to_string(), &conn, &ImportOptions { non_interactive: true, ..Default::default() }).unwrap();
+    handle_archive(&[id as i32], "sold", &conn).unwrap();
+
+    // Most recent first: the status change, then the import
+    let outcome = undo_last_operation(&conn).unwrap().unwrap();
+    assert_eq!((outcome.kind, outcome.items), (OperationKind::Update, 1));
+    let status: String = conn.query_row("SELECT status FROM items WHERE item_id = ?", [id], |row| row.get(0)).unwrap();
+    assert_eq!(status, "active");
+
+    assert_eq!(count_items(&conn), 2);
+    let outcome = undo_last_operation(&conn).unwrap().unwrap();
+    assert_eq!((outcome.kind, outcome.items), (OperationKind::Import, 1));
+    assert_eq!(count_items(&conn), 1);
+}
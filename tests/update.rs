@@ -1,6 +1,7 @@
 use inventory::db::schema::initialize_database;
 use inventory::db::queries::{insert_item, NewItem, get_item_by_id, update_item};
 use inventory::commands::update::{Update, execute};
+use inventory::error::error::ExitCode;
 use rusqlite::Connection;
 use std::fs::File;
 use std::io::Write;
@@ -39,6 +40,9 @@ fn test_update_valid_csv() -> anyhow::Result<()> {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     })?;
     
     let mut csv_file = NamedTempFile::new()?;
@@ -53,9 +57,9 @@ fn test_update_valid_csv() -> anyhow::Result<()> {
     assert!(result.is_ok(), "Valid CSV update should succeed");
     
     let item = get_item_by_id(&conn, item_id.try_into().unwrap())?.unwrap();
-    assert_eq!(item["title"], "Updated Item");
-    assert_eq!(item["price"], "20.00");
-    assert_eq!(item["quantity"], "5"); // Unchanged
+    assert_eq!(item.title, "Updated Item");
+    assert_eq!(item.price, 20.0);
+    assert_eq!(item.quantity, 5); // Unchanged
     Ok(())
 }
 
@@ -72,7 +76,7 @@ fn test_update_invalid_id() -> anyhow::Result<()> {
         retry: None 
     };
     let result = execute(args, &conn);
-    assert!(result.is_ok(), "Should handle invalid ID gracefully");
+    assert_eq!(ExitCode::of(&result.unwrap_err()), ExitCode::Validation, "A row for a missing item fails the update");
     
     // Check that failed rows were saved
     let failed_dir = dirs::home_dir().unwrap().join(".inventory/failed");
@@ -110,6 +114,9 @@ fn test_update_invalid_validation() -> anyhow::Result<()> {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     })?;
     
     let mut csv_file = NamedTempFile::new()?;
@@ -123,7 +130,7 @@ fn test_update_invalid_validation() -> anyhow::Result<()> {
         retry: None 
     };
     let result = execute(args, &conn);
-    assert!(result.is_ok(), "Should handle validation errors gracefully");
+    assert_eq!(ExitCode::of(&result.unwrap_err()), ExitCode::Validation, "An invalid row fails the update");
     
     // Check that failed rows were saved
     let failed_dir = dirs::home_dir().unwrap().join(".inventory/failed");
@@ -161,6 +168,9 @@ fn test_update_retry_valid() -> anyhow::Result<()> {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     })?;
     
     let mut json_file = NamedTempFile::new()?;
@@ -181,10 +191,10 @@ fn test_update_retry_valid() -> anyhow::Result<()> {
         retry: Some(json_file.path().to_str().unwrap().to_string()) 
     };
     let result = execute(args, &conn);
-    assert!(result.is_ok(), "Retry should handle errors gracefully");
+    assert_eq!(ExitCode::of(&result.unwrap_err()), ExitCode::Validation, "A row still failing fails the retry");
     
     let item = get_item_by_id(&conn, item_id.try_into().unwrap())?.unwrap();
-    assert_eq!(item["title"], "Item 1"); // Title not updated due to retry failure
+    assert_eq!(item.title, "Item 1"); // Title not updated due to retry failure
     Ok(())
 }
 
@@ -229,16 +239,19 @@ fn test_update_partial_fields() -> anyhow::Result<()> {
         platform_status: None,
         internal_notes: None,
         status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
     })?;
     
     // Test partial update - only update title
     update_item(&conn, item_id.try_into().unwrap(), Some("Updated Title"), None, None, None, None, None, None)?;
     
     let item = get_item_by_id(&conn, item_id.try_into().unwrap())?.unwrap();
-    assert_eq!(item["title"], "Updated Title");
-    assert_eq!(item["price"], "10.00"); // Unchanged - formatted with 2 decimal places
-    assert_eq!(item["quantity"], "5"); // Unchanged
-    assert_eq!(item["category"], "sneakers"); // Unchanged
+    assert_eq!(item.title, "Updated Title");
+    assert_eq!(item.price, 10.0); // Unchanged
+    assert_eq!(item.quantity, 5); // Unchanged
+    assert_eq!(item.category, "sneakers"); // Unchanged
     Ok(())
 }
 
@@ -248,5 +261,95 @@ fn test_update_nonexistent_item() -> anyhow::Result<()> {
     
     let result = update_item(&conn, 999, Some("New Title"), None, None, None, None, None, None);
     assert!(result.is_err(), "Should fail when updating non-existent item");
+    assert_eq!(result.unwrap_err().to_string(), "No item with ID 999");
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_update_csv_failure_rolls_back_earlier_rows() -> anyhow::Result<()> {
+    let conn = setup_test_db();
+    let mut ids = Vec::new();
+    for title in ["Item 1", "Item 2"] {
+        insert_item(&conn, &NewItem {
+            title,
+            description: None,
+            price: 10.0,
+            quantity: 5,
+            photos: None,
+            category: "sneakers",
+            condition: "new",
+            brand: Some("Nike"),
+            upc: Some("123456789012"),
+            item_specifics: None,
+            shipping_details: None,
+            size: None,
+            original_price: None,
+            hashtags: None,
+            colorway: None,
+            release_date: None,
+            platform_status: None,
+            internal_notes: None,
+            status: "active",
+            currency: None,
+            import_run: None,
+            sku: None,
+        })?;
+        ids.push(conn.last_insert_rowid());
+    }
+
+    // The second row can't be parsed, so the first row's update must not stick
+    let mut csv_file = NamedTempFile::new()?;
+    writeln!(csv_file, "id,title,price")?;
+    writeln!(csv_file, "{},Updated Item,20.0", ids[0])?;
+    writeln!(csv_file, "{},Broken Item,abc", ids[1])?;
+
+    let args = Update {
+        file: Some(csv_file.path().to_str().unwrap().to_string()),
+        retry: None
+    };
+    assert!(execute(args, &conn).is_err());
+
+    let item = get_item_by_id(&conn, ids[0])?.unwrap();
+    assert_eq!(item.title, "Item 1");
+    assert_eq!(item.price, 10.0);
+    Ok(())
+}
+
+#[test]
+fn test_get_item_by_id_serializes_numbers() -> anyhow::Result<()> {
+    let conn = setup_test_db();
+    
+    let item_id = insert_item(&conn, &NewItem {
+        title: "Item 1",
+        description: None,
+        price: 20.0,
+        quantity: 5,
+        photos: None,
+        category: "sneakers",
+        condition: "new",
+        brand: None,
+        upc: None,
+        item_specifics: None,
+        shipping_details: None,
+        size: None,
+        original_price: None,
+        hashtags: None,
+        colorway: None,
+        release_date: None,
+        platform_status: None,
+        internal_notes: None,
+        status: "active",
+        currency: None,
+        import_run: None,
+        sku: None,
+    })?;
+    
+    let item = get_item_by_id(&conn, item_id.try_into().unwrap())?.unwrap();
+    let json = serde_json::to_value(&item)?;
+    assert!(json["price"].is_number(), "Price should serialize as a JSON number");
+    assert!(json["quantity"].is_number(), "Quantity should serialize as a JSON number");
+    assert_eq!(json["price"], 20.0);
+    assert_eq!(json["quantity"], 5);
+    assert!(json["brand"].is_null());
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use inventory::commands::vacuum::handle_vacuum;
+use inventory::config::optimization::optimize_database;
+use inventory::db::schema::initialize_database;
+use tempfile::TempDir;
+
+#[test]
+fn test_vacuum_reclaims_deleted_rows() {
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    optimize_database(&conn).unwrap();
+    
+    let description = "x".repeat(2000);
+    for i in 0..1000 {
+        conn.execute(
+            "INSERT INTO items (title, description, price, quantity, category, condition, last_updated, status) \
+             VALUES (?, ?, 10.0, 1, 'sneakers', 'new', datetime('now'), 'active')",
+            rusqlite::params![format!("Item {}", i), description],
+        ).unwrap();
+    }
+    conn.execute("DELETE FROM items WHERE item_id > 10", []).unwrap();
+    
+    let (before, after) = handle_vacuum(&conn, &db_path).unwrap();
+    assert!(after < before / 2, "expected the file to shrink: {} -> {}", before, after);
+    let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(remaining, 10);
+    
+    // The planner's statistics are there for the indexes
+    let analyzed: i64 = conn.query_row("SELECT COUNT(*) FROM sqlite_stat1", [], |row| row.get(0)).unwrap();
+    assert!(analyzed > 0);
+}
@@ -1,7 +1,10 @@
+use inventory::commands::import::validate_csv;
 use inventory::commands::validate::handle_validate;
+use inventory::db::schema::initialize_database;
 use std::fs::{self, File};
 use std::io::Write;
-use tempfile::NamedTempFile;
+use std::process::Command;
+use tempfile::{NamedTempFile, TempDir};
 
 fn write_csv(contents: &str) -> NamedTempFile {
     let mut file = NamedTempFile::new().unwrap();
@@ -11,15 +14,15 @@ fn write_csv(contents: &str) -> NamedTempFile {
 
 #[test]
 fn test_validate_valid_file() {
-    let csv = write_csv("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,123,shoes,new,Nike\n");
-    let result = handle_validate(csv.path().to_str().unwrap());
+    let csv = write_csv("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,123456789012,shoes,new,Nike\n");
+    let result = handle_validate(csv.path().to_str().unwrap(), None, false, None, None);
     assert!(result.is_ok());
 }
 
 #[test]
 fn test_validate_invalid_file() {
     let csv = write_csv("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,-5.0,5,123,shoes,new,Nike\n2,LongTitleThatExceedsEbayEightyCharacterLimitLongTitleLongTitleLongTitleLongTitleLongTitle,Desc,10.0,5,123,shoes,new,Nike\n3,,Desc,10.0,5,123,shoes,new,Nike\n4,Test,Desc,abc,5,123,shoes,new,Nike\n5,Test,Desc,10.0,-1,123,shoes,new,Nike\n6,Test,Desc,10.0,5,123,shoes,,Nike\n");
-    let result = handle_validate(csv.path().to_str().unwrap());
+    let result = handle_validate(csv.path().to_str().unwrap(), None, false, None, None);
     assert!(result.is_err());
     // Check that a failed file was created
     let failed_dir = dirs::home_dir().unwrap().join(".inventory/failed");
@@ -29,16 +32,106 @@ fn test_validate_invalid_file() {
     assert!(found);
 }
 
+#[test]
+fn test_validate_checks_condition_like_import() {
+    // Import rejects an unknown condition, so validate does too; aliases pass both
+    let csv = write_csv("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,,shoes,brand-spanking,Nike\n");
+    assert!(handle_validate(csv.path().to_str().unwrap(), None, false, None, None).is_err());
+    
+    let csv = write_csv("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,,shoes,pre-owned,Nike\n");
+    assert!(handle_validate(csv.path().to_str().unwrap(), None, false, None, None).is_ok());
+}
+
+#[test]
+fn test_validate_checks_upc() {
+    let path_of = |csv: &NamedTempFile| csv.path().to_path_buf();
+    // Too short to be any product code
+    let csv = write_csv("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,12345,shoes,new,Nike\n");
+    let result = validate_csv(&path_of(&csv), None, false, None, None).unwrap();
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!((result.errors[0].field.as_str(), result.errors[0].row), ("upc", Some(1)));
+    assert_eq!(result.errors[0].value.as_deref(), Some("12345"));
+    
+    // StockX listings need one at all
+    let csv = write_csv("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,,stockx,new,Nike\n");
+    let result = validate_csv(&path_of(&csv), None, false, None, None).unwrap();
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].message, "UPC required for StockX");
+}
+
+#[test]
+fn test_import_validate_only_writes_nothing() {
+    let home = TempDir::new().unwrap();
+    let db_path = home.path().join("inventory.db");
+    let conn = initialize_database(Some(&db_path)).unwrap();
+    let run = |contents: &str| {
+        let csv = write_csv(contents);
+        Command::new(env!("CARGO_BIN_EXE_openinv"))
+            .args(["import", "--validate-only", "--file", csv.path().to_str().unwrap()])
+            .env("HOME", home.path())
+            .env("INVENTORY_DB_PATH", &db_path)
+            .output()
+            .unwrap()
+    };
+    
+    let output = run("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,,shoes,new,Nike\n");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Validation successful"));
+    
+    let output = run("item_id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,,shoes,mint,Nike\n");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("✗ Row 1: condition: Invalid condition 'mint'"));
+    
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 0);
+}
+
 #[test]
 fn test_validate_schema_mismatch() {
     let csv = write_csv("id,title,description,price,quantity,upc,category,condition,brand\n1,Test,Desc,10.0,5,123,shoes,new,Nike\n");
-    let result = handle_validate(csv.path().to_str().unwrap());
+    let result = handle_validate(csv.path().to_str().unwrap(), None, false, None, None);
     assert!(result.is_err());
 }
 
 #[test]
 fn test_validate_empty_file() {
     let csv = write_csv("");
-    let result = handle_validate(csv.path().to_str().unwrap());
+    let result = handle_validate(csv.path().to_str().unwrap(), None, false, None, None);
     assert!(result.is_err());
-} 
\ No newline at end of file
+}
+
+fn validate_item(home: &TempDir, extra: &[&str]) -> std::process::Output {
+    let mut args = vec!["validate-item", "-t", "Air Jordan 1", "-p", "150", "-q", "1", "-c", "sneakers", "-n", "new"];
+    args.extend_from_slice(extra);
+    Command::new(env!("CARGO_BIN_EXE_openinv"))
+        .args(&args)
+        .env("HOME", home.path())
+        .env_remove("INVENTORY_DB_PATH")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_validate_item_reports_each_platform() {
+    let home = TempDir::new().unwrap();
+    
+    let output = validate_item(&home, &[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ ebay      pass"));
+    assert!(stdout.contains("✗ stockx    fail"));
+    assert!(stdout.contains("- upc: UPC is required for StockX listings"));
+    assert!(stdout.contains("Passes 2 of 4 platforms"));
+    
+    let output = validate_item(&home, &["--format", "json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["ebay"]["valid"], true);
+    assert_eq!(report["stockx"]["valid"], false);
+    assert_eq!(report["stockx"]["errors"][1]["field"], "size");
+    assert_eq!(report["poshmark"]["errors"].as_array().unwrap().len(), 1);
+    
+    // A single platform the item fails exits with an error
+    let output = validate_item(&home, &["--platform", "stockx"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Passes 0 of 1 platforms"));
+}
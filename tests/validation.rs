@@ -27,13 +27,13 @@ fn test_validate_title_ebay_empty() {
 
 #[test]
 fn test_validate_price_valid() {
-    let result = validate_price(10.99).unwrap();
+    let result = validate_price(10.99, "USD").unwrap();
     assert!(result.is_valid(), "Valid price should pass validation");
 }
 
 #[test]
 fn test_validate_price_negative() {
-    let result = validate_price(-1.0).unwrap();
+    let result = validate_price(-1.0, "USD").unwrap();
     assert!(!result.is_valid(), "Negative price should fail");
     assert_eq!(result.errors.len(), 1);
     assert_eq!(result.errors[0].field, "price");
@@ -42,13 +42,36 @@ fn test_validate_price_negative() {
 
 #[test]
 fn test_validate_price_too_high() {
-    let result = validate_price(1000000.0).unwrap();
+    let result = validate_price(1000000.0, "USD").unwrap();
     assert!(!result.is_valid(), "Price over limit should fail");
     assert_eq!(result.errors.len(), 1);
     assert_eq!(result.errors[0].field, "price");
     assert!(result.errors[0].message.contains("maximum allowed"));
 }
 
+#[test]
+fn test_validate_price_currency_bounds() {
+    // JPY has no minor units and a higher cap
+    assert!(validate_price(5_000_000.0, "JPY").unwrap().is_valid());
+    let result = validate_price(1500.5, "JPY").unwrap();
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors[0].message.contains("decimal places"));
+    
+    // Three-decimal currencies accept fils, two-decimal ones don't accept fractions of a cent
+    assert!(validate_price(12.345, "KWD").unwrap().is_valid());
+    assert!(!validate_price(12.345, "USD").unwrap().is_valid());
+    assert!(!validate_price(5_000_000.0, "EUR").unwrap().is_valid());
+}
+
+#[test]
+fn test_validate_currency_unknown_code() {
+    assert!(validate_currency("eur").unwrap().is_valid());
+    
+    let result = validate_currency("XYZ").unwrap();
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].field, "currency");
+}
+
 #[test]
 fn test_validate_quantity_valid() {
     let result = validate_quantity(5).unwrap();
@@ -123,6 +146,56 @@ fn test_validate_size_poshmark_valid() {
     assert!(result.is_valid(), "Valid size should pass for Poshmark");
 }
 
+#[test]
+fn test_validate_release_date() {
+    assert_eq!(normalize_release_date("2023-03-15").as_deref(), Some("2023-03-15"));
+    assert_eq!(normalize_release_date(" 3/5/2023 ").as_deref(), Some("2023-03-05"));
+    assert_eq!(normalize_release_date("2024-02-29").as_deref(), Some("2024-02-29"));
+    
+    // Optional, so blank is fine
+    assert!(validate_release_date("").unwrap().is_valid());
+    for date in ["2023-13-45", "2023-02-29", "next tuesday", "15/03/2023"] {
+        let result = validate_release_date(date).unwrap();
+        assert_eq!(result.errors.len(), 1, "'{}' should be rejected", date);
+        assert_eq!(result.errors[0].field, "release_date");
+    }
+}
+
+#[test]
+fn test_validate_size_stockx_sneaker() {
+    for size in ["3.5", "10", "10.5", "US 9", "9.5us", "18"] {
+        assert!(validate_size_stockx_sneaker(size).unwrap().is_valid(), "'{}' should be a US sneaker size", size);
+    }
+    
+    let message = |size| validate_size_stockx_sneaker(size).unwrap().errors[0].message.clone();
+    assert_eq!(message("10.25"), "'10.25' isn't a US sneaker size. Nearest valid: 10 or 10.5");
+    assert_eq!(message("19"), "'19' isn't a US sneaker size. Nearest valid: 18");
+    assert_eq!(message("M"), "'M' isn't a US sneaker size; expected a number from 3.5 to 18 in steps of 0.5");
+    assert!(message("").contains("required"));
+}
+
+#[test]
+fn test_stockx_size_chart_only_applies_to_footwear() {
+    assert_eq!(size_chart(Platform::Stockx, "Running Shoes"), Some(&US_SNEAKER_SIZES));
+    assert_eq!(size_chart(Platform::Stockx, "apparel"), None);
+    assert_eq!(size_chart(Platform::Ebay, "sneakers"), None);
+    
+    let validate = |category, size| validate_item_stockx(&ItemFields {
+        title: "Valid Title",
+        price: 10.99,
+        quantity: 1,
+        category,
+        condition: "new",
+        upc: Some("123456789012"),
+        size: Some(size),
+        ..Default::default()
+    }).unwrap();
+    assert!(validate("apparel", "M").is_valid());
+    let result = validate("sneakers", "M");
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].field, "size");
+}
+
 #[test]
 fn test_validate_condition_valid() {
     let valid_conditions = ["new", "used", "deadstock", "like new", "good", "fair"];
@@ -142,6 +215,36 @@ fn test_validate_condition_invalid() {
     assert!(result.errors[0].message.contains("Invalid condition"));
 }
 
+#[test]
+fn test_validate_condition_suggests_close_match() {
+    let result = validate_condition("lik new").unwrap();
+    assert_eq!(result.errors[0].message, "Invalid condition 'lik new'. Did you mean 'like new'?");
+    
+    let result = validate_condition("Uesd").unwrap();
+    assert!(result.errors[0].message.ends_with("Did you mean 'used'?"));
+    
+    // Nothing close, so it lists the options instead
+    let result = validate_condition("broken").unwrap();
+    assert!(result.errors[0].message.contains("Must be one of: new, used"));
+}
+
+#[test]
+fn test_closest_match() {
+    let brands = ["Adidas", "New Balance", "Nike"];
+    assert_eq!(closest_match("addidas", brands), Some("Adidas"));
+    assert_eq!(closest_match("NIKE", brands), Some("Nike"));
+    assert_eq!(closest_match("Reebok", brands), None);
+}
+
+#[test]
+fn test_validate_condition_accepts_aliases() {
+    assert_eq!(normalize_condition("Brand New"), "new");
+    assert_eq!(normalize_condition("pre-owned"), "used");
+    assert_eq!(normalize_condition("Good"), "Good");
+    assert!(validate_condition("Pre-Owned").unwrap().is_valid());
+    assert!(validate_condition("brand new").unwrap().is_valid());
+}
+
 #[test]
 fn test_validate_category_empty() {
     let result = validate_category("").unwrap();
@@ -181,30 +284,32 @@ fn test_validate_brand_too_long() {
 
 #[test]
 fn test_validate_item_ebay_valid() {
-    let result = validate_item_ebay(
-        "Valid Title",
-        10.99,
-        5,
-        "sneakers",
-        "new",
-        Some("Nike"),
-        Some("123456789012"),
-    ).unwrap();
+    let result = validate_item_ebay(&ItemFields {
+        title: "Valid Title",
+        price: 10.99,
+        quantity: 5,
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        upc: Some("123456789012"),
+        ..Default::default()
+    }).unwrap();
     
     assert!(result.is_valid(), "Valid eBay item should pass validation");
 }
 
 #[test]
 fn test_validate_item_ebay_multiple_errors() {
-    let result = validate_item_ebay(
-        "",  // Empty title
-        -1.0,  // Negative price
-        5,
-        "sneakers",
-        "new",
-        Some("Nike"),
-        Some("123456789012"),
-    ).unwrap();
+    let result = validate_item_ebay(&ItemFields {
+        title: "",  // Empty title
+        price: -1.0,  // Negative price
+        quantity: 5,
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        upc: Some("123456789012"),
+        ..Default::default()
+    }).unwrap();
     
     assert!(!result.is_valid(), "Item with multiple errors should fail");
     assert!(result.errors.len() >= 2, "Should have at least 2 errors");
@@ -216,32 +321,34 @@ fn test_validate_item_ebay_multiple_errors() {
 
 #[test]
 fn test_validate_item_stockx_valid() {
-    let result = validate_item_stockx(
-        "Valid Title",
-        10.99,
-        5,
-        "sneakers",
-        "new",
-        Some("Nike"),
-        "123456789012",
-        "M",
-    ).unwrap();
+    let result = validate_item_stockx(&ItemFields {
+        title: "Valid Title",
+        price: 10.99,
+        quantity: 5,
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        upc: Some("123456789012"),
+        size: Some("10.5"),
+        ..Default::default()
+    }).unwrap();
     
     assert!(result.is_valid(), "Valid StockX item should pass validation");
 }
 
 #[test]
 fn test_validate_item_stockx_missing_required() {
-    let result = validate_item_stockx(
-        "Valid Title",
-        10.99,
-        5,
-        "sneakers",
-        "new",
-        Some("Nike"),
-        "",  // Empty UPC
-        "",  // Empty size
-    ).unwrap();
+    let result = validate_item_stockx(&ItemFields {
+        title: "Valid Title",
+        price: 10.99,
+        quantity: 5,
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        upc: Some(""),  // Empty UPC
+        size: None,  // Missing size
+        ..Default::default()
+    }).unwrap();
     
     assert!(!result.is_valid(), "StockX item missing required fields should fail");
     assert!(result.errors.len() >= 2, "Should have at least 2 errors");
@@ -269,4 +376,81 @@ fn test_validation_result_is_valid() {
     
     result.add_error("test", "error", None, None);
     assert!(!result.is_valid(), "Result with errors should not be valid");
-} 
\ No newline at end of file
+} 
+#[test]
+fn test_truncate_title() {
+    let words = "Vintage Nike Air Jordan 1 Retro High OG Chicago Lost and Found Sneakers Size 10 Men";
+    let truncated = truncate_title(words, TITLE_MAX_LEN);
+    assert_eq!(truncated, "Vintage Nike Air Jordan 1 Retro High OG Chicago Lost and Found Sneakers Size 10");
+    assert!(validate_title_ebay(&truncated).unwrap().is_valid());
+    
+    // A single overlong word is cut on a character boundary
+    let accents = "é".repeat(50);
+    assert_eq!(truncate_title(&accents, TITLE_MAX_LEN), "é".repeat(40));
+    assert_eq!(truncate_title("Short title", TITLE_MAX_LEN), "Short title");
+}
+
+#[test]
+fn test_validate_item_for_platform() {
+    let item = ItemFields { title: "Air Jordan 1", price: 150.0, quantity: 1, category: "sneakers", condition: "new", ..Default::default() };
+    let validate = |platform| validate_item_for(platform, &ItemFields { brand: Some("Nike"), ..item }).unwrap();
+    
+    assert!(validate(Platform::Ebay).is_valid());
+    assert!(validate(Platform::Mercari).is_valid());
+    
+    let fields = |result: ValidationResult| result.errors.into_iter().map(|e| e.message).collect::<Vec<_>>();
+    assert_eq!(fields(validate(Platform::Stockx)), vec!["UPC is required for StockX listings", "Size is required for StockX listings"]);
+    assert_eq!(fields(validate(Platform::Poshmark)), vec!["Size is required for Poshmark clothing items"]);
+    
+    let stockx = validate_item_for(Platform::Stockx, &ItemFields { upc: Some("123456789012"), size: Some("10"), ..item }).unwrap();
+    assert!(stockx.is_valid());
+}
+
+#[test]
+fn test_validate_item_all_platforms() {
+    let results = validate_item_all(&ItemFields {
+        title: "Air Jordan 1",
+        price: 150.0,
+        quantity: 1,
+        category: "sneakers",
+        condition: "new",
+        brand: Some("Nike"),
+        ..Default::default()
+    }).unwrap();
+    
+    assert_eq!(results.len(), Platform::ALL.len());
+    assert!(results["ebay"].is_valid());
+    assert!(results["mercari"].is_valid());
+    assert!(!results["poshmark"].is_valid());
+    let stockx_fields: Vec<&str> = results["stockx"].errors.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(stockx_fields, vec!["upc", "size"]);
+}
+
+#[test]
+fn test_code_kind() {
+    assert_eq!(code_kind("96385074"), Some(CodeKind::Ean8));
+    assert_eq!(code_kind("0-306-40615-2"), Some(CodeKind::Isbn10));
+    assert_eq!(code_kind("080442957X"), Some(CodeKind::Isbn10));
+    assert_eq!(code_kind("123456789012"), Some(CodeKind::UpcA));
+    assert_eq!(code_kind("1234567890123"), Some(CodeKind::Ean13));
+    assert_eq!(code_kind("978-0-306-40615-7"), Some(CodeKind::Isbn13));
+    assert_eq!(code_kind("12345"), None);
+    assert_eq!(code_kind("1234567X"), None);
+}
+
+#[test]
+fn test_validate_upc_ean8_and_isbn10_checksums() {
+    assert!(validate_upc_stockx("96385074").unwrap().is_valid());
+    assert!(validate_upc_ebay("0-306-40615-2").unwrap().is_valid());
+    assert!(validate_upc_ebay("080442957x").unwrap().is_valid());
+    
+    let result = validate_upc_stockx("96385075").unwrap();
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].message, "Invalid EAN-8 check digit");
+    let result = validate_upc_ebay("0306406153").unwrap();
+    assert_eq!(result.errors[0].message, "Invalid ISBN-10 check digit");
+    
+    // StockX still requires a code; eBay still accepts none
+    assert!(!validate_upc_stockx("").unwrap().is_valid());
+    assert!(validate_upc_ebay("").unwrap().is_valid());
+}